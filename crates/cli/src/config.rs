@@ -79,6 +79,67 @@ impl CliConfig {
 
         Ok(())
     }
+
+    /// Replace `auth_token` in the CLI configuration file, keeping everything else
+    pub fn save_auth_token(token: &str) -> Result<()> {
+        let config_path = Self::config_path()?;
+        let contents = if config_path.exists() {
+            fs::read_to_string(&config_path).context("Failed to read CLI configuration")?
+        } else {
+            String::new()
+        };
+
+        let updated = replace_auth_token(&contents, token);
+
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        fs::write(&config_path, updated).context("Failed to write CLI configuration")?;
+
+        // Contains the auth token
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&config_path, fs::Permissions::from_mode(0o600))
+                .context("Failed to set CLI configuration permissions")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Set the top-level `auth_token` key in TOML text, preserving comments
+fn replace_auth_token(contents: &str, token: &str) -> String {
+    let line = format!("auth_token = \"{}\"", token);
+    let mut lines: Vec<String> = Vec::new();
+    let mut replaced = false;
+    let mut table_start = None;
+
+    for current in contents.lines() {
+        let trimmed = current.trim_start();
+        if table_start.is_none() && trimmed.starts_with('[') {
+            table_start = Some(lines.len());
+        }
+        let is_key = trimmed
+            .strip_prefix("auth_token")
+            .is_some_and(|rest| rest.trim_start().starts_with('='));
+        if table_start.is_none() && is_key && !replaced {
+            lines.push(line.clone());
+            replaced = true;
+        } else {
+            lines.push(current.to_string());
+        }
+    }
+
+    // New key goes before the first table so it stays top-level
+    if !replaced {
+        lines.insert(table_start.unwrap_or(lines.len()), line);
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
 }
 
 #[cfg(test)]
@@ -126,4 +187,21 @@ mod tests {
             "https://example.com:3443"
         );
     }
+
+    #[test]
+    fn test_replace_auth_token() {
+        let contents = "# CLI config\nconnection_mode = \"https\"\nauth_token = \"old\"\n";
+        assert_eq!(
+            replace_auth_token(contents, "new"),
+            "# CLI config\nconnection_mode = \"https\"\nauth_token = \"new\"\n"
+        );
+
+        // Added when missing, ahead of any table
+        let contents = "connection_mode = \"http\"\n[extra]\nauth_token = \"x\"\n";
+        assert_eq!(
+            replace_auth_token(contents, "new"),
+            "connection_mode = \"http\"\nauth_token = \"new\"\n[extra]\nauth_token = \"x\"\n"
+        );
+        assert_eq!(replace_auth_token("", "new"), "auth_token = \"new\"\n");
+    }
 }
@@ -7,7 +7,7 @@
 mod config;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use comfy_table::{Attribute, Cell, Color, ContentArrangement, Table};
 use dialoguer::{Confirm, Input, Password};
@@ -17,11 +17,14 @@ use std::path::PathBuf;
 use futures::StreamExt;
 
 use ssh_tunnel_common::{
-    delete_profile_by_name, load_all_profiles, load_profile_by_name, profile_exists_by_name,
+    delete_profile_by_name, format_duration, load_all_profiles, load_profile_by_name, profile_exists_by_name,
     save_profile, start_tunnel_with_events, stop_tunnel as stop_tunnel_shared,
-    AuthRequest, AuthType, ConnectionConfig, TunnelEvent,
-    ForwardingConfig, ForwardingType, PasswordStorage, Profile, TunnelEventHandler, TunnelOptions,
-    TunnelStatus, TunnelStatusResponse, Uuid,
+    AuditEntry, AuthRequest, AuthType, LockKeysRequest, HistorySummary, ConnectionConfig, CreateTokenRequest, CreateTokenResponse,
+    TunnelEvent, ForwardingConfig, ForwardingType, PasswordStorage, PreflightIssue,
+    PreflightReport, PreflightSeverity, Profile, RotateTokenRequest, RotateTokenResponse,
+    LogLevel, SetLogLevelRequest, TokenInfo, TokenScope, TunnelEventHandler, TunnelLogEntry,
+    TunnelLogLevel, TunnelSession, TunnelOptions, TunnelStatus, TunnelStatusResponse, UnlockKeyRequest, UnlockedKey, Utc, Uuid, Vault, VaultLock,
+    preflight,
 };
 
 #[derive(Parser)]
@@ -60,7 +63,7 @@ enum Commands {
         #[arg(short = 'b', long, default_value = "127.0.0.1")]
         bind_address: Option<String>,
 
-        /// Local port to bind
+        /// Local port to bind (0 = any free port, reported by `status`)
         #[arg(short = 'l', long, default_value = "4443")]
         local_port: Option<u16>,
 
@@ -107,6 +110,15 @@ enum Commands {
         /// SSH window size in bytes
         #[arg(long)]
         window_size: Option<u32>,
+
+        /// Seconds to wait for an answer to an auth prompt (default: the daemon's)
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        auth_timeout: Option<u64>,
+
+        /// Command printing the password or key passphrase, run by the daemon
+        /// (e.g. "pass show ssh/db"); nothing is stored in the keychain
+        #[arg(long, value_name = "COMMAND")]
+        secret_command: Option<String>,
     },
 
     /// List all tunnel profiles
@@ -162,6 +174,10 @@ enum Commands {
         /// Show status for all profiles
         #[arg(short, long)]
         all: bool,
+
+        /// Output as JSON (includes the actually bound local address)
+        #[arg(long)]
+        json: bool,
     },
 
     /// Daemon management
@@ -174,6 +190,172 @@ enum Commands {
         /// Optional profile name to filter by
         name: Option<String>,
     },
+
+    /// Check a profile for port conflicts, key problems and privileged ports
+    Check {
+        /// Profile name
+        name: String,
+    },
+
+    /// Manage scoped API tokens on the daemon
+    Token {
+        #[command(subcommand)]
+        action: TokenCommands,
+    },
+
+    /// Manage the daemon's cache of decrypted SSH keys
+    Keys {
+        #[command(subcommand)]
+        action: KeysCommands,
+    },
+
+    /// Manage the encrypted secret vault (password_storage = "file")
+    Vault {
+        #[command(subcommand)]
+        action: VaultCommands,
+    },
+
+    /// Show past sessions of a tunnel: uptime, end reasons, traffic
+    History {
+        /// Profile name
+        name: String,
+
+        /// Show at most this many of the most recent sessions
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// Print sessions as JSON Lines
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show a tunnel's diagnostic log
+    Logs {
+        /// Profile name
+        name: String,
+
+        /// Keep following new lines
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Show at most this many of the most recent lines
+        #[arg(short = 'n', long, default_value_t = 100)]
+        limit: usize,
+
+        /// Capture this tunnel at another level (error, warn, info, debug, trace) for a while
+        #[arg(long)]
+        level: Option<LogLevel>,
+
+        /// How long --level applies: an age like 90s, 10m, 1h (default: 10m)
+        #[arg(long = "for", value_name = "DURATION", requires = "level")]
+        duration: Option<String>,
+    },
+
+    /// Show the daemon's audit log
+    Audit {
+        /// Only entries since this time: RFC 3339 or an age like 30m, 12h, 7d
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only entries for this profile (name or ID)
+        #[arg(short, long)]
+        profile: Option<String>,
+
+        /// Show at most this many of the most recent entries
+        #[arg(short = 'n', long, default_value_t = 50)]
+        limit: usize,
+
+        /// Print entries as JSON Lines
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokenCommands {
+    /// Create a token (the secret is shown only once)
+    Create {
+        /// Token name
+        name: String,
+
+        /// Scope to grant: read, control, auth or admin (repeatable)
+        #[arg(short, long = "scope", required = true)]
+        scopes: Vec<TokenScope>,
+
+        /// Restrict the token to a profile, by name or ID (repeatable)
+        #[arg(short, long = "profile")]
+        profiles: Vec<String>,
+
+        /// Restrict the token to profiles with this tag (repeatable)
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+    },
+    /// List tokens
+    List,
+    /// Revoke a token
+    Revoke {
+        /// Token name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeysCommands {
+    /// Decrypt a key on the daemon once, for all tunnels using it
+    Unlock {
+        /// Profile name, or key file in ~/.ssh on the daemon host (e.g. id_ed25519)
+        key: String,
+    },
+    /// Drop decrypted keys from the daemon (all of them by default)
+    Lock {
+        /// Key path or fingerprint
+        key: Option<String>,
+    },
+    /// List the decrypted keys
+    List,
+}
+
+#[derive(Subcommand)]
+enum VaultCommands {
+    /// Store a profile's password or key passphrase in the vault
+    Set {
+        /// Profile name
+        name: String,
+    },
+    /// Change the vault's master passphrase
+    Passwd,
+    /// Move stored secrets between the system keychain and the vault
+    Migrate {
+        /// Where to move them
+        #[arg(long, value_enum)]
+        to: SecretBackend,
+
+        /// Profile name (default: every profile storing its secret in the other place)
+        name: Option<String>,
+    },
+}
+
+/// Places `vault migrate` moves secrets between
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SecretBackend {
+    Keychain,
+    File,
+}
+
+impl SecretBackend {
+    fn storage(self) -> PasswordStorage {
+        match self {
+            SecretBackend::Keychain => PasswordStorage::Keychain,
+            SecretBackend::File => PasswordStorage::File,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            SecretBackend::Keychain => "system keychain",
+            SecretBackend::File => "vault file",
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -184,6 +366,12 @@ enum DaemonCommands {
     Stop,
     /// Check daemon status
     Status,
+    /// Issue a new API token and update the CLI config
+    RotateToken {
+        /// Seconds the old token stays valid (default: 300, 0 = revoke immediately)
+        #[arg(long)]
+        grace: Option<u64>,
+    },
 }
 
 #[tokio::main]
@@ -213,6 +401,8 @@ async fn main() -> Result<()> {
             tcp_keepalive,
             max_packet_size,
             window_size,
+            auth_timeout,
+            secret_command,
         } => {
             add_profile(
                 name,
@@ -233,6 +423,8 @@ async fn main() -> Result<()> {
                 tcp_keepalive,
                 max_packet_size,
                 window_size,
+                auth_timeout,
+                secret_command,
             )
             .await?;
         }
@@ -260,11 +452,11 @@ async fn main() -> Result<()> {
         Commands::Restart { name } => {
             restart_tunnel(name).await?;
         }
-        Commands::Status { name, all } => {
+        Commands::Status { name, all, json } => {
             if all {
-                show_all_tunnels_status().await?;
+                show_all_tunnels_status(json).await?;
             } else if let Some(n) = name {
-                show_tunnel_status(n).await?;
+                show_tunnel_status(n, json).await?;
             } else {
                 anyhow::bail!("Either provide a profile name or use --all to show all tunnel statuses");
             }
@@ -283,11 +475,59 @@ async fn main() -> Result<()> {
                     println!("Checking daemon status...");
                     // TODO: Implement daemon status
                 }
+                DaemonCommands::RotateToken { grace } => {
+                    rotate_daemon_token(grace).await?;
+                }
             }
         }
         Commands::Watch { name } => {
             watch_events(name).await?;
         }
+        Commands::Check { name } => {
+            check_profile(name).await?;
+        }
+        Commands::Token { action } => match action {
+            TokenCommands::Create { name, scopes, profiles, tags } => {
+                create_token(name, scopes, profiles, tags).await?;
+            }
+            TokenCommands::List => {
+                list_tokens().await?;
+            }
+            TokenCommands::Revoke { name } => {
+                revoke_token(name).await?;
+            }
+        },
+        Commands::Keys { action } => match action {
+            KeysCommands::Unlock { key } => {
+                unlock_key(key).await?;
+            }
+            KeysCommands::Lock { key } => {
+                lock_keys(key).await?;
+            }
+            KeysCommands::List => {
+                list_keys().await?;
+            }
+        },
+        Commands::Vault { action } => match action {
+            VaultCommands::Set { name } => {
+                vault_set(name)?;
+            }
+            VaultCommands::Passwd => {
+                vault_passwd()?;
+            }
+            VaultCommands::Migrate { to, name } => {
+                vault_migrate(to, name)?;
+            }
+        },
+        Commands::History { name, limit, json } => {
+            show_history(name, limit, json).await?;
+        }
+        Commands::Logs { name, follow, limit, level, duration } => {
+            show_logs(name, follow, limit, level, duration).await?;
+        }
+        Commands::Audit { since, profile, limit, json } => {
+            show_audit_log(since, profile, limit, json).await?;
+        }
     }
 
     Ok(())
@@ -296,6 +536,7 @@ async fn main() -> Result<()> {
 /// CLI event handler for interactive authentication and status display
 struct CliEventHandler {
     profile: Profile,
+    bound_address: Option<std::net::SocketAddr>,
 }
 
 impl TunnelEventHandler for CliEventHandler {
@@ -303,8 +544,21 @@ impl TunnelEventHandler for CliEventHandler {
         prompt_for_auth(request)
     }
 
+    fn on_auth_prompts(&mut self, request: &AuthRequest) -> Result<Vec<String>> {
+        // Server-provided title and instructions once, then each prompt in turn
+        println!();
+        for header in [&request.name, &request.instructions].into_iter().flatten() {
+            println!("{}", header.bold());
+        }
+        request
+            .prompts
+            .iter()
+            .map(|prompt| read_auth_input(&prompt.prompt, !prompt.echo))
+            .collect()
+    }
+
     fn on_connected(&mut self) {
-        announce_connected(&self.profile);
+        announce_connected(&self.profile, self.bound_address);
     }
 
     fn on_event(&mut self, event: &TunnelEvent) {
@@ -312,11 +566,29 @@ impl TunnelEventHandler for CliEventHandler {
             TunnelEvent::Starting { .. } => {
                 println!("{}", "Start request accepted, connecting...".dimmed());
             }
+            TunnelEvent::Connected { bound_address, .. } => {
+                self.bound_address = *bound_address;
+            }
+            TunnelEvent::Hook { stage, success, output, .. } => {
+                print_hook_result(&format!("{} hook", stage), *success, output);
+            }
             _ => {}
         }
     }
 }
 
+/// Print the result of a lifecycle hook reported by the daemon
+fn print_hook_result(label: &str, success: bool, output: &str) {
+    if success {
+        println!("{}", format!("✓ {} completed", label).dimmed());
+        for line in output.lines() {
+            println!("  {}", line.dimmed());
+        }
+    } else {
+        println!("{}", format!("⚠️  {} failed: {}", label, output).yellow());
+    }
+}
+
 /// Ensure daemon configuration is valid before attempting connection
 ///
 /// This function checks if the CLI config exists and offers to copy the
@@ -398,7 +670,10 @@ async fn start_tunnel(name: String) -> Result<()> {
     let client = create_daemon_client()?;
     let cli_config = config::CliConfig::load()?;
 
-    let mut handler = CliEventHandler { profile: profile.clone() };
+    let mut handler = CliEventHandler {
+        profile: profile.clone(),
+        bound_address: None,
+    };
 
     // Use the shared SSE-first helper
     start_tunnel_with_events(&client, &cli_config.daemon_config, tunnel_id, &profile, &mut handler).await
@@ -408,25 +683,29 @@ async fn start_tunnel(name: String) -> Result<()> {
 fn prompt_for_auth(auth_request: &AuthRequest) -> Result<String> {
     // Display the prompt exactly as received from the SSH server
     // The 'hidden' field (from SSH protocol's 'echo' field) determines input visibility
+    read_auth_input(&auth_request.prompt, auth_request.hidden)
+}
 
-    if auth_request.hidden {
+/// Read one answer, without echo when `hidden`
+fn read_auth_input(prompt: &str, hidden: bool) -> Result<String> {
+    if hidden {
         // Hidden input (password, passphrase, 2FA code, etc.)
         let response = Password::new()
-            .with_prompt(&auth_request.prompt)
+            .with_prompt(prompt)
             .interact()
             .context("Failed to read password input")?;
         Ok(response)
     } else {
         // Visible input
         let response: String = Input::new()
-            .with_prompt(&auth_request.prompt)
+            .with_prompt(prompt)
             .interact_text()
             .context("Failed to read input")?;
         Ok(response)
     }
 }
 
-fn announce_connected(profile: &Profile) {
+fn announce_connected(profile: &Profile, bound_address: Option<std::net::SocketAddr>) {
     println!();
     println!(
         "{}",
@@ -437,6 +716,9 @@ fn announce_connected(profile: &Profile) {
         .green()
         .bold()
     );
+    if let Some(addr) = bound_address {
+        println!("  Listening on: {}", addr.to_string().cyan());
+    }
     println!();
     println!(
         "{}",
@@ -517,6 +799,7 @@ async fn restart_tunnel(name: String) -> Result<()> {
 
     let mut handler = CliEventHandler {
         profile: profile.clone(),
+        bound_address: None,
     };
 
     match start_tunnel_with_events(&client, &cli_config.daemon_config, tunnel_id, &profile, &mut handler).await {
@@ -649,7 +932,7 @@ async fn stop_all_tunnels() -> Result<()> {
     Ok(())
 }
 
-async fn show_tunnel_status(name: String) -> Result<()> {
+async fn show_tunnel_status(name: String, json: bool) -> Result<()> {
     // Validate config BEFORE attempting daemon connection
     ensure_daemon_config()?;
 
@@ -660,12 +943,14 @@ async fn show_tunnel_status(name: String) -> Result<()> {
     let cli_config = config::CliConfig::load()?;
     let base_url = daemon_base_url()?;
 
-    println!(
-        "{}",
-        format!("Checking status for '{}' ({})", profile.metadata.name, tunnel_id)
-            .bold()
-    );
-    println!();
+    if !json {
+        println!(
+            "{}",
+            format!("Checking status for '{}' ({})", profile.metadata.name, tunnel_id)
+                .bold()
+        );
+        println!();
+    }
 
     // Query daemon for tunnel status
     let status_url = format!("{}/api/tunnels/{}/status", base_url, tunnel_id);
@@ -678,6 +963,16 @@ async fn show_tunnel_status(name: String) -> Result<()> {
     .context("Failed to query tunnel status")?;
 
     if status_resp.status() == reqwest::StatusCode::NOT_FOUND {
+        if json {
+            let not_active = TunnelStatusResponse {
+                id: tunnel_id,
+                status: TunnelStatus::NotConnected,
+                pending_auth: None,
+                bound_address: None,
+            };
+            println!("{}", serde_json::to_string_pretty(&not_active)?);
+            return Ok(());
+        }
         println!("{}", "Status: Not Active".dimmed());
         println!("{}", "The tunnel is not currently running in the daemon".dimmed());
         return Ok(());
@@ -692,6 +987,11 @@ async fn show_tunnel_status(name: String) -> Result<()> {
         .await
         .context("Failed to parse tunnel status")?;
 
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
     // Display status with color coding
     let status_text = match &status.status {
         TunnelStatus::Connected => "Connected".green().bold(),
@@ -705,6 +1005,9 @@ async fn show_tunnel_status(name: String) -> Result<()> {
     };
 
     println!("Status: {}", status_text);
+    if let Some(addr) = status.bound_address {
+        println!("Listening on: {}", addr.to_string().cyan());
+    }
 
     // Show pending auth if any
     if let Some(auth_req) = status.pending_auth {
@@ -729,7 +1032,7 @@ async fn show_tunnel_status(name: String) -> Result<()> {
     Ok(())
 }
 
-async fn show_all_tunnels_status() -> Result<()> {
+async fn show_all_tunnels_status(json: bool) -> Result<()> {
     use serde::Deserialize;
 
     // Validate config BEFORE attempting daemon connection
@@ -744,8 +1047,10 @@ async fn show_all_tunnels_status() -> Result<()> {
     let cli_config = config::CliConfig::load()?;
     let base_url = daemon_base_url()?;
 
-    println!("{}", "Querying daemon for tunnel statuses...".dimmed());
-    println!();
+    if !json {
+        println!("{}", "Querying daemon for tunnel statuses...".dimmed());
+        println!();
+    }
 
     // Query daemon for all tunnels
     let url = format!("{}/api/tunnels", base_url);
@@ -763,6 +1068,11 @@ async fn show_all_tunnels_status() -> Result<()> {
         .await
         .context("Failed to parse tunnels list")?;
 
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tunnels_response.tunnels)?);
+        return Ok(());
+    }
+
     if tunnels_response.tunnels.is_empty() {
         println!("{}", "No active tunnels found in daemon".yellow());
         println!();
@@ -813,10 +1123,19 @@ async fn show_all_tunnels_status() -> Result<()> {
             "N/A".to_string()
         };
 
-        let forwarding_str = if let Some(p) = profile {
-            ssh_tunnel_common::format_tunnel_description(&p.forwarding)
-        } else {
-            "N/A".to_string()
+        let forwarding_str = match (profile, tunnel.bound_address) {
+            (Some(p), Some(addr)) if p.forwarding.uses_ephemeral_port() => {
+                format!(
+                    "local: {} → remote: {}",
+                    addr,
+                    ssh_tunnel_common::format_host_port(
+                        p.forwarding.remote_host.as_deref().unwrap_or("localhost"),
+                        p.forwarding.remote_port.unwrap_or(0)
+                    )
+                )
+            }
+            (Some(p), _) => ssh_tunnel_common::format_tunnel_description(&p.forwarding),
+            (None, _) => "N/A".to_string(),
         };
 
         table.add_row(vec![
@@ -856,6 +1175,8 @@ async fn add_profile(
     tcp_keepalive: Option<bool>,
     max_packet_size: Option<u32>,
     window_size: Option<u32>,
+    auth_timeout: Option<u64>,
+    secret_command: Option<String>,
 ) -> Result<()> {
     println!("{}", "Creating new SSH tunnel profile".bold().green());
     println!();
@@ -902,7 +1223,17 @@ async fn add_profile(
     };
 
     // Authentication type selection
-    let (auth_type, key_path, password_storage) = if let Some(path) = key_path {
+    let (auth_type, key_path, password_storage) = if let Some(command) = secret_command {
+        // The daemon runs the command for the secret; nothing to ask or store here
+        let password_storage = PasswordStorage::Command { command };
+        match key_path {
+            Some(path) => {
+                validate_ssh_key(&path)?;
+                (AuthType::Key, Some(path), password_storage)
+            }
+            None => (AuthType::Password, None, password_storage),
+        }
+    } else if let Some(path) = key_path {
         // Key path provided via CLI argument - use key auth
         validate_ssh_key(&path)?;
 
@@ -1026,7 +1357,7 @@ async fn add_profile(
         anyhow::bail!("Local port is required in non-interactive mode");
     } else {
         let port: u16 = Input::new()
-            .with_prompt("Local port to bind")
+            .with_prompt("Local port to bind (0 = any free port)")
             .interact_text()?;
         validate_local_port(port, non_interactive)?;
         port
@@ -1088,10 +1419,14 @@ async fn add_profile(
             tcp_keepalive: tcp_keepalive.unwrap_or(false),
             max_packet_size: max_packet_size.unwrap_or(65536),
             window_size: window_size.unwrap_or(2097152),
+            auth_timeout_secs: auth_timeout,
         }
     } else if non_interactive {
         // Non-interactive mode with no CLI options: use defaults
-        TunnelOptions::default()
+        TunnelOptions {
+            auth_timeout_secs: auth_timeout,
+            ..TunnelOptions::default()
+        }
     } else {
         // Interactive mode with no CLI options: ask user if they want to configure
         println!();
@@ -1135,9 +1470,13 @@ async fn add_profile(
                     .with_prompt("Window size (bytes)")
                     .default(2097152)
                     .interact_text()?,
+                auth_timeout_secs: auth_timeout,
             }
         } else {
-            TunnelOptions::default()
+            TunnelOptions {
+                auth_timeout_secs: auth_timeout,
+                ..TunnelOptions::default()
+            }
         }
     };
 
@@ -1167,6 +1506,15 @@ async fn add_profile(
     // Validate the profile
     profile.validate().context("Profile validation failed")?;
 
+    // Warn (but don't refuse) when the local port is already taken
+    let others = load_all_profiles().unwrap_or_default();
+    let mut conflicts = preflight::check_profile_conflicts(&profile, &others);
+    conflicts.extend(preflight::check_port_in_use(&profile, &preflight::listening_sockets()));
+    if !conflicts.is_empty() {
+        println!();
+        print_preflight_issues(&conflicts);
+    }
+
     // Save the profile
     let profile_path = save_profile(&profile, false)?;
 
@@ -1217,6 +1565,7 @@ fn store_password_in_keychain(profile_id: &Uuid, password: &str) -> Result<bool>
         println!("{}", "⚠️  System keychain not available".yellow());
         println!("{}", "    Password will NOT be stored - you'll be prompted when starting tunnels".dimmed());
         println!("{}", "    (This is normal on headless servers and containers)".dimmed());
+        println!("{}", "    To store it in the encrypted vault file instead: ssh-tunnel vault set <profile>".dimmed());
         return Ok(false); // Not stored, but not an error
     }
 
@@ -1236,6 +1585,165 @@ fn store_password_in_keychain(profile_id: &Uuid, password: &str) -> Result<bool>
     }
 }
 
+/// Ask for the master passphrase and unlock the secret vault for changes
+///
+/// With `create`, a missing vault is created with a new (confirmed) passphrase.
+/// Other processes can't change the vault while the returned lock is held.
+fn unlock_vault(create: bool) -> Result<(Vault, VaultLock)> {
+    let path = Vault::default_path()?;
+    if path.exists() {
+        let lock = Vault::lock(&path)?;
+        let passphrase = Password::new()
+            .with_prompt("Vault master passphrase")
+            .interact()?;
+        Ok((Vault::open(&path, &passphrase)?, lock))
+    } else if create {
+        let lock = Vault::lock(&path)?;
+        println!("{}", format!("Creating a new secret vault at {}", path.display()).dimmed());
+        let passphrase = Password::new()
+            .with_prompt("New vault master passphrase")
+            .with_confirmation("Confirm master passphrase", "Passphrases don't match")
+            .interact()?;
+        // Another process may have created it while we waited for the lock
+        Ok((Vault::open_or_create(&path, &passphrase)?, lock))
+    } else {
+        anyhow::bail!(
+            "No secret vault at {} (store a secret with `ssh-tunnel vault set <profile>`)",
+            path.display()
+        );
+    }
+}
+
+/// Store a profile's secret in the vault and switch the profile to it
+fn vault_set(name: String) -> Result<()> {
+    let mut profile = load_profile_by_name(&name)?;
+    let key_path = match profile.connection.auth_type {
+        AuthType::Key => profile.connection.key_path.clone(),
+        _ => None,
+    };
+
+    let (mut vault, _lock) = unlock_vault(true)?;
+    let secret = Password::new()
+        .with_prompt(if key_path.is_some() { "SSH key passphrase" } else { "SSH password" })
+        .interact()?;
+
+    if let Some(key_path) = key_path.filter(|p| p.exists()) {
+        if let Err(e) = validate_key_passphrase(&key_path, &secret) {
+            anyhow::bail!("Invalid passphrase: {}", e);
+        }
+    }
+
+    vault.set(profile.metadata.id, &secret);
+    vault.save()?;
+
+    if profile.connection.password_storage != PasswordStorage::File {
+        if profile.connection.password_storage == PasswordStorage::Keychain {
+            // The vault copy replaces it; ignore a keychain that went away
+            let _ = ssh_tunnel_common::remove_password(&profile.metadata.id);
+        }
+        profile.connection.password_storage = PasswordStorage::File;
+        profile.metadata.modified_at = Utc::now();
+        save_profile(&profile, true)?;
+    }
+
+    println!("{}", format!("✓ Secret of '{}' stored in the vault", name).green());
+    Ok(())
+}
+
+/// Re-encrypt the vault under a new master passphrase
+fn vault_passwd() -> Result<()> {
+    let (mut vault, _lock) = unlock_vault(false)?;
+    let passphrase = Password::new()
+        .with_prompt("New vault master passphrase")
+        .with_confirmation("Confirm master passphrase", "Passphrases don't match")
+        .interact()?;
+    vault.change_passphrase(&passphrase)?;
+    vault.save()?;
+
+    println!("{}", "✓ Master passphrase changed".green());
+    println!(
+        "{}",
+        "  A running daemon asks for the new one the next time it needs the vault".dimmed()
+    );
+    Ok(())
+}
+
+/// Move secrets to `to` from the other backend, updating the profiles
+fn vault_migrate(to: SecretBackend, name: Option<String>) -> Result<()> {
+    let from = match to {
+        SecretBackend::Keychain => SecretBackend::File,
+        SecretBackend::File => SecretBackend::Keychain,
+    };
+    let profiles = match name {
+        Some(name) => vec![load_profile_by_name(&name)?],
+        None => load_all_profiles()?,
+    };
+    let candidates: Vec<Profile> = profiles
+        .into_iter()
+        .filter(|p| p.connection.password_storage == from.storage())
+        .collect();
+    if candidates.is_empty() {
+        println!("No profiles store their secret in the {}", from.describe());
+        return Ok(());
+    }
+    if !ssh_tunnel_common::is_keychain_available() {
+        anyhow::bail!("System keychain not available");
+    }
+
+    let (mut vault, _lock) = unlock_vault(to == SecretBackend::File)?;
+
+    // Copy first; a profile only switches once its secret is in place
+    let mut moved = Vec::new();
+    for profile in candidates {
+        let id = profile.metadata.id;
+        let copied = match to {
+            SecretBackend::File => {
+                ssh_tunnel_common::get_password(&id).map(|secret| vault.set(id, &secret))
+            }
+            SecretBackend::Keychain => match vault.get(&id) {
+                Some(secret) => ssh_tunnel_common::store_password(&id, secret),
+                None => Err(ssh_tunnel_common::Error::Vault(
+                    "No secret for this profile in the vault".to_string(),
+                )),
+            },
+        };
+        match copied {
+            Ok(()) => moved.push(profile),
+            Err(e) => println!(
+                "{}",
+                format!("⚠️  Skipping '{}': {}", profile.metadata.name, e).yellow()
+            ),
+        }
+    }
+    if to == SecretBackend::File {
+        vault.save()?;
+    }
+
+    for mut profile in moved {
+        let id = profile.metadata.id;
+        profile.connection.password_storage = to.storage();
+        profile.metadata.modified_at = Utc::now();
+        save_profile(&profile, true)?;
+        match to {
+            SecretBackend::File => {
+                let _ = ssh_tunnel_common::remove_password(&id);
+            }
+            SecretBackend::Keychain => {
+                vault.remove(&id);
+            }
+        }
+        println!(
+            "{}",
+            format!("  ✓ '{}' moved to the {}", profile.metadata.name, to.describe()).green()
+        );
+    }
+    if to == SecretBackend::Keychain {
+        vault.save()?;
+    }
+
+    Ok(())
+}
+
 fn validate_ssh_key(key_path: &PathBuf) -> Result<()> {
     // Check if file exists
     if !key_path.exists() {
@@ -1314,7 +1822,8 @@ fn validate_key_passphrase(key_path: &PathBuf, passphrase: &str) -> Result<()> {
 }
 
 fn validate_local_port(port: u16, non_interactive: bool) -> Result<()> {
-    if port <= 1024 {
+    // Port 0 lets the daemon pick any free port when the tunnel starts
+    if port != 0 && port <= 1024 {
         let warning = format!(
             "⚠️  Port {} requires root/admin privileges (privileged port)",
             port
@@ -1576,49 +2085,12 @@ async fn watch_events(name: Option<String>) -> Result<()> {
                 match serde_json::from_str::<TunnelEvent>(json_str) {
                     Ok(ev) => {
                         // Optional filter by tunnel id
-                        if let Some(fid) = filter_id {
-                            let id = match &ev {
-                                TunnelEvent::Starting { id }
-                                | TunnelEvent::Connected { id }
-                                | TunnelEvent::Disconnected { id, .. }
-                                | TunnelEvent::Error { id, .. }
-                                | TunnelEvent::AuthRequired { id, .. } => Some(id),
-                                TunnelEvent::Heartbeat { .. } => None,
-                            };
-                            if let Some(id) = id {
-                                if *id != fid {
-                                    continue;
-                                }
-                            }
-                        }
-
-                        match ev {
-                            TunnelEvent::Starting { id } => {
-                                println!("{}", format!("Starting tunnel {id}").cyan());
-                            }
-                            TunnelEvent::Connected { id } => {
-                                println!("{}", format!("Tunnel {id} connected").green());
-                            }
-                            TunnelEvent::Disconnected { id, reason } => {
-                                println!(
-                                    "{}",
-                                    format!("Tunnel {id} disconnected: {reason}").yellow()
-                                );
-                            }
-                            TunnelEvent::Error { id, error } => {
-                                eprintln!("{}", format!("Tunnel {id} error: {error}").red());
-                            }
-                            TunnelEvent::AuthRequired { id, request } => {
-                                println!(
-                                    "{}",
-                                    format!("Auth required for tunnel {id}: {}", request.prompt)
-                                        .magenta()
-                                );
-                            }
-                            TunnelEvent::Heartbeat { .. } => {
-                                // Ignore heartbeats in watch mode
+                        if let (Some(fid), Some(id)) = (filter_id, ev.tunnel_id()) {
+                            if id != fid {
+                                continue;
                             }
                         }
+                        print_watch_event(ev, filter_id);
                     }
                     Err(e) => {
                         eprintln!("Failed to parse event JSON: {e} (line: {json_str})");
@@ -1631,6 +2103,94 @@ async fn watch_events(name: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Print one event in `watch` mode, prefixed with its local time and the
+/// tunnel's profile name (or ID for daemons that don't send names)
+fn print_watch_event(ev: TunnelEvent, filter_id: Option<Uuid>) {
+    let prefix = ev.meta().map(|meta| {
+        let name = match &meta.profile_name {
+            Some(name) => name.clone(),
+            None => ev.tunnel_id().map(|id| id.to_string()).unwrap_or_default(),
+        };
+        let time = meta.timestamp.with_timezone(&chrono::Local).format("%H:%M:%S");
+        format!("[{time}] {name}")
+    });
+    let prefix = prefix.unwrap_or_default();
+
+    match ev {
+        TunnelEvent::Starting { .. } => {
+            println!("{}", format!("{prefix}: starting").cyan());
+        }
+        TunnelEvent::Connected { bound_address, .. } => match bound_address {
+            Some(addr) => println!(
+                "{}",
+                format!("{prefix}: connected (listening on {addr})").green()
+            ),
+            None => println!("{}", format!("{prefix}: connected").green()),
+        },
+        TunnelEvent::Disconnected { reason, .. } => {
+            println!("{}", format!("{prefix}: disconnected: {reason}").yellow());
+        }
+        TunnelEvent::Error { error, .. } => {
+            eprintln!("{}", format!("{prefix}: error: {error}").red());
+        }
+        TunnelEvent::AuthRequired { request, .. } => {
+            println!(
+                "{}",
+                format!("{prefix}: auth required: {}", request.prompt).magenta()
+            );
+        }
+        TunnelEvent::AuthResolved { .. } => {
+            println!("{}", format!("{prefix}: auth answered").dimmed());
+        }
+        TunnelEvent::AuthCancelled { reason, .. } => {
+            println!("{}", format!("{prefix}: auth cancelled: {reason}").dimmed());
+        }
+        TunnelEvent::Hook { stage, success, output, .. } => {
+            print_hook_result(&format!("{prefix}: {stage} hook"), success, &output);
+        }
+        TunnelEvent::Stats { stats, .. } => {
+            println!(
+                "{}",
+                format!(
+                    "{prefix}: {} sent, {} received, {} active / {} total connections",
+                    format_bytes(stats.bytes_sent),
+                    format_bytes(stats.bytes_received),
+                    stats.active_connections,
+                    stats.total_connections
+                )
+                .dimmed()
+            );
+        }
+        TunnelEvent::Heartbeat { .. } => {
+            // Ignore heartbeats in watch mode
+        }
+        TunnelEvent::Snapshot { tunnels } => {
+            println!("{}", "Missed events, current state:".dimmed());
+            for tunnel in tunnels
+                .iter()
+                .filter(|t| filter_id.is_none() || filter_id == Some(t.id))
+            {
+                println!("  Tunnel {} {:?}", tunnel.id, tunnel.status);
+            }
+        }
+    }
+}
+
+/// Human-readable byte count (1024-based)
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
 /// Delete a profile by name
 async fn delete_profile(name: String) -> Result<()> {
     // Check if profile exists
@@ -1667,17 +2227,399 @@ async fn delete_profile(name: String) -> Result<()> {
 }
 
 /// Show detailed information about a profile
-async fn show_profile_info(name: String) -> Result<()> {
-    // Load the profile
+async fn check_profile(name: String) -> Result<()> {
     let profile = load_profile_by_name(&name)?;
 
+    println!(
+        "{}",
+        format!("Preflight check for '{}'", profile.metadata.name).bold()
+    );
     println!();
-    println!("{}", format!("Profile: {}", profile.metadata.name).bold().green());
-    println!("  ID: {}", profile.metadata.id.to_string().dimmed());
 
-    if let Some(desc) = &profile.metadata.description {
-        println!("  Description: {}", desc);
-    }
+    // Prefer the daemon's view: it sees running tunnels and its own key files
+    let report = match fetch_preflight_report(&profile.metadata.id).await {
+        Ok(report) => report,
+        Err(e) => {
+            println!(
+                "{}",
+                format!("Daemon preflight unavailable ({}), running local checks only", e).dimmed()
+            );
+            println!();
+            let others = load_all_profiles().unwrap_or_default();
+            preflight::run_checks(&profile, &others, &[])
+        }
+    };
+
+    if report.issues.is_empty() {
+        println!("{}", "✓ No problems found".green().bold());
+        return Ok(());
+    }
+
+    print_preflight_issues(&report.issues);
+
+    if report.has_errors() {
+        anyhow::bail!("Profile '{}' will not start as configured", profile.metadata.name);
+    }
+
+    Ok(())
+}
+
+async fn fetch_preflight_report(id: &Uuid) -> Result<PreflightReport> {
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/tunnels/{}/preflight", daemon_base_url()?, id);
+
+    let response = ssh_tunnel_common::add_auth_header(client.get(&url), &cli_config.daemon_config)?
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("daemon returned {}", response.status());
+    }
+
+    response.json().await.context("Failed to parse preflight report")
+}
+
+fn print_preflight_issues(issues: &[PreflightIssue]) {
+    for issue in issues {
+        match issue.severity {
+            PreflightSeverity::Error => println!("{}", format!("✗ {}", issue.message).red()),
+            PreflightSeverity::Warning => println!("{}", format!("⚠️  {}", issue.message).yellow()),
+        }
+    }
+}
+
+/// Error message from a failed daemon response (`{"error": ...}` body if present)
+async fn daemon_error(response: reqwest::Response) -> String {
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    match body.get("error").and_then(|e| e.as_str()) {
+        Some(error) => format!("{} ({})", error, status),
+        None => status.to_string(),
+    }
+}
+
+async fn create_token(
+    name: String,
+    scopes: Vec<TokenScope>,
+    profiles: Vec<String>,
+    tags: Vec<String>,
+) -> Result<()> {
+    // Profiles may be given by name (resolved locally) or by ID
+    let mut profile_ids = Vec::new();
+    for profile in &profiles {
+        let id = match Uuid::parse_str(profile) {
+            Ok(id) => id,
+            Err(_) => load_profile_by_name(profile)?.metadata.id,
+        };
+        profile_ids.push(id);
+    }
+
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/tokens", daemon_base_url()?);
+    let request = CreateTokenRequest {
+        name,
+        scopes,
+        profile_ids,
+        profile_tags: tags,
+    };
+
+    let response = ssh_tunnel_common::add_auth_header(client.post(&url), &cli_config.daemon_config)?
+        .json(&request)
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to create token: {}", daemon_error(response).await);
+    }
+
+    let created: CreateTokenResponse = response
+        .json()
+        .await
+        .context("Failed to parse token response")?;
+
+    println!("{}", format!("✓ Token '{}' created", created.info.name).green());
+    println!();
+    println!("  {}", created.token.bold());
+    println!();
+    println!(
+        "{}",
+        "Store this token now; it cannot be shown again. Clients send it in the X-Tunnel-Token header."
+            .dimmed()
+    );
+
+    Ok(())
+}
+
+async fn list_tokens() -> Result<()> {
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/tokens", daemon_base_url()?);
+
+    let response = ssh_tunnel_common::add_auth_header(client.get(&url), &cli_config.daemon_config)?
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to list tokens: {}", daemon_error(response).await);
+    }
+
+    let tokens: Vec<TokenInfo> = response
+        .json()
+        .await
+        .context("Failed to parse token list")?;
+
+    if tokens.is_empty() {
+        println!("No additional tokens. Create one with: ssh-tunnel token create <name> --scope read");
+        return Ok(());
+    }
+
+    // Show profile names where the ID is known locally
+    let all_profiles = load_all_profiles().unwrap_or_default();
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Name").add_attribute(Attribute::Bold),
+            Cell::new("Scopes").add_attribute(Attribute::Bold),
+            Cell::new("Profiles").add_attribute(Attribute::Bold),
+            Cell::new("Created").add_attribute(Attribute::Bold),
+        ]);
+
+    for token in &tokens {
+        let scopes = token
+            .scopes
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut allowed: Vec<String> = token
+            .profile_ids
+            .iter()
+            .map(|id| {
+                all_profiles
+                    .iter()
+                    .find(|p| &p.metadata.id == id)
+                    .map(|p| p.metadata.name.clone())
+                    .unwrap_or_else(|| id.to_string())
+            })
+            .collect();
+        allowed.extend(token.profile_tags.iter().map(|tag| format!("tag:{}", tag)));
+        let allowed = if allowed.is_empty() {
+            "all".to_string()
+        } else {
+            allowed.join(", ")
+        };
+
+        table.add_row(vec![
+            Cell::new(&token.name),
+            Cell::new(scopes),
+            Cell::new(allowed),
+            Cell::new(token.created_at.format("%Y-%m-%d %H:%M").to_string()),
+        ]);
+    }
+
+    println!("{table}");
+    Ok(())
+}
+
+async fn revoke_token(name: String) -> Result<()> {
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/tokens/{}", daemon_base_url()?, name);
+
+    let response = ssh_tunnel_common::add_auth_header(client.delete(&url), &cli_config.daemon_config)?
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to revoke token: {}", daemon_error(response).await);
+    }
+
+    println!("{}", format!("✓ Token '{}' revoked", name).green());
+    Ok(())
+}
+
+async fn unlock_key(key: String) -> Result<()> {
+    // A profile name stands for its key
+    let key_path = match load_profile_by_name(&key) {
+        Ok(profile) => match (profile.connection.auth_type, profile.connection.key_path) {
+            (AuthType::Key, Some(path)) => path.to_string_lossy().into_owned(),
+            _ => anyhow::bail!("Profile '{}' doesn't use an SSH key", key),
+        },
+        Err(_) => key,
+    };
+    // The daemon unlocks keys by their path relative to its ~/.ssh
+    let key_path = dirs::home_dir()
+        .and_then(|home| {
+            let relative = std::path::Path::new(&key_path).strip_prefix(home.join(".ssh")).ok()?;
+            Some(relative.to_string_lossy().into_owned())
+        })
+        .unwrap_or(key_path);
+
+    let passphrase = Password::new()
+        .with_prompt(format!("Passphrase for {}", key_path))
+        .interact()?;
+
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/keys/unlock", daemon_base_url()?);
+
+    let response = ssh_tunnel_common::add_auth_header(client.post(&url), &cli_config.daemon_config)?
+        .json(&UnlockKeyRequest { key_path, passphrase })
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to unlock key: {}", daemon_error(response).await);
+    }
+
+    let unlocked: UnlockedKey = response
+        .json()
+        .await
+        .context("Failed to parse unlock response")?;
+
+    println!("{}", format!("✓ Key {} unlocked", unlocked.path).green());
+    println!("  {} {}", unlocked.algorithm, unlocked.fingerprint.dimmed());
+    match unlocked.expires_at {
+        Some(expires_at) => println!("  Until {}", expires_at.format("%Y-%m-%d %H:%M")),
+        None => println!("  Until `ssh-tunnel keys lock`"),
+    }
+    Ok(())
+}
+
+async fn lock_keys(key: Option<String>) -> Result<()> {
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/keys/lock", daemon_base_url()?);
+
+    let response = ssh_tunnel_common::add_auth_header(client.post(&url), &cli_config.daemon_config)?
+        .json(&LockKeysRequest { key })
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to lock keys: {}", daemon_error(response).await);
+    }
+
+    let result: serde_json::Value = response.json().await.unwrap_or_default();
+    let message = result["message"].as_str().unwrap_or("Keys locked");
+    println!("{}", format!("✓ {}", message).green());
+    Ok(())
+}
+
+async fn list_keys() -> Result<()> {
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/keys", daemon_base_url()?);
+
+    let response = ssh_tunnel_common::add_auth_header(client.get(&url), &cli_config.daemon_config)?
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to list keys: {}", daemon_error(response).await);
+    }
+
+    let keys: Vec<UnlockedKey> = response
+        .json()
+        .await
+        .context("Failed to parse key list")?;
+
+    if keys.is_empty() {
+        println!("No unlocked keys. Unlock one with: ssh-tunnel keys unlock <profile>");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Key").add_attribute(Attribute::Bold),
+            Cell::new("Type").add_attribute(Attribute::Bold),
+            Cell::new("Fingerprint").add_attribute(Attribute::Bold),
+            Cell::new("Unlocked").add_attribute(Attribute::Bold),
+            Cell::new("Expires").add_attribute(Attribute::Bold),
+        ]);
+
+    for key in &keys {
+        table.add_row(vec![
+            Cell::new(&key.path),
+            Cell::new(&key.algorithm),
+            Cell::new(&key.fingerprint),
+            Cell::new(key.unlocked_at.format("%Y-%m-%d %H:%M").to_string()),
+            Cell::new(
+                key.expires_at
+                    .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_else(|| "never".to_string()),
+            ),
+        ]);
+    }
+
+    println!("{table}");
+    Ok(())
+}
+
+async fn rotate_daemon_token(grace_secs: Option<u64>) -> Result<()> {
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/daemon/token/rotate", daemon_base_url()?);
+
+    let response = ssh_tunnel_common::add_auth_header(client.post(&url), &cli_config.daemon_config)?
+        .json(&RotateTokenRequest { grace_secs })
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to rotate token: {}", daemon_error(response).await);
+    }
+
+    let rotated: RotateTokenResponse = response
+        .json()
+        .await
+        .context("Failed to parse rotation response")?;
+
+    config::CliConfig::save_auth_token(&rotated.token)?;
+
+    println!("{}", "✓ Daemon token rotated".green());
+    println!(
+        "  Updated {}",
+        config::CliConfig::config_path()?.display()
+    );
+    if rotated.grace_secs > 0 {
+        println!(
+            "  The old token stays valid for {}s; update other clients before then.",
+            rotated.grace_secs
+        );
+    } else {
+        println!("  The old token no longer works; update other clients now.");
+    }
+
+    Ok(())
+}
+
+async fn show_profile_info(name: String) -> Result<()> {
+    // Load the profile
+    let profile = load_profile_by_name(&name)?;
+
+    println!();
+    println!("{}", format!("Profile: {}", profile.metadata.name).bold().green());
+    println!("  ID: {}", profile.metadata.id.to_string().dimmed());
+
+    if let Some(desc) = &profile.metadata.description {
+        println!("  Description: {}", desc);
+    }
 
     if !profile.metadata.tags.is_empty() {
         println!("  Tags: {}", profile.metadata.tags.join(", ").cyan());
@@ -1694,6 +2636,13 @@ async fn show_profile_info(name: String) -> Result<()> {
         println!("    Key:  {}", key_path.display());
     }
 
+    match &profile.connection.password_storage {
+        PasswordStorage::None => {}
+        PasswordStorage::Keychain => println!("    Secret: system keychain"),
+        PasswordStorage::File => println!("    Secret: encrypted vault file"),
+        PasswordStorage::Command { command } => println!("    Secret: command `{}`", command),
+    }
+
     println!();
     println!("{}", "  Port Forwarding:".bold());
     println!("    Type:        {:?}", profile.forwarding.forwarding_type);
@@ -1725,8 +2674,352 @@ async fn show_profile_info(name: String) -> Result<()> {
     println!("    TCP Keepalive:     {}", profile.options.tcp_keepalive);
     println!("    Max Packet Size:   {} bytes", profile.options.max_packet_size);
     println!("    Window Size:       {} bytes", profile.options.window_size);
+    if let Some(secs) = profile.options.auth_timeout_secs {
+        println!("    Auth Timeout:      {} seconds", secs);
+    }
 
     println!();
 
     Ok(())
 }
+
+async fn show_audit_log(
+    since: Option<String>,
+    profile: Option<String>,
+    limit: usize,
+    json: bool,
+) -> Result<()> {
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!("{}/api/audit", daemon_base_url()?);
+
+    let mut query = vec![("limit", limit.to_string())];
+    if let Some(since) = since {
+        query.push(("since", parse_since(&since)?.to_rfc3339()));
+    }
+    if let Some(profile) = profile {
+        query.push(("profile", profile));
+    }
+
+    let request = client.get(&url).query(&query);
+    let response = ssh_tunnel_common::add_auth_header(request, &cli_config.daemon_config)?
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to read audit log: {}", daemon_error(response).await);
+    }
+
+    let entries: Vec<AuditEntry> = response
+        .json()
+        .await
+        .context("Failed to parse audit log")?;
+
+    if json {
+        for entry in &entries {
+            println!("{}", serde_json::to_string(entry)?);
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No matching audit entries");
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Time").add_attribute(Attribute::Bold),
+            Cell::new("Action").add_attribute(Attribute::Bold),
+            Cell::new("Actor").add_attribute(Attribute::Bold),
+            Cell::new("Source").add_attribute(Attribute::Bold),
+            Cell::new("Profile").add_attribute(Attribute::Bold),
+            Cell::new("Result").add_attribute(Attribute::Bold),
+        ]);
+
+    for entry in &entries {
+        let profile = entry
+            .profile_name
+            .clone()
+            .or_else(|| entry.profile_id.map(|id| id.to_string()))
+            .unwrap_or_else(|| "-".to_string());
+        let (mark, color) = if entry.success {
+            ("✓", Color::Green)
+        } else {
+            ("✗", Color::Red)
+        };
+        let result = match &entry.detail {
+            Some(detail) => format!("{} {}", mark, detail),
+            None => mark.to_string(),
+        };
+
+        table.add_row(vec![
+            Cell::new(
+                entry
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+            ),
+            Cell::new(entry.action.as_str()),
+            Cell::new(entry.actor.as_deref().unwrap_or("-")),
+            Cell::new(entry.source.as_deref().unwrap_or("-")),
+            Cell::new(profile),
+            Cell::new(result).fg(color),
+        ]);
+    }
+
+    println!("{}", table);
+    Ok(())
+}
+
+async fn show_history(name: String, limit: usize, json: bool) -> Result<()> {
+    let profile = load_profile_by_name(&name)?;
+    let client = create_daemon_client()?;
+    let cli_config = config::CliConfig::load()?;
+    let url = format!(
+        "{}/api/tunnels/{}/history",
+        daemon_base_url()?,
+        profile.metadata.id
+    );
+
+    let request = client.get(&url).query(&[("limit", limit)]);
+    let response = ssh_tunnel_common::add_auth_header(request, &cli_config.daemon_config)?
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to read session history: {}", daemon_error(response).await);
+    }
+
+    let sessions: Vec<TunnelSession> = response
+        .json()
+        .await
+        .context("Failed to parse session history")?;
+
+    if json {
+        for session in &sessions {
+            println!("{}", serde_json::to_string(session)?);
+        }
+        return Ok(());
+    }
+
+    if sessions.is_empty() {
+        println!("No sessions recorded for '{}'", profile.metadata.name);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Started").add_attribute(Attribute::Bold),
+            Cell::new("Ended").add_attribute(Attribute::Bold),
+            Cell::new("Uptime").add_attribute(Attribute::Bold),
+            Cell::new("Sent").add_attribute(Attribute::Bold),
+            Cell::new("Received").add_attribute(Attribute::Bold),
+            Cell::new("End").add_attribute(Attribute::Bold),
+        ]);
+
+    for session in &sessions {
+        let (mark, color) = if session.failed {
+            ("✗", Color::Red)
+        } else {
+            ("✓", Color::Green)
+        };
+        table.add_row(vec![
+            Cell::new(
+                session
+                    .started_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+            ),
+            Cell::new(
+                session
+                    .ended_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S"),
+            ),
+            Cell::new(match session.uptime() {
+                Some(uptime) => format_duration(uptime),
+                None => "never connected".to_string(),
+            }),
+            Cell::new(format_bytes(session.bytes_sent)),
+            Cell::new(format_bytes(session.bytes_received)),
+            Cell::new(format!("{} {}", mark, session.end_reason)).fg(color),
+        ]);
+    }
+    println!("{}", table);
+
+    let summary = HistorySummary::from_sessions(&sessions);
+    println!(
+        "{} sessions, {} failed, {} connected in total",
+        summary.sessions,
+        summary.failed,
+        format_duration(summary.uptime)
+    );
+    if let Some((reason, count)) = summary.top_failure {
+        println!("Most frequent failure ({}×): {}", count, reason.red());
+    }
+    Ok(())
+}
+
+async fn show_logs(
+    name: String,
+    follow: bool,
+    limit: usize,
+    level: Option<LogLevel>,
+    duration: Option<String>,
+) -> Result<()> {
+    let profile = load_profile_by_name(&name)?;
+    let client = create_daemon_client()?;
+    let logs_url = format!(
+        "{}/api/tunnels/{}/logs",
+        daemon_base_url()?,
+        profile.metadata.id
+    );
+
+    if let Some(level) = level {
+        let duration_secs = match duration {
+            Some(value) => Some(
+                parse_age(&value)
+                    .and_then(|age| u64::try_from(age.num_seconds()).ok())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Invalid --for '{}': use an age like 90s, 10m, 1h", value)
+                    })?,
+            ),
+            None => None,
+        };
+        let request = client
+            .put(format!("{}/level", logs_url))
+            .json(&SetLogLevelRequest { level, duration_secs });
+        let response = add_auth_header(request)?
+            .send()
+            .await
+            .context("Failed to reach daemon")?;
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to set log level: {}", daemon_error(response).await);
+        }
+        let applied: TunnelLogLevel = response.json().await.context("Failed to parse log level")?;
+        match applied.until {
+            Some(until) => println!(
+                "{}",
+                format!(
+                    "Capturing '{}' at {} until {}",
+                    profile.metadata.name,
+                    applied.level,
+                    until.with_timezone(&chrono::Local).format("%H:%M:%S")
+                )
+                .dimmed()
+            ),
+            None => println!(
+                "{}",
+                format!("Capturing '{}' at {}", profile.metadata.name, applied.level).dimmed()
+            ),
+        }
+    }
+
+    let mut request = client.get(&logs_url).query(&[("limit", limit)]);
+    if follow {
+        request = request.query(&[("follow", true)]);
+    }
+    let response = add_auth_header(request)?
+        .send()
+        .await
+        .context("Failed to reach daemon")?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to read tunnel logs: {}", daemon_error(response).await);
+    }
+
+    if !follow {
+        let entries: Vec<TunnelLogEntry> = response
+            .json()
+            .await
+            .context("Failed to parse tunnel logs")?;
+        if entries.is_empty() {
+            println!("No log lines captured for '{}'", profile.metadata.name);
+        }
+        for entry in &entries {
+            print_log_entry(entry);
+        }
+        return Ok(());
+    }
+
+    // Same line-based SSE parsing as `watch`
+    let mut stream = response.bytes_stream();
+    let mut buffer = String::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("Error reading from log stream")?;
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap_or(""));
+
+        while let Some(pos) = buffer.find('\n') {
+            let line = buffer[..pos].trim_end().to_string();
+            buffer.drain(..=pos);
+
+            let Some(json_str) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            match serde_json::from_str::<TunnelLogEntry>(json_str) {
+                Ok(entry) => print_log_entry(&entry),
+                Err(e) => eprintln!("Failed to parse log line: {e} (line: {json_str})"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_log_entry(entry: &TunnelLogEntry) {
+    let time = entry.timestamp.with_timezone(&chrono::Local).format("%H:%M:%S%.3f");
+    let level = format!("{:>5}", entry.level.as_str().to_uppercase());
+    let level = match entry.level {
+        LogLevel::Error => level.red().bold(),
+        LogLevel::Warn => level.yellow(),
+        LogLevel::Info => level.green(),
+        LogLevel::Debug => level.blue(),
+        LogLevel::Trace => level.dimmed(),
+    };
+    println!(
+        "{} {} {} {}",
+        time.to_string().dimmed(),
+        level,
+        format!("{}:", entry.target).dimmed(),
+        entry.message
+    );
+}
+
+/// Parse `--since`: an RFC 3339 timestamp or an age such as `90s`, `30m`, `12h`, `7d`
+fn parse_since(value: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    if let Ok(timestamp) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&chrono::Utc));
+    }
+
+    let age = parse_age(value).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid --since '{}': use RFC 3339 or an age like 30m, 12h, 7d",
+            value
+        )
+    })?;
+    Ok(chrono::Utc::now() - age)
+}
+
+/// Parse an age such as `90s`, `30m`, `12h`, `7d`
+fn parse_age(value: &str) -> Option<chrono::Duration> {
+    let split = value
+        .len()
+        .checked_sub(1)
+        .filter(|&i| value.is_char_boundary(i))?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().ok()?;
+    match unit {
+        "s" => Some(chrono::Duration::seconds(amount)),
+        "m" => Some(chrono::Duration::minutes(amount)),
+        "h" => Some(chrono::Duration::hours(amount)),
+        "d" => Some(chrono::Duration::days(amount)),
+        _ => None,
+    }
+}
@@ -13,6 +13,7 @@ use crate::types::{AuthType, ForwardingType};
 
 /// Complete tunnel profile configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Profile {
     #[serde(flatten)]
     pub metadata: ProfileMetadata,
@@ -20,10 +21,13 @@ pub struct Profile {
     pub forwarding: ForwardingConfig,
     #[serde(default)]
     pub options: TunnelOptions,
+    #[serde(default, skip_serializing_if = "LifecycleHooks::is_empty")]
+    pub hooks: LifecycleHooks,
 }
 
 /// Profile metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ProfileMetadata {
     /// Unique profile identifier
     pub id: Uuid,
@@ -43,13 +47,19 @@ pub struct ProfileMetadata {
 
 /// Where password/passphrase is stored
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum PasswordStorage {
     /// Not stored - user will be prompted
     None,
     /// Stored in system keychain/keyring
     Keychain,
-    /// Stored in encrypted file (future feature)
+    /// Stored in the encrypted secret vault file (see [`crate::vault`])
     File,
+    /// Printed on stdout by a command run by the daemon (`pass`, `gopass`,
+    /// `op`, a Vault agent ...)
+    ///
+    /// Written as `password_storage = { command = "pass show ssh/db" }`.
+    Command { command: String },
 }
 
 impl Default for PasswordStorage {
@@ -67,6 +77,12 @@ impl Serialize for PasswordStorage {
             PasswordStorage::None => serializer.serialize_str("none"),
             PasswordStorage::Keychain => serializer.serialize_str("keychain"),
             PasswordStorage::File => serializer.serialize_str("file"),
+            PasswordStorage::Command { command } => {
+                use serde::ser::SerializeStruct;
+                let mut state = serializer.serialize_struct("PasswordStorage", 1)?;
+                state.serialize_field("command", command)?;
+                state.end()
+            }
         }
     }
 }
@@ -82,7 +98,9 @@ impl<'de> Deserialize<'de> for PasswordStorage {
             type Value = PasswordStorage;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("a password storage type (none/keychain/file or boolean)")
+                formatter.write_str(
+                    "a password storage type (none/keychain/file, { command = \"...\" } or boolean)",
+                )
             }
 
             fn visit_bool<E>(self, v: bool) -> std::result::Result<Self::Value, E>
@@ -108,6 +126,23 @@ impl<'de> Deserialize<'de> for PasswordStorage {
                     _ => Err(E::custom(format!("unknown password storage type: {}", v))),
                 }
             }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                let mut command = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "command" => command = Some(map.next_value::<String>()?),
+                        _ => {
+                            return Err(serde::de::Error::unknown_field(&key, &["command"]));
+                        }
+                    }
+                }
+                let command = command.ok_or_else(|| serde::de::Error::missing_field("command"))?;
+                Ok(PasswordStorage::Command { command })
+            }
         }
 
         deserializer.deserialize_any(PasswordStorageVisitor)
@@ -116,6 +151,7 @@ impl<'de> Deserialize<'de> for PasswordStorage {
 
 /// SSH connection configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ConnectionConfig {
     /// SSH server hostname or IP
     pub host: String,
@@ -128,6 +164,7 @@ pub struct ConnectionConfig {
     pub auth_type: AuthType,
     /// Path to SSH private key (for key auth)
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
     pub key_path: Option<PathBuf>,
     /// Where password/passphrase is stored
     #[serde(default)]
@@ -136,11 +173,12 @@ pub struct ConnectionConfig {
 
 /// Port forwarding configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ForwardingConfig {
     /// Type of forwarding
     #[serde(rename = "type")]
     pub forwarding_type: ForwardingType,
-    /// Local port to bind
+    /// Local port to bind (0 or unset for local forwarding = any free port)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub local_port: Option<u16>,
     /// Remote host to forward to (for local/remote forwarding)
@@ -154,8 +192,16 @@ pub struct ForwardingConfig {
     pub bind_address: String,
 }
 
+impl ForwardingConfig {
+    /// Whether the daemon should pick a free local port instead of a fixed one
+    pub fn uses_ephemeral_port(&self) -> bool {
+        self.forwarding_type == ForwardingType::Local && matches!(self.local_port, None | Some(0))
+    }
+}
+
 /// Tunnel options and behavior configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TunnelOptions {
     /// Enable SSH compression
     #[serde(default)]
@@ -181,6 +227,98 @@ pub struct TunnelOptions {
     /// SSH window size in bytes
     #[serde(default = "default_window_size")]
     pub window_size: u32,
+    /// Seconds to wait for an answer to an auth prompt (overrides the daemon's
+    /// `[auth_prompts] timeout_secs`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_timeout_secs: Option<u64>,
+}
+
+/// Lifecycle transition a hook runs at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum HookStage {
+    /// Before connecting (may abort the start)
+    PreUp,
+    /// After the local forward is listening
+    PostUp,
+    /// Before a user-requested stop tears the tunnel down
+    PreDown,
+    /// After the tunnel has stopped or failed
+    PostDown,
+}
+
+impl HookStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookStage::PreUp => "pre_up",
+            HookStage::PostUp => "post_up",
+            HookStage::PreDown => "pre_down",
+            HookStage::PostDown => "post_down",
+        }
+    }
+}
+
+impl std::fmt::Display for HookStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Shell commands the daemon runs at tunnel lifecycle transitions
+///
+/// Each command runs via `sh -c` with `SSH_TUNNEL_*` environment variables
+/// describing the tunnel (name, id, bound address, failure reason).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LifecycleHooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_up: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_up: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_down: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_down: Option<String>,
+    /// Seconds a hook may run before it is killed
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_secs: u64,
+    /// Abort the start if the pre-up hook fails or times out
+    #[serde(default)]
+    pub abort_on_pre_up_failure: bool,
+}
+
+impl LifecycleHooks {
+    /// Command configured for a stage, if any
+    pub fn command(&self, stage: HookStage) -> Option<&str> {
+        let command = match stage {
+            HookStage::PreUp => &self.pre_up,
+            HookStage::PostUp => &self.post_up,
+            HookStage::PreDown => &self.pre_down,
+            HookStage::PostDown => &self.post_down,
+        };
+        command.as_deref().filter(|c| !c.trim().is_empty())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre_up.is_none()
+            && self.post_up.is_none()
+            && self.pre_down.is_none()
+            && self.post_down.is_none()
+    }
+}
+
+impl Default for LifecycleHooks {
+    fn default() -> Self {
+        Self {
+            pre_up: None,
+            post_up: None,
+            pre_down: None,
+            post_down: None,
+            timeout_secs: default_hook_timeout(),
+            abort_on_pre_up_failure: false,
+        }
+    }
 }
 
 // Default value functions
@@ -216,6 +354,10 @@ fn default_window_size() -> u32 {
     2097152 // 2 MiB
 }
 
+fn default_hook_timeout() -> u64 {
+    30
+}
+
 impl Default for TunnelOptions {
     fn default() -> Self {
         Self {
@@ -227,6 +369,7 @@ impl Default for TunnelOptions {
             tcp_keepalive: false,
             max_packet_size: default_max_packet_size(),
             window_size: default_window_size(),
+            auth_timeout_secs: None,
         }
     }
 }
@@ -247,6 +390,7 @@ impl Profile {
             connection,
             forwarding,
             options: TunnelOptions::default(),
+            hooks: LifecycleHooks::default(),
         }
     }
 
@@ -270,6 +414,7 @@ impl Profile {
             connection,
             forwarding,
             options,
+            hooks: LifecycleHooks::default(),
         }
     }
 
@@ -296,7 +441,10 @@ impl Profile {
         // Validate forwarding configuration
         match self.forwarding.forwarding_type {
             ForwardingType::Local | ForwardingType::Remote => {
-                if self.forwarding.local_port.is_none() {
+                // Local forwards may leave the port unset (or 0) to get any free port
+                if self.forwarding.forwarding_type == ForwardingType::Remote
+                    && matches!(self.forwarding.local_port, None | Some(0))
+                {
                     return Err(Error::Config("Local port required".to_string()));
                 }
                 if self.forwarding.remote_host.is_none() {
@@ -315,6 +463,20 @@ impl Profile {
             }
         }
 
+        if !self.hooks.is_empty() && self.hooks.timeout_secs == 0 {
+            return Err(Error::Config("Hook timeout must be greater than 0".to_string()));
+        }
+
+        if self.options.auth_timeout_secs == Some(0) {
+            return Err(Error::Config("Auth timeout must be greater than 0".to_string()));
+        }
+
+        if let PasswordStorage::Command { command } = &self.connection.password_storage {
+            if command.trim().is_empty() {
+                return Err(Error::Config("Secret command cannot be empty".to_string()));
+            }
+        }
+
         Ok(())
     }
 
@@ -379,4 +541,130 @@ mod tests {
 
         assert!(profile.validate().is_err());
     }
+
+    #[test]
+    fn test_local_forward_ephemeral_port() {
+        let mut forwarding = ForwardingConfig {
+            forwarding_type: ForwardingType::Local,
+            local_port: Some(0),
+            remote_host: Some("db.internal".to_string()),
+            remote_port: Some(5432),
+            bind_address: "127.0.0.1".to_string(),
+        };
+        let connection = ConnectionConfig {
+            host: "example.com".to_string(),
+            port: 22,
+            user: "user".to_string(),
+            auth_type: AuthType::Password,
+            key_path: None,
+            password_storage: PasswordStorage::None,
+        };
+
+        assert!(forwarding.uses_ephemeral_port());
+        let profile = Profile::new("test".to_string(), connection.clone(), forwarding.clone());
+        assert!(profile.validate().is_ok());
+
+        forwarding.local_port = None;
+        assert!(forwarding.uses_ephemeral_port());
+        let profile = Profile::new("test".to_string(), connection.clone(), forwarding.clone());
+        assert!(profile.validate().is_ok());
+
+        // Remote forwards still need a concrete local target port
+        forwarding.forwarding_type = ForwardingType::Remote;
+        assert!(!forwarding.uses_ephemeral_port());
+        let profile = Profile::new("test".to_string(), connection, forwarding);
+        assert!(profile.validate().is_err());
+    }
+
+    #[test]
+    fn test_lifecycle_hooks_toml() {
+        let mut profile = Profile::new(
+            "hooks".to_string(),
+            ConnectionConfig {
+                host: "example.com".to_string(),
+                port: 22,
+                user: "user".to_string(),
+                auth_type: AuthType::Password,
+                key_path: None,
+                password_storage: PasswordStorage::None,
+            },
+            ForwardingConfig {
+                forwarding_type: ForwardingType::Local,
+                local_port: Some(6443),
+                remote_host: Some("kube-api".to_string()),
+                remote_port: Some(6443),
+                bind_address: "127.0.0.1".to_string(),
+            },
+        );
+
+        // No hooks: nothing is written to the profile file
+        let toml_str = toml::to_string(&profile).unwrap();
+        assert!(!toml_str.contains("[hooks]"));
+
+        profile.hooks.post_up = Some("kubectl config use-context tunnel".to_string());
+        profile.hooks.pre_down = Some("   ".to_string());
+        let toml_str = toml::to_string(&profile).unwrap();
+        let parsed: Profile = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.hooks, profile.hooks);
+        assert_eq!(parsed.hooks.timeout_secs, 30);
+        assert_eq!(
+            parsed.hooks.command(HookStage::PostUp),
+            Some("kubectl config use-context tunnel")
+        );
+        // Blank commands are treated as unset
+        assert_eq!(parsed.hooks.command(HookStage::PreDown), None);
+
+        profile.hooks.timeout_secs = 0;
+        assert!(profile.validate().is_err());
+    }
+
+    #[test]
+    fn test_password_storage_command() {
+        let mut profile = Profile::new(
+            "vault".to_string(),
+            ConnectionConfig {
+                host: "example.com".to_string(),
+                port: 22,
+                user: "user".to_string(),
+                auth_type: AuthType::Password,
+                key_path: None,
+                password_storage: PasswordStorage::Command {
+                    command: "pass show ssh/example".to_string(),
+                },
+            },
+            ForwardingConfig {
+                forwarding_type: ForwardingType::Local,
+                local_port: Some(5432),
+                remote_host: Some("db".to_string()),
+                remote_port: Some(5432),
+                bind_address: "127.0.0.1".to_string(),
+            },
+        );
+        assert!(profile.validate().is_ok());
+
+        let toml_str = toml::to_string(&profile).unwrap();
+        let parsed: Profile = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.connection.password_storage, profile.connection.password_storage);
+
+        let json = serde_json::to_string(&profile.connection.password_storage).unwrap();
+        assert_eq!(json, r#"{"command":"pass show ssh/example"}"#);
+
+        // Inline table form, and the older forms still parse
+        #[derive(Deserialize)]
+        struct Wrapper {
+            password_storage: PasswordStorage,
+        }
+        let parsed: Wrapper =
+            toml::from_str(r#"password_storage = { command = "op read op://ssh/db" }"#).unwrap();
+        assert_eq!(
+            parsed.password_storage,
+            PasswordStorage::Command { command: "op read op://ssh/db".to_string() }
+        );
+        let parsed: Wrapper = toml::from_str("password_storage = true").unwrap();
+        assert_eq!(parsed.password_storage, PasswordStorage::Keychain);
+        assert!(toml::from_str::<Wrapper>(r#"password_storage = { cmd = "x" }"#).is_err());
+
+        profile.connection.password_storage = PasswordStorage::Command { command: " ".to_string() };
+        assert!(profile.validate().is_err());
+    }
 }
@@ -5,6 +5,7 @@
 // Shared daemon connection logic for CLI and GUI
 
 use std::ffi::OsStr;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -13,7 +14,7 @@ use futures_util::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
-use crate::tls::{create_insecure_tls_config, create_pinned_tls_config};
+use crate::tls::{create_insecure_tls_config, create_pinned_tls_config, ClientCertPaths};
 use crate::{AuthRequest, AuthResponse, TunnelStatus, Uuid};
 use crate::sse::TunnelEvent;
 
@@ -62,6 +63,14 @@ pub struct DaemonClientConfig {
     #[serde(default)]
     pub tls_cert_fingerprint: String,
 
+    /// Client certificate (PEM) for daemons that require mutual TLS (HTTPS mode)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert_path: Option<PathBuf>,
+
+    /// Private key (PEM) for `client_cert_path`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key_path: Option<PathBuf>,
+
     /// Skip SSH key setup warning for remote daemon connections
     /// When true, the warning dialog about copying SSH keys to remote daemon is not shown
     #[serde(default)]
@@ -85,12 +94,22 @@ impl Default for DaemonClientConfig {
             daemon_url: String::new(),
             auth_token: String::new(),
             tls_cert_fingerprint: String::new(),
+            client_cert_path: None,
+            client_key_path: None,
             skip_ssh_setup_warning: false,
         }
     }
 }
 
 impl DaemonClientConfig {
+    /// Client certificate to present over HTTPS, when both files are configured
+    pub fn client_cert(&self) -> Option<ClientCertPaths<'_>> {
+        match (&self.client_cert_path, &self.client_key_path) {
+            (Some(cert_path), Some(key_path)) => Some(ClientCertPaths { cert_path, key_path }),
+            _ => None,
+        }
+    }
+
     /// Get the daemon base URL based on connection mode
     /// Constructs the full URL with protocol (http:// or https://) from connection_mode
     pub fn daemon_base_url(&self) -> Result<String> {
@@ -196,8 +215,13 @@ pub fn validate_client_config(config: &DaemonClientConfig) -> Result<()> {
         );
     }
 
-    // Validate auth token is present (daemon requires auth by default)
-    if config.auth_token.is_empty() {
+    if config.client_cert_path.is_some() != config.client_key_path.is_some() {
+        anyhow::bail!("client_cert_path and client_key_path must be set together");
+    }
+
+    // Validate auth token is present (daemon requires auth by default),
+    // unless a client certificate authenticates instead
+    if config.auth_token.is_empty() && config.client_cert().is_none() {
         anyhow::bail!("Authentication token is required but is empty");
     }
 
@@ -231,11 +255,14 @@ pub fn create_daemon_client(config: &DaemonClientConfig) -> Result<Client> {
             // HTTPS mode - configure TLS with optional certificate pinning
             if !config.tls_cert_fingerprint.is_empty() {
                 // Certificate pinning enabled
-                let tls_config = create_pinned_tls_config(config.tls_cert_fingerprint.clone())?;
+                let tls_config = create_pinned_tls_config(
+                    config.tls_cert_fingerprint.clone(),
+                    config.client_cert(),
+                )?;
                 client_builder = client_builder.use_preconfigured_tls(tls_config);
             } else {
                 // No pinning - use default system roots (accept any valid cert)
-                let tls_config = create_insecure_tls_config()?;
+                let tls_config = create_insecure_tls_config(config.client_cert())?;
                 client_builder = client_builder.use_preconfigured_tls(tls_config);
             }
         }
@@ -373,6 +400,25 @@ mod tests {
         let result = add_auth_header(request, &config_with_auth);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_client_cert_replaces_token() {
+        let mut config = DaemonClientConfig {
+            connection_mode: ConnectionMode::Https,
+            tls_cert_fingerprint: "AA:BB".to_string(),
+            ..Default::default()
+        };
+        assert!(validate_client_config(&config).is_err());
+
+        // Certificate without key is incomplete
+        config.client_cert_path = Some(PathBuf::from("/tmp/client.pem"));
+        assert!(config.client_cert().is_none());
+        assert!(validate_client_config(&config).is_err());
+
+        config.client_key_path = Some(PathBuf::from("/tmp/client.key"));
+        assert!(config.client_cert().is_some());
+        assert!(validate_client_config(&config).is_ok());
+    }
 }
 
 // ============================================================================
@@ -383,11 +429,17 @@ mod tests {
 // Re-exported from lib.rs
 
 /// Response from tunnel status endpoint
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TunnelStatusResponse {
     pub id: Uuid,
     pub status: TunnelStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pending_auth: Option<AuthRequest>,
+    /// Address the local forward is actually bound to (resolves port 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
+    pub bound_address: Option<SocketAddr>,
 }
 
 /// Callback trait for handling tunnel events and authentication
@@ -396,6 +448,17 @@ pub trait TunnelEventHandler: Send {
     /// Should return the authentication response (password, passphrase, etc.)
     fn on_auth_required(&mut self, request: &AuthRequest) -> Result<String>;
 
+    /// Called when authentication needs answers to several prompts (`request.prompts`)
+    /// Should return one answer per prompt, in order; by default each prompt is
+    /// asked through `on_auth_required` in turn
+    fn on_auth_prompts(&mut self, request: &AuthRequest) -> Result<Vec<String>> {
+        request
+            .prompts
+            .iter()
+            .map(|prompt| self.on_auth_required(&request.for_prompt(prompt)))
+            .collect()
+    }
+
     /// Called when tunnel successfully connects (optional)
     fn on_connected(&mut self) {}
 
@@ -518,15 +581,8 @@ pub async fn start_tunnel_with_events<H: TunnelEventHandler>(
 
                     match serde_json::from_str::<TunnelEvent>(json_str) {
                         Ok(ev) => {
-                            // Filter events for this tunnel (except heartbeats)
-                            let should_forward = match &ev {
-                                TunnelEvent::Heartbeat { .. } => true,
-                                TunnelEvent::Starting { id }
-                                | TunnelEvent::Connected { id }
-                                | TunnelEvent::Disconnected { id, .. }
-                                | TunnelEvent::Error { id, .. }
-                                | TunnelEvent::AuthRequired { id, .. } => *id == tunnel_id,
-                            };
+                            // Filter events for this tunnel (heartbeats and snapshots always pass)
+                            let should_forward = ev.tunnel_id().is_none_or(|id| id == tunnel_id);
 
                             if should_forward {
                                 let _ = event_tx.send(Ok(ev));
@@ -653,7 +709,24 @@ pub async fn start_tunnel_with_events<H: TunnelEventHandler>(
                             TunnelEvent::AuthRequired { request, .. } => {
                                 handle_auth_interactive(client, config, tunnel_id, &request, handler).await?;
                             }
-                            TunnelEvent::Starting { .. } | TunnelEvent::Heartbeat { .. } => {}
+                            // Events were lost; the snapshot tells where the tunnel got to
+                            TunnelEvent::Snapshot { tunnels } => {
+                                match tunnels.iter().find(|t| t.id == tunnel_id).map(|t| &t.status) {
+                                    Some(TunnelStatus::Connected) => {
+                                        handler.on_connected();
+                                        return Ok(());
+                                    }
+                                    Some(TunnelStatus::Failed(reason)) => anyhow::bail!("Tunnel failed: {reason}"),
+                                    None => anyhow::bail!("Tunnel disconnected"),
+                                    Some(_) => {}
+                                }
+                            }
+                            TunnelEvent::Starting { .. }
+                            | TunnelEvent::AuthResolved { .. }
+                            | TunnelEvent::AuthCancelled { .. }
+                            | TunnelEvent::Hook { .. }
+                            | TunnelEvent::Stats { .. }
+                            | TunnelEvent::Heartbeat { .. } => {}
                         }
                     }
                     Some(Err(e)) => {
@@ -713,15 +786,20 @@ async fn handle_auth_interactive<H: TunnelEventHandler>(
     auth_request: &AuthRequest,
     handler: &mut H,
 ) -> Result<()> {
-    let response = handler.on_auth_required(auth_request)?;
-
     let base_url = config.daemon_base_url()?;
     let auth_url = format!("{}/api/tunnels/{}/auth", base_url, tunnel_id);
     // Include the request_id so the daemon can pair this response with the pending prompt
-    let payload = serde_json::json!({
-        "request_id": auth_request.id,
-        "response": response,
-    });
+    let payload = if auth_request.prompts.is_empty() {
+        serde_json::json!({
+            "request_id": auth_request.id,
+            "response": handler.on_auth_required(auth_request)?,
+        })
+    } else {
+        serde_json::json!({
+            "request_id": auth_request.id,
+            "responses": handler.on_auth_prompts(auth_request)?,
+        })
+    };
 
     let auth_resp = add_auth_header(client.post(&auth_url).json(&payload), config)?
         .send()
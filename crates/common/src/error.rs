@@ -43,6 +43,9 @@ pub enum Error {
     #[error("Keychain error: {0}")]
     Keychain(String),
 
+    #[error("Vault error: {0}")]
+    Vault(String),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -9,33 +9,44 @@ pub mod daemon_client;
 pub mod error;
 pub mod keychain;
 pub mod network;
+pub mod preflight;
 pub mod profile_manager;
 pub mod sse;
 pub mod tls;
 pub mod types;
+pub mod vault;
+pub mod ws;
 
-pub use config::{ConnectionConfig, ForwardingConfig, PasswordStorage, Profile, TunnelOptions};
+pub use config::{
+    ConnectionConfig, ForwardingConfig, HookStage, LifecycleHooks, PasswordStorage, Profile,
+    TunnelOptions,
+};
 pub use daemon_client::{
     add_auth_header, cli_config_snippet_exists, config_needs_ip_address, create_daemon_client,
     get_cli_config_snippet_path, start_tunnel_with_events, stop_tunnel, validate_client_config,
     validate_daemon_config, ConfigValidationResult, ConnectionMode, DaemonClientConfig,
     TunnelEventHandler, TunnelStatusResponse,
 };
-pub use sse::{EventListener, TunnelEvent};
+pub use sse::{EventListener, EventMeta, TunnelEvent};
 pub use error::{Error, Result};
 pub use keychain::{get_password, has_password, is_keychain_available, remove_password, store_password};
 pub use network::{is_loopback_address, is_valid_host};
+pub use preflight::{PreflightCheck, PreflightIssue, PreflightReport, PreflightSeverity, RunningBind};
 pub use profile_manager::{
     delete_profile_by_id, delete_profile_by_name, get_remote_key_setup_message,
     load_all_profiles, load_profile, load_profile_by_id, load_profile_by_name,
     prepare_profile_for_remote, profile_exists_by_id, profile_exists_by_name, profiles_dir,
     save_profile,
 };
-pub use tls::{create_insecure_tls_config, create_pinned_tls_config};
+pub use tls::{create_insecure_tls_config, create_pinned_tls_config, ClientCertPaths};
+pub use vault::{Vault, VaultLock};
 pub use types::{
-    AuthRequest, AuthRequestType, AuthResponse, AuthType, DaemonInfo, ForwardingType,
-    ProfileSourceMode, StartTunnelRequest, StartTunnelResult, TunnelDomainEvent, TunnelStatus,
+    AuditAction, AuditEntry, AuthPrompt, AuthRequest, AuthRequestType, AuthResponse, AuthType, CreateTokenRequest,
+    CreateTokenResponse, DaemonCapabilities, DaemonFeature, DaemonInfo, ForwardingType, HistorySummary, ListenerInfo, LockKeysRequest, LockoutInfo, LogLevel, ProfileSourceMode,
+    RotateTokenRequest, RotateTokenResponse, SetLogLevelRequest, StartTunnelRequest, StartTunnelResult, TokenInfo, TokenScope, TunnelLogEntry, TunnelLogLevel,
+    TunnelSession, TunnelStats, TunnelStatus, UnlockKeyRequest, UnlockedKey, API_VERSION,
 };
+pub use ws::{WsClient, WsCommand, WsMessage, WsRequest, WsResponse};
 
 // Re-export commonly used external types
 pub use chrono::{DateTime, Utc};
@@ -76,6 +87,31 @@ pub fn format_host_port(host: &str, port: u16) -> String {
     }
 }
 
+/// Format a duration compactly, with its two largest units.
+///
+/// # Examples
+/// ```
+/// use ssh_tunnel_common::format_duration;
+///
+/// assert_eq!(format_duration(chrono::Duration::seconds(42)), "42s");
+/// assert_eq!(format_duration(chrono::Duration::seconds(308)), "5m 8s");
+/// assert_eq!(format_duration(chrono::Duration::minutes(192)), "3h 12m");
+/// assert_eq!(format_duration(chrono::Duration::hours(52)), "2d 4h");
+/// ```
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let secs = duration.num_seconds().max(0);
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 /// Format a tunnel description based on forwarding configuration.
 ///
 /// Creates a human-readable description of the tunnel forwarding,
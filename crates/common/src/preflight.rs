@@ -0,0 +1,549 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Preflight checks for tunnel profiles
+//
+// Catches the usual reasons a tunnel fails to start before we try to connect:
+// local port clashes (with other profiles, running tunnels or sockets already
+// open on the host), missing or world-readable key files, and privileged ports.
+
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::Profile;
+use crate::types::{AuthType, ForwardingType};
+
+/// How serious a preflight finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightSeverity {
+    /// The tunnel may still start, but something looks off
+    Warning,
+    /// The tunnel will fail to start as configured
+    Error,
+}
+
+/// Which check produced a finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightCheck {
+    /// Another profile binds the same local address
+    ProfileConflict,
+    /// A running tunnel already holds the local address
+    RunningTunnel,
+    /// Some other process is already listening on the local address
+    PortInUse,
+    /// SSH key file is missing, unreadable or has loose permissions
+    KeyFile,
+    /// Local port requires elevated privileges
+    PrivilegedPort,
+}
+
+/// A single preflight finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PreflightIssue {
+    pub check: PreflightCheck,
+    pub severity: PreflightSeverity,
+    pub message: String,
+}
+
+impl PreflightIssue {
+    fn warning(check: PreflightCheck, message: String) -> Self {
+        Self { check, severity: PreflightSeverity::Warning, message }
+    }
+
+    fn error(check: PreflightCheck, message: String) -> Self {
+        Self { check, severity: PreflightSeverity::Error, message }
+    }
+}
+
+/// Result of running all preflight checks against a profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PreflightReport {
+    pub profile_id: Uuid,
+    pub profile_name: String,
+    pub issues: Vec<PreflightIssue>,
+}
+
+impl PreflightReport {
+    /// True if any finding would prevent the tunnel from starting
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == PreflightSeverity::Error)
+    }
+}
+
+/// Local address held by a tunnel the daemon is currently running
+#[derive(Debug, Clone)]
+pub struct RunningBind {
+    pub profile_id: Uuid,
+    pub profile_name: String,
+    pub addr: SocketAddr,
+}
+
+/// A TCP socket in LISTEN state on this host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListeningSocket {
+    pub addr: SocketAddr,
+    /// Owning process as "name (pid N)", when /proc lets us see it
+    pub process: Option<String>,
+}
+
+/// Run every preflight check for `profile`.
+///
+/// `others` are the remaining saved profiles; `running` are the local
+/// addresses of tunnels already running in the daemon. Sockets owned by those
+/// tunnels are reported as running-tunnel conflicts rather than as foreign
+/// listeners.
+pub fn run_checks(profile: &Profile, others: &[Profile], running: &[RunningBind]) -> PreflightReport {
+    let mut issues = Vec::new();
+
+    issues.extend(check_privileged_port(profile));
+    issues.extend(check_key_file(profile));
+    issues.extend(check_profile_conflicts(profile, others));
+    issues.extend(check_running_conflicts(profile, running));
+
+    let foreign: Vec<ListeningSocket> = listening_sockets()
+        .into_iter()
+        .filter(|s| !running.iter().any(|r| r.addr == s.addr))
+        .collect();
+    issues.extend(check_port_in_use(profile, &foreign));
+
+    PreflightReport {
+        profile_id: profile.metadata.id,
+        profile_name: profile.metadata.name.clone(),
+        issues,
+    }
+}
+
+/// Local address a profile binds on this host, if it has a fixed one.
+///
+/// Remote forwards bind on the SSH server, and port 0 means "any free port",
+/// so neither can clash locally.
+fn local_bind(profile: &Profile) -> Option<(&str, u16)> {
+    match profile.forwarding.forwarding_type {
+        ForwardingType::Local | ForwardingType::Dynamic => {}
+        ForwardingType::Remote => return None,
+    }
+    match profile.forwarding.local_port {
+        Some(port) if port != 0 => Some((profile.forwarding.bind_address.as_str(), port)),
+        _ => None,
+    }
+}
+
+/// Addresses a bind host resolves to without DNS ("localhost" covers both loopbacks)
+fn bind_ips(host: &str) -> Option<Vec<IpAddr>> {
+    if host.eq_ignore_ascii_case("localhost") {
+        return Some(vec![IpAddr::V4(Ipv4Addr::LOCALHOST), IpAddr::V6(Ipv6Addr::LOCALHOST)]);
+    }
+    let trimmed = host.trim_start_matches('[').trim_end_matches(']');
+    trimmed.parse::<IpAddr>().ok().map(|ip| vec![ip])
+}
+
+/// Whether binding `a` would fail because `b` is bound (or vice versa).
+///
+/// A wildcard address overlaps everything in its family; the IPv6 wildcard is
+/// dual-stack on Linux by default, so it overlaps IPv4 too.
+fn ips_overlap(a: IpAddr, b: IpAddr) -> bool {
+    if a == b {
+        return true;
+    }
+    match (a, b) {
+        (IpAddr::V6(v6), _) | (_, IpAddr::V6(v6)) if v6.is_unspecified() => true,
+        (IpAddr::V4(x), IpAddr::V4(y)) => x.is_unspecified() || y.is_unspecified(),
+        _ => false,
+    }
+}
+
+fn hosts_overlap(a: &str, b: &str) -> bool {
+    if a.eq_ignore_ascii_case(b) {
+        return true;
+    }
+    match (bind_ips(a), bind_ips(b)) {
+        (Some(xs), Some(ys)) => xs.iter().any(|x| ys.iter().any(|y| ips_overlap(*x, *y))),
+        _ => false,
+    }
+}
+
+/// Find other profiles that bind the same local address.
+///
+/// These are warnings: both profiles can exist, they just cannot run at the same time.
+pub fn check_profile_conflicts(profile: &Profile, others: &[Profile]) -> Vec<PreflightIssue> {
+    let Some((host, port)) = local_bind(profile) else {
+        return Vec::new();
+    };
+
+    others
+        .iter()
+        .filter(|other| other.metadata.id != profile.metadata.id)
+        .filter_map(|other| {
+            let (other_host, other_port) = local_bind(other)?;
+            (other_port == port && hosts_overlap(host, other_host)).then(|| {
+                PreflightIssue::warning(
+                    PreflightCheck::ProfileConflict,
+                    format!(
+                        "Profile '{}' also binds {}; only one of them can run at a time",
+                        other.metadata.name,
+                        crate::format_host_port(other_host, other_port)
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Find running tunnels (other than this profile's) holding the local address
+pub fn check_running_conflicts(profile: &Profile, running: &[RunningBind]) -> Vec<PreflightIssue> {
+    let Some((host, port)) = local_bind(profile) else {
+        return Vec::new();
+    };
+
+    running
+        .iter()
+        .filter(|r| r.profile_id != profile.metadata.id)
+        .filter(|r| r.addr.port() == port && hosts_overlap(host, &r.addr.ip().to_string()))
+        .map(|r| {
+            PreflightIssue::error(
+                PreflightCheck::RunningTunnel,
+                format!("Running tunnel '{}' is already listening on {}", r.profile_name, r.addr),
+            )
+        })
+        .collect()
+}
+
+/// Find host sockets already listening on the profile's local address
+pub fn check_port_in_use(profile: &Profile, sockets: &[ListeningSocket]) -> Vec<PreflightIssue> {
+    let Some((host, port)) = local_bind(profile) else {
+        return Vec::new();
+    };
+
+    sockets
+        .iter()
+        .filter(|s| s.addr.port() == port && hosts_overlap(host, &s.addr.ip().to_string()))
+        .map(|s| {
+            let owner = s
+                .process
+                .as_deref()
+                .map(|p| format!(" by {}", p))
+                .unwrap_or_default();
+            PreflightIssue::error(
+                PreflightCheck::PortInUse,
+                format!("{} is already in use{}", s.addr, owner),
+            )
+        })
+        .collect()
+}
+
+/// Warn about local ports below 1024
+pub fn check_privileged_port(profile: &Profile) -> Vec<PreflightIssue> {
+    match local_bind(profile) {
+        Some((_, port)) if port < 1024 => vec![PreflightIssue::warning(
+            PreflightCheck::PrivilegedPort,
+            format!(
+                "Port {} is privileged; the daemon needs root or CAP_NET_BIND_SERVICE to bind it",
+                port
+            ),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Resolve a profile key path the same way the daemon does (relative to ~/.ssh)
+fn resolve_key_path(key_path: &std::path::Path) -> PathBuf {
+    if key_path.is_relative() {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("/root"))
+            .join(".ssh")
+            .join(key_path)
+    } else {
+        key_path.to_path_buf()
+    }
+}
+
+/// Check the key file exists, is a regular file and is not readable by others
+pub fn check_key_file(profile: &Profile) -> Vec<PreflightIssue> {
+    if profile.connection.auth_type != AuthType::Key {
+        return Vec::new();
+    }
+    let Some(key_path) = &profile.connection.key_path else {
+        return vec![PreflightIssue::error(
+            PreflightCheck::KeyFile,
+            "Key authentication selected but no key path is set".to_string(),
+        )];
+    };
+
+    let full_path = resolve_key_path(key_path);
+    let metadata = match std::fs::metadata(&full_path) {
+        Ok(m) => m,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return vec![PreflightIssue::error(
+                PreflightCheck::KeyFile,
+                format!("SSH key not found: {}", full_path.display()),
+            )];
+        }
+        Err(e) => {
+            return vec![PreflightIssue::error(
+                PreflightCheck::KeyFile,
+                format!("Cannot access SSH key {}: {}", full_path.display(), e),
+            )];
+        }
+    };
+
+    if !metadata.is_file() {
+        return vec![PreflightIssue::error(
+            PreflightCheck::KeyFile,
+            format!("SSH key path is not a file: {}", full_path.display()),
+        )];
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return vec![PreflightIssue::warning(
+                PreflightCheck::KeyFile,
+                format!(
+                    "SSH key {} has permissions {:o}; it should be 600 (chmod 600 {})",
+                    full_path.display(),
+                    mode,
+                    full_path.display()
+                ),
+            )];
+        }
+    }
+
+    Vec::new()
+}
+
+/// List TCP sockets in LISTEN state on this host.
+///
+/// Reads /proc/net/tcp and /proc/net/tcp6, then maps socket inodes to
+/// processes via /proc/<pid>/fd. Processes we are not allowed to inspect are
+/// simply left unattributed. Returns an empty list on non-Linux hosts.
+pub fn listening_sockets() -> Vec<ListeningSocket> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut entries = Vec::new();
+        for (path, ipv6) in [("/proc/net/tcp", false), ("/proc/net/tcp6", true)] {
+            if let Ok(contents) = std::fs::read_to_string(path) {
+                entries.extend(parse_proc_net_tcp(&contents, ipv6));
+            }
+        }
+
+        let owners = socket_owners();
+        entries
+            .into_iter()
+            .map(|(addr, inode)| ListeningSocket {
+                addr,
+                process: owners.get(&inode).cloned(),
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+/// TCP state code for LISTEN in /proc/net/tcp
+const TCP_LISTEN: &str = "0A";
+
+/// Parse /proc/net/tcp{,6} into (listen address, socket inode) pairs
+fn parse_proc_net_tcp(contents: &str, ipv6: bool) -> Vec<(SocketAddr, u64)> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != TCP_LISTEN {
+                return None;
+            }
+            let (ip_hex, port_hex) = fields[1].split_once(':')?;
+            let ip = parse_proc_ip(ip_hex, ipv6)?;
+            let port = u16::from_str_radix(port_hex, 16).ok()?;
+            let inode = fields[9].parse().ok()?;
+            Some((SocketAddr::new(ip, port), inode))
+        })
+        .collect()
+}
+
+/// Decode an address from /proc/net/tcp: 32-bit words in host byte order
+fn parse_proc_ip(hex: &str, ipv6: bool) -> Option<IpAddr> {
+    let word = |chunk: &str| u32::from_str_radix(chunk, 16).ok().map(u32::to_ne_bytes);
+
+    if ipv6 {
+        if hex.len() != 32 {
+            return None;
+        }
+        let mut octets = [0u8; 16];
+        for i in 0..4 {
+            octets[i * 4..i * 4 + 4].copy_from_slice(&word(&hex[i * 8..i * 8 + 8])?);
+        }
+        Some(IpAddr::V6(Ipv6Addr::from(octets)))
+    } else {
+        if hex.len() != 8 {
+            return None;
+        }
+        Some(IpAddr::V4(Ipv4Addr::from(word(hex)?)))
+    }
+}
+
+/// Map socket inodes to "name (pid N)" for every process we can inspect
+#[cfg(target_os = "linux")]
+fn socket_owners() -> HashMap<u64, String> {
+    let mut owners = HashMap::new();
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return owners;
+    };
+
+    for entry in procs.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let name = std::fs::read_to_string(entry.path().join("comm"))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|_| "?".to_string());
+
+        for fd in fds.flatten() {
+            let Ok(target) = std::fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|t| t.strip_prefix("socket:["))
+                .and_then(|t| t.strip_suffix(']'))
+                .and_then(|t| t.parse::<u64>().ok());
+            if let Some(inode) = inode {
+                owners.entry(inode).or_insert_with(|| format!("{} (pid {})", name, pid));
+            }
+        }
+    }
+
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConnectionConfig, ForwardingConfig, PasswordStorage};
+
+    fn profile(name: &str, bind: &str, port: u16) -> Profile {
+        Profile::new(
+            name.to_string(),
+            ConnectionConfig {
+                host: "example.com".to_string(),
+                port: 22,
+                user: "user".to_string(),
+                auth_type: AuthType::Password,
+                key_path: None,
+                password_storage: PasswordStorage::None,
+            },
+            ForwardingConfig {
+                forwarding_type: ForwardingType::Local,
+                bind_address: bind.to_string(),
+                local_port: Some(port),
+                remote_host: Some("localhost".to_string()),
+                remote_port: Some(5432),
+            },
+        )
+    }
+
+    #[test]
+    fn test_parse_proc_net_tcp() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   \
+            0: 0100007F:1538 00000000:0000 0A 00000000:00000000 00:00000000 00000000   113        0 23456 1 0000000000000000 100 0 0 10 0\n   \
+            1: 0100007F:D3A2 0100007F:1538 01 00000000:00000000 00:00000000 00000000  1000        0 98765 1 0000000000000000 20 4 30 10 -1\n";
+        let parsed = parse_proc_net_tcp(tcp, false);
+        // Only the LISTEN row is kept; the ESTABLISHED one is skipped
+        if cfg!(target_endian = "little") {
+            assert_eq!(parsed, vec![("127.0.0.1:5432".parse().unwrap(), 23456)]);
+        }
+        assert_eq!(parsed.len(), 1);
+
+        let tcp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   \
+            0: 00000000000000000000000000000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1234 1 0000000000000000 100 0 0 10 0\n";
+        let parsed = parse_proc_net_tcp(tcp6, true);
+        assert_eq!(parsed, vec![("[::]:22".parse().unwrap(), 1234)]);
+    }
+
+    #[test]
+    fn test_hosts_overlap() {
+        assert!(hosts_overlap("127.0.0.1", "127.0.0.1"));
+        assert!(hosts_overlap("0.0.0.0", "127.0.0.1"));
+        assert!(hosts_overlap("localhost", "127.0.0.1"));
+        assert!(hosts_overlap("::", "192.168.1.10"));
+        assert!(!hosts_overlap("127.0.0.1", "127.0.0.2"));
+        assert!(!hosts_overlap("0.0.0.0", "::1"));
+    }
+
+    #[test]
+    fn test_profile_conflicts() {
+        let db = profile("db", "127.0.0.1", 5432);
+        let other_db = profile("db-staging", "0.0.0.0", 5432);
+        let web = profile("web", "127.0.0.1", 8080);
+        let mut any_port = profile("any", "127.0.0.1", 0);
+        any_port.forwarding.local_port = Some(0);
+
+        let issues = check_profile_conflicts(&db, &[db.clone(), other_db, web, any_port]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].check, PreflightCheck::ProfileConflict);
+        assert_eq!(issues[0].severity, PreflightSeverity::Warning);
+        assert!(issues[0].message.contains("db-staging"));
+    }
+
+    #[test]
+    fn test_running_and_host_conflicts() {
+        let db = profile("db", "127.0.0.1", 5432);
+        let running = vec![
+            RunningBind {
+                profile_id: db.metadata.id,
+                profile_name: "db".to_string(),
+                addr: "127.0.0.1:5432".parse().unwrap(),
+            },
+            RunningBind {
+                profile_id: Uuid::new_v4(),
+                profile_name: "db-staging".to_string(),
+                addr: "0.0.0.0:5432".parse().unwrap(),
+            },
+        ];
+        // The profile's own running tunnel is not a conflict
+        let issues = check_running_conflicts(&db, &running);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, PreflightSeverity::Error);
+        assert!(issues[0].message.contains("db-staging"));
+
+        let sockets = vec![ListeningSocket {
+            addr: "127.0.0.1:5432".parse().unwrap(),
+            process: Some("postgres (pid 812)".to_string()),
+        }];
+        let issues = check_port_in_use(&db, &sockets);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].message.contains("postgres (pid 812)"));
+    }
+
+    #[test]
+    fn test_privileged_port_and_key_file() {
+        assert_eq!(check_privileged_port(&profile("web", "127.0.0.1", 443)).len(), 1);
+        assert!(check_privileged_port(&profile("web", "127.0.0.1", 8443)).is_empty());
+
+        let mut keyed = profile("keyed", "127.0.0.1", 8443);
+        keyed.connection.auth_type = AuthType::Key;
+        keyed.connection.key_path = Some(PathBuf::from("/nonexistent/ssh-tunnel-preflight-key"));
+        let issues = check_key_file(&keyed);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, PreflightSeverity::Error);
+    }
+}
@@ -320,6 +320,7 @@ mod tests {
                 remote_port: Some(80),
             },
             options: Default::default(),
+            hooks: Default::default(),
         }
     }
 
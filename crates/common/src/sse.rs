@@ -5,42 +5,173 @@
 //!
 //! Framework-agnostic SSE client that works with any async runtime (tokio).
 
+use std::net::SocketAddr;
+
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio::time::{sleep, Duration};
 use uuid::Uuid;
 
-use crate::{add_auth_header, AuthRequest, DaemonClientConfig};
+use crate::{
+    add_auth_header, AuthRequest, DaemonClientConfig, HookStage, TunnelStats, TunnelStatus,
+    TunnelStatusResponse,
+};
+
+/// Context the daemon attaches to every tunnel event
+///
+/// All fields are optional on the wire so events from older daemons (which
+/// only sent IDs) still parse; `timestamp` then defaults to the receive time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EventMeta {
+    #[serde(default = "Utc::now")]
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_name: Option<String>,
+    /// Status before the event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<TunnelStatus>,
+    /// Status after the event (unchanged for hooks, stats and auth cancellations)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<TunnelStatus>,
+}
+
+impl EventMeta {
+    /// Whether the event moved the tunnel to a different status
+    pub fn is_transition(&self) -> bool {
+        self.status.is_some() && self.previous_status != self.status
+    }
+}
+
+impl Default for EventMeta {
+    fn default() -> Self {
+        Self {
+            timestamp: Utc::now(),
+            profile_name: None,
+            previous_status: None,
+            status: None,
+        }
+    }
+}
 
-/// Event from daemon SSE stream
-/// Matches the daemon's OutgoingEvent structure
-#[derive(Debug, Clone, Deserialize)]
+/// Event on the daemon's event stream (`/api/events`, `/api/ws`)
+///
+/// The daemon serializes this type directly, so clients and daemon share
+/// one wire format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TunnelEvent {
     Starting {
         id: Uuid,
+        #[serde(flatten)]
+        meta: EventMeta,
     },
     Connected {
         id: Uuid,
+        /// Local address the forward is bound to (absent from older daemons)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "openapi", schema(value_type = Option<String>))]
+        bound_address: Option<SocketAddr>,
+        #[serde(flatten)]
+        meta: EventMeta,
     },
     Disconnected {
         id: Uuid,
         reason: String,
+        #[serde(flatten)]
+        meta: EventMeta,
     },
     Error {
         id: Uuid,
         error: String,
+        #[serde(flatten)]
+        meta: EventMeta,
     },
     AuthRequired {
         id: Uuid,
         request: AuthRequest,
+        #[serde(flatten)]
+        meta: EventMeta,
+    },
+    /// A response to the auth request was accepted; other clients should
+    /// close their prompt for it
+    AuthResolved {
+        id: Uuid,
+        request_id: Uuid,
+        #[serde(flatten)]
+        meta: EventMeta,
+    },
+    /// The auth request is no longer answerable (tunnel stopped, failed or
+    /// the prompt timed out)
+    AuthCancelled {
+        id: Uuid,
+        request_id: Uuid,
+        reason: String,
+        #[serde(flatten)]
+        meta: EventMeta,
+    },
+    /// A lifecycle hook finished (output is truncated; see the daemon log)
+    Hook {
+        id: Uuid,
+        stage: HookStage,
+        success: bool,
+        output: String,
+        #[serde(flatten)]
+        meta: EventMeta,
+    },
+    /// Periodic traffic counters of a connected tunnel
+    Stats {
+        id: Uuid,
+        stats: TunnelStats,
+        #[serde(flatten)]
+        meta: EventMeta,
     },
     Heartbeat {
         timestamp: DateTime<Utc>,
     },
+    /// State of every active tunnel, sent when events were missed and can't
+    /// be replayed (replaces whatever the client knew)
+    Snapshot {
+        tunnels: Vec<TunnelStatusResponse>,
+    },
+}
+
+impl TunnelEvent {
+    /// Tunnel the event is about (`None` for heartbeats and snapshots)
+    pub fn tunnel_id(&self) -> Option<Uuid> {
+        match self {
+            TunnelEvent::Starting { id, .. }
+            | TunnelEvent::Connected { id, .. }
+            | TunnelEvent::Disconnected { id, .. }
+            | TunnelEvent::Error { id, .. }
+            | TunnelEvent::AuthRequired { id, .. }
+            | TunnelEvent::AuthResolved { id, .. }
+            | TunnelEvent::AuthCancelled { id, .. }
+            | TunnelEvent::Hook { id, .. }
+            | TunnelEvent::Stats { id, .. } => Some(*id),
+            TunnelEvent::Heartbeat { .. } | TunnelEvent::Snapshot { .. } => None,
+        }
+    }
+
+    /// Timestamp, profile name and status transition of a tunnel event
+    pub fn meta(&self) -> Option<&EventMeta> {
+        match self {
+            TunnelEvent::Starting { meta, .. }
+            | TunnelEvent::Connected { meta, .. }
+            | TunnelEvent::Disconnected { meta, .. }
+            | TunnelEvent::Error { meta, .. }
+            | TunnelEvent::AuthRequired { meta, .. }
+            | TunnelEvent::AuthResolved { meta, .. }
+            | TunnelEvent::AuthCancelled { meta, .. }
+            | TunnelEvent::Hook { meta, .. }
+            | TunnelEvent::Stats { meta, .. } => Some(meta),
+            TunnelEvent::Heartbeat { .. } | TunnelEvent::Snapshot { .. } => None,
+        }
+    }
 }
 
 /// Event listener for daemon SSE stream
@@ -56,7 +187,9 @@ impl EventListener {
 
     /// Start listening to daemon events
     /// Returns a channel receiver that yields TunnelEvent items.
-    /// Automatically reconnects with exponential backoff if the stream drops.
+    /// Automatically reconnects with exponential backoff if the stream drops,
+    /// resuming after the last event seen (the daemon replays missed events
+    /// or sends a `Snapshot`).
     pub async fn listen(&self) -> Result<mpsc::Receiver<TunnelEvent>> {
         let (tx, rx) = mpsc::channel(100);
 
@@ -64,9 +197,10 @@ impl EventListener {
         tokio::spawn(async move {
             let mut backoff = Duration::from_secs(1);
             let max_backoff = Duration::from_secs(30);
+            let mut last_event_id = None;
 
             loop {
-                if let Err(e) = Self::stream_events(&config, tx.clone()).await {
+                if let Err(e) = Self::stream_events(&config, tx.clone(), &mut last_event_id).await {
                     tracing::warn!("Event stream error: {}", e);
                 }
 
@@ -86,6 +220,7 @@ impl EventListener {
     async fn stream_events(
         config: &DaemonClientConfig,
         tx: mpsc::Sender<TunnelEvent>,
+        last_event_id: &mut Option<u64>,
     ) -> Result<()> {
         let base_url = config.daemon_base_url()?;
         let url = format!("{}/api/events", base_url);
@@ -93,8 +228,11 @@ impl EventListener {
         // Create HTTP client
         let client = crate::create_daemon_client(config)?;
 
-        // Build request with auth
-        let request = client.get(&url);
+        // Build request with auth, resuming after the last event we saw
+        let mut request = client.get(&url);
+        if let Some(id) = last_event_id {
+            request = request.header("Last-Event-ID", id.to_string());
+        }
         let request = add_auth_header(request, config)?;
 
         // Send request and get response stream
@@ -125,6 +263,10 @@ impl EventListener {
 
                             tracing::debug!("Raw SSE message: {:?}", message);
 
+                            if let Some(id) = Self::parse_sse_id(&message) {
+                                *last_event_id = Some(id);
+                            }
+
                             // Parse SSE message
                             if let Some(event) = Self::parse_sse_message(&message) {
                                 tracing::debug!("Sending event to channel: {:?}", event);
@@ -150,6 +292,14 @@ impl EventListener {
         Ok(())
     }
 
+    /// Event ID of an SSE message (heartbeats have none)
+    fn parse_sse_id(message: &str) -> Option<u64> {
+        message
+            .lines()
+            .find_map(|line| line.strip_prefix("id:"))
+            .and_then(|id| id.trim().parse().ok())
+    }
+
     /// Parse an SSE message into a TunnelEvent
     fn parse_sse_message(message: &str) -> Option<TunnelEvent> {
         // SSE format: "data: {json}\n"
@@ -176,3 +326,65 @@ impl Default for EventListener {
         Self::new(DaemonClientConfig::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sse_message_with_id() {
+        let message = "id: 1700000000000042\ndata: {\"type\":\"starting\",\"id\":\"6f1c1f8e-8a4e-4b8e-9d43-2f0f2b1f3c11\"}";
+        assert_eq!(EventListener::parse_sse_id(message), Some(1_700_000_000_000_042));
+        assert!(matches!(
+            EventListener::parse_sse_message(message),
+            Some(TunnelEvent::Starting { .. })
+        ));
+
+        // Heartbeats carry no ID, so they don't move the resume point
+        let heartbeat = "data: {\"type\":\"heartbeat\",\"timestamp\":\"2025-01-01T00:00:00Z\"}";
+        assert_eq!(EventListener::parse_sse_id(heartbeat), None);
+
+        let snapshot = "id: 7\ndata: {\"type\":\"snapshot\",\"tunnels\":[{\"id\":\"6f1c1f8e-8a4e-4b8e-9d43-2f0f2b1f3c11\",\"status\":\"connected\"}]}";
+        match EventListener::parse_sse_message(snapshot) {
+            Some(TunnelEvent::Snapshot { tunnels }) => {
+                assert_eq!(tunnels.len(), 1);
+                assert!(tunnels[0].pending_auth.is_none());
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_event_meta_round_trip() {
+        let id = Uuid::new_v4();
+        let event = TunnelEvent::Error {
+            id,
+            error: "Connection refused".to_string(),
+            meta: EventMeta {
+                profile_name: Some("prod-db".to_string()),
+                previous_status: Some(TunnelStatus::Connecting),
+                status: Some(TunnelStatus::Failed("Connection refused".to_string())),
+                ..EventMeta::default()
+            },
+        };
+
+        // Metadata sits next to the event's own fields
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "error");
+        assert_eq!(json["profile_name"], "prod-db");
+        assert_eq!(json["previous_status"], "connecting");
+
+        let parsed: TunnelEvent = serde_json::from_value(json).unwrap();
+        let meta = parsed.meta().unwrap();
+        assert_eq!(parsed.tunnel_id(), Some(id));
+        assert_eq!(meta.profile_name.as_deref(), Some("prod-db"));
+        assert!(meta.is_transition());
+
+        // Events from daemons without metadata still parse
+        let old = format!(r#"{{"type":"disconnected","id":"{}","reason":"Stopped by user"}}"#, id);
+        let parsed: TunnelEvent = serde_json::from_str(&old).unwrap();
+        let meta = parsed.meta().unwrap();
+        assert!(meta.profile_name.is_none());
+        assert!(!meta.is_transition());
+    }
+}
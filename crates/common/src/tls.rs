@@ -5,13 +5,17 @@
 // Handles TLS certificate verification and pinning for HTTPS connections
 // Shared between CLI and GUI clients
 
+use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
-use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::client::WantsClientCert;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
 use rustls::{
-    ClientConfig, DigitallySignedStruct, Error as TlsError, RootCertStore, SignatureScheme,
+    ClientConfig, ConfigBuilder, DigitallySignedStruct, Error as TlsError, RootCertStore,
+    SignatureScheme,
 };
 use sha2::{Digest, Sha256};
 use x509_parser::prelude::*;
@@ -147,23 +151,57 @@ impl ServerCertVerifier for FingerprintVerifier {
     }
 }
 
+/// Client certificate and private key (PEM files) for daemons using mutual TLS
+#[derive(Debug, Clone, Copy)]
+pub struct ClientCertPaths<'a> {
+    pub cert_path: &'a Path,
+    pub key_path: &'a Path,
+}
+
+/// Finish a client config, presenting the client certificate if configured
+fn with_client_cert(
+    builder: ConfigBuilder<ClientConfig, WantsClientCert>,
+    client_cert: Option<ClientCertPaths>,
+) -> Result<ClientConfig> {
+    let Some(paths) = client_cert else {
+        return Ok(builder.with_no_client_auth());
+    };
+
+    let certs = CertificateDer::pem_file_iter(paths.cert_path)
+        .with_context(|| format!("Failed to open client certificate {}", paths.cert_path.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse client certificate")?;
+    if certs.is_empty() {
+        anyhow::bail!("No certificates found in {}", paths.cert_path.display());
+    }
+
+    let key = PrivateKeyDer::from_pem_file(paths.key_path)
+        .with_context(|| format!("Failed to load client key {}", paths.key_path.display()))?;
+
+    builder
+        .with_client_auth_cert(certs, key)
+        .context("Client certificate and key do not match")
+}
+
 /// Create a rustls ClientConfig with certificate pinning
-pub fn create_pinned_tls_config(fingerprint: String) -> Result<ClientConfig> {
+pub fn create_pinned_tls_config(
+    fingerprint: String,
+    client_cert: Option<ClientCertPaths>,
+) -> Result<ClientConfig> {
     // Install default crypto provider if not already set
     let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
 
     let verifier = FingerprintVerifier::new(fingerprint);
 
-    let config = ClientConfig::builder()
+    let builder = ClientConfig::builder()
         .dangerous()
-        .with_custom_certificate_verifier(Arc::new(verifier))
-        .with_no_client_auth();
+        .with_custom_certificate_verifier(Arc::new(verifier));
 
-    Ok(config)
+    with_client_cert(builder, client_cert)
 }
 
 /// Create a rustls ClientConfig that accepts any certificate (for HTTP mode or no pinning)
-pub fn create_insecure_tls_config() -> Result<ClientConfig> {
+pub fn create_insecure_tls_config(client_cert: Option<ClientCertPaths>) -> Result<ClientConfig> {
     // Install default crypto provider if not already set
     let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
 
@@ -173,11 +211,9 @@ pub fn create_insecure_tls_config() -> Result<ClientConfig> {
     // Add webpki roots
     root_store.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
 
-    let config = ClientConfig::builder()
-        .with_root_certificates(root_store)
-        .with_no_client_auth();
+    let builder = ClientConfig::builder().with_root_certificates(root_store);
 
-    Ok(config)
+    with_client_cert(builder, client_cert)
 }
 
 #[cfg(test)]
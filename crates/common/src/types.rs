@@ -11,6 +11,7 @@ use crate::Profile;
 
 /// Authentication type for SSH connection
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum AuthType {
     /// SSH key authentication
@@ -23,6 +24,7 @@ pub enum AuthType {
 
 /// Type of port forwarding
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum ForwardingType {
     /// Local port forwarding (bind local port, forward to remote)
@@ -35,6 +37,7 @@ pub enum ForwardingType {
 
 /// Status of a tunnel connection
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum TunnelStatus {
     NotConnected,   // no active tunnel task
@@ -47,55 +50,25 @@ pub enum TunnelStatus {
     Failed(String), // connection attempt failed (reason)
 }
 
-/// Domain events for tunnel lifecycle (business logic)
-/// Note: This is different from the SSE wire protocol TunnelEvent in crate::sse
-/// This type is for domain/business logic, not SSE communication
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum TunnelDomainEvent {
-    /// Tunnel connected successfully
-    Starting { id: Uuid, timestamp: DateTime<Utc> },
-
-    /// Tunnel connected successfully
-    Connected { id: Uuid, timestamp: DateTime<Utc> },
-
-    /// Tunnel disconnected
-    Disconnected {
-        id: Uuid,
-        reason: String,
-        timestamp: DateTime<Utc>,
-    },
-
-    /// Tunnel is reconnecting
-    Reconnecting {
-        id: Uuid,
-        attempt: u32,
-        timestamp: DateTime<Utc>,
-    },
-
-    /// Tunnel error occurred
-    Error {
-        id: Uuid,
-        error: String,
-        timestamp: DateTime<Utc>,
-    },
-
-    AuthRequired {
-        id: Uuid,
-        prompt: String,
-        timestamp: DateTime<Utc>,
-    },
-
-    /// 2FA code required
-    TwoFactorRequired {
-        id: Uuid,
-        prompt: String,
-        timestamp: DateTime<Utc>,
-    },
+/// Traffic counters of a connected tunnel (sent in `stats` events)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TunnelStats {
+    /// Bytes forwarded from local clients to the remote end
+    pub bytes_sent: u64,
+    /// Bytes forwarded from the remote end to local clients
+    pub bytes_received: u64,
+    /// Forwarded connections currently open
+    pub active_connections: u64,
+    /// Forwarded connections since the tunnel connected
+    pub total_connections: u64,
+    /// Seconds since the tunnel connected
+    pub uptime_secs: u64,
 }
 
 /// Type of authentication input required from user
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum AuthRequestType {
     /// SSH key passphrase needed
@@ -108,10 +81,28 @@ pub enum AuthRequestType {
     KeyboardInteractive,
     /// SSH host key verification needed (first connection or key changed)
     HostKeyVerification,
+    /// Master passphrase of the encrypted secret vault needed
+    VaultPassphrase,
+}
+
+/// One field of a multi-prompt (keyboard-interactive) request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuthPrompt {
+    /// Prompt text as sent by the server (e.g. "Verification code: ")
+    pub prompt: String,
+    /// Whether the answer may be shown while typing
+    pub echo: bool,
 }
 
 /// Authentication request from daemon to client
+///
+/// Keyboard-interactive requests list all the server's prompts in `prompts`,
+/// to be answered together with `AuthResponse::responses`. `prompt` and
+/// `hidden` then describe the first of them, for clients that answer one
+/// prompt at a time; the daemon asks again for the prompts left unanswered.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AuthRequest {
     /// Unique request ID for this auth request
     pub id: Uuid,
@@ -123,19 +114,57 @@ pub struct AuthRequest {
     pub prompt: String,
     /// Whether input should be hidden (like passwords)
     pub hidden: bool,
+    /// Title of a keyboard-interactive request, as sent by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Instructions of a keyboard-interactive request, as sent by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    /// All prompts of a keyboard-interactive request, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompts: Vec<AuthPrompt>,
+}
+
+impl AuthRequest {
+    /// Single-prompt request for one of this request's `prompts`
+    pub fn for_prompt(&self, prompt: &AuthPrompt) -> AuthRequest {
+        AuthRequest {
+            prompt: prompt.prompt.clone(),
+            hidden: !prompt.echo,
+            name: None,
+            instructions: None,
+            prompts: Vec::new(),
+            ..self.clone()
+        }
+    }
 }
 
 /// Authentication response from client to daemon
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct AuthResponse {
     /// Tunnel ID this response is for
     pub tunnel_id: Uuid,
     /// The user's input (password, code, etc.)
+    #[serde(default)]
     pub response: String,
+    /// Answers to the request's `prompts`, in order (replaces `response`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub responses: Vec<String>,
+}
+
+// The response is a secret; keep it out of debug output
+impl std::fmt::Debug for AuthResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthResponse")
+            .field("tunnel_id", &self.tunnel_id)
+            .finish_non_exhaustive()
+    }
 }
 
 /// Profile source mode for tunnel start request
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum ProfileSourceMode {
     /// Load profile from daemon's filesystem (Unix socket mode)
@@ -149,6 +178,7 @@ pub enum ProfileSourceMode {
 
 /// Request to start a tunnel
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct StartTunnelRequest {
     /// Profile ID (always required for identification)
     pub profile_id: String,
@@ -196,6 +226,8 @@ pub struct TunnelStatusResponse {
     pub id: Uuid,
     pub status: TunnelStatus,
     pub pending_auth: Option<AuthRequest>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bound_address: Option<std::net::SocketAddr>,
 }
 
 /* impl TunnelStatus {
@@ -208,8 +240,31 @@ pub struct TunnelStatusResponse {
    }
 } */
 
+/// One API listener served by the daemon
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ListenerInfo {
+    pub mode: String, // "unix-socket", "tcp-http", "tcp-https"
+    pub bind_host: Option<String>, // for TCP modes
+    pub bind_port: Option<u16>,    // for TCP modes
+    pub socket_path: Option<String>, // for Unix socket mode
+    pub require_auth: bool,
+}
+
+/// A client address locked out after repeated authentication failures
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LockoutInfo {
+    #[cfg_attr(feature = "openapi", schema(value_type = String))]
+    pub source: std::net::IpAddr,
+    /// How many times this source has been locked out (the lockout doubles each time)
+    pub lockouts: u32,
+    pub remaining_secs: u64,
+}
+
 /// Information about the daemon (version, config, runtime stats)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct DaemonInfo {
     // Version info
     pub version: String,
@@ -225,14 +280,609 @@ pub struct DaemonInfo {
     pub socket_path: Option<String>, // for Unix socket mode
     pub require_auth: bool,
     pub group_access: bool,
+    /// All listeners the daemon serves (the fields above describe the first one)
+    #[serde(default)]
+    pub listeners: Vec<ListenerInfo>,
 
     // Runtime info
     pub config_file_path: String,
     pub known_hosts_path: String,
     pub ssh_key_dir: String, // Directory where daemon looks for SSH keys (e.g., /home/user/.ssh)
     pub active_tunnels_count: usize,
+    /// Client addresses currently locked out by brute-force protection
+    #[serde(default)]
+    pub lockouts: Vec<LockoutInfo>,
 
     // Process info
     pub pid: u32,
     pub user: String, // username running daemon
 }
+
+/// Version of the REST API served under `/api/v1`
+pub const API_VERSION: &str = "v1";
+
+/// Optional daemon feature advertised in `DaemonCapabilities`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonFeature {
+    /// Scoped API tokens (`/api/v1/tokens`)
+    ScopedTokens,
+    /// Primary token rotation (`/api/v1/daemon/token/rotate`)
+    TokenRotation,
+    /// Preflight checks (`/api/v1/tunnels/{id}/preflight`)
+    Preflight,
+    /// Per-profile lifecycle hooks
+    Hooks,
+    /// Local forwards on port 0
+    EphemeralPorts,
+    /// Audit log query (`/api/v1/audit`); only when the audit log is enabled
+    AuditLog,
+    /// WebSocket control channel (`/api/v1/ws`)
+    #[serde(rename = "websocket")]
+    WebSocket,
+    /// Prometheus metrics (`/api/v1/metrics`)
+    Metrics,
+    /// Session history (`/api/v1/tunnels/{id}/history`); only when enabled
+    History,
+    /// Per-tunnel logs (`/api/v1/tunnels/{id}/logs`)
+    TunnelLogs,
+    /// Key cache (`/api/v1/keys`); only when enabled
+    KeyCache,
+    /// A feature this client doesn't know about (newer daemon)
+    #[serde(other)]
+    Unknown,
+}
+
+/// What a daemon supports (`GET /api/v1/capabilities`)
+///
+/// Daemons that predate the versioned API have no capabilities endpoint;
+/// clients should fall back to `DaemonCapabilities::legacy()`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DaemonCapabilities {
+    /// API versions served under `/api/<version>`, e.g. `["v1"]`
+    pub api_versions: Vec<String>,
+    pub daemon_version: String,
+    /// Forwarding types the daemon can run
+    pub forwarding_types: Vec<ForwardingType>,
+    /// SSH authentication types the daemon can handle
+    pub auth_types: Vec<AuthType>,
+    #[serde(default)]
+    pub features: Vec<DaemonFeature>,
+}
+
+impl DaemonCapabilities {
+    /// What daemons without `/api/v1` support: local forwarding, all auth
+    /// types, unversioned `/api` paths
+    pub fn legacy() -> Self {
+        Self {
+            api_versions: Vec::new(),
+            daemon_version: "unknown".to_string(),
+            forwarding_types: vec![ForwardingType::Local],
+            auth_types: vec![AuthType::Key, AuthType::Password, AuthType::PasswordWith2FA],
+            features: Vec::new(),
+        }
+    }
+
+    pub fn supports_forwarding(&self, forwarding_type: &ForwardingType) -> bool {
+        self.forwarding_types.contains(forwarding_type)
+    }
+
+    pub fn supports_auth(&self, auth_type: &AuthType) -> bool {
+        self.auth_types.contains(auth_type)
+    }
+
+    pub fn has_feature(&self, feature: DaemonFeature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    /// Path prefix for API requests: `/api/v1` when the daemon serves this
+    /// client's API version, otherwise the unversioned `/api`
+    pub fn api_prefix(&self) -> String {
+        if self.api_versions.iter().any(|v| v == API_VERSION) {
+            format!("/api/{}", API_VERSION)
+        } else {
+            "/api".to_string()
+        }
+    }
+}
+
+/// What an API token is allowed to do
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    /// Read tunnel status, daemon info and the event stream
+    Read,
+    /// Start and stop tunnels
+    Control,
+    /// Answer authentication prompts (passwords, 2FA codes)
+    Auth,
+    /// Everything, including daemon shutdown and token management
+    Admin,
+}
+
+impl TokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::Read => "read",
+            TokenScope::Control => "control",
+            TokenScope::Auth => "auth",
+            TokenScope::Admin => "admin",
+        }
+    }
+}
+
+impl std::fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for TokenScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "read" => Ok(TokenScope::Read),
+            "control" => Ok(TokenScope::Control),
+            "auth" => Ok(TokenScope::Auth),
+            "admin" => Ok(TokenScope::Admin),
+            _ => Err(format!(
+                "unknown scope '{}' (expected read, control, auth or admin)",
+                s
+            )),
+        }
+    }
+}
+
+/// API token as listed by the daemon (never includes the secret)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TokenInfo {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    /// Profiles the token may touch; empty together with `profile_tags` means all
+    #[serde(default)]
+    pub profile_ids: Vec<Uuid>,
+    /// Profile tags the token may touch
+    #[serde(default)]
+    pub profile_tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for creating an API token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateTokenRequest {
+    pub name: String,
+    pub scopes: Vec<TokenScope>,
+    #[serde(default)]
+    pub profile_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub profile_tags: Vec<String>,
+}
+
+/// Response to token creation; the secret is only ever returned here
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateTokenResponse {
+    pub token: String,
+    pub info: TokenInfo,
+}
+
+impl std::fmt::Debug for CreateTokenResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CreateTokenResponse")
+            .field("info", &self.info)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Request to rotate the daemon's primary token
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RotateTokenRequest {
+    /// Seconds the old token stays valid (daemon default when unset, 0 = revoke now,
+    /// at most one week)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_secs: Option<u64>,
+}
+
+/// Response to a token rotation with the new primary token
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RotateTokenResponse {
+    pub token: String,
+    /// Seconds the previous token remains valid
+    pub grace_secs: u64,
+}
+
+impl std::fmt::Debug for RotateTokenResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RotateTokenResponse")
+            .field("grace_secs", &self.grace_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A decrypted SSH key held in the daemon's key cache (never includes the key)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UnlockedKey {
+    /// Key file on the daemon host
+    pub path: String,
+    /// e.g. `ssh-ed25519`
+    pub algorithm: String,
+    /// SHA256 fingerprint, as printed by `ssh-keygen -l`
+    pub fingerprint: String,
+    pub unlocked_at: DateTime<Utc>,
+    /// When the key is locked again (`None`: only on request)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Request to decrypt a key into the daemon's key cache
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UnlockKeyRequest {
+    /// Key file on the daemon host; relative paths are looked up in its `~/.ssh`
+    pub key_path: String,
+    pub passphrase: String,
+}
+
+impl std::fmt::Debug for UnlockKeyRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UnlockKeyRequest")
+            .field("key_path", &self.key_path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Request to drop keys from the daemon's key cache
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LockKeysRequest {
+    /// Key path or fingerprint; all keys when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+/// What an audit log entry records
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    TunnelStart,
+    TunnelStop,
+    /// Answer to an authentication prompt (the secret itself is never logged)
+    AuthSubmit,
+    DaemonShutdown,
+    TokenCreate,
+    TokenRevoke,
+    TokenRotate,
+    /// A key was decrypted into the key cache
+    KeyUnlock,
+    /// Keys were dropped from the key cache
+    KeyLock,
+    /// Tunnel state change reported by the daemon (no caller)
+    TunnelState,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::TunnelStart => "tunnel_start",
+            AuditAction::TunnelStop => "tunnel_stop",
+            AuditAction::AuthSubmit => "auth_submit",
+            AuditAction::DaemonShutdown => "daemon_shutdown",
+            AuditAction::TokenCreate => "token_create",
+            AuditAction::TokenRevoke => "token_revoke",
+            AuditAction::TokenRotate => "token_rotate",
+            AuditAction::KeyUnlock => "key_unlock",
+            AuditAction::KeyLock => "key_lock",
+            AuditAction::TunnelState => "tunnel_state",
+        }
+    }
+}
+
+impl std::fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One line of the daemon's audit log (JSON Lines)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: AuditAction,
+    /// Token, certificate or peer identity that made the call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// Client address, or the local user for Unix socket calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile_name: Option<String>,
+    /// SSH destination as `user@host:port`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub success: bool,
+    /// Error message, new tunnel state, token name, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    /// Successful entry stamped with the current time
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            action,
+            actor: None,
+            source: None,
+            profile_id: None,
+            profile_name: None,
+            target: None,
+            success: true,
+            detail: None,
+        }
+    }
+
+    pub fn with_profile_id(mut self, id: Uuid) -> Self {
+        self.profile_id = Some(id);
+        self
+    }
+
+    /// Fill in the profile ID, name and SSH destination
+    pub fn with_profile(mut self, profile: &Profile) -> Self {
+        let connection = &profile.connection;
+        self.profile_id = Some(profile.metadata.id);
+        self.profile_name = Some(profile.metadata.name.clone());
+        self.target = Some(format!(
+            "{}@{}:{}",
+            connection.user, connection.host, connection.port
+        ));
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Mark the action as failed with the given reason
+    pub fn failed(mut self, reason: impl Into<String>) -> Self {
+        self.success = false;
+        self.detail = Some(reason.into());
+        self
+    }
+}
+
+/// One run of a tunnel, from start until it was stopped or failed
+/// (`GET /api/tunnels/{id}/history`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TunnelSession {
+    pub profile_id: Uuid,
+    pub profile_name: String,
+    pub started_at: DateTime<Utc>,
+    /// When the forward first came up; `None` if it never connected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connected_at: Option<DateTime<Utc>>,
+    pub ended_at: DateTime<Utc>,
+    /// Disconnect reason or error message
+    pub end_reason: String,
+    /// Ended by an error rather than a stop
+    pub failed: bool,
+    #[serde(default)]
+    pub bytes_sent: u64,
+    #[serde(default)]
+    pub bytes_received: u64,
+}
+
+impl TunnelSession {
+    /// Time spent connected
+    pub fn uptime(&self) -> Option<chrono::Duration> {
+        self.connected_at.map(|at| self.ended_at - at)
+    }
+}
+
+/// Totals over a list of sessions, for spotting recurring failures
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistorySummary {
+    pub sessions: usize,
+    pub failed: usize,
+    pub uptime: chrono::Duration,
+    /// Most frequent failure reason and how often it occurred
+    pub top_failure: Option<(String, usize)>,
+}
+
+impl HistorySummary {
+    pub fn from_sessions(sessions: &[TunnelSession]) -> Self {
+        let mut failures: Vec<(String, usize)> = Vec::new();
+        for session in sessions.iter().filter(|s| s.failed) {
+            match failures.iter_mut().find(|(reason, _)| *reason == session.end_reason) {
+                Some((_, count)) => *count += 1,
+                None => failures.push((session.end_reason.clone(), 1)),
+            }
+        }
+
+        Self {
+            sessions: sessions.len(),
+            failed: failures.iter().map(|(_, count)| count).sum(),
+            uptime: sessions
+                .iter()
+                .filter_map(TunnelSession::uptime)
+                .fold(chrono::Duration::zero(), |total, uptime| total + uptime),
+            // Ties go to the reason seen first
+            top_failure: failures
+                .into_iter()
+                .rev()
+                .max_by_key(|(_, count)| *count),
+        }
+    }
+}
+
+/// Severity of a log line, most severe first
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            "trace" => Ok(LogLevel::Trace),
+            _ => Err(format!(
+                "unknown log level '{}' (expected error, warn, info, debug or trace)",
+                s
+            )),
+        }
+    }
+}
+
+/// One line of a tunnel's diagnostic log (`GET /api/tunnels/{id}/logs`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TunnelLogEntry {
+    /// Increases by one per line of this tunnel
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    /// Module that logged the line (e.g. `ssh_tunnel_daemon::tunnel`, `russh::client`)
+    pub target: String,
+    /// Message followed by any extra fields as `key=value`
+    pub message: String,
+}
+
+/// Request body for `PUT /api/tunnels/{id}/logs/level`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SetLogLevelRequest {
+    pub level: LogLevel,
+    /// How long the level applies before falling back to the default.
+    /// Default: 600 (ignored when `level` is the default level)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<u64>,
+}
+
+/// Level a tunnel's log is captured at
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TunnelLogLevel {
+    pub level: LogLevel,
+    /// When a raised level falls back to the default; `None` for the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(minutes_up: Option<i64>, failure: Option<&str>) -> TunnelSession {
+        let started_at = Utc::now();
+        TunnelSession {
+            profile_id: Uuid::nil(),
+            profile_name: "db".to_string(),
+            started_at,
+            connected_at: minutes_up.map(|_| started_at),
+            ended_at: started_at + chrono::Duration::minutes(minutes_up.unwrap_or(0)),
+            end_reason: failure.unwrap_or("Stopped by user").to_string(),
+            failed: failure.is_some(),
+            bytes_sent: 0,
+            bytes_received: 0,
+        }
+    }
+
+    #[test]
+    fn test_history_summary() {
+        let sessions = [
+            session(Some(90), None),
+            session(Some(30), Some("Connection reset")),
+            session(None, Some("Authentication failed")),
+            session(Some(5), Some("Connection reset")),
+        ];
+
+        let summary = HistorySummary::from_sessions(&sessions);
+        assert_eq!(summary.sessions, 4);
+        assert_eq!(summary.failed, 3);
+        assert_eq!(summary.uptime, chrono::Duration::minutes(125));
+        assert_eq!(summary.top_failure, Some(("Connection reset".to_string(), 2)));
+
+        assert_eq!(HistorySummary::from_sessions(&[]), HistorySummary::default());
+    }
+
+    #[test]
+    fn test_multi_prompt_auth_request() {
+        // Single-prompt requests look as they did before
+        let json = r#"{
+            "id": "00000000-0000-0000-0000-000000000001",
+            "tunnel_id": "00000000-0000-0000-0000-000000000002",
+            "auth_type": "password",
+            "prompt": "Password:",
+            "hidden": true
+        }"#;
+        let request: AuthRequest = serde_json::from_str(json).unwrap();
+        assert!(request.prompts.is_empty());
+        assert!(!serde_json::to_string(&request).unwrap().contains("prompts"));
+
+        let request = AuthRequest {
+            auth_type: AuthRequestType::KeyboardInteractive,
+            name: Some("Login".to_string()),
+            prompts: vec![
+                AuthPrompt { prompt: "Password: ".to_string(), echo: false },
+                AuthPrompt { prompt: "Username: ".to_string(), echo: true },
+            ],
+            ..request
+        };
+        let single = request.for_prompt(&request.prompts[1]);
+        assert_eq!(single.id, request.id);
+        assert_eq!(single.prompt, "Username: ");
+        assert!(!single.hidden);
+        assert!(single.prompts.is_empty() && single.name.is_none());
+
+        let response: AuthResponse = serde_json::from_str(
+            r#"{"tunnel_id": "00000000-0000-0000-0000-000000000002", "responses": ["a", "b"]}"#,
+        )
+        .unwrap();
+        assert_eq!(response.responses, ["a", "b"]);
+        assert!(response.response.is_empty());
+    }
+}
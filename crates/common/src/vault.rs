@@ -0,0 +1,443 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Encrypted secret vault - file storage for passwords/passphrases
+//
+// Backs `PasswordStorage::File` on machines without a Secret Service. All
+// secrets live in one file (`vault.toml` in the config directory), encrypted
+// with AES-256-GCM under a key derived from a master passphrase with Argon2id.
+// Profile IDs are encrypted along with the secrets; the file only reveals the
+// KDF parameters.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::error::{Error, Result};
+
+const FORMAT_VERSION: u32 = 1;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+/// Binds the ciphertext to this file format
+const AAD: &[u8] = b"ssh-tunnel-manager vault v1";
+
+/// Upper bounds for KDF parameters read from a file, so that a corrupted or
+/// planted vault can't make the daemon allocate gigabytes or spin for minutes
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+/// An unlocked vault: the derived key and the decrypted secrets by profile ID
+///
+/// Secrets and key are zeroed when dropped. Changes are written with
+/// [`Vault::save`].
+///
+/// # Examples
+/// ```no_run
+/// use uuid::Uuid;
+/// use ssh_tunnel_common::vault::Vault;
+///
+/// let path = Vault::default_path()?;
+/// let mut vault = Vault::open_or_create(&path, "master passphrase")?;
+/// vault.set(Uuid::new_v4(), "my-secret-password");
+/// vault.save()?;
+/// # Ok::<(), ssh_tunnel_common::Error>(())
+/// ```
+pub struct Vault {
+    path: PathBuf,
+    kdf: KdfParams,
+    key: Zeroizing<[u8; KEY_LEN]>,
+    secrets: BTreeMap<Uuid, Zeroizing<String>>,
+}
+
+/// Exclusive lock on a vault against other writers, released when dropped
+///
+/// See [`Vault::lock`].
+pub struct VaultLock {
+    _file: fs::File,
+}
+
+/// On-disk layout
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    /// Base64 AES-256-GCM nonce
+    nonce: String,
+    /// Base64 ciphertext of the JSON map of profile IDs to secrets
+    ciphertext: String,
+}
+
+/// Argon2id parameters, stored so they can be raised without breaking old files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    /// Base64 salt
+    salt: String,
+}
+
+impl KdfParams {
+    /// Argon2's recommended defaults (19 MiB, 2 passes) with a fresh salt
+    fn generate() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            algorithm: "argon2id".to_string(),
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+            salt: BASE64.encode(salt),
+        }
+    }
+
+    fn derive_key(&self, passphrase: &str) -> Result<Zeroizing<[u8; KEY_LEN]>> {
+        if self.algorithm != "argon2id" {
+            return Err(Error::Vault(format!("Unsupported KDF: {}", self.algorithm)));
+        }
+        for (name, value, max) in [
+            ("memory_kib", self.memory_kib, MAX_MEMORY_KIB),
+            ("iterations", self.iterations, MAX_ITERATIONS),
+            ("parallelism", self.parallelism, MAX_PARALLELISM),
+        ] {
+            if value > max {
+                return Err(Error::Vault(format!(
+                    "KDF parameter {} = {} exceeds the maximum of {}",
+                    name, value, max
+                )));
+            }
+        }
+        let salt = BASE64
+            .decode(&self.salt)
+            .map_err(|e| Error::Vault(format!("Invalid salt: {}", e)))?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(KEY_LEN))
+            .map_err(|e| Error::Vault(format!("Invalid KDF parameters: {}", e)))?;
+
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+            .map_err(|e| Error::Vault(format!("Key derivation failed: {}", e)))?;
+        Ok(key)
+    }
+}
+
+impl Vault {
+    /// `vault.toml` in the ssh-tunnel-manager config directory
+    pub fn default_path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| Error::Config("Could not determine config directory".to_string()))?;
+        Ok(config_dir.join("ssh-tunnel-manager").join("vault.toml"))
+    }
+
+    /// Lock the vault at `path` against other writers, waiting for them
+    ///
+    /// An advisory lock on `vault.toml.lock` next to it. Take it before
+    /// opening the vault and hold it until after [`Vault::save`] when changing
+    /// secrets, so two processes editing the vault at once don't lose each
+    /// other's changes. Readers don't need it: saves replace the file
+    /// atomically.
+    pub fn lock(path: &Path) -> Result<VaultLock> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = private_file()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path.with_extension("toml.lock"))?;
+        file.lock()?;
+        Ok(VaultLock { _file: file })
+    }
+
+    /// Create an empty vault protected by `passphrase` (not written until saved)
+    pub fn create(path: &Path, passphrase: &str) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(Error::Vault("Master passphrase cannot be empty".to_string()));
+        }
+        let kdf = KdfParams::generate();
+        let key = kdf.derive_key(passphrase)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            kdf,
+            key,
+            secrets: BTreeMap::new(),
+        })
+    }
+
+    /// Unlock the vault at `path`
+    ///
+    /// Fails with [`Error::Vault`] when the passphrase is wrong or the file
+    /// has been tampered with.
+    pub fn open(path: &Path, passphrase: &str) -> Result<Self> {
+        let file = read_file(path)?;
+        let key = file.kdf.derive_key(passphrase)?;
+        let secrets = decrypt(&file, &key)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            kdf: file.kdf,
+            key,
+            secrets,
+        })
+    }
+
+    /// Unlock the vault at `path`, or start a new one if there is none yet
+    pub fn open_or_create(path: &Path, passphrase: &str) -> Result<Self> {
+        if path.exists() {
+            Self::open(path, passphrase)
+        } else {
+            Self::create(path, passphrase)
+        }
+    }
+
+    /// Re-read the file with the key already derived, picking up changes
+    /// made by other processes
+    ///
+    /// Fails if the master passphrase was changed since (a new salt means a
+    /// new key); the vault must then be opened again.
+    pub fn reload(&mut self) -> Result<()> {
+        let file = read_file(&self.path)?;
+        if file.kdf != self.kdf {
+            return Err(Error::Vault("The master passphrase has changed".to_string()));
+        }
+        self.secrets = decrypt(&file, &self.key)?;
+        Ok(())
+    }
+
+    /// Encrypt the secrets with a fresh nonce and write the file (mode 0600)
+    pub fn save(&self) -> Result<()> {
+        let plaintext = {
+            let map: BTreeMap<&Uuid, &str> =
+                self.secrets.iter().map(|(id, s)| (id, s.as_str())).collect();
+            Zeroizing::new(serde_json::to_vec(&map)?)
+        };
+
+        let cipher = Aes256Gcm::new_from_slice(self.key.as_ref())
+            .map_err(|e| Error::Vault(format!("Invalid key: {}", e)))?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, Payload { msg: &plaintext, aad: AAD })
+            .map_err(|_| Error::Vault("Encryption failed".to_string()))?;
+
+        let file = VaultFile {
+            version: FORMAT_VERSION,
+            kdf: self.kdf.clone(),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        let content = toml::to_string_pretty(&file)
+            .map_err(|e| Error::Vault(format!("Failed to serialize vault: {}", e)))?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write a temporary file first so a crash never leaves a truncated vault
+        let tmp_path = self.path.with_extension("toml.tmp");
+        let mut tmp = private_file()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+        tmp.write_all(content.as_bytes())?;
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Protect the vault with a new passphrase (and salt); takes effect on save
+    pub fn change_passphrase(&mut self, new_passphrase: &str) -> Result<()> {
+        if new_passphrase.is_empty() {
+            return Err(Error::Vault("Master passphrase cannot be empty".to_string()));
+        }
+        let kdf = KdfParams::generate();
+        self.key = kdf.derive_key(new_passphrase)?;
+        self.kdf = kdf;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The secret stored for a profile
+    pub fn get(&self, profile_id: &Uuid) -> Option<&str> {
+        self.secrets.get(profile_id).map(|s| s.as_str())
+    }
+
+    pub fn set(&mut self, profile_id: Uuid, secret: &str) {
+        self.secrets.insert(profile_id, Zeroizing::new(secret.to_string()));
+    }
+
+    /// Remove a profile's secret; returns whether there was one
+    pub fn remove(&mut self, profile_id: &Uuid) -> bool {
+        self.secrets.remove(profile_id).is_some()
+    }
+
+    /// IDs of the profiles with a stored secret
+    pub fn profile_ids(&self) -> impl Iterator<Item = &Uuid> {
+        self.secrets.keys()
+    }
+}
+
+/// Options for a file only the owner may read (mode 0600 on Unix)
+fn private_file() -> fs::OpenOptions {
+    let mut options = fs::OpenOptions::new();
+    #[cfg(unix)]
+    options.mode(0o600);
+    options
+}
+
+fn read_file(path: &Path) -> Result<VaultFile> {
+    if !path.exists() {
+        return Err(Error::Vault(format!("No vault at {}", path.display())));
+    }
+    let content = fs::read_to_string(path)?;
+    let file: VaultFile = toml::from_str(&content)?;
+    if file.version != FORMAT_VERSION {
+        return Err(Error::Vault(format!("Unsupported vault version {}", file.version)));
+    }
+    Ok(file)
+}
+
+fn decrypt(file: &VaultFile, key: &[u8; KEY_LEN]) -> Result<BTreeMap<Uuid, Zeroizing<String>>> {
+    let nonce = BASE64
+        .decode(&file.nonce)
+        .map_err(|e| Error::Vault(format!("Invalid nonce: {}", e)))?;
+    if nonce.len() != 12 {
+        return Err(Error::Vault("Invalid nonce length".to_string()));
+    }
+    let ciphertext = BASE64
+        .decode(&file.ciphertext)
+        .map_err(|e| Error::Vault(format!("Invalid ciphertext: {}", e)))?;
+
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| Error::Vault(format!("Invalid key: {}", e)))?;
+    let plaintext = Zeroizing::new(
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: AAD })
+            .map_err(|_| {
+                Error::Vault("Wrong master passphrase, or the vault file is corrupted".to_string())
+            })?,
+    );
+
+    let secrets: BTreeMap<Uuid, String> = serde_json::from_slice(&plaintext)?;
+    Ok(secrets
+        .into_iter()
+        .map(|(id, secret)| (id, Zeroizing::new(secret)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("vault.toml");
+        let id = Uuid::new_v4();
+
+        let mut vault = Vault::open_or_create(&path, "correct horse").unwrap();
+        vault.set(id, "hunter22");
+        vault.save().unwrap();
+
+        // Neither secrets nor profile IDs are readable in the file
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("hunter22"));
+        assert!(!content.contains(&id.to_string()));
+        assert!(content.contains("argon2id"));
+        #[cfg(unix)]
+        {
+            let mode = fs::metadata(&path).unwrap().permissions();
+            assert_eq!(std::os::unix::fs::PermissionsExt::mode(&mode) & 0o777, 0o600);
+        }
+
+        let opened = Vault::open(&path, "correct horse").unwrap();
+        assert_eq!(opened.get(&id), Some("hunter22"));
+        assert!(matches!(Vault::open(&path, "wrong horse"), Err(Error::Vault(_))));
+
+        // Another process adds a secret; reload picks it up with the same key
+        let other = Uuid::new_v4();
+        let mut writer = Vault::open(&path, "correct horse").unwrap();
+        writer.set(other, "s3cret");
+        writer.save().unwrap();
+        vault.reload().unwrap();
+        assert_eq!(vault.get(&other), Some("s3cret"));
+
+        // A new passphrase invalidates the old key
+        writer.change_passphrase("battery staple").unwrap();
+        writer.save().unwrap();
+        assert!(vault.reload().is_err());
+        assert!(Vault::open(&path, "correct horse").is_err());
+        let reopened = Vault::open(&path, "battery staple").unwrap();
+        assert_eq!(reopened.get(&id), Some("hunter22"));
+        assert_eq!(reopened.profile_ids().count(), 2);
+    }
+
+    #[test]
+    fn test_vault_tampering_detected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("vault.toml");
+        let mut vault = Vault::create(&path, "pw").unwrap();
+        vault.set(Uuid::new_v4(), "secret");
+        vault.save().unwrap();
+
+        let mut file: VaultFile = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut ciphertext = BASE64.decode(&file.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        file.ciphertext = BASE64.encode(ciphertext);
+        fs::write(&path, toml::to_string(&file).unwrap()).unwrap();
+
+        assert!(Vault::open(&path, "pw").is_err());
+        assert!(Vault::create(&path, "").is_err());
+    }
+
+    #[test]
+    fn test_vault_lock_is_exclusive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("vault.toml");
+        let try_lock = || {
+            let file = fs::File::open(path.with_extension("toml.lock")).unwrap();
+            file.try_lock().is_ok()
+        };
+
+        let lock = Vault::lock(&path).unwrap();
+        assert!(!try_lock());
+        drop(lock);
+        assert!(try_lock());
+    }
+
+    #[test]
+    fn test_vault_kdf_limits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("vault.toml");
+        Vault::create(&path, "pw").unwrap().save().unwrap();
+        let file: VaultFile = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        // Refused before any memory is allocated for the KDF
+        for kdf in [
+            KdfParams { memory_kib: u32::MAX, ..file.kdf.clone() },
+            KdfParams { iterations: MAX_ITERATIONS + 1, ..file.kdf.clone() },
+            KdfParams { parallelism: MAX_PARALLELISM + 1, ..file.kdf.clone() },
+        ] {
+            let err = kdf.derive_key("pw").unwrap_err();
+            assert!(err.to_string().contains("exceeds the maximum"));
+        }
+        assert!(file.kdf.derive_key("pw").is_ok());
+    }
+}
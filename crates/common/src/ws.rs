@@ -0,0 +1,436 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+//! WebSocket control channel (`/api/v1/ws`)
+//!
+//! One authenticated connection carries tunnel events downstream (the same
+//! payloads as `/api/events`) and commands upstream: start, stop, auth
+//! answers and event subscriptions. Every command carries a client-chosen ID
+//! that the daemon echoes in its response.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpStream, UnixStream};
+use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::TlsConnector;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use uuid::Uuid;
+
+use crate::{
+    create_insecure_tls_config, create_pinned_tls_config, ConnectionMode, DaemonClientConfig,
+    Profile, ProfileSourceMode, TunnelEvent, API_VERSION,
+};
+
+/// A command from the client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsRequest {
+    /// Chosen by the client and echoed in the response
+    pub id: u64,
+    #[serde(flatten)]
+    pub command: WsCommand,
+}
+
+/// Commands accepted on the WebSocket; each mirrors a REST endpoint and
+/// needs the same token scope
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum WsCommand {
+    /// `POST /tunnels/{id}/start` (`control` scope)
+    Start {
+        tunnel_id: Uuid,
+        mode: ProfileSourceMode,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<Box<Profile>>,
+    },
+    /// `POST /tunnels/{id}/stop` (`control` scope)
+    Stop { tunnel_id: Uuid },
+    /// `POST /tunnels/{id}/auth` (`auth` scope)
+    SubmitAuth {
+        tunnel_id: Uuid,
+        auth_request_id: Uuid,
+        #[serde(default)]
+        response: String,
+        /// Answers to all prompts of a multi-prompt request (replaces `response`)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        responses: Vec<String>,
+    },
+    /// Only receive events for these tunnels; an empty list means all
+    Subscribe { tunnel_ids: Vec<Uuid> },
+}
+
+impl WsCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WsCommand::Start { .. } => "start",
+            WsCommand::Stop { .. } => "stop",
+            WsCommand::SubmitAuth { .. } => "submit_auth",
+            WsCommand::Subscribe { .. } => "subscribe",
+        }
+    }
+}
+
+// Auth answers are secrets; keep them out of debug output
+impl std::fmt::Debug for WsCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WsCommand::Start { tunnel_id, mode, .. } => f
+                .debug_struct("Start")
+                .field("tunnel_id", tunnel_id)
+                .field("mode", mode)
+                .finish_non_exhaustive(),
+            WsCommand::Stop { tunnel_id } => {
+                f.debug_struct("Stop").field("tunnel_id", tunnel_id).finish()
+            }
+            WsCommand::SubmitAuth { tunnel_id, auth_request_id, .. } => f
+                .debug_struct("SubmitAuth")
+                .field("tunnel_id", tunnel_id)
+                .field("auth_request_id", auth_request_id)
+                .finish_non_exhaustive(),
+            WsCommand::Subscribe { tunnel_ids } => {
+                f.debug_struct("Subscribe").field("tunnel_ids", tunnel_ids).finish()
+            }
+        }
+    }
+}
+
+/// A message from the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WsMessage {
+    Event { event: TunnelEvent },
+    Response(WsResponse),
+}
+
+/// Result of a command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WsResponse {
+    /// ID of the request this answers
+    pub id: u64,
+    /// HTTP status the equivalent REST call returns
+    pub status: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl WsResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// `Ok` with the message, or an error with the daemon's reason
+    pub fn into_result(self) -> Result<Option<String>> {
+        if self.is_success() {
+            Ok(self.message)
+        } else {
+            anyhow::bail!(
+                "{}",
+                self.error
+                    .unwrap_or_else(|| format!("daemon returned status {}", self.status))
+            )
+        }
+    }
+}
+
+type PendingResponses = Arc<Mutex<HashMap<u64, oneshot::Sender<WsResponse>>>>;
+
+/// Handle to a WebSocket connection; clones share the connection
+///
+/// The connection does not reconnect: once it drops, the event receiver
+/// returned by `connect` ends and commands fail. Callers reconnect the same
+/// way they would re-open an SSE stream.
+#[derive(Clone)]
+pub struct WsClient {
+    outgoing: mpsc::Sender<WsRequest>,
+    pending: PendingResponses,
+    next_id: Arc<AtomicU64>,
+}
+
+impl WsClient {
+    /// Connect and authenticate; events arrive on the returned receiver
+    pub async fn connect(
+        config: &DaemonClientConfig,
+    ) -> Result<(Self, mpsc::Receiver<TunnelEvent>)> {
+        let request = handshake_request(config)?;
+        let host_port = crate::format_host_port(&config.daemon_host, config.daemon_port);
+
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(32);
+        let (event_tx, event_rx) = mpsc::channel(100);
+        let pending = PendingResponses::default();
+        let connection = Connection {
+            outgoing: outgoing_rx,
+            events: event_tx,
+            pending: pending.clone(),
+        };
+
+        match config.connection_mode {
+            ConnectionMode::UnixSocket => {
+                let stream = UnixStream::connect(config.socket_path()?)
+                    .await
+                    .context("Failed to connect to daemon socket")?;
+                connection.start(request, stream).await?;
+            }
+            ConnectionMode::Http => {
+                let stream = TcpStream::connect(&host_port)
+                    .await
+                    .with_context(|| format!("Failed to connect to {}", host_port))?;
+                connection.start(request, stream).await?;
+            }
+            ConnectionMode::Https => {
+                let tls_config = if config.tls_cert_fingerprint.is_empty() {
+                    create_insecure_tls_config(config.client_cert())?
+                } else {
+                    create_pinned_tls_config(
+                        config.tls_cert_fingerprint.clone(),
+                        config.client_cert(),
+                    )?
+                };
+                let server_name = ServerName::try_from(config.daemon_host.clone())
+                    .context("Invalid daemon host name")?;
+                let stream = TcpStream::connect(&host_port)
+                    .await
+                    .with_context(|| format!("Failed to connect to {}", host_port))?;
+                let stream = TlsConnector::from(Arc::new(tls_config))
+                    .connect(server_name, stream)
+                    .await
+                    .context("TLS handshake with daemon failed")?;
+                connection.start(request, stream).await?;
+            }
+        }
+
+        let client = Self {
+            outgoing: outgoing_tx,
+            pending,
+            next_id: Arc::new(AtomicU64::new(1)),
+        };
+        Ok((client, event_rx))
+    }
+
+    /// Send a command and wait for its response
+    pub async fn request(&self, command: WsCommand) -> Result<WsResponse> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+
+        if self.outgoing.send(WsRequest { id, command }).await.is_err() {
+            self.pending.lock().await.remove(&id);
+            anyhow::bail!("WebSocket connection closed");
+        }
+        rx.await
+            .map_err(|_| anyhow::anyhow!("WebSocket connection closed before the daemon answered"))
+    }
+
+    /// Start a tunnel, sending the profile along for remote daemons
+    pub async fn start_tunnel(
+        &self,
+        tunnel_id: Uuid,
+        mode: ProfileSourceMode,
+        profile: Option<Profile>,
+    ) -> Result<()> {
+        let profile = profile.map(Box::new);
+        self.request(WsCommand::Start { tunnel_id, mode, profile })
+            .await?
+            .into_result()
+            .context("Failed to start tunnel")?;
+        Ok(())
+    }
+
+    pub async fn stop_tunnel(&self, tunnel_id: Uuid) -> Result<()> {
+        self.request(WsCommand::Stop { tunnel_id })
+            .await?
+            .into_result()
+            .context("Failed to stop tunnel")?;
+        Ok(())
+    }
+
+    pub async fn submit_auth(
+        &self,
+        tunnel_id: Uuid,
+        auth_request_id: Uuid,
+        response: String,
+    ) -> Result<()> {
+        self.request(WsCommand::SubmitAuth {
+            tunnel_id,
+            auth_request_id,
+            response,
+            responses: Vec::new(),
+        })
+            .await?
+            .into_result()
+            .context("Failed to submit auth")?;
+        Ok(())
+    }
+
+    /// Limit events to these tunnels (empty: all tunnels)
+    pub async fn subscribe(&self, tunnel_ids: Vec<Uuid>) -> Result<()> {
+        self.request(WsCommand::Subscribe { tunnel_ids })
+            .await?
+            .into_result()
+            .context("Failed to subscribe")?;
+        Ok(())
+    }
+}
+
+/// `ws://…/api/v1/ws` request with the auth header
+fn handshake_request(config: &DaemonClientConfig) -> Result<Request> {
+    let base_url = config.daemon_base_url()?;
+    let base_url = match base_url.strip_prefix("https://") {
+        Some(rest) => format!("wss://{}", rest),
+        None => base_url.replacen("http://", "ws://", 1),
+    };
+    let url = format!("{}/api/{}/ws", base_url, API_VERSION);
+
+    let mut request = url
+        .into_client_request()
+        .context("Invalid WebSocket URL")?;
+    if !config.auth_token.is_empty() {
+        request.headers_mut().insert(
+            "X-Tunnel-Token",
+            HeaderValue::from_str(&config.auth_token).context("Invalid auth token")?,
+        );
+    }
+    Ok(request)
+}
+
+/// Channels between `WsClient` handles and the connection task
+struct Connection {
+    outgoing: mpsc::Receiver<WsRequest>,
+    events: mpsc::Sender<TunnelEvent>,
+    pending: PendingResponses,
+}
+
+impl Connection {
+    /// Complete the WebSocket handshake and spawn the connection task
+    async fn start<S>(self, request: Request, stream: S) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (socket, _) = tokio_tungstenite::client_async(request, stream)
+            .await
+            .context("WebSocket handshake with daemon failed")?;
+        tokio::spawn(self.run(socket));
+        Ok(())
+    }
+
+    async fn run<S>(mut self, socket: WebSocketStream<S>)
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let (mut sink, mut stream) = socket.split();
+        let mut handles_dropped = false;
+
+        loop {
+            tokio::select! {
+                request = self.outgoing.recv(), if !handles_dropped => {
+                    let Some(request) = request else {
+                        // No more commands; keep delivering events
+                        handles_dropped = true;
+                        continue;
+                    };
+                    let json = match serde_json::to_string(&request) {
+                        Ok(json) => json,
+                        Err(e) => {
+                            tracing::error!("Failed to serialize WebSocket command: {}", e);
+                            self.pending.lock().await.remove(&request.id);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = sink.send(Message::Text(json)).await {
+                        tracing::warn!("Failed to send WebSocket command: {}", e);
+                        break;
+                    }
+                }
+
+                message = stream.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | None => break,
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => {
+                            tracing::warn!("WebSocket error: {}", e);
+                            break;
+                        }
+                    };
+
+                    match serde_json::from_str::<WsMessage>(&text) {
+                        Ok(WsMessage::Event { event }) => {
+                            if self.events.send(event).await.is_err() && handles_dropped {
+                                break;
+                            }
+                        }
+                        Ok(WsMessage::Response(response)) => {
+                            if let Some(tx) = self.pending.lock().await.remove(&response.id) {
+                                let _ = tx.send(response);
+                            }
+                        }
+                        Err(e) => tracing::warn!("Failed to parse WebSocket message: {} ({})", text, e),
+                    }
+                }
+            }
+        }
+
+        // Dropping the senders fails every command still waiting
+        self.pending.lock().await.clear();
+        let _ = sink.close().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ws_wire_format() {
+        let tunnel_id = Uuid::new_v4();
+        let request = WsRequest {
+            id: 7,
+            command: WsCommand::Stop { tunnel_id },
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["id"], 7);
+        assert_eq!(json["command"], "stop");
+        assert_eq!(json["tunnel_id"], tunnel_id.to_string());
+
+        let secret = WsCommand::SubmitAuth {
+            tunnel_id,
+            auth_request_id: Uuid::new_v4(),
+            response: "hunter2".to_string(),
+            responses: vec!["hunter3".to_string()],
+        };
+        assert!(!format!("{:?}", secret).contains("hunter"));
+
+        let response: WsMessage = serde_json::from_str(
+            r#"{"type":"response","id":7,"status":404,"error":"Tunnel is not active"}"#,
+        )
+        .unwrap();
+        match response {
+            WsMessage::Response(response) => {
+                assert_eq!(response.id, 7);
+                assert!(response.into_result().is_err());
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        let event: WsMessage = serde_json::from_str(&format!(
+            r#"{{"type":"event","event":{{"type":"starting","id":"{}"}}}}"#,
+            tunnel_id
+        ))
+        .unwrap();
+        assert!(matches!(
+            event,
+            WsMessage::Event { event: TunnelEvent::Starting { id, .. } } if id == tunnel_id
+        ));
+    }
+}
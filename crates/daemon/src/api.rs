@@ -4,38 +4,60 @@
 // SSH Tunnel Manager - REST API Module
 // Handles HTTP API endpoints for tunnel control
 
+use std::fmt;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
-    Json, Router,
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts, Path, Query, State},
+    http::{header::HeaderName, request::Parts, HeaderMap, StatusCode},
+    middleware::from_fn_with_state,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Extension, Json, Router,
 };
 use tower_http::trace::{TraceLayer, DefaultMakeSpan, DefaultOnResponse};
 use tower_http::LatencyUnit;
 
 // added for /api/event management
-use axum::response::sse::Event;
+use axum::response::sse::{Event, KeepAlive};
 use axum::response::Sse;
 use futures::{stream, StreamExt};
 use std::convert::Infallible;
-use tokio_stream::wrappers::BroadcastStream;
 
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
 use uuid::Uuid;
+use zeroize::Zeroizing;
 
 use ssh_tunnel_common::{
-    load_profile_by_id, AuthRequest, ProfileSourceMode, StartTunnelRequest,
-    TunnelStatus,
+    load_all_profiles, load_profile_by_id, preflight, AuditAction, AuditEntry, AuthRequest,
+    CreateTokenRequest, CreateTokenResponse, DaemonCapabilities, DaemonFeature, DaemonInfo,
+    LockKeysRequest, PasswordStorage, PreflightReport, ProfileSourceMode, RotateTokenRequest,
+    RotateTokenResponse, Profile, SetLogLevelRequest, StartTunnelRequest, TokenInfo, TokenScope,
+    TunnelLogEntry, TunnelLogLevel, TunnelSession, TunnelStatusResponse, UnlockKeyRequest,
+    UnlockedKey, API_VERSION,
 };
 use chrono::{DateTime, Utc};
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 
+use crate::audit::{AuditFilter, AuditLog};
+use crate::history::SessionHistory;
+use crate::keycache;
+use crate::auth::{
+    require_scope, Identity, NewToken, PrimaryToken, TokenRegistry, DEFAULT_ROTATION_GRACE_SECS,
+    MAX_ROTATION_GRACE_SECS,
+};
 use crate::config::DaemonConfig;
-use crate::tunnel::{TunnelEvent, TunnelManager};
+use crate::events::{EventFeed, FeedItem};
+use crate::peercred::PeerCredentials;
+use crate::ratelimit::AuthLimiter;
+use crate::tunnel_log::TunnelLogs;
+use crate::tunnel::{TunnelManager, SUPPORTED_AUTH_TYPES, SUPPORTED_FORWARDING_TYPES};
 
 /// Check if an anyhow error represents a "not found" condition from TunnelManager
 ///
@@ -50,66 +72,168 @@ fn is_tunnel_not_found_error(err: &anyhow::Error) -> bool {
     msg == "Tunnel not found" || msg == "Tunnel is not active"
 }
 
+/// SSE reconnect header carrying the last event ID the client saw
+const LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+/// How long a raised tunnel log level lasts when the request doesn't say
+const DEFAULT_LOG_RAISE_SECS: u64 = 600;
+
 /// Shared application state
 pub struct AppState {
     pub tunnel_manager: TunnelManager,
     pub shutdown_tx: tokio::sync::broadcast::Sender<()>,
+    /// Asks the daemon to shut down as it does on SIGTERM
+    pub shutdown_request: Arc<tokio::sync::Notify>,
     pub started_at: Arc<tokio::sync::RwLock<SystemTime>>,
     pub config: Arc<DaemonConfig>,
+    pub tokens: Arc<tokio::sync::RwLock<TokenRegistry>>,
+    /// Primary token, `None` when no listener requires authentication
+    pub primary_token: Option<Arc<tokio::sync::RwLock<PrimaryToken>>>,
+    pub auth_limiter: Arc<AuthLimiter>,
+    /// `None` when `[audit] enabled = false`
+    pub audit_log: Option<Arc<AuditLog>>,
+    /// `None` when `[history] enabled = false`
+    pub history: Option<Arc<SessionHistory>>,
+    pub tunnel_logs: TunnelLogs,
+}
+
+impl AppState {
+    /// Append to the audit log, if enabled
+    fn audit(&self, entry: AuditEntry) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(entry);
+        }
+    }
 }
 
 /// API error response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct ErrorResponse {
     error: String,
 }
 
 /// API success response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct SuccessResponse {
     message: String,
 }
 
-/// Tunnel status response
-#[derive(Serialize)]
-struct TunnelStatusResponse {
-    id: Uuid,
-    status: TunnelStatus,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pending_auth: Option<AuthRequest>,
-}
-
 /// List of active tunnels
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct TunnelsListResponse {
     tunnels: Vec<TunnelStatusResponse>,
 }
 
-// Event type
-#[derive(Debug, serde::Serialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum OutgoingEvent {
-    Starting { id: Uuid },
-    Connected { id: Uuid },
-    Disconnected { id: Uuid, reason: String },
-    Error { id: Uuid, error: String },
-    AuthRequired { id: Uuid, request: AuthRequest },
-    Heartbeat { timestamp: DateTime<Utc> },
+/// Event as sent to clients; the wire format is shared with the clients
+/// through `ssh-tunnel-common`
+pub type OutgoingEvent = ssh_tunnel_common::TunnelEvent;
+
+/// Caller identity as attached by `auth_middleware` (absent without auth)
+pub(crate) type CallerIdentity = Option<Extension<Identity>>;
+
+/// Who made a request, for log lines and the audit log
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Caller {
+    /// Identity name (absent on listeners without authentication)
+    actor: Option<String>,
+    /// Client address, or the local user on the Unix socket
+    source: Option<String>,
+}
+
+impl Caller {
+    /// Audit entry for an action by this caller
+    fn audit(&self, action: AuditAction) -> AuditEntry {
+        let mut entry = AuditEntry::new(action);
+        entry.actor = self.actor.clone();
+        entry.source = self.source.clone();
+        entry
+    }
+}
+
+impl fmt::Display for Caller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.actor, &self.source) {
+            (Some(actor), Some(source)) => write!(f, "{} from {}", actor, source),
+            (Some(actor), None) => f.write_str(actor),
+            (None, Some(source)) => f.write_str(source),
+            (None, None) => f.write_str("unauthenticated caller"),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Caller {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let actor = parts.extensions.get::<Identity>().map(|identity| identity.name.clone());
+        let source = match parts.extensions.get::<PeerCredentials>() {
+            Some(peer) => Some(peer.to_string()),
+            None => parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_canonical().to_string()),
+        };
+        Ok(Self { actor, source })
+    }
 }
 
 /// Create the API router
+///
+/// Routes are grouped by the token scope they require; the health check,
+/// capabilities and OpenAPI document are open to any authenticated caller.
+/// Everything is served under `/api/v1`, and the unversioned `/api` paths
+/// stay as aliases for clients that predate versioning.
 pub fn create_router(state: Arc<AppState>) -> Router {
+    let read = Router::new()
+        .route("/daemon/info", get(get_daemon_info))
+        .route("/tunnels", get(list_tunnels))
+        .route("/tunnels/:id/status", get(tunnel_status))
+        .route("/tunnels/:id/preflight", get(tunnel_preflight))
+        .route("/tunnels/:id/history", get(tunnel_history))
+        .route("/tunnels/:id/logs", get(tunnel_logs))
+        .route("/tunnels/:id/logs/level", get(get_log_level))
+        .route("/events", get(event_stream))
+        .route("/ws", get(crate::ws::ws_handler))
+        .route("/metrics", get(crate::metrics::metrics_handler))
+        .route("/keys", get(list_keys))
+        .route_layer(from_fn_with_state(TokenScope::Read, require_scope));
+
+    let control = Router::new()
+        .route("/tunnels/:id/start", post(start_tunnel))
+        .route("/tunnels/:id/stop", post(stop_tunnel))
+        .route("/tunnels/:id/logs/level", put(set_log_level))
+        .route_layer(from_fn_with_state(TokenScope::Control, require_scope));
+
+    let auth = Router::new()
+        .route("/tunnels/:id/auth", get(get_pending_auth).post(submit_auth))
+        .route("/keys/unlock", post(unlock_key))
+        .route("/keys/lock", post(lock_keys))
+        .route_layer(from_fn_with_state(TokenScope::Auth, require_scope));
+
+    let admin = Router::new()
+        .route("/daemon/shutdown", post(shutdown_daemon))
+        .route("/daemon/token/rotate", post(rotate_primary_token))
+        .route("/tokens", get(list_tokens).post(create_token))
+        .route("/tokens/:name", delete(revoke_token))
+        .route("/audit", get(get_audit))
+        .route_layer(from_fn_with_state(TokenScope::Admin, require_scope));
+
+    let api = Router::new()
+        .route("/health", get(health))
+        .merge(read)
+        .merge(control)
+        .merge(auth)
+        .merge(admin);
+
+    let v1 = api
+        .clone()
+        .route("/capabilities", get(get_capabilities))
+        .route("/openapi.json", get(openapi_document));
+
     Router::new()
-        .route("/api/health", get(health))
-        .route("/api/daemon/info", get(get_daemon_info))
-        .route("/api/daemon/shutdown", post(shutdown_daemon))
-        .route("/api/tunnels", get(list_tunnels))
-        .route("/api/tunnels/:id/start", post(start_tunnel))
-        .route("/api/tunnels/:id/stop", post(stop_tunnel))
-        .route("/api/tunnels/:id/status", get(tunnel_status))
-        .route("/api/tunnels/:id/auth", get(get_pending_auth))
-        .route("/api/tunnels/:id/auth", post(submit_auth))
-        .route("/api/events", get(event_stream))
+        .nest(&format!("/api/{}", API_VERSION), v1)
+        .nest("/api", api)
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(DefaultMakeSpan::new().include_headers(false))
@@ -122,131 +246,203 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .with_state(state)
 }
 
+/// OpenAPI description of the v1 API, served at `/api/v1/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "SSH Tunnel Manager daemon API"),
+    paths(
+        health,
+        get_capabilities,
+        get_daemon_info,
+        list_tunnels,
+        start_tunnel,
+        stop_tunnel,
+        tunnel_status,
+        tunnel_preflight,
+        tunnel_history,
+        tunnel_logs,
+        get_log_level,
+        set_log_level,
+        get_pending_auth,
+        submit_auth,
+        list_keys,
+        unlock_key,
+        lock_keys,
+        event_stream,
+        crate::ws::ws_handler,
+        crate::metrics::metrics_handler,
+        shutdown_daemon,
+        rotate_primary_token,
+        list_tokens,
+        create_token,
+        revoke_token,
+        get_audit,
+    ),
+    modifiers(&TokenSecurity),
+    security(("token" = [])),
+    tags(
+        (name = "daemon", description = "Daemon status and lifecycle"),
+        (name = "tunnels", description = "Tunnel control and events (`read`/`control` scopes)"),
+        (name = "auth", description = "Interactive SSH authentication (`auth` scope)"),
+        (name = "keys", description = "Key cache of decrypted SSH keys (`read`/`auth` scopes)"),
+        (name = "tokens", description = "API token management (`admin` scope)"),
+        (name = "audit", description = "Audit log (`admin` scope)"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the `X-Tunnel-Token` header (not needed on listeners without
+/// authentication or with a client certificate)
+struct TokenSecurity;
+
+impl Modify for TokenSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .add_security_scheme(
+                "token",
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Tunnel-Token"))),
+            );
+    }
+}
+
 /// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/api/v1/health",
+    tag = "daemon",
+    responses((status = 200, description = "Daemon is up", body = String, example = "OK"))
+)]
 async fn health() -> &'static str {
     "OK"
 }
 
+/// Tags of a profile on the daemon's filesystem
+///
+/// Profile ACLs only trust the daemon's copy; tags sent by clients in hybrid
+/// mode are ignored. Unknown profiles have no tags.
+fn daemon_profile_tags(id: &Uuid) -> Vec<String> {
+    load_profile_by_id(id)
+        .map(|p| p.metadata.tags)
+        .unwrap_or_default()
+}
+
+/// Whether the caller may touch the given profile
+pub(crate) fn caller_allows_profile(identity: &CallerIdentity, id: &Uuid) -> bool {
+    match identity {
+        Some(Extension(identity)) if identity.is_restricted() => {
+            identity.allows_profile(id, &daemon_profile_tags(id))
+        }
+        _ => true,
+    }
+}
+
+/// 403 response if the caller's token is not allowed to touch the profile
+fn deny_profile_access(identity: &CallerIdentity, id: &Uuid) -> Option<Response> {
+    if caller_allows_profile(identity, id) {
+        return None;
+    }
+
+    if let Some(Extension(identity)) = identity {
+        tracing::warn!("Token '{}' denied access to profile {}", identity.name, id);
+    }
+    Some(
+        (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: format!("Token is not allowed to access profile {}", id),
+            }),
+        )
+            .into_response(),
+    )
+}
+
 /// List all active tunnels
-async fn list_tunnels(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let tunnels = state.tunnel_manager.list_active().await;
+#[utoipa::path(
+    get,
+    path = "/api/v1/tunnels",
+    tag = "tunnels",
+    responses((status = 200, body = TunnelsListResponse))
+)]
+async fn list_tunnels(
+    State(state): State<Arc<AppState>>,
+    identity: CallerIdentity,
+) -> impl IntoResponse {
+    let response = TunnelsListResponse {
+        tunnels: tunnel_snapshot(&state, &identity).await,
+    };
+
+    Json(response)
+}
 
-    let mut response_tunnels = Vec::new();
-    for (id, status) in tunnels {
+/// Status of every active tunnel the caller may see
+async fn tunnel_snapshot(state: &AppState, identity: &CallerIdentity) -> Vec<TunnelStatusResponse> {
+    let mut tunnels = Vec::new();
+    for (id, status) in state.tunnel_manager.list_active().await {
+        if !caller_allows_profile(identity, &id) {
+            continue;
+        }
         let pending_auth = state.tunnel_manager.get_pending_auth(&id).await;
-        response_tunnels.push(TunnelStatusResponse {
+        let bound_address = state.tunnel_manager.get_bound_addr(&id).await;
+        tunnels.push(TunnelStatusResponse {
             id,
             status,
             pending_auth,
+            bound_address,
         });
     }
-
-    let response = TunnelsListResponse {
-        tunnels: response_tunnels,
-    };
-
-    Json(response)
+    tunnels
 }
 
 /// Start a tunnel
-async fn start_tunnel(
+#[utoipa::path(
+    post,
+    path = "/api/v1/tunnels/{id}/start",
+    tag = "tunnels",
+    params(("id" = Uuid, Path, description = "Profile ID")),
+    request_body = StartTunnelRequest,
+    responses(
+        (status = 202, description = "Tunnel starting", body = SuccessResponse),
+        (status = 400, description = "Invalid request or key missing on the daemon", body = ErrorResponse),
+        (status = 403, description = "Token may not use this profile", body = ErrorResponse),
+        (status = 404, description = "Profile not on the daemon filesystem", body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+pub(crate) async fn start_tunnel(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    identity: CallerIdentity,
+    caller: Caller,
     Json(request): Json<StartTunnelRequest>,
 ) -> impl IntoResponse {
-    info!("API: Start tunnel request for {} (mode: {:?})", id, request.mode);
+    info!(
+        "API: Start tunnel request for {} (mode: {:?}) by {}",
+        id, request.mode, caller
+    );
+    let entry = caller.audit(AuditAction::TunnelStart).with_profile_id(id);
 
-    // Validate that the profile_id in the request matches the URL path
-    if request.profile_id != id.to_string() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: format!(
-                    "Profile ID mismatch: URL has {} but request has {}",
-                    id, request.profile_id
-                ),
-            }),
-        )
-            .into_response();
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        state.audit(entry.failed("access denied"));
+        return response;
     }
 
-    // Get the profile based on the source mode
-    let profile = match request.mode {
-        ProfileSourceMode::Local => {
-            // Load from daemon's filesystem
-            match load_profile_by_id(&id) {
-                Ok(p) => p,
-                Err(e) => {
-                    error!("Failed to load profile {} from filesystem: {}", id, e);
-                    return (
-                        StatusCode::NOT_FOUND,
-                        Json(ErrorResponse {
-                            error: format!("Profile not found on daemon filesystem: {}", e),
-                        }),
-                    )
-                        .into_response();
-                }
-            }
-        }
-        ProfileSourceMode::Hybrid => {
-            // Use profile from request
-            match request.profile {
-                Some(p) => {
-                    // Validate SSH key exists if specified
-                    if let Some(key_path) = &p.connection.key_path {
-                        // Expand ~ to home directory
-                        let home_dir = dirs::home_dir().unwrap_or_else(|| "/root".into());
-                        let ssh_dir = home_dir.join(".ssh");
-                        let full_key_path = ssh_dir.join(key_path);
-
-                        if !full_key_path.exists() {
-                            let key_filename = key_path.display();
-                            let error_msg = format!(
-                                "SSH key not found on daemon: ~/.ssh/{}\n\n\
-                                To copy your SSH key to the daemon:\n\
-                                1. Copy the private key:\n   \
-                                   scp <local-key-path> <daemon-host>:~/.ssh/{}\n\n\
-                                2. Set correct permissions:\n   \
-                                   ssh <daemon-host> chmod 600 ~/.ssh/{}",
-                                key_filename, key_filename, key_filename
-                            );
-                            error!("{}", error_msg);
-                            return (
-                                StatusCode::BAD_REQUEST,
-                                Json(ErrorResponse { error: error_msg }),
-                            )
-                                .into_response();
-                        }
-                    }
-                    p
-                }
-                None => {
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse {
-                            error: "Hybrid mode requires profile data in request".to_string(),
-                        }),
-                    )
-                        .into_response();
-                }
-            }
-        }
-        ProfileSourceMode::Remote => {
-            // Not yet implemented
-            return (
-                StatusCode::NOT_IMPLEMENTED,
-                Json(ErrorResponse {
-                    error: "Remote mode not yet implemented".to_string(),
-                }),
-            )
-                .into_response();
+    let shared_keys = shares_cached_keys(&identity, &request.mode);
+    let profile = match resolve_start_profile(&id, request) {
+        Ok(profile) => profile,
+        Err((status, error)) => {
+            state.audit(entry.failed(error.clone()));
+            return (status, Json(ErrorResponse { error })).into_response();
         }
     };
+    let entry = entry.with_profile(&profile);
 
     // Start the tunnel
-    match state.tunnel_manager.start(profile).await {
+    match state.tunnel_manager.start(profile, shared_keys).await {
         Ok(()) => {
             info!("Tunnel {} start initiated", id);
+            state.audit(entry);
             (
                 StatusCode::ACCEPTED,
                 Json(SuccessResponse {
@@ -257,6 +453,7 @@ async fn start_tunnel(
         }
         Err(e) => {
             error!("Failed to start tunnel {}: {}", id, e);
+            state.audit(entry.failed(e.to_string()));
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
@@ -268,16 +465,143 @@ async fn start_tunnel(
     }
 }
 
+/// Whether a tunnel may use keys unlocked in the key cache
+///
+/// Cached keys serve profiles stored on the daemon, and any profile of a
+/// caller who may touch all of them. A token limited to some profiles could
+/// otherwise name another profile's key in a Hybrid profile and use it
+/// without knowing its passphrase.
+fn shares_cached_keys(identity: &CallerIdentity, mode: &ProfileSourceMode) -> bool {
+    *mode == ProfileSourceMode::Local
+        || !matches!(identity, Some(Extension(identity)) if identity.is_restricted())
+}
+
+/// Profile to start, based on the request's source mode
+fn resolve_start_profile(
+    id: &Uuid,
+    request: StartTunnelRequest,
+) -> Result<Profile, (StatusCode, String)> {
+    // Validate that the profile_id in the request matches the URL path
+    if request.profile_id != id.to_string() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Profile ID mismatch: URL has {} but request has {}",
+                id, request.profile_id
+            ),
+        ));
+    }
+
+    match request.mode {
+        ProfileSourceMode::Local => {
+            // Load from daemon's filesystem
+            load_profile_by_id(id).map_err(|e| {
+                error!("Failed to load profile {} from filesystem: {}", id, e);
+                (
+                    StatusCode::NOT_FOUND,
+                    format!("Profile not found on daemon filesystem: {}", e),
+                )
+            })
+        }
+        ProfileSourceMode::Hybrid => {
+            // Use profile from request
+            let Some(profile) = request.profile else {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Hybrid mode requires profile data in request".to_string(),
+                ));
+            };
+
+            // Hooks are shell commands run as the daemon user, so they only
+            // come from profiles stored on the daemon
+            if !profile.hooks.is_empty() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Lifecycle hooks are only run from profiles stored on the daemon; \
+                     remove [hooks] from the profile or use local mode"
+                        .to_string(),
+                ));
+            }
+
+            // Same for secret commands, which the daemon runs to fetch the password
+            if matches!(
+                profile.connection.password_storage,
+                PasswordStorage::Command { .. }
+            ) {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Secret commands are only run from profiles stored on the daemon; \
+                     use another password storage or local mode"
+                        .to_string(),
+                ));
+            }
+
+            // Validate SSH key exists if specified
+            if let Some(key_path) = &profile.connection.key_path {
+                // Expand ~ to home directory
+                let home_dir = dirs::home_dir().unwrap_or_else(|| "/root".into());
+                let ssh_dir = home_dir.join(".ssh");
+                let full_key_path = ssh_dir.join(key_path);
+
+                if !full_key_path.exists() {
+                    let key_filename = key_path.display();
+                    let error_msg = format!(
+                        "SSH key not found on daemon: ~/.ssh/{}\n\n\
+                        To copy your SSH key to the daemon:\n\
+                        1. Copy the private key:\n   \
+                           scp <local-key-path> <daemon-host>:~/.ssh/{}\n\n\
+                        2. Set correct permissions:\n   \
+                           ssh <daemon-host> chmod 600 ~/.ssh/{}",
+                        key_filename, key_filename, key_filename
+                    );
+                    error!("{}", error_msg);
+                    return Err((StatusCode::BAD_REQUEST, error_msg));
+                }
+            }
+            Ok(profile)
+        }
+        // Not yet implemented
+        ProfileSourceMode::Remote => Err((
+            StatusCode::NOT_IMPLEMENTED,
+            "Remote mode not yet implemented".to_string(),
+        )),
+    }
+}
+
 /// Stop a tunnel
-async fn stop_tunnel(
+#[utoipa::path(
+    post,
+    path = "/api/v1/tunnels/{id}/stop",
+    tag = "tunnels",
+    params(("id" = Uuid, Path, description = "Profile ID")),
+    responses(
+        (status = 200, description = "Tunnel stopping", body = SuccessResponse),
+        (status = 403, body = ErrorResponse),
+        (status = 404, description = "Tunnel not active", body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+pub(crate) async fn stop_tunnel(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    identity: CallerIdentity,
+    caller: Caller,
 ) -> impl IntoResponse {
-    info!("API: Stop tunnel request for {}", id);
+    info!("API: Stop tunnel request for {} by {}", id, caller);
+    let mut entry = caller.audit(AuditAction::TunnelStop).with_profile_id(id);
+    if let Some(profile) = state.tunnel_manager.get_profile(&id).await {
+        entry = entry.with_profile(&profile);
+    }
+
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        state.audit(entry.failed("access denied"));
+        return response;
+    }
 
     match state.tunnel_manager.stop(&id).await {
         Ok(()) => {
             info!("Tunnel {} stop initiated", id);
+            state.audit(entry);
             (
                 StatusCode::OK,
                 Json(SuccessResponse {
@@ -288,6 +612,7 @@ async fn stop_tunnel(
         }
         Err(e) => {
             error!("Failed to stop tunnel {}: {}", id, e);
+            state.audit(entry.failed(e.to_string()));
             // Map common tunnel lifecycle errors to client-friendly status codes
             let status = if is_tunnel_not_found_error(&e) {
                 StatusCode::NOT_FOUND
@@ -300,20 +625,295 @@ async fn stop_tunnel(
     }
 }
 
+/// Run preflight checks for a profile on the daemon's filesystem
+#[utoipa::path(
+    get,
+    path = "/api/v1/tunnels/{id}/preflight",
+    tag = "tunnels",
+    params(("id" = Uuid, Path, description = "Profile ID")),
+    responses(
+        (status = 200, body = PreflightReport),
+        (status = 403, body = ErrorResponse),
+        (status = 404, description = "Profile not on the daemon filesystem", body = ErrorResponse),
+    )
+)]
+async fn tunnel_preflight(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    identity: CallerIdentity,
+) -> impl IntoResponse {
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        return response;
+    }
+
+    let profile = match load_profile_by_id(&id) {
+        Ok(p) => p,
+        Err(e) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("Profile not found on daemon filesystem: {}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    let others = load_all_profiles().unwrap_or_default();
+    let running = state.tunnel_manager.running_binds().await;
+
+    // /proc scanning walks every process' fd table; keep it off the async workers
+    let report = match tokio::task::spawn_blocking(move || {
+        preflight::run_checks(&profile, &others, &running)
+    })
+    .await
+    {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Preflight check for {} failed: {}", id, e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e.to_string() }),
+            )
+                .into_response();
+        }
+    };
+
+    (StatusCode::OK, Json(report)).into_response()
+}
+
+/// Query parameters for `GET /api/tunnels/{id}/history`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct HistoryQuery {
+    /// Return at most this many of the most recent sessions
+    limit: Option<usize>,
+}
+
+/// Past sessions of a tunnel, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/tunnels/{id}/history",
+    tag = "tunnels",
+    params(("id" = Uuid, Path, description = "Profile ID"), HistoryQuery),
+    responses(
+        (status = 200, body = [TunnelSession]),
+        (status = 403, body = ErrorResponse),
+        (status = 404, description = "Session history is disabled", body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+async fn tunnel_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<HistoryQuery>,
+    identity: CallerIdentity,
+) -> impl IntoResponse {
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        return response;
+    }
+
+    let Some(history) = state.history.clone() else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: "Session history is disabled in daemon.toml".to_string(),
+            }),
+        )
+            .into_response();
+    };
+
+    match tokio::task::spawn_blocking(move || history.query(&id, query.limit)).await {
+        Ok(Ok(sessions)) => (StatusCode::OK, Json(sessions)).into_response(),
+        Ok(Err(e)) => {
+            error!("Failed to read session history of {}: {:#}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("{:#}", e),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e.to_string() }),
+        )
+            .into_response(),
+    }
+}
+
+/// Query parameters for `GET /api/tunnels/{id}/logs`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct LogsQuery {
+    /// Return at most this many of the most recent lines
+    limit: Option<usize>,
+    /// Only lines with a higher `seq`
+    after: Option<u64>,
+    /// Keep the response open and stream new lines as server-sent events
+    #[serde(default)]
+    follow: bool,
+}
+
+/// Log lines captured for a tunnel, oldest first
+///
+/// With `follow=true` the buffered lines and everything logged afterwards are
+/// sent as server-sent `log` events, one JSON `TunnelLogEntry` per `data:`
+/// line, with the line's `seq` as the event ID.
+#[utoipa::path(
+    get,
+    path = "/api/v1/tunnels/{id}/logs",
+    tag = "tunnels",
+    params(("id" = Uuid, Path, description = "Profile ID"), LogsQuery),
+    responses(
+        (status = 200, body = [TunnelLogEntry]),
+        (status = 403, body = ErrorResponse),
+    )
+)]
+async fn tunnel_logs(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<LogsQuery>,
+    identity: CallerIdentity,
+) -> Response {
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        return response;
+    }
+
+    if !query.follow {
+        let entries = state.tunnel_logs.entries(&id, query.after, query.limit);
+        return (StatusCode::OK, Json(entries)).into_response();
+    }
+
+    let (backlog, log_rx) = state.tunnel_logs.follow(&id, query.after, query.limit);
+    let mut shutdown_rx = state.shutdown_tx.subscribe();
+
+    let live = tokio_stream::wrappers::BroadcastStream::new(log_rx).filter_map(move |item| {
+        let entry = match item {
+            Ok((tunnel_id, entry)) if tunnel_id == id => Some(entry),
+            Ok(_) => None,
+            Err(e) => {
+                // A slow follower misses lines rather than holding up logging
+                tracing::debug!("Log follower of {} fell behind: {}", id, e);
+                None
+            }
+        };
+        async move { entry }
+    });
+    let lines = stream::iter(backlog).chain(live).filter_map(|entry| async move {
+        match serde_json::to_string(&entry) {
+            Ok(json) => Some(Ok::<_, Infallible>(
+                Event::default().event("log").id(entry.seq.to_string()).data(json),
+            )),
+            Err(e) => {
+                tracing::error!("Failed to serialize TunnelLogEntry: {e}");
+                None
+            }
+        }
+    });
+
+    let shutdown_aware = lines.take_until(async move {
+        let _ = shutdown_rx.recv().await;
+    });
+
+    Sse::new(shutdown_aware)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Level a tunnel's log is currently captured at
+#[utoipa::path(
+    get,
+    path = "/api/v1/tunnels/{id}/logs/level",
+    tag = "tunnels",
+    params(("id" = Uuid, Path, description = "Profile ID")),
+    responses(
+        (status = 200, body = TunnelLogLevel),
+        (status = 403, body = ErrorResponse),
+    )
+)]
+async fn get_log_level(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    identity: CallerIdentity,
+) -> Response {
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        return response;
+    }
+
+    (StatusCode::OK, Json(state.tunnel_logs.level(&id))).into_response()
+}
+
+/// Temporarily capture a tunnel's log at another level
+///
+/// The duration is capped at `[tunnel_logs] max_raise_secs`.
+#[utoipa::path(
+    put,
+    path = "/api/v1/tunnels/{id}/logs/level",
+    tag = "tunnels",
+    params(("id" = Uuid, Path, description = "Profile ID")),
+    request_body = SetLogLevelRequest,
+    responses(
+        (status = 200, body = TunnelLogLevel),
+        (status = 403, body = ErrorResponse),
+    )
+)]
+async fn set_log_level(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    identity: CallerIdentity,
+    caller: Caller,
+    Json(request): Json<SetLogLevelRequest>,
+) -> Response {
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        return response;
+    }
+
+    let secs = request
+        .duration_secs
+        .unwrap_or(DEFAULT_LOG_RAISE_SECS)
+        .min(state.config.tunnel_logs.max_raise_secs);
+    let level = state
+        .tunnel_logs
+        .set_level(id, request.level, Duration::from_secs(secs));
+    info!("Log level of tunnel {} set to {} by {}", id, level.level, caller);
+
+    (StatusCode::OK, Json(level)).into_response()
+}
+
 /// Get tunnel status
+#[utoipa::path(
+    get,
+    path = "/api/v1/tunnels/{id}/status",
+    tag = "tunnels",
+    params(("id" = Uuid, Path, description = "Profile ID")),
+    responses(
+        (status = 200, body = TunnelStatusResponse),
+        (status = 403, body = ErrorResponse),
+        (status = 404, description = "Tunnel not active", body = ErrorResponse),
+    )
+)]
 async fn tunnel_status(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    identity: CallerIdentity,
 ) -> impl IntoResponse {
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        return response;
+    }
+
     match state.tunnel_manager.get_status(&id).await {
         Some(status) => {
             let pending_auth = state.tunnel_manager.get_pending_auth(&id).await;
+            let bound_address = state.tunnel_manager.get_bound_addr(&id).await;
             (
                 StatusCode::OK,
                 Json(TunnelStatusResponse {
                     id,
                     status,
                     pending_auth,
+                    bound_address,
                 }),
             )
                 .into_response()
@@ -329,10 +929,26 @@ async fn tunnel_status(
 }
 
 /// Get pending authentication request for a tunnel
+#[utoipa::path(
+    get,
+    path = "/api/v1/tunnels/{id}/auth",
+    tag = "auth",
+    params(("id" = Uuid, Path, description = "Profile ID")),
+    responses(
+        (status = 200, body = AuthRequest),
+        (status = 403, body = ErrorResponse),
+        (status = 404, description = "No pending request", body = ErrorResponse),
+    )
+)]
 async fn get_pending_auth(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    identity: CallerIdentity,
 ) -> impl IntoResponse {
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        return response;
+    }
+
     match state.tunnel_manager.get_pending_auth(&id).await {
         Some(auth_request) => (StatusCode::OK, Json(auth_request)).into_response(),
         None => (
@@ -346,27 +962,76 @@ async fn get_pending_auth(
 }
 
 /// Submit authentication response payload with request_id
-#[derive(Debug, Deserialize, Serialize)]
-struct SubmitAuthPayload {
+#[derive(Deserialize, Serialize, ToSchema)]
+pub(crate) struct SubmitAuthPayload {
     pub request_id: Uuid,
+    /// Answer to a single-prompt request
+    #[serde(default)]
     pub response: String,
+    /// Answers to the request's `prompts`, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub responses: Vec<String>,
+}
+
+impl SubmitAuthPayload {
+    fn into_answers(self) -> Vec<String> {
+        if self.responses.is_empty() {
+            vec![self.response]
+        } else {
+            self.responses
+        }
+    }
+}
+
+// The response is a secret; keep it out of debug output
+impl fmt::Debug for SubmitAuthPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubmitAuthPayload")
+            .field("request_id", &self.request_id)
+            .finish_non_exhaustive()
+    }
 }
 
 /// Submit authentication response
-async fn submit_auth(
+#[utoipa::path(
+    post,
+    path = "/api/v1/tunnels/{id}/auth",
+    tag = "auth",
+    params(("id" = Uuid, Path, description = "Profile ID")),
+    request_body = SubmitAuthPayload,
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = 400, description = "No matching pending request", body = ErrorResponse),
+        (status = 403, body = ErrorResponse),
+    )
+)]
+pub(crate) async fn submit_auth(
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
+    identity: CallerIdentity,
+    caller: Caller,
     Json(payload): Json<SubmitAuthPayload>,
 ) -> impl IntoResponse {
     info!("API: Auth response received for tunnel {} (request_id: {})", id, payload.request_id);
+    // The response itself is a secret and never goes into the audit log
+    let mut entry = caller.audit(AuditAction::AuthSubmit).with_profile_id(id);
+    if let Some(profile) = state.tunnel_manager.get_profile(&id).await {
+        entry = entry.with_profile(&profile);
+    }
+
+    if let Some(response) = deny_profile_access(&identity, &id) {
+        state.audit(entry.failed("access denied"));
+        return response;
+    }
 
     match state
         .tunnel_manager
-        .submit_auth_by_request_id(&id, payload.request_id, payload.response)
+        .submit_auth_by_request_id(&id, payload.request_id, payload.into_answers())
         .await
     {
         Ok(()) => {
             info!("Auth response submitted for tunnel {}", id);
+            state.audit(entry);
             (
                 StatusCode::OK,
                 Json(SuccessResponse {
@@ -377,6 +1042,7 @@ async fn submit_auth(
         }
         Err(e) => {
             error!("Failed to submit auth for tunnel {}: {}", id, e);
+            state.audit(entry.failed(e.to_string()));
             (
                 StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
@@ -388,47 +1054,167 @@ async fn submit_auth(
     }
 }
 
+/// Key files of the daemon's profiles that a restricted caller may touch
+///
+/// `None` for callers who may touch every profile, and so every key.
+fn caller_key_paths(identity: &CallerIdentity) -> Option<Vec<PathBuf>> {
+    let Some(Extension(identity)) = identity else {
+        return None;
+    };
+    if !identity.is_restricted() {
+        return None;
+    }
+    let paths = load_all_profiles()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|p| identity.allows_profile(&p.metadata.id, &p.metadata.tags))
+        .filter_map(|p| p.connection.key_path)
+        .filter_map(|path| keycache::resolve_key_path(&path).ok())
+        .collect();
+    Some(paths)
+}
+
+/// List the keys in the key cache (never the keys themselves)
+///
+/// Tokens limited to some profiles only see the keys of those profiles.
+#[utoipa::path(
+    get,
+    path = "/api/v1/keys",
+    tag = "keys",
+    responses((status = 200, body = [UnlockedKey]))
+)]
+async fn list_keys(State(state): State<Arc<AppState>>, identity: CallerIdentity) -> Json<Vec<UnlockedKey>> {
+    let mut keys = state.tunnel_manager.key_cache().list();
+    if let Some(paths) = caller_key_paths(&identity) {
+        keys.retain(|key| paths.contains(&PathBuf::from(&key.path)));
+    }
+    Json(keys)
+}
+
+/// Decrypt a key into the key cache, for all tunnels using it
+#[utoipa::path(
+    post,
+    path = "/api/v1/keys/unlock",
+    tag = "keys",
+    request_body = UnlockKeyRequest,
+    responses(
+        (status = 200, body = UnlockedKey),
+        (status = 400, description = "Wrong passphrase, unreadable key or cache disabled", body = ErrorResponse),
+    )
+)]
+async fn unlock_key(
+    State(state): State<Arc<AppState>>,
+    caller: Caller,
+    Json(request): Json<UnlockKeyRequest>,
+) -> impl IntoResponse {
+    info!("API: Unlock key request for '{}'", request.key_path);
+    // The passphrase is a secret and never goes into the audit log
+    let entry = caller.audit(AuditAction::KeyUnlock);
+
+    let UnlockKeyRequest { key_path, passphrase } = request;
+    match state
+        .tunnel_manager
+        .unlock_key(&key_path, Zeroizing::new(passphrase))
+        .await
+    {
+        Ok(key) => {
+            state.audit(entry.with_detail(format!("{} ({})", key.path, key.fingerprint)));
+            (StatusCode::OK, Json(key)).into_response()
+        }
+        Err(e) => {
+            error!("Failed to unlock key '{}': {:#}", key_path, e);
+            state.audit(entry.failed(format!("{}: {:#}", key_path, e)));
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("{:#}", e),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Drop a key (by path or fingerprint) or all keys from the key cache
+///
+/// Tokens limited to some profiles only lock keys of those profiles.
+#[utoipa::path(
+    post,
+    path = "/api/v1/keys/lock",
+    tag = "keys",
+    request_body = LockKeysRequest,
+    responses((status = 200, body = SuccessResponse))
+)]
+async fn lock_keys(
+    State(state): State<Arc<AppState>>,
+    caller: Caller,
+    identity: CallerIdentity,
+    Json(request): Json<LockKeysRequest>,
+) -> impl IntoResponse {
+    info!("API: Lock keys request ({})", request.key.as_deref().unwrap_or("all"));
+    let keys = state.tunnel_manager.key_cache();
+    let locked = match caller_key_paths(&identity) {
+        Some(paths) => keys.lock_within(request.key.as_deref(), |path| {
+            paths.iter().any(|allowed| allowed == path)
+        }),
+        None => keys.lock(request.key.as_deref()),
+    };
+    state.audit(
+        caller
+            .audit(AuditAction::KeyLock)
+            .with_detail(format!("{} ({} locked)", request.key.as_deref().unwrap_or("all"), locked)),
+    );
+    Json(SuccessResponse {
+        message: format!("{} key(s) locked", locked),
+    })
+}
+
 /// GET /api/events  → SSE stream of tunnel events
+///
+/// Each tunnel event carries its sequence number as the SSE `id`. A client
+/// reconnecting with `Last-Event-ID` gets the events it missed replayed, or a
+/// `snapshot` event with the current state when they are no longer buffered.
+#[utoipa::path(
+    get,
+    path = "/api/v1/events",
+    tag = "tunnels",
+    params(("Last-Event-ID" = Option<u64>, Header, description = "Resume after this event")),
+    responses((
+        status = 200,
+        description = "Server-sent events, one JSON `OutgoingEvent` per `data:` line",
+        content_type = "text/event-stream",
+        body = OutgoingEvent
+    ))
+)]
 pub async fn event_stream(
     State(state): State<Arc<AppState>>,
+    identity: CallerIdentity,
+    headers: HeaderMap,
 ) -> Sse<impl futures::Stream<Item = Result<Event, Infallible>>> {
-    // subscribe to the broadcast channel
-    let rx = state.tunnel_manager.subscribe();
+    // An ID we can't parse can't be resumed from either: treat it as a gap
+    let last_event_id = headers
+        .get(LAST_EVENT_ID)
+        .map(|value| value.to_str().ok().and_then(|v| v.trim().parse().ok()).unwrap_or(0));
+    let feed = EventFeed::new(state.tunnel_manager.events(), last_event_id);
     let mut shutdown_rx = state.shutdown_tx.subscribe();
 
-    // Broadcast events from tunnel manager
-    let tunnel_events = BroadcastStream::new(rx).filter_map(|msg| async move {
-        match msg {
-            Ok(ev) => {
-                let outgoing = match ev {
-                    TunnelEvent::Starting { id } => OutgoingEvent::Starting { id },
-                    TunnelEvent::Connected { id } => OutgoingEvent::Connected { id },
-                    TunnelEvent::Disconnected { id, reason } => {
-                        OutgoingEvent::Disconnected { id, reason }
-                    }
-                    TunnelEvent::Error { id, error } => OutgoingEvent::Error { id, error },
-                    TunnelEvent::AuthRequired { id, request } => {
-                        OutgoingEvent::AuthRequired { id, request }
-                    }
-                };
-
-                let json = match serde_json::to_string(&outgoing) {
-                    Ok(j) => j,
-                    Err(e) => {
-                        tracing::error!("Failed to serialize OutgoingEvent: {e}");
-                        return None;
-                    }
-                };
-
-                Some(Ok(Event::default().data(json)))
-            }
-            Err(lagged) => {
-                // We lagged behind in the broadcast channel
-                // This happens when events are broadcast faster than this client can consume them
-                // Continue processing - the client will catch up with future events
-                tracing::debug!("Event stream lagged: {:?}, continuing", lagged);
-                None
-            }
+    // Tunnel events (and snapshots after gaps) from the tunnel manager
+    let tunnel_events = stream::unfold((feed, state, identity), |(mut feed, state, identity)| async move {
+        loop {
+            let item = feed.next().await?;
+            let Some((seq, outgoing)) = outgoing_event(&state, &identity, item).await else {
+                // Tokens limited to some profiles only see their events
+                continue;
+            };
+            let json = match serde_json::to_string(&outgoing) {
+                Ok(j) => j,
+                Err(e) => {
+                    tracing::error!("Failed to serialize OutgoingEvent: {e}");
+                    continue;
+                }
+            };
+            let event = Event::default().id(seq.to_string()).data(json);
+            return Some((Ok(event), (feed, state, identity)));
         }
     });
 
@@ -446,6 +1232,29 @@ pub async fn event_stream(
     Sse::new(shutdown_aware)
 }
 
+/// The outgoing event for a feed item and its sequence number, or `None` if
+/// the caller may not see it
+pub(crate) async fn outgoing_event(
+    state: &AppState,
+    identity: &CallerIdentity,
+    item: FeedItem,
+) -> Option<(u64, OutgoingEvent)> {
+    match item {
+        FeedItem::Event(event) => {
+            if !caller_allows_profile(identity, &event.event.id()) {
+                return None;
+            }
+            Some((event.seq, OutgoingEvent::from(event)))
+        }
+        FeedItem::Snapshot(seq) => Some((
+            seq,
+            OutgoingEvent::Snapshot {
+                tunnels: tunnel_snapshot(state, identity).await,
+            },
+        )),
+    }
+}
+
 fn heartbeat_stream(
 ) -> impl futures::Stream<Item = Result<Event, Infallible>> + Send + Sync + 'static {
     tokio_stream::wrappers::IntervalStream::new(tokio::time::interval(heartbeat_interval()))
@@ -465,19 +1274,26 @@ fn heartbeat_payload() -> String {
 }
 
 #[cfg(not(test))]
-fn heartbeat_interval() -> Duration {
+pub(crate) fn heartbeat_interval() -> Duration {
     Duration::from_secs(10)
 }
 
 #[cfg(test)]
-fn heartbeat_interval() -> Duration {
+pub(crate) fn heartbeat_interval() -> Duration {
     Duration::from_millis(100)
 }
 
 /// Get daemon information (version, config, uptime, etc.)
+#[utoipa::path(
+    get,
+    path = "/api/v1/daemon/info",
+    tag = "daemon",
+    responses((status = 200, body = DaemonInfo))
+)]
 async fn get_daemon_info(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     use std::time::UNIX_EPOCH;
-    use ssh_tunnel_common::DaemonInfo;
+    use ssh_tunnel_common::ListenerInfo;
+    use crate::config::ListenerMode;
 
     // Compute uptime
     let started = *state.started_at.read().await;
@@ -502,21 +1318,34 @@ async fn get_daemon_info(State(state): State<Arc<AppState>>) -> impl IntoRespons
     // Get config details
     let config = &state.config;
 
-    // Determine listener mode string
-    let listener_mode = match config.listener_mode {
-        crate::config::ListenerMode::UnixSocket => "unix-socket",
-        crate::config::ListenerMode::TcpHttp => "tcp-http",
-        crate::config::ListenerMode::TcpHttps => "tcp-https",
-    }.to_string();
-
-    // Get socket path (for Unix socket mode)
-    let socket_path = if matches!(config.listener_mode, crate::config::ListenerMode::UnixSocket) {
-        crate::config::socket_path()
-            .ok()
-            .map(|p| p.display().to_string())
-    } else {
-        None
-    };
+    // Describe every listener; the legacy top-level fields mirror the first one
+    let listeners: Vec<ListenerInfo> = config
+        .effective_listeners()
+        .iter()
+        .map(|listener| {
+            let is_tcp = listener.mode != ListenerMode::UnixSocket;
+            ListenerInfo {
+                mode: listener.mode.as_str().to_string(),
+                bind_host: is_tcp.then(|| listener.bind_host.clone()),
+                bind_port: is_tcp.then_some(listener.bind_port),
+                socket_path: if is_tcp {
+                    None
+                } else {
+                    crate::config::socket_path()
+                        .ok()
+                        .map(|p| p.display().to_string())
+                },
+                require_auth: listener.requires_auth(config.require_auth),
+            }
+        })
+        .collect();
+    let primary = listeners.first().cloned().unwrap_or(ListenerInfo {
+        mode: config.listener_mode.as_str().to_string(),
+        bind_host: None,
+        bind_port: None,
+        socket_path: None,
+        require_auth: config.require_auth,
+    });
 
     // Get config file path
     let config_file_path = dirs::config_dir()
@@ -537,24 +1366,18 @@ async fn get_daemon_info(State(state): State<Arc<AppState>>) -> impl IntoRespons
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: uptime,
         started_at: started_at_iso,
-        listener_mode,
-        bind_host: if matches!(config.listener_mode, crate::config::ListenerMode::TcpHttp | crate::config::ListenerMode::TcpHttps) {
-            Some(config.bind_host.clone())
-        } else {
-            None
-        },
-        bind_port: if matches!(config.listener_mode, crate::config::ListenerMode::TcpHttp | crate::config::ListenerMode::TcpHttps) {
-            Some(config.bind_port)
-        } else {
-            None
-        },
-        socket_path,
-        require_auth: config.require_auth,
+        listener_mode: primary.mode,
+        bind_host: primary.bind_host,
+        bind_port: primary.bind_port,
+        socket_path: primary.socket_path,
+        require_auth: primary.require_auth,
         group_access: config.group_access,
+        listeners,
         config_file_path,
         known_hosts_path: config.known_hosts_path.display().to_string(),
         ssh_key_dir,
         active_tunnels_count: active_count,
+        lockouts: state.auth_limiter.lockouts(),
         pid: std::process::id(),
         user: username,
     };
@@ -562,16 +1385,305 @@ async fn get_daemon_info(State(state): State<Arc<AppState>>) -> impl IntoRespons
     Json(info)
 }
 
+/// What this daemon supports, so clients can adapt to older or newer daemons
+#[utoipa::path(
+    get,
+    path = "/api/v1/capabilities",
+    tag = "daemon",
+    responses((status = 200, body = DaemonCapabilities))
+)]
+async fn get_capabilities(State(state): State<Arc<AppState>>) -> Json<DaemonCapabilities> {
+    let mut features = vec![
+        DaemonFeature::ScopedTokens,
+        DaemonFeature::Preflight,
+        DaemonFeature::Hooks,
+        DaemonFeature::EphemeralPorts,
+        DaemonFeature::WebSocket,
+    ];
+    if state.primary_token.is_some() {
+        features.push(DaemonFeature::TokenRotation);
+    }
+    if state.audit_log.is_some() {
+        features.push(DaemonFeature::AuditLog);
+    }
+    features.push(DaemonFeature::TunnelLogs);
+    if state.config.key_cache.enabled {
+        features.push(DaemonFeature::KeyCache);
+    }
+    if state.history.is_some() {
+        features.push(DaemonFeature::History);
+    }
+    if state.config.metrics.enabled {
+        features.push(DaemonFeature::Metrics);
+    }
+
+    Json(DaemonCapabilities {
+        api_versions: vec![API_VERSION.to_string()],
+        daemon_version: env!("CARGO_PKG_VERSION").to_string(),
+        forwarding_types: SUPPORTED_FORWARDING_TYPES.to_vec(),
+        auth_types: SUPPORTED_AUTH_TYPES.to_vec(),
+        features,
+    })
+}
+
+/// The OpenAPI document for `/api/v1`
+async fn openapi_document() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// List registered API tokens (secrets are never returned)
+#[utoipa::path(
+    get,
+    path = "/api/v1/tokens",
+    tag = "tokens",
+    responses((status = 200, body = [TokenInfo]))
+)]
+async fn list_tokens(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    Json(state.tokens.read().await.list())
+}
+
+/// Create a scoped API token; the secret is only returned here
+#[utoipa::path(
+    post,
+    path = "/api/v1/tokens",
+    tag = "tokens",
+    request_body = CreateTokenRequest,
+    responses(
+        (status = 201, body = CreateTokenResponse),
+        (status = 400, body = ErrorResponse),
+    )
+)]
+async fn create_token(
+    State(state): State<Arc<AppState>>,
+    caller: Caller,
+    Json(request): Json<CreateTokenRequest>,
+) -> impl IntoResponse {
+    info!("API: Create token request for '{}'", request.name);
+    let entry = caller.audit(AuditAction::TokenCreate).with_detail(request.name.clone());
+
+    // Hash before taking the lock; Argon2 takes a moment
+    let result = match NewToken::generate().await {
+        Ok(token) => state.tokens.write().await.create(request, token),
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok((token, info)) => {
+            let scopes: Vec<String> = info.scopes.iter().map(|s| s.to_string()).collect();
+            state.audit(entry.with_detail(format!("{} ({})", info.name, scopes.join(", "))));
+            (StatusCode::CREATED, Json(CreateTokenResponse { token, info })).into_response()
+        }
+        Err(e) => {
+            error!("Failed to create token: {:#}", e);
+            state.audit(entry.failed(format!("{:#}", e)));
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("{:#}", e),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Revoke an API token by name
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tokens/{name}",
+    tag = "tokens",
+    params(("name" = String, Path, description = "Token name")),
+    responses(
+        (status = 200, body = SuccessResponse),
+        (status = 404, body = ErrorResponse),
+    )
+)]
+async fn revoke_token(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    caller: Caller,
+) -> impl IntoResponse {
+    info!("API: Revoke token request for '{}'", name);
+    let entry = caller.audit(AuditAction::TokenRevoke);
+
+    match state.tokens.write().await.revoke(&name) {
+        Ok(()) => {
+            state.audit(entry.with_detail(name.clone()));
+            (
+                StatusCode::OK,
+                Json(SuccessResponse {
+                    message: format!("Token '{}' revoked", name),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("Failed to revoke token '{}': {:#}", name, e);
+            state.audit(entry.failed(format!("{}: {:#}", name, e)));
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: format!("{:#}", e),
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Rotate the primary token; the old one stays valid for a grace period
+#[utoipa::path(
+    post,
+    path = "/api/v1/daemon/token/rotate",
+    tag = "tokens",
+    request_body(content = RotateTokenRequest, description = "Optional; defaults to a 300s grace period"),
+    responses(
+        (status = 200, body = RotateTokenResponse),
+        (status = 400, description = "Authentication is disabled, or the grace period exceeds a week", body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+async fn rotate_primary_token(
+    State(state): State<Arc<AppState>>,
+    caller: Caller,
+    request: Option<Json<RotateTokenRequest>>,
+) -> impl IntoResponse {
+    let entry = caller.audit(AuditAction::TokenRotate);
+    let Some(primary) = &state.primary_token else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "Authentication is disabled; there is no token to rotate".to_string(),
+            }),
+        )
+            .into_response();
+    };
+
+    let grace_secs = request
+        .and_then(|Json(request)| request.grace_secs)
+        .unwrap_or(DEFAULT_ROTATION_GRACE_SECS);
+    info!("API: Token rotation request (grace period: {}s)", grace_secs);
+    if grace_secs > MAX_ROTATION_GRACE_SECS {
+        let error = format!(
+            "Grace period of {}s exceeds the maximum of {}s",
+            grace_secs, MAX_ROTATION_GRACE_SECS
+        );
+        state.audit(entry.failed(error.clone()));
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response();
+    }
+
+    // Hash before taking the lock; Argon2 takes a moment
+    let rotated = match NewToken::generate().await {
+        Ok(next) => primary.write().await.rotate(next, Duration::from_secs(grace_secs)),
+        Err(e) => Err(e),
+    };
+    let token = match rotated {
+        Ok(token) => token,
+        Err(e) => {
+            error!("Failed to rotate token: {:#}", e);
+            state.audit(entry.failed(format!("{:#}", e)));
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("{:#}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    // Keep the snippet in sync so copying it still yields a working CLI config
+    let config = &state.config;
+    let fingerprint = if config.has_tls_listener() {
+        crate::tls::get_cert_fingerprint(&config.tls_cert_path).ok()
+    } else {
+        None
+    };
+    if let Err(e) =
+        crate::config::write_cli_config_snippet(config, Some(&token), fingerprint.as_deref())
+    {
+        tracing::warn!("Token rotated but the CLI config snippet could not be updated: {:#}", e);
+    }
+
+    state.audit(entry.with_detail(format!("grace period {}s", grace_secs)));
+    (StatusCode::OK, Json(RotateTokenResponse { token, grace_secs })).into_response()
+}
+
+/// Query parameters for `GET /api/audit`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct AuditQuery {
+    /// RFC 3339 timestamp
+    since: Option<DateTime<Utc>>,
+    /// Profile ID or name
+    profile: Option<String>,
+    limit: Option<usize>,
+}
+
+/// Read back the audit log, oldest entry first
+#[utoipa::path(
+    get,
+    path = "/api/v1/audit",
+    tag = "audit",
+    params(AuditQuery),
+    responses(
+        (status = 200, body = [AuditEntry]),
+        (status = 404, description = "Audit log is disabled", body = ErrorResponse),
+        (status = 500, body = ErrorResponse),
+    )
+)]
+async fn get_audit(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditQuery>,
+) -> impl IntoResponse {
+    let Some(audit_log) = state.audit_log.clone() else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: "Audit log is disabled in daemon.toml".to_string(),
+            }),
+        )
+            .into_response();
+    };
+
+    let filter = AuditFilter {
+        since: query.since,
+        profile: query.profile,
+        limit: query.limit,
+    };
+    match tokio::task::spawn_blocking(move || audit_log.query(&filter)).await {
+        Ok(Ok(entries)) => (StatusCode::OK, Json(entries)).into_response(),
+        Ok(Err(e)) => {
+            error!("Failed to read audit log: {:#}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("{:#}", e),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e.to_string() }),
+        )
+            .into_response(),
+    }
+}
+
 /// Shutdown the daemon
-async fn shutdown_daemon(State(_state): State<Arc<AppState>>) -> impl IntoResponse {
-    info!("API: Shutdown request received");
-
-    // Spawn a task to exit after a short delay
-    tokio::spawn(async {
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        info!("Shutting down daemon...");
-        std::process::exit(0);
-    });
+#[utoipa::path(
+    post,
+    path = "/api/v1/daemon/shutdown",
+    tag = "daemon",
+    responses((status = 202, description = "Daemon stops all tunnels and exits"))
+)]
+async fn shutdown_daemon(State(state): State<Arc<AppState>>, caller: Caller) -> impl IntoResponse {
+    info!("API: Shutdown request received from {}", caller);
+    state.audit(caller.audit(AuditAction::DaemonShutdown));
+
+    // Same teardown as SIGTERM: hooks, history, audit and sockets are all
+    // finished by main before the process exits
+    state.shutdown_request.notify_one();
 
     StatusCode::ACCEPTED
 }
@@ -594,4 +1706,123 @@ mod tests {
         let json = heartbeat_payload();
         assert!(json.contains("heartbeat"), "heartbeat payload missing marker");
     }
+
+    #[test]
+    fn openapi_document_covers_v1() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let paths = doc["paths"].as_object().unwrap();
+        for path in [
+            "/api/v1/capabilities",
+            "/api/v1/tunnels/{id}/start",
+            "/api/v1/events",
+            "/api/v1/audit",
+            "/api/v1/metrics",
+            "/api/v1/tunnels/{id}/history",
+            "/api/v1/tunnels/{id}/logs",
+            "/api/v1/tunnels/{id}/logs/level",
+            "/api/v1/keys",
+            "/api/v1/keys/unlock",
+        ] {
+            assert!(paths.contains_key(path), "missing {}", path);
+        }
+        assert!(paths.keys().all(|p| p.starts_with("/api/v1/")));
+
+        let schemas = &doc["components"]["schemas"];
+        assert!(schemas["DaemonCapabilities"].is_object());
+        assert!(schemas["Profile"].is_object());
+        assert!(doc["components"]["securitySchemes"]["token"].is_object());
+    }
+
+    fn hybrid_request() -> (Uuid, StartTunnelRequest) {
+        use ssh_tunnel_common::{AuthType, ConnectionConfig, ForwardingConfig, ForwardingType};
+
+        let profile = Profile::new(
+            "remote".to_string(),
+            ConnectionConfig {
+                host: "example.com".to_string(),
+                port: 22,
+                user: "user".to_string(),
+                auth_type: AuthType::Password,
+                key_path: None,
+                password_storage: PasswordStorage::None,
+            },
+            ForwardingConfig {
+                forwarding_type: ForwardingType::Local,
+                bind_address: "127.0.0.1".to_string(),
+                local_port: Some(0),
+                remote_host: Some("localhost".to_string()),
+                remote_port: Some(80),
+            },
+        );
+        let id = profile.metadata.id;
+        let request = StartTunnelRequest {
+            profile_id: id.to_string(),
+            mode: ProfileSourceMode::Hybrid,
+            profile: Some(profile),
+        };
+        (id, request)
+    }
+
+    #[test]
+    fn hybrid_start_refuses_hooks() {
+        let (id, request) = hybrid_request();
+        assert!(resolve_start_profile(&id, request).is_ok());
+
+        let marker = std::env::temp_dir().join(format!("ssh-tunnel-hook-{}", Uuid::new_v4()));
+        let (id, mut request) = hybrid_request();
+        request.profile.as_mut().unwrap().hooks.pre_up =
+            Some(format!("touch {}", marker.display()));
+
+        let (status, message) = resolve_start_profile(&id, request).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("hooks"));
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn hybrid_start_refuses_secret_commands() {
+        let marker = std::env::temp_dir().join(format!("ssh-tunnel-secret-{}", Uuid::new_v4()));
+        let (id, mut request) = hybrid_request();
+        request.profile.as_mut().unwrap().connection.password_storage = PasswordStorage::Command {
+            command: format!("touch {}", marker.display()),
+        };
+
+        let (status, message) = resolve_start_profile(&id, request).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("Secret commands"));
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn restricted_hybrid_starts_get_no_cached_keys() {
+        let restricted = Some(Extension(Identity::new(
+            "ci",
+            vec![TokenScope::Control],
+            vec![Uuid::new_v4()],
+            vec![],
+        )));
+        let admin = Some(Extension(Identity::admin("default")));
+
+        assert!(!shares_cached_keys(&restricted, &ProfileSourceMode::Hybrid));
+        assert!(shares_cached_keys(&restricted, &ProfileSourceMode::Local));
+        assert!(shares_cached_keys(&admin, &ProfileSourceMode::Hybrid));
+        assert!(shares_cached_keys(&None, &ProfileSourceMode::Hybrid));
+    }
+
+    #[test]
+    fn capabilities_tolerate_unknown_features() {
+        // A newer daemon may advertise features this client doesn't know
+        let json = r#"{
+            "api_versions": ["v1", "v2"],
+            "daemon_version": "9.9.9",
+            "forwarding_types": ["local"],
+            "auth_types": ["key"],
+            "features": ["audit_log", "teleportation"]
+        }"#;
+        let capabilities: DaemonCapabilities = serde_json::from_str(json).unwrap();
+        assert!(capabilities.has_feature(DaemonFeature::AuditLog));
+        assert!(capabilities.has_feature(DaemonFeature::Unknown));
+        assert_eq!(capabilities.api_prefix(), "/api/v1");
+        assert_eq!(DaemonCapabilities::legacy().api_prefix(), "/api");
+    }
 }
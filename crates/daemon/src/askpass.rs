@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Askpass fallback for auth prompts
+//
+// Tunnels restored at boot or reconnecting on their own may need a passphrase
+// or code while no client is there to ask. `[auth_prompts] askpass` names a
+// command that answers such prompts, in the manner of SSH_ASKPASS: it gets the
+// prompt as its first argument and prints the answer on stdout. It can be a
+// desktop-session helper that asks the user, or a password manager CLI.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use tracing::warn;
+
+use ssh_tunnel_common::{AuthRequest, AuthRequestType};
+
+use crate::config::AuthPromptsConfig;
+use crate::shell;
+
+/// The configured askpass command
+#[derive(Debug, Clone)]
+pub struct Askpass {
+    command: String,
+    /// How long clients get to answer before the command runs
+    pub grace: Duration,
+}
+
+impl Askpass {
+    pub fn from_config(config: &AuthPromptsConfig) -> Option<Self> {
+        let command = config.askpass.as_deref()?.trim();
+        (!command.is_empty()).then(|| Self {
+            command: command.to_string(),
+            grace: Duration::from_secs(config.askpass_grace_secs),
+        })
+    }
+
+    /// Whether the command may answer prompts of this type
+    ///
+    /// Only secrets: host key prompts are never answered by askpass, as that
+    /// would accept unknown host keys silently.
+    pub fn answers(auth_type: &AuthRequestType) -> bool {
+        match auth_type {
+            AuthRequestType::KeyPassphrase
+            | AuthRequestType::Password
+            | AuthRequestType::TwoFactorCode
+            | AuthRequestType::KeyboardInteractive
+            | AuthRequestType::VaultPassphrase => true,
+            AuthRequestType::HostKeyVerification => false,
+        }
+    }
+
+    /// Run the command for `request` and return its answer
+    ///
+    /// The command runs via `sh -c` with the prompt as `$1`, and with
+    /// `SSH_TUNNEL_PROMPT`, `SSH_TUNNEL_AUTH_TYPE`, `SSH_TUNNEL_HIDDEN` (`1`
+    /// or `0`), `SSH_TUNNEL_PROFILE_NAME` and `SSH_TUNNEL_PROFILE_ID` set. A
+    /// non-zero exit status means it has no answer. The child is killed after
+    /// `timeout`.
+    pub async fn ask(
+        &self,
+        request: &AuthRequest,
+        profile_name: &str,
+        timeout: Duration,
+    ) -> Result<String> {
+        let mut cmd = shell::sh(&format!("{} \"$@\"", self.command));
+        cmd.arg("ssh-tunnel-askpass")
+            .arg(&request.prompt)
+            .env("SSH_TUNNEL_PROMPT", &request.prompt)
+            .env("SSH_TUNNEL_AUTH_TYPE", auth_type_name(&request.auth_type))
+            .env("SSH_TUNNEL_HIDDEN", if request.hidden { "1" } else { "0" })
+            .env("SSH_TUNNEL_PROFILE_NAME", profile_name)
+            .env("SSH_TUNNEL_PROFILE_ID", request.tunnel_id.to_string());
+        let output = shell::run(&mut cmd, timeout, "askpass").await?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let answer = stdout
+            .strip_suffix('\n')
+            .map(|s| s.strip_suffix('\r').unwrap_or(s))
+            .unwrap_or(&stdout)
+            .to_string();
+
+        for line in String::from_utf8_lossy(&output.stderr).lines() {
+            warn!("[askpass] {}", line);
+        }
+
+        if !output.status.success() {
+            anyhow::bail!("askpass {}", shell::describe_failure(output.status));
+        }
+        crate::logging::register_answer(request.tunnel_id, &answer);
+        Ok(answer)
+    }
+}
+
+/// Name of the auth type as serialized in the API (e.g. `key_passphrase`)
+fn auth_type_name(auth_type: &AuthRequestType) -> String {
+    serde_json::to_value(auth_type)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use uuid::Uuid;
+
+    fn request(prompt: &str) -> AuthRequest {
+        AuthRequest {
+            id: Uuid::new_v4(),
+            tunnel_id: Uuid::nil(),
+            auth_type: AuthRequestType::KeyPassphrase,
+            prompt: prompt.to_string(),
+            hidden: true,
+            name: None,
+            instructions: None,
+            prompts: Vec::new(),
+        }
+    }
+
+    fn askpass(command: &str) -> Askpass {
+        let config = AuthPromptsConfig {
+            askpass: Some(command.to_string()),
+            ..AuthPromptsConfig::default()
+        };
+        Askpass::from_config(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stub_script_answers() {
+        // Stand-in for a desktop helper: echoes what it was asked
+        let dir = tempfile::TempDir::new().unwrap();
+        let script = dir.path().join("askpass-stub");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\necho \"$SSH_TUNNEL_AUTH_TYPE/$SSH_TUNNEL_HIDDEN/$SSH_TUNNEL_PROFILE_NAME/$1\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o700)).unwrap();
+
+        let answer = askpass(script.to_str().unwrap())
+            .ask(&request("Passphrase for key:"), "db", Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(answer, "key_passphrase/1/db/Passphrase for key:");
+    }
+
+    #[tokio::test]
+    async fn test_failures() {
+        let timeout = Duration::from_secs(5);
+        let err = askpass("false").ask(&request("Password:"), "db", timeout).await;
+        assert!(err.unwrap_err().to_string().contains("status 1"));
+
+        let err = askpass("sleep 5; :")
+            .ask(&request("Password:"), "db", Duration::from_millis(100))
+            .await;
+        assert!(err.unwrap_err().to_string().contains("timed out"));
+
+        assert!(Askpass::from_config(&AuthPromptsConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_only_secret_prompts() {
+        assert!(Askpass::answers(&AuthRequestType::KeyPassphrase));
+        assert!(Askpass::answers(&AuthRequestType::Password));
+        assert!(Askpass::answers(&AuthRequestType::KeyboardInteractive));
+        assert!(!Askpass::answers(&AuthRequestType::HostKeyVerification));
+    }
+}
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Audit log
+//
+// Append-only JSON Lines record of mutating API calls (who, from where, what
+// result) and of every tunnel state change. Files are rotated by size:
+// `audit.jsonl` is current, `audit.jsonl.1` the newest rotated file.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use ssh_tunnel_common::{AuditAction, AuditEntry, Profile};
+
+use crate::config::AuditConfig;
+use crate::tunnel::{TunnelEvent, TunnelManager};
+
+/// Filter for reading the audit log back
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub since: Option<DateTime<Utc>>,
+    /// Profile ID or name
+    pub profile: Option<String>,
+    /// Return at most this many entries (the most recent ones)
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        if self.since.is_some_and(|since| entry.timestamp < since) {
+            return false;
+        }
+        match &self.profile {
+            Some(profile) => {
+                entry.profile_id.is_some_and(|id| id.to_string() == *profile)
+                    || entry.profile_name.as_deref() == Some(profile.as_str())
+            }
+            None => true,
+        }
+    }
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    file: Mutex<Option<File>>,
+}
+
+impl AuditLog {
+    /// Open (or create) the log for appending
+    pub fn open(config: &AuditConfig) -> Result<Self> {
+        let log = Self {
+            path: config.path.clone(),
+            max_file_bytes: config.max_file_bytes,
+            max_files: config.max_files,
+            file: Mutex::new(None),
+        };
+        let file = log.open_current()?;
+        *log.file.lock().unwrap() = Some(file);
+
+        info!("Audit log: {}", log.path.display());
+        Ok(log)
+    }
+
+    /// Append an entry
+    ///
+    /// Failures are logged but never fail the action being audited.
+    pub fn record(&self, entry: AuditEntry) {
+        if let Err(e) = self.append(&entry) {
+            warn!("Failed to write audit log entry ({}): {:#}", entry.action, e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_string(entry).context("Failed to serialize audit entry")?;
+        line.push('\n');
+
+        let mut guard = self.file.lock().unwrap();
+        let size = match guard.as_ref() {
+            Some(file) => file.metadata().map(|m| m.len()).unwrap_or(0),
+            None => 0,
+        };
+        if size > 0 && size + line.len() as u64 > self.max_file_bytes {
+            *guard = None;
+            self.rotate()?;
+        }
+        if guard.is_none() {
+            *guard = Some(self.open_current()?);
+        }
+
+        let file = guard.as_mut().expect("audit log file is open");
+        file.write_all(line.as_bytes())
+            .context("Failed to append to audit log")?;
+        file.flush().context("Failed to flush audit log")?;
+        Ok(())
+    }
+
+    fn open_current(&self) -> Result<File> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create audit log directory {}", parent.display())
+            })?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log {}", self.path.display()))?;
+        crate::permissions::set_file_permissions_private(&self.path)?;
+        Ok(file)
+    }
+
+    fn rotate(&self) -> Result<()> {
+        rotate_files(&self.path, self.max_files).context("Failed to rotate audit log")?;
+        debug!("Rotated audit log {}", self.path.display());
+        Ok(())
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        rotated_path(&self.path, n)
+    }
+
+    /// Entries matching `filter`, oldest first
+    pub fn query(&self, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+        // Hold the lock so a rotation can't move files mid-read
+        let _guard = self.file.lock().unwrap();
+
+        let mut files: Vec<PathBuf> = (1..=self.max_files)
+            .rev()
+            .map(|n| self.rotated_path(n))
+            .collect();
+        files.push(self.path.clone());
+
+        let mut entries = Vec::new();
+        for path in files.iter().filter(|p| p.exists()) {
+            read_entries(path, filter, &mut entries)?;
+        }
+
+        if let Some(limit) = filter.limit {
+            let skip = entries.len().saturating_sub(limit);
+            entries.drain(..skip);
+        }
+        Ok(entries)
+    }
+}
+
+/// Shift `<path>.N` to `.N+1` (dropping the oldest) and move `path` to `.1`
+///
+/// Doesn't log, so it is safe to call from a log writer.
+pub(crate) fn rotate_files(path: &Path, max_files: usize) -> Result<()> {
+    if max_files == 0 {
+        fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        return Ok(());
+    }
+
+    let oldest = rotated_path(path, max_files);
+    if oldest.exists() {
+        fs::remove_file(&oldest)
+            .with_context(|| format!("Failed to remove {}", oldest.display()))?;
+    }
+    for n in (1..max_files).rev() {
+        let from = rotated_path(path, n);
+        if from.exists() {
+            fs::rename(&from, rotated_path(path, n + 1))
+                .with_context(|| format!("Failed to rotate {}", from.display()))?;
+        }
+    }
+    fs::rename(path, rotated_path(path, 1))
+        .with_context(|| format!("Failed to rotate {}", path.display()))?;
+    Ok(())
+}
+
+/// `<path>.N`
+pub(crate) fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+fn read_entries(path: &Path, filter: &AuditFilter, entries: &mut Vec<AuditEntry>) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<AuditEntry>(&line) {
+            Ok(entry) if filter.matches(&entry) => entries.push(entry),
+            Ok(_) => {}
+            Err(e) => debug!("Skipping malformed audit line in {}: {}", path.display(), e),
+        }
+    }
+    Ok(())
+}
+
+/// Record every tunnel state change from the tunnel manager's event stream
+pub fn spawn_transition_recorder(log: Arc<AuditLog>, tunnel_manager: TunnelManager) {
+    let mut event_rx = tunnel_manager.subscribe();
+    tokio::spawn(async move {
+        // Tunnels are gone from the manager by the time they report
+        // "disconnected", so remember profiles from earlier events
+        let mut profiles: HashMap<Uuid, Profile> = HashMap::new();
+
+        loop {
+            let event = match event_rx.recv().await {
+                Ok(sequenced) => sequenced.event,
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("Audit log missed {} tunnel events", missed);
+                    log.record(
+                        AuditEntry::new(AuditAction::TunnelState)
+                            .failed(format!("{} tunnel events were not recorded", missed)),
+                    );
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            let Some((success, detail)) = transition(&event) else {
+                continue;
+            };
+
+            let id = event.id();
+            if let Some(profile) = tunnel_manager.get_profile(&id).await {
+                profiles.insert(id, profile);
+            }
+            let mut entry = match profiles.get(&id) {
+                Some(profile) => AuditEntry::new(AuditAction::TunnelState).with_profile(profile),
+                None => AuditEntry::new(AuditAction::TunnelState).with_profile_id(id),
+            };
+            entry.success = success;
+            log.record(entry.with_detail(detail));
+        }
+    });
+}
+
+/// Whether an event is a state transition, and how to describe it
+fn transition(event: &TunnelEvent) -> Option<(bool, String)> {
+    match event {
+        TunnelEvent::Starting { .. } => Some((true, "starting".to_string())),
+        TunnelEvent::Connected { bound_addr, .. } => Some((
+            true,
+            match bound_addr {
+                Some(addr) => format!("connected ({})", addr),
+                None => "connected".to_string(),
+            },
+        )),
+        TunnelEvent::AuthRequired { .. } => Some((true, "waiting_for_auth".to_string())),
+        TunnelEvent::Disconnected { reason, .. } => {
+            Some((true, format!("disconnected: {}", reason)))
+        }
+        TunnelEvent::Error { error, .. } => Some((false, format!("failed: {}", error))),
+        TunnelEvent::AuthResolved { .. }
+        | TunnelEvent::AuthCancelled { .. }
+        | TunnelEvent::Hook { .. }
+        | TunnelEvent::Stats { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_audit_log_rotation_and_query() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = AuditConfig {
+            enabled: true,
+            path: temp_dir.path().join("audit.jsonl"),
+            max_file_bytes: 600,
+            max_files: 2,
+        };
+        let log = AuditLog::open(&config).unwrap();
+
+        let id = Uuid::new_v4();
+        for n in 0..20 {
+            let mut entry = AuditEntry::new(AuditAction::TunnelStart).with_detail(format!("#{}", n));
+            entry.actor = Some("default".to_string());
+            if n % 2 == 0 {
+                entry = entry.with_profile_id(id);
+            }
+            log.record(entry);
+        }
+
+        assert!(config.path.exists());
+        assert!(log.rotated_path(2).exists());
+        assert!(!log.rotated_path(3).exists());
+        assert!(fs::metadata(&config.path).unwrap().len() <= 600);
+
+        // Old entries were rotated away; the rest come back oldest first
+        let all = log.query(&AuditFilter::default()).unwrap();
+        assert!(all.len() < 20);
+        assert_eq!(all.last().unwrap().detail.as_deref(), Some("#19"));
+
+        let filter = AuditFilter {
+            profile: Some(id.to_string()),
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mine = log.query(&filter).unwrap();
+        assert_eq!(mine.len(), 2);
+        assert_eq!(mine[1].detail.as_deref(), Some("#18"));
+
+        let filter = AuditFilter {
+            since: Some(Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(log.query(&filter).unwrap().is_empty());
+    }
+}
@@ -5,19 +5,33 @@
 // Handles token-based authentication for the daemon API
 
 use std::fs;
-use std::path::PathBuf;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{ConnectInfo, Request},
+    http::{header, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ssh_tunnel_common::{CreateTokenRequest, TokenInfo, TokenScope};
+use subtle::ConstantTimeEq;
+use tokio::sync::RwLock;
 use tracing::{info, warn};
 use uuid::Uuid;
 use zeroize::Zeroizing;
 
+use crate::peercred::PeerCredentials;
+use crate::ratelimit::AuthLimiter;
+
 /// HTTP header name for authentication token
 pub const AUTH_TOKEN_HEADER: &str = "X-Tunnel-Token";
 
@@ -36,110 +50,698 @@ pub fn obfuscate_token(token: &str) -> String {
 
 /// Generate a new authentication token
 pub fn generate_token() -> String {
-    Uuid::new_v4().to_string()
+    let token = Uuid::new_v4().to_string();
+    crate::logging::register_secret(&token);
+    token
+}
+
+/// Default time the previous primary token stays valid after a rotation
+pub const DEFAULT_ROTATION_GRACE_SECS: u64 = 300;
+
+/// Longest grace period a rotation accepts (one week)
+pub const MAX_ROTATION_GRACE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// How much work a token check may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HashCheck {
+    /// Only compare against secrets already verified in this run
+    CachedOnly,
+    /// Fall back to a full Argon2 verification
+    Full,
+}
+
+/// Argon2id hash of an API token
+///
+/// Only the PHC hash string is persisted. After the first successful
+/// verification the secret is kept in memory, so later requests cost a
+/// constant-time comparison instead of a full Argon2 run. Clones share that
+/// memory, so a clone verified off the async workers serves the original too.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TokenHash {
+    hash: String,
+    verified: Arc<OnceLock<Zeroizing<String>>>,
 }
 
-/// Load or generate authentication token from file
-/// Returns (token, was_newly_generated)
-pub fn load_or_generate_token(token_path: &PathBuf) -> Result<(String, bool)> {
-    // If token file exists, load it
-    if token_path.exists() {
-        let token = fs::read_to_string(token_path)
-            .context("Failed to read authentication token file")?
-            .trim()
+impl TokenHash {
+    /// Hash a token with a fresh random salt
+    pub fn new(secret: &str) -> Result<Self> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default()
+            .hash_password(secret.as_bytes(), &salt)
+            .map_err(|e| anyhow::anyhow!("Failed to hash token: {}", e))?
             .to_string();
 
-        if token.is_empty() {
-            warn!("Token file exists but is empty, regenerating");
-        } else {
-            info!("Loaded authentication token from: {}", token_path.display());
-            return Ok((token, false));
+        Ok(Self {
+            hash,
+            verified: Arc::default(),
+        })
+    }
+
+    /// Check a presented token against the hash
+    pub fn verify(&self, secret: &str) -> bool {
+        self.check(secret, HashCheck::Full)
+    }
+
+    fn check(&self, secret: &str, mode: HashCheck) -> bool {
+        if let Some(known) = self.verified.get() {
+            return known.as_bytes().ct_eq(secret.as_bytes()).into();
+        }
+        if mode == HashCheck::CachedOnly {
+            return false;
+        }
+
+        let matches = PasswordHash::new(&self.hash)
+            .map(|parsed| {
+                Argon2::default()
+                    .verify_password(secret.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false);
+        if matches {
+            crate::logging::register_secret(secret);
+            let _ = self.verified.set(Zeroizing::new(secret.to_string()));
+        }
+        matches
+    }
+
+    /// Whether a stored value is a hash (as opposed to a legacy plaintext token)
+    fn is_hash(value: &str) -> bool {
+        value.starts_with("$argon2")
+    }
+}
+
+impl TryFrom<String> for TokenHash {
+    type Error = String;
+
+    fn try_from(hash: String) -> Result<Self, Self::Error> {
+        PasswordHash::new(&hash).map_err(|e| format!("invalid token hash: {}", e))?;
+        Ok(Self {
+            hash,
+            verified: Arc::default(),
+        })
+    }
+}
+
+impl From<TokenHash> for String {
+    fn from(token: TokenHash) -> Self {
+        token.hash
+    }
+}
+
+/// A freshly generated token and its hash
+pub struct NewToken {
+    secret: String,
+    hash: TokenHash,
+}
+
+impl NewToken {
+    pub fn new() -> Result<Self> {
+        let secret = generate_token();
+        let hash = TokenHash::new(&secret)?;
+        Ok(Self { secret, hash })
+    }
+
+    /// Generate a token on the blocking pool, as hashing takes a moment
+    pub async fn generate() -> Result<Self> {
+        tokio::task::spawn_blocking(Self::new)
+            .await
+            .context("Token hashing task failed")?
+    }
+}
+
+/// The primary API token (`auth_token_path`), stored as an Argon2id hash
+pub struct PrimaryToken {
+    path: PathBuf,
+    current: TokenHash,
+    /// Token replaced by the last rotation and the instant it stops working
+    previous: Option<(TokenHash, Instant)>,
+}
+
+impl PrimaryToken {
+    /// Load the token hash from file, generating a token on first start
+    ///
+    /// Also returns the plaintext token when it is known in this run: freshly
+    /// generated, or read from a token file written before tokens were hashed
+    /// (the file is rewritten with the hash).
+    pub fn load_or_generate(token_path: &Path) -> Result<(Self, Option<String>)> {
+        if token_path.exists() {
+            let content = Zeroizing::new(
+                fs::read_to_string(token_path)
+                    .context("Failed to read authentication token file")?,
+            );
+            let content = content.trim();
+
+            if content.is_empty() {
+                warn!("Token file exists but is empty, regenerating");
+            } else if TokenHash::is_hash(content) {
+                let current = TokenHash::try_from(content.to_string())
+                    .map_err(|e| anyhow::anyhow!("{} in {}", e, token_path.display()))?;
+                info!("Loaded authentication token hash from: {}", token_path.display());
+                return Ok((Self::with_hash(token_path, current), None));
+            } else {
+                let token = content.to_string();
+                let current = TokenHash::new(&token)?;
+                save_token_hash(token_path, &current)?;
+                info!(
+                    "Replaced plaintext authentication token with its hash in: {}",
+                    token_path.display()
+                );
+                return Ok((Self::with_hash(token_path, current), Some(token)));
+            }
+        }
+
+        // Generate new token
+        let token = generate_token();
+        let current = TokenHash::new(&token)?;
+        save_token_hash(token_path, &current)?;
+
+        info!("Generated new authentication token");
+        info!("Token hash saved to: {}", token_path.display());
+        info!("");
+        info!("⚠️  IMPORTANT: Clients must provide this token to connect!");
+        info!("   Token: {} (full token in the CLI config snippet)", obfuscate_token(&token));
+        info!("   Add to CLI config or use X-Tunnel-Token header");
+
+        Ok((Self::with_hash(token_path, current), Some(token)))
+    }
+
+    fn with_hash(token_path: &Path, current: TokenHash) -> Self {
+        Self {
+            path: token_path.to_path_buf(),
+            current,
+            previous: None,
+        }
+    }
+
+    /// Check a presented token against the current and, during the grace
+    /// period, the previous token
+    #[cfg(test)]
+    pub fn verify(&self, secret: &str) -> bool {
+        self.check(secret, HashCheck::Full)
+    }
+
+    fn check(&self, secret: &str, mode: HashCheck) -> bool {
+        self.hashes().iter().any(|hash| hash.check(secret, mode))
+    }
+
+    /// The hashes that currently grant access
+    fn hashes(&self) -> Vec<TokenHash> {
+        let mut hashes = vec![self.current.clone()];
+        match &self.previous {
+            Some((previous, until)) if Instant::now() < *until => hashes.push(previous.clone()),
+            _ => {}
         }
+        hashes
     }
 
-    // Generate new token
-    let token = generate_token();
-    save_token(token_path, &token)?;
+    /// Replace the token with `next`; the current one keeps working for `grace`
+    ///
+    /// Returns the new token. Fails without changing anything when `grace`
+    /// exceeds `MAX_ROTATION_GRACE_SECS`.
+    pub fn rotate(&mut self, next: NewToken, grace: Duration) -> Result<String> {
+        if grace > Duration::from_secs(MAX_ROTATION_GRACE_SECS) {
+            anyhow::bail!(
+                "Grace period of {}s exceeds the maximum of {}s",
+                grace.as_secs(),
+                MAX_ROTATION_GRACE_SECS
+            );
+        }
+        let until = Instant::now()
+            .checked_add(grace)
+            .ok_or_else(|| anyhow::anyhow!("Grace period of {}s is out of range", grace.as_secs()))?;
+        save_token_hash(&self.path, &next.hash)?;
 
-    info!("Generated new authentication token");
-    info!("Token saved to: {}", token_path.display());
-    info!("");
-    info!("⚠️  IMPORTANT: Clients must provide this token to connect!");
-    info!("   Token: {} (full token in {})", obfuscate_token(&token), token_path.display());
-    info!("   Add to CLI config or use X-Tunnel-Token header");
+        let previous = std::mem::replace(&mut self.current, next.hash);
+        self.previous = (!grace.is_zero()).then_some((previous, until));
 
-    Ok((token, true))
+        info!(
+            "Rotated authentication token (previous token valid for {}s)",
+            grace.as_secs()
+        );
+        Ok(next.secret)
+    }
 }
 
-/// Save authentication token to file
-fn save_token(token_path: &PathBuf, token: &str) -> Result<()> {
+/// Save the token hash to file
+fn save_token_hash(token_path: &Path, hash: &TokenHash) -> Result<()> {
     // Ensure parent directory exists
     if let Some(parent) = token_path.parent() {
         fs::create_dir_all(parent).context("Failed to create token directory")?;
     }
 
-    // Write token to file
-    fs::write(token_path, token).context("Failed to write token file")?;
+    // Write token hash to file
+    fs::write(token_path, format!("{}\n", hash.hash)).context("Failed to write token file")?;
 
-    // Set restrictive permissions (Unix only)
     // Set restrictive permissions (600 = owner read/write only)
     crate::permissions::set_file_permissions_private(token_path)?;
 
     Ok(())
 }
 
+/// Name reported for the primary token from `auth_token_path`
+pub const PRIMARY_TOKEN_NAME: &str = "default";
+
+/// A registered API token as persisted in the tokens file
+#[derive(Clone, Serialize, Deserialize)]
+struct StoredToken {
+    name: String,
+    hash: TokenHash,
+    scopes: Vec<TokenScope>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    profile_ids: Vec<Uuid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    profile_tags: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+impl StoredToken {
+    fn identity(&self) -> Identity {
+        Identity::new(
+            &self.name,
+            self.scopes.clone(),
+            self.profile_ids.clone(),
+            self.profile_tags.clone(),
+        )
+    }
+
+    fn info(&self) -> TokenInfo {
+        TokenInfo {
+            name: self.name.clone(),
+            scopes: self.scopes.clone(),
+            profile_ids: self.profile_ids.clone(),
+            profile_tags: self.profile_tags.clone(),
+            created_at: self.created_at,
+        }
+    }
+}
+
+/// On-disk layout of the tokens file (`[[token]]` tables)
+#[derive(Default, Serialize, Deserialize)]
+struct TokenFile {
+    #[serde(default, rename = "token")]
+    tokens: Vec<StoredToken>,
+}
+
+/// Additional API tokens with scopes and profile allowlists
+///
+/// The primary token (`auth_token_path`) keeps full access and is not part of
+/// the registry. Changes are written back to the tokens file immediately.
+pub struct TokenRegistry {
+    path: PathBuf,
+    tokens: Vec<StoredToken>,
+}
+
+impl TokenRegistry {
+    /// Load the registry, starting empty if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        let tokens = if path.exists() {
+            let content = fs::read_to_string(path).context("Failed to read tokens file")?;
+            let file: TokenFile = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse tokens file {}", path.display()))?;
+            file.tokens
+        } else {
+            Vec::new()
+        };
+
+        if !tokens.is_empty() {
+            info!("Loaded {} API token(s) from: {}", tokens.len(), path.display());
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            tokens,
+        })
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create tokens directory")?;
+        }
+
+        let file = TokenFile {
+            tokens: self.tokens.clone(),
+        };
+        let content = Zeroizing::new(toml::to_string(&file).context("Failed to serialize tokens")?);
+        fs::write(&self.path, content.as_bytes()).context("Failed to write tokens file")?;
+        crate::permissions::set_file_permissions_private(&self.path)?;
+
+        Ok(())
+    }
+
+    /// All registered tokens, without secrets
+    pub fn list(&self) -> Vec<TokenInfo> {
+        self.tokens.iter().map(StoredToken::info).collect()
+    }
+
+    /// Register `token` as described by `request` and return its secret
+    pub fn create(
+        &mut self,
+        request: CreateTokenRequest,
+        token: NewToken,
+    ) -> Result<(String, TokenInfo)> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Token name cannot be empty");
+        }
+        if name == PRIMARY_TOKEN_NAME {
+            anyhow::bail!("Token name '{}' is reserved for the primary token", PRIMARY_TOKEN_NAME);
+        }
+        if self.tokens.iter().any(|t| t.name == name) {
+            anyhow::bail!("A token named '{}' already exists", name);
+        }
+        if request.scopes.is_empty() {
+            anyhow::bail!("A token needs at least one scope");
+        }
+
+        let NewToken { secret, hash } = token;
+        let token = StoredToken {
+            name: name.to_string(),
+            hash,
+            scopes: request.scopes,
+            profile_ids: request.profile_ids,
+            profile_tags: request.profile_tags,
+            created_at: Utc::now(),
+        };
+        let info = token.info();
+
+        self.tokens.push(token);
+        if let Err(e) = self.save() {
+            self.tokens.pop();
+            return Err(e);
+        }
+
+        info!("Created API token '{}' (scopes: {:?})", info.name, info.scopes);
+        Ok((secret, info))
+    }
+
+    /// Remove a token; it stops working immediately
+    pub fn revoke(&mut self, name: &str) -> Result<()> {
+        let index = self
+            .tokens
+            .iter()
+            .position(|t| t.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Token not found: {}", name))?;
+
+        let removed = self.tokens.remove(index);
+        if let Err(e) = self.save() {
+            self.tokens.insert(index, removed);
+            return Err(e);
+        }
+
+        info!("Revoked API token '{}'", name);
+        Ok(())
+    }
+
+    /// Identity for a presented secret, if it matches a registered token
+    fn identify(&self, secret: &str, mode: HashCheck) -> Option<Identity> {
+        self.tokens
+            .iter()
+            .find(|t| t.hash.check(secret, mode))
+            .map(StoredToken::identity)
+    }
+
+    /// The hash of every token with the identity it grants
+    fn hashes(&self) -> Vec<(TokenHash, Identity)> {
+        self.tokens
+            .iter()
+            .map(|t| (t.hash.clone(), t.identity()))
+            .collect()
+    }
+}
+
+/// Caller identity, attached to the request by `auth_middleware`
+///
+/// Listeners without authentication attach no identity; handlers treat
+/// that as full access.
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub name: String,
+    scopes: Vec<TokenScope>,
+    profile_ids: Vec<Uuid>,
+    profile_tags: Vec<String>,
+}
+
+impl Identity {
+    pub fn new(
+        name: &str,
+        scopes: Vec<TokenScope>,
+        profile_ids: Vec<Uuid>,
+        profile_tags: Vec<String>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            scopes,
+            profile_ids,
+            profile_tags,
+        }
+    }
+
+    /// Unrestricted identity (the primary token)
+    pub fn admin(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            scopes: vec![TokenScope::Admin],
+            profile_ids: Vec::new(),
+            profile_tags: Vec::new(),
+        }
+    }
+
+    /// Whether the identity holds `scope` (admin implies every scope)
+    pub fn has_scope(&self, scope: TokenScope) -> bool {
+        self.scopes.contains(&TokenScope::Admin) || self.scopes.contains(&scope)
+    }
+
+    /// Whether the identity is limited to some profiles
+    pub fn is_restricted(&self) -> bool {
+        !self.profile_ids.is_empty() || !self.profile_tags.is_empty()
+    }
+
+    /// Whether the identity may touch a profile with this ID and these tags
+    pub fn allows_profile(&self, id: &Uuid, tags: &[String]) -> bool {
+        !self.is_restricted()
+            || self.profile_ids.contains(id)
+            || tags.iter().any(|tag| self.profile_tags.contains(tag))
+    }
+}
+
+/// Identity from the TLS client certificate, attached per connection by the
+/// HTTPS acceptor (`None` when no certificate was presented or it matched no
+/// configured identity)
+#[derive(Debug, Clone)]
+pub struct ClientCertIdentity(pub Option<Identity>);
+
 /// Authentication middleware state
 #[derive(Clone)]
 pub struct AuthState {
-    token: Zeroizing<String>,
+    primary: Arc<RwLock<PrimaryToken>>,
+    registry: Arc<RwLock<TokenRegistry>>,
+    limiter: Arc<AuthLimiter>,
 }
 
 impl AuthState {
-    pub fn new(token: String) -> Self {
+    pub fn new(
+        primary: Arc<RwLock<PrimaryToken>>,
+        registry: Arc<RwLock<TokenRegistry>>,
+        limiter: Arc<AuthLimiter>,
+    ) -> Self {
         Self {
-            token: Zeroizing::new(token),
+            primary,
+            registry,
+            limiter,
+        }
+    }
+
+    async fn identify(&self, token: &str, mode: HashCheck) -> Option<Identity> {
+        if self.primary.read().await.check(token, HashCheck::CachedOnly) {
+            return Some(Identity::admin(PRIMARY_TOKEN_NAME));
+        }
+        if let Some(identity) = self.registry.read().await.identify(token, HashCheck::CachedOnly) {
+            return Some(identity);
         }
+        if mode == HashCheck::CachedOnly {
+            return None;
+        }
+
+        // Argon2 runs on the blocking pool, on copies of the hashes, so the
+        // locks aren't held while it works
+        let mut candidates: Vec<(TokenHash, Identity)> = self
+            .primary
+            .read()
+            .await
+            .hashes()
+            .into_iter()
+            .map(|hash| (hash, Identity::admin(PRIMARY_TOKEN_NAME)))
+            .collect();
+        candidates.extend(self.registry.read().await.hashes());
+
+        let token = Zeroizing::new(token.to_string());
+        tokio::task::spawn_blocking(move || {
+            candidates
+                .into_iter()
+                .find(|(hash, _)| hash.check(&token, HashCheck::Full))
+                .map(|(_, identity)| identity)
+        })
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Token verification task failed: {}", e);
+            None
+        })
     }
 }
 
 /// Authentication middleware for Axum
 ///
-/// This middleware checks for the X-Tunnel-Token header and validates it
-/// against the configured token. Returns 401 Unauthorized if token is missing
-/// or invalid.
+/// A mapped TLS client certificate, or a Unix socket peer listed in
+/// `token_exempt_uids`, authenticates the request on its own. Otherwise
+/// this middleware checks for the X-Tunnel-Token header and validates it
+/// against the primary token and the token registry. Returns 401
+/// Unauthorized if the token is missing or invalid, and 429 Too Many
+/// Requests while the source is locked out or the daemon-wide budget for
+/// unauthenticated requests is spent. On success the caller's `Identity` is
+/// attached to the request for scope and profile checks.
 pub async fn auth_middleware(
     axum::extract::State(auth_state): axum::extract::State<AuthState>,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
+    let cert_identity = request
+        .extensions()
+        .get::<ClientCertIdentity>()
+        .and_then(|cert| cert.0.clone());
+    if let Some(identity) = cert_identity {
+        tracing::trace!("Authenticated by client certificate ({})", identity.name);
+        request.extensions_mut().insert(identity);
+        return Ok(next.run(request).await);
+    }
+
+    let exempt_peer = request
+        .extensions()
+        .get::<PeerCredentials>()
+        .filter(|peer| peer.token_exempt)
+        .map(|peer| peer.identity_name());
+    if let Some(name) = exempt_peer {
+        tracing::trace!("Authenticated by Unix peer credentials ({})", name);
+        request.extensions_mut().insert(Identity::admin(&name));
+        return Ok(next.run(request).await);
+    }
+
+    // Unix socket connections have no client address
+    let source = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_canonical());
+    let limiter = &auth_state.limiter;
+
+    if let Some((ip, remaining)) = source.and_then(|ip| Some((ip, limiter.locked_out(ip)?))) {
+        warn!(
+            "Blocked request from {}: locked out for another {}s after repeated authentication failures",
+            ip,
+            remaining.as_secs().max(1)
+        );
+        return Ok(too_many_requests(remaining));
+    }
+
     // Get the provided token from headers
     let provided_token = request
         .headers()
         .get(AUTH_TOKEN_HEADER)
         .and_then(|h| h.to_str().ok());
 
+    // Tokens already verified in this run are a cheap comparison. Anything
+    // else needs Argon2 (or fails outright) and counts against the budget
+    // for unauthenticated requests.
+    let mut identity = match provided_token {
+        Some(token) => auth_state.identify(token, HashCheck::CachedOnly).await,
+        None => None,
+    };
+    if identity.is_none() {
+        if !limiter.allow_unauthenticated() {
+            warn!(
+                "Blocked request from {}: too many unauthenticated requests",
+                source_label(source)
+            );
+            return Ok(too_many_requests(Duration::from_secs(1)));
+        }
+        if let Some(token) = provided_token {
+            identity = auth_state.identify(token, HashCheck::Full).await;
+        }
+    }
+
     // Validate token
-    match provided_token {
-        Some(token) if token == auth_state.token.as_str() => {
+    match (provided_token, identity) {
+        (_, Some(identity)) => {
             // Too chatty at debug when clients poll frequently; keep at trace.
-            tracing::trace!("Authentication successful");
+            tracing::trace!("Authentication successful ({})", identity.name);
+            if let Some(ip) = source {
+                limiter.record_success(ip);
+            }
+            request.extensions_mut().insert(identity);
             Ok(next.run(request).await)
         }
-        Some(_) => {
-            warn!("Authentication failed: invalid token");
+        (provided, None) => {
+            let reason = if provided.is_some() { "invalid token" } else { "missing token" };
+            warn!("Authentication failed from {}: {}", source_label(source), reason);
+            // Missing tokens count too, or one client could drain the shared
+            // budget for unauthenticated requests without ever being locked out
+            if let Some(lockout) = source.and_then(|ip| limiter.record_failure(ip)) {
+                warn!(
+                    "Locking out {} for {}s after repeated authentication failures",
+                    source_label(source),
+                    lockout.as_secs()
+                );
+            }
             Err(StatusCode::UNAUTHORIZED)
         }
-        None => {
-            warn!("Authentication failed: missing token");
-            Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+fn source_label(source: Option<IpAddr>) -> String {
+    source.map_or_else(|| "local socket".to_string(), |ip| ip.to_string())
+}
+
+/// 429 with a Retry-After header
+fn too_many_requests(retry_after: Duration) -> Response {
+    let secs = retry_after.as_secs().max(1);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, secs.to_string())],
+    )
+        .into_response()
+}
+
+/// Route middleware rejecting callers whose identity lacks `scope`
+///
+/// Requests without an identity come from listeners that don't require
+/// authentication and pass through.
+pub async fn require_scope(
+    axum::extract::State(scope): axum::extract::State<TokenScope>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    match request.extensions().get::<Identity>() {
+        Some(identity) if !identity.has_scope(scope) => {
+            warn!(
+                "Token '{}' denied {} {}: missing '{}' scope",
+                identity.name,
+                request.method(),
+                request.uri().path(),
+                scope
+            );
+            Err(StatusCode::FORBIDDEN)
         }
+        _ => Ok(next.run(request).await),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::AuthLimitsConfig;
+    use axum::body::Body;
+    use axum::routing::get;
     use tempfile::TempDir;
+    use tower::ServiceExt;
 
     #[test]
     fn test_obfuscate_token() {
@@ -178,14 +780,71 @@ mod tests {
         let token_path = temp_dir.path().join("test.token");
 
         // Generate and save token
-        let (token, was_new) = load_or_generate_token(&token_path).unwrap();
+        let (primary, token) = PrimaryToken::load_or_generate(&token_path).unwrap();
+        let token = token.expect("new token should be returned");
         assert!(!token.is_empty());
-        assert!(was_new);
+        assert!(primary.verify(&token));
+
+        // Only the hash is stored
+        let stored = fs::read_to_string(&token_path).unwrap();
+        assert!(stored.starts_with("$argon2id$"));
+        assert!(!stored.contains(&token));
 
         // Load token again
-        let (loaded_token, was_new2) = load_or_generate_token(&token_path).unwrap();
-        assert_eq!(token, loaded_token);
-        assert!(!was_new2);
+        let (loaded, token2) = PrimaryToken::load_or_generate(&token_path).unwrap();
+        assert!(token2.is_none());
+        assert!(loaded.verify(&token));
+        assert!(!loaded.verify("wrong-token"));
+    }
+
+    #[test]
+    fn test_plaintext_token_file_is_migrated() {
+        let temp_dir = TempDir::new().unwrap();
+        let token_path = temp_dir.path().join("test.token");
+        fs::write(&token_path, "legacy-token\n").unwrap();
+
+        let (primary, token) = PrimaryToken::load_or_generate(&token_path).unwrap();
+        assert_eq!(token.as_deref(), Some("legacy-token"));
+        assert!(primary.verify("legacy-token"));
+
+        let stored = fs::read_to_string(&token_path).unwrap();
+        assert!(!stored.contains("legacy-token"));
+        let (reloaded, _) = PrimaryToken::load_or_generate(&token_path).unwrap();
+        assert!(reloaded.verify("legacy-token"));
+    }
+
+    #[test]
+    fn test_token_rotation_grace_period() {
+        let temp_dir = TempDir::new().unwrap();
+        let token_path = temp_dir.path().join("test.token");
+        let (mut primary, old) = PrimaryToken::load_or_generate(&token_path).unwrap();
+        let old = old.unwrap();
+
+        // Old token keeps working during the grace period
+        let new = primary
+            .rotate(NewToken::new().unwrap(), Duration::from_secs(60))
+            .unwrap();
+        assert!(primary.verify(&new));
+        assert!(primary.verify(&old));
+
+        // Without a grace period it stops working immediately
+        let newer = primary.rotate(NewToken::new().unwrap(), Duration::ZERO).unwrap();
+        assert!(primary.verify(&newer));
+        assert!(!primary.verify(&new));
+        assert!(!primary.verify(&old));
+
+        // Grace periods beyond the maximum are refused before anything changes
+        let stored = fs::read_to_string(&token_path).unwrap();
+        for grace in [MAX_ROTATION_GRACE_SECS + 1, u64::MAX] {
+            let next = NewToken::new().unwrap();
+            assert!(primary.rotate(next, Duration::from_secs(grace)).is_err());
+        }
+        assert!(primary.verify(&newer));
+        assert_eq!(fs::read_to_string(&token_path).unwrap(), stored);
+
+        // The new token survives a restart
+        let (reloaded, _) = PrimaryToken::load_or_generate(&token_path).unwrap();
+        assert!(reloaded.verify(&newer));
     }
 
     #[test]
@@ -193,7 +852,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let token_path = temp_dir.path().join("test.token");
 
-        let (_token, _was_new) = load_or_generate_token(&token_path).unwrap();
+        let (_primary, _token) = PrimaryToken::load_or_generate(&token_path).unwrap();
 
         #[cfg(unix)]
         {
@@ -203,4 +862,118 @@ mod tests {
             assert_eq!(permissions.mode() & 0o777, 0o600);
         }
     }
+
+    #[test]
+    fn test_token_registry_lifecycle() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("tokens.toml");
+        let mut registry = TokenRegistry::load(&path).unwrap();
+        assert!(registry.list().is_empty());
+
+        let (secret, info) = registry
+            .create(
+                CreateTokenRequest {
+                    name: "dashboard".to_string(),
+                    scopes: vec![TokenScope::Read],
+                    profile_ids: vec![],
+                    profile_tags: vec!["prod".to_string()],
+                },
+                NewToken::new().unwrap(),
+            )
+            .unwrap();
+        assert_eq!(info.name, "dashboard");
+
+        // Duplicate, reserved and scope-less tokens are rejected
+        for (name, scopes) in [
+            ("dashboard", vec![TokenScope::Read]),
+            (PRIMARY_TOKEN_NAME, vec![TokenScope::Read]),
+            ("empty", vec![]),
+        ] {
+            assert!(registry
+                .create(
+                    CreateTokenRequest {
+                        name: name.to_string(),
+                        scopes,
+                        profile_ids: vec![],
+                        profile_tags: vec![],
+                    },
+                    NewToken::new().unwrap(),
+                )
+                .is_err());
+        }
+
+        // Persisted as a hash and reloaded
+        assert!(!fs::read_to_string(&path).unwrap().contains(&secret));
+        let reloaded = TokenRegistry::load(&path).unwrap();
+        let identity = reloaded.identify(&secret, HashCheck::Full).unwrap();
+        assert_eq!(identity.name, "dashboard");
+        assert!(reloaded.identify("wrong", HashCheck::Full).is_none());
+
+        registry.revoke("dashboard").unwrap();
+        assert!(registry.revoke("dashboard").is_err());
+        assert!(TokenRegistry::load(&path).unwrap().identify(&secret, HashCheck::Full).is_none());
+    }
+
+    #[test]
+    fn test_identity_scopes_and_profiles() {
+        let admin = Identity::admin(PRIMARY_TOKEN_NAME);
+        assert!(admin.has_scope(TokenScope::Control));
+        assert!(admin.allows_profile(&Uuid::new_v4(), &[]));
+
+        let allowed = Uuid::new_v4();
+        let reader = Identity {
+            name: "reader".to_string(),
+            scopes: vec![TokenScope::Read],
+            profile_ids: vec![allowed],
+            profile_tags: vec!["prod".to_string()],
+        };
+        assert!(reader.has_scope(TokenScope::Read));
+        assert!(!reader.has_scope(TokenScope::Control));
+        assert!(!reader.has_scope(TokenScope::Admin));
+        assert!(reader.allows_profile(&allowed, &[]));
+        assert!(reader.allows_profile(&Uuid::new_v4(), &["prod".to_string()]));
+        assert!(!reader.allows_profile(&Uuid::new_v4(), &["dev".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_missing_token_cannot_starve_other_sources() {
+        let temp_dir = TempDir::new().unwrap();
+        let (primary, token) = PrimaryToken::load_or_generate(&temp_dir.path().join("t")).unwrap();
+        let registry = TokenRegistry::load(&temp_dir.path().join("tokens.toml")).unwrap();
+        let limiter = Arc::new(AuthLimiter::new(AuthLimitsConfig {
+            max_failures: 3,
+            unauthenticated_per_minute: 5,
+            ..AuthLimitsConfig::default()
+        }));
+        let state = AuthState::new(
+            Arc::new(RwLock::new(primary)),
+            Arc::new(RwLock::new(registry)),
+            limiter,
+        );
+        let app = axum::Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(state, auth_middleware));
+
+        let send = |source: &str, token: Option<&str>| {
+            let mut request = axum::http::Request::builder().uri("/");
+            if let Some(token) = token {
+                request = request.header(AUTH_TOKEN_HEADER, token);
+            }
+            let mut request = request.body(Body::empty()).unwrap();
+            let addr: SocketAddr = format!("{}:40000", source).parse().unwrap();
+            request.extensions_mut().insert(ConnectInfo(addr));
+            app.clone().oneshot(request)
+        };
+
+        // Locked out after three attempts, without using up the budget
+        let mut statuses = Vec::new();
+        for _ in 0..10 {
+            statuses.push(send("192.0.2.1", None).await.unwrap().status());
+        }
+        assert_eq!(statuses[..3], [StatusCode::UNAUTHORIZED; 3]);
+        assert!(statuses[3..].iter().all(|s| *s == StatusCode::TOO_MANY_REQUESTS));
+
+        let response = send("192.0.2.2", token.as_deref()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
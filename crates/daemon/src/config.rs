@@ -7,11 +7,15 @@
 
 use std::ffi::OsStr;
 use std::fs;
-use std::path::PathBuf;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use ssh_tunnel_common::{LogLevel, TokenScope};
+use tracing::{info, warn};
+use uuid::Uuid;
 
 /// Get the runtime directory for daemon state
 pub fn runtime_dir() -> Result<PathBuf> {
@@ -47,6 +51,67 @@ impl Default for ListenerMode {
     }
 }
 
+impl ListenerMode {
+    /// Wire name of the mode, as used in daemon.toml and DaemonInfo
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListenerMode::UnixSocket => "unix-socket",
+            ListenerMode::TcpHttp => "tcp-http",
+            ListenerMode::TcpHttps => "tcp-https",
+        }
+    }
+}
+
+/// A single API listener
+///
+/// Several listeners can be configured in `[[listeners]]` tables, e.g. a Unix
+/// socket for the local CLI plus TcpHttps for a remote GUI. All of them serve
+/// the same API and share the daemon state.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct ListenerConfig {
+    /// Listener mode (UnixSocket, TcpHttp, or TcpHttps)
+    pub mode: ListenerMode,
+
+    /// Bind host for TCP modes (ignored for UnixSocket)
+    #[serde(default = "default_bind_host")]
+    pub bind_host: String,
+
+    /// Bind port for TCP modes (ignored for UnixSocket)
+    #[serde(default = "default_bind_port")]
+    pub bind_port: u16,
+
+    /// Per-listener authentication requirement
+    /// Falls back to the global `require_auth` when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_auth: Option<bool>,
+}
+
+impl ListenerConfig {
+    /// Whether requests on this listener must carry a valid token
+    pub fn requires_auth(&self, global_require_auth: bool) -> bool {
+        self.require_auth.unwrap_or(global_require_auth)
+    }
+
+    /// Human-readable address ("host:port" or the socket path)
+    pub fn display_address(&self) -> String {
+        match self.mode {
+            ListenerMode::UnixSocket => socket_path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| "auto-detect".to_string()),
+            ListenerMode::TcpHttp | ListenerMode::TcpHttps => {
+                ssh_tunnel_common::format_host_port(&self.bind_host, self.bind_port)
+            }
+        }
+    }
+}
+
+/// Inclusive range of local ports for tunnels that ask for "any free port"
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
 /// Daemon configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DaemonConfig {
@@ -74,6 +139,11 @@ pub struct DaemonConfig {
     #[serde(default = "default_auth_token_path")]
     pub auth_token_path: PathBuf,
 
+    /// Path to the registry of additional API tokens (scoped, per-profile)
+    /// Managed with `ssh-tunnel token create|list|revoke`
+    #[serde(default = "default_tokens_path")]
+    pub tokens_path: PathBuf,
+
     /// Require authentication (recommended for TCP modes, optional for UnixSocket)
     #[serde(default = "default_require_auth")]
     pub require_auth: bool,
@@ -90,6 +160,503 @@ pub struct DaemonConfig {
     /// Default: false (restrictive permissions for single-user security)
     #[serde(default = "default_group_access")]
     pub group_access: bool,
+
+    /// Port range for local forwards with `local_port = 0` (or unset)
+    /// When not set, the OS assigns any free port
+    /// Example: ephemeral_port_range = { start = 20000, end = 20999 }
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ephemeral_port_range: Option<PortRange>,
+
+    /// Listeners to serve concurrently
+    /// When empty, a single listener is built from `listener_mode`,
+    /// `bind_host` and `bind_port` (the pre-multi-listener configuration)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub listeners: Vec<ListenerConfig>,
+
+    /// Mutual TLS (client certificate) authentication for TcpHttps listeners
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_auth: Option<ClientAuthConfig>,
+
+    /// Peer credential (SO_PEERCRED) rules for the Unix socket listener
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_access: Option<PeerAccessConfig>,
+
+    /// Brute-force protection for token authentication
+    #[serde(default)]
+    pub auth_limits: AuthLimitsConfig,
+
+    /// Client networks allowed to connect to TcpHttps listeners, in CIDR
+    /// notation (e.g. "192.168.1.0/24", "2001:db8::/32")
+    /// Checked before the TLS handshake; when empty, any address may connect
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_networks: Vec<String>,
+
+    /// Audit log of API actions and tunnel state changes
+    #[serde(default)]
+    pub audit: AuditConfig,
+
+    /// Interactive auth prompts (passwords, 2FA codes, host key confirmations)
+    #[serde(default)]
+    pub auth_prompts: AuthPromptsConfig,
+
+    /// Stored passwords and passphrases read from secret commands
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+
+    /// Decrypted SSH keys shared by all tunnels (`ssh-tunnel keys`)
+    #[serde(default)]
+    pub key_cache: KeyCacheConfig,
+
+    /// Seconds between traffic `stats` events for each connected tunnel
+    /// 0 disables them. Default: 30
+    #[serde(default = "default_stats_interval_secs")]
+    pub stats_interval_secs: u64,
+
+    /// Prometheus metrics endpoint
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+
+    /// Per-profile history of tunnel sessions
+    #[serde(default)]
+    pub history: HistoryConfig,
+
+    /// In-memory log lines kept for each tunnel
+    #[serde(default)]
+    pub tunnel_logs: TunnelLogsConfig,
+
+    /// Format and destination of the daemon's own log
+    #[serde(default)]
+    pub logging: LoggingConfig,
+}
+
+/// What an auth prompt does while no event stream client is connected
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NoClientPolicy {
+    /// Wait for an answer as usual; a client may still poll for the prompt
+    #[default]
+    Wait,
+    /// Fail the prompt at once
+    Fail,
+    /// Hold the prompt until a client connects; the timeout starts then
+    Defer,
+}
+
+/// Auth prompts sent to clients while a tunnel authenticates
+///
+/// A profile's `auth_timeout_secs` overrides `timeout_secs`.
+///
+/// ```toml
+/// [auth_prompts]
+/// timeout_secs = 120
+/// no_client = "fail"
+/// askpass = "/usr/lib/ssh/x11-ssh-askpass"
+/// askpass_grace_secs = 5
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AuthPromptsConfig {
+    /// Seconds to wait for an answer
+    pub timeout_secs: u64,
+    pub no_client: NoClientPolicy,
+    /// Command answering prompts that no client answers, like SSH_ASKPASS
+    /// (the prompt is passed as its first argument, the answer read from stdout)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub askpass: Option<String>,
+    /// Seconds clients get to answer before `askpass` runs; it runs at once
+    /// when no client is connected
+    pub askpass_grace_secs: u64,
+}
+
+impl Default for AuthPromptsConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 60,
+            no_client: NoClientPolicy::Wait,
+            askpass: None,
+            askpass_grace_secs: 5,
+        }
+    }
+}
+
+/// Stored secrets: secret commands (`password_storage = { command = "..." }`)
+/// and the encrypted vault file (`password_storage = "file"`)
+///
+/// ```toml
+/// [secrets]
+/// command_timeout_secs = 10
+/// cache_ttl_secs = 300
+/// vault_path = "/var/lib/tunneld/.config/ssh-tunnel-manager/vault.toml"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// Seconds a secret command may run before it is killed
+    pub command_timeout_secs: u64,
+    /// Seconds a command's secret is reused for later connects (0: never)
+    pub cache_ttl_secs: u64,
+    /// Vault file (default: `vault.toml` in the config directory)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_path: Option<PathBuf>,
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            command_timeout_secs: 10,
+            cache_ttl_secs: 300,
+            vault_path: None,
+        }
+    }
+}
+
+/// Decrypted SSH keys kept in memory, so a passphrase-protected key is
+/// unlocked once for all tunnels and reconnects
+///
+/// Off by default: while enabled, anyone holding an `auth`-scoped token can
+/// unlock keys in the daemon user's `~/.ssh` for tunnels to use.
+///
+/// ```toml
+/// [key_cache]
+/// enabled = true
+/// ttl_secs = 3600   # 0: until `ssh-tunnel keys lock`
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct KeyCacheConfig {
+    pub enabled: bool,
+    /// Seconds a key stays unlocked
+    pub ttl_secs: u64,
+}
+
+impl Default for KeyCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 3600,
+        }
+    }
+}
+
+/// Prometheus metrics
+///
+/// Served at `/api/v1/metrics` on the API listeners (`read` scope). With
+/// `bind_port` set, a separate loopback listener serves `/metrics` without
+/// token authentication.
+///
+/// ```toml
+/// [metrics]
+/// enabled = true
+/// bind_host = "127.0.0.1"
+/// bind_port = 9187
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    /// Must be a loopback address
+    pub bind_host: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind_port: Option<u16>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bind_host: "127.0.0.1".to_string(),
+            bind_port: None,
+        }
+    }
+}
+
+impl MetricsConfig {
+    /// Address of the unauthenticated metrics listener, if one is configured
+    pub fn listen_address(&self) -> Option<String> {
+        match self.bind_port {
+            Some(port) if self.enabled => {
+                Some(ssh_tunnel_common::format_host_port(&self.bind_host, port))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Append-only JSON Lines audit log
+///
+/// ```toml
+/// [audit]
+/// path = "/var/log/ssh-tunnel-manager/audit.jsonl"
+/// max_file_bytes = 10485760
+/// max_files = 5
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AuditConfig {
+    pub enabled: bool,
+    /// Default: ~/.local/share/ssh-tunnel-manager/audit.jsonl
+    pub path: PathBuf,
+    /// Rotate once the current file would grow beyond this size
+    pub max_file_bytes: u64,
+    /// Rotated files to keep (`audit.jsonl.1` is the newest)
+    pub max_files: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: dirs::data_local_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("ssh-tunnel-manager")
+                .join("audit.jsonl"),
+            max_file_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+/// Session history: one JSON Lines file per profile
+///
+/// ```toml
+/// [history]
+/// path = "/var/lib/ssh-tunnel-manager/history"
+/// max_sessions = 500
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub enabled: bool,
+    /// Directory of `<profile-id>.jsonl` files.
+    /// Default: ~/.local/share/ssh-tunnel-manager/history
+    pub path: PathBuf,
+    /// Sessions kept per profile; older ones are dropped
+    pub max_sessions: usize,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: dirs::data_local_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("ssh-tunnel-manager")
+                .join("history"),
+            max_sessions: 500,
+        }
+    }
+}
+
+/// Format of log lines written to stderr or a file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Where the daemon's log goes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogOutput {
+    #[default]
+    Stderr,
+    /// `path`, rotated by size and age
+    File,
+    /// The systemd journal, with structured fields (`format` is ignored)
+    Journald,
+}
+
+/// The daemon's own log
+///
+/// `RUST_LOG`, when set, overrides `filter`.
+///
+/// ```toml
+/// [logging]
+/// format = "json"
+/// output = "file"
+/// filter = "info,ssh_tunnel_daemon=debug"
+/// path = "/var/log/ssh-tunnel-manager/daemon.log"
+/// max_file_bytes = 10485760
+/// max_file_age_hours = 24
+/// max_files = 5
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub format: LogFormat,
+    pub output: LogOutput,
+    /// `RUST_LOG` syntax
+    pub filter: String,
+    /// Log file for `output = "file"`.
+    /// Default: ~/.local/state/ssh-tunnel-manager/daemon.log
+    pub path: PathBuf,
+    /// Rotate once the current file would grow beyond this size
+    pub max_file_bytes: u64,
+    /// Rotate once the current file is this old; 0 rotates by size only
+    pub max_file_age_hours: u64,
+    /// Rotated files to keep (`daemon.log.1` is the newest)
+    pub max_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            output: LogOutput::Stderr,
+            filter: "ssh_tunnel_daemon=debug,tower_http=debug".to_string(),
+            path: dirs::state_dir()
+                .or_else(dirs::data_local_dir)
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("ssh-tunnel-manager")
+                .join("daemon.log"),
+            max_file_bytes: 10 * 1024 * 1024,
+            max_file_age_hours: 24,
+            max_files: 5,
+        }
+    }
+}
+
+/// Per-tunnel diagnostic logs (`ssh-tunnel logs`)
+///
+/// Independent of `RUST_LOG`: each tunnel's lines are captured at `level`,
+/// or at a level raised for a while through the API.
+///
+/// ```toml
+/// [tunnel_logs]
+/// lines = 1000
+/// level = "info"
+/// max_raise_secs = 3600
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TunnelLogsConfig {
+    /// Lines kept per tunnel; older ones are dropped
+    pub lines: usize,
+    pub level: LogLevel,
+    /// Longest a raised level may last
+    pub max_raise_secs: u64,
+}
+
+impl Default for TunnelLogsConfig {
+    fn default() -> Self {
+        Self {
+            lines: 1000,
+            level: LogLevel::Info,
+            max_raise_secs: 3600,
+        }
+    }
+}
+
+/// Limits on failed token authentication
+///
+/// After `max_failures` consecutive failures from one address, that address
+/// is locked out for `lockout_secs`, doubling with every further lockout up
+/// to `max_lockout_secs`. Independently, requests without valid credentials
+/// are limited to `unauthenticated_per_minute` across all clients.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AuthLimitsConfig {
+    pub max_failures: u32,
+    pub lockout_secs: u64,
+    pub max_lockout_secs: u64,
+    pub unauthenticated_per_minute: u32,
+}
+
+impl Default for AuthLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_failures: 5,
+            lockout_secs: 30,
+            max_lockout_secs: 3600,
+            unauthenticated_per_minute: 60,
+        }
+    }
+}
+
+/// Which local users may use the Unix socket
+///
+/// ```toml
+/// [peer_access]
+/// allowed_gids = [985]
+/// token_exempt_uids = [1000]
+/// ```
+///
+/// The daemon's own UID is always allowed. When both allowlists are empty,
+/// anyone who can open the socket is allowed (socket permissions decide).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PeerAccessConfig {
+    /// UIDs allowed to connect
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_uids: Vec<u32>,
+
+    /// GIDs allowed to connect (primary or supplementary group of the peer)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_gids: Vec<u32>,
+
+    /// UIDs whose requests are accepted without a token (full access)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_exempt_uids: Vec<u32>,
+}
+
+/// Client certificate authentication for HTTPS listeners
+///
+/// ```toml
+/// [client_auth]
+/// ca_path = "/etc/ssh-tunnel-manager/client-ca.pem"
+/// required = true
+///
+/// [[client_auth.identity]]
+/// name = "ops-laptop"
+/// common_name = "ops-laptop.example.com"
+/// scopes = ["control", "auth", "read"]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClientAuthConfig {
+    /// PEM bundle of the CAs that issue client certificates
+    pub ca_path: PathBuf,
+
+    /// Reject TLS handshakes without a valid client certificate
+    /// When false, clients without a certificate fall back to token authentication
+    #[serde(default = "default_client_auth_required")]
+    pub required: bool,
+
+    /// Map certificate subjects to identities
+    /// Certificates matching no mapping get no identity and need a token
+    #[serde(default, rename = "identity", skip_serializing_if = "Vec::is_empty")]
+    pub identities: Vec<ClientIdentityMapping>,
+}
+
+/// Identity granted to client certificates matching a subject CN or SAN
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClientIdentityMapping {
+    /// Identity name used for authorization and logs
+    pub name: String,
+
+    /// Match the certificate's subject common name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub common_name: Option<String>,
+
+    /// Match a DNS, email or URI subject alternative name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub san: Option<String>,
+
+    /// Scopes granted (same as API tokens)
+    pub scopes: Vec<TokenScope>,
+
+    /// Restrict to these profile IDs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profile_ids: Vec<Uuid>,
+
+    /// Restrict to profiles with these tags
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profile_tags: Vec<String>,
+}
+
+fn default_client_auth_required() -> bool {
+    true
 }
 
 fn default_bind_host() -> String {
@@ -121,6 +688,13 @@ fn default_auth_token_path() -> PathBuf {
         .join("daemon.token")
 }
 
+fn default_tokens_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("ssh-tunnel-manager")
+        .join("tokens.toml")
+}
+
 fn default_require_auth() -> bool {
     true // Default to true for security
 }
@@ -136,6 +710,10 @@ fn default_group_access() -> bool {
     false // Default to restrictive single-user permissions
 }
 
+fn default_stats_interval_secs() -> u64 {
+    30
+}
+
 impl Default for DaemonConfig {
     fn default() -> Self {
         Self {
@@ -145,23 +723,133 @@ impl Default for DaemonConfig {
             tls_cert_path: default_tls_cert_path(),
             tls_key_path: default_tls_key_path(),
             auth_token_path: default_auth_token_path(),
+            tokens_path: default_tokens_path(),
             require_auth: default_require_auth(),
             known_hosts_path: default_known_hosts_path(),
             group_access: default_group_access(),
+            ephemeral_port_range: None,
+            listeners: Vec::new(),
+            client_auth: None,
+            peer_access: None,
+            auth_limits: AuthLimitsConfig::default(),
+            allowed_networks: Vec::new(),
+            audit: AuditConfig::default(),
+            auth_prompts: AuthPromptsConfig::default(),
+            secrets: SecretsConfig::default(),
+            key_cache: KeyCacheConfig::default(),
+            stats_interval_secs: default_stats_interval_secs(),
+            metrics: MetricsConfig::default(),
+            history: HistoryConfig::default(),
+            tunnel_logs: TunnelLogsConfig::default(),
+            logging: LoggingConfig::default(),
+        }
+    }
+}
+
+/// Host part of a TCP bind address, as far as overlaps are concerned
+#[derive(Debug, PartialEq)]
+enum BindHost {
+    /// `0.0.0.0` or `::`: every address
+    Any,
+    Addrs(Vec<IpAddr>),
+    /// Resolved at bind time; only equal names are known to clash
+    Name(String),
+}
+
+impl BindHost {
+    fn parse(host: &str) -> Self {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        match host.parse::<IpAddr>() {
+            Ok(ip) if ip.is_unspecified() => BindHost::Any,
+            Ok(ip) => BindHost::Addrs(vec![ip.to_canonical()]),
+            Err(_) if host.eq_ignore_ascii_case("localhost") => BindHost::Addrs(vec![
+                IpAddr::from([127, 0, 0, 1]),
+                IpAddr::from(std::net::Ipv6Addr::LOCALHOST),
+            ]),
+            Err(_) => BindHost::Name(host.to_ascii_lowercase()),
         }
     }
 }
 
+/// Whether two TCP listeners would compete for the same address
+///
+/// A wildcard host takes the port on every address, so it clashes with
+/// any other host on that port.
+fn binds_overlap(a: (&str, u16), b: (&str, u16)) -> bool {
+    if a.1 != b.1 {
+        return false;
+    }
+    match (BindHost::parse(a.0), BindHost::parse(b.0)) {
+        (BindHost::Any, _) | (_, BindHost::Any) => true,
+        (BindHost::Addrs(a), BindHost::Addrs(b)) => a.iter().any(|ip| b.contains(ip)),
+        (BindHost::Name(a), BindHost::Name(b)) => a == b,
+        _ => false,
+    }
+}
+
 impl DaemonConfig {
+    /// Listeners the daemon should serve
+    ///
+    /// Returns the configured `listeners` list, or a single listener derived
+    /// from the legacy top-level `listener_mode`/`bind_host`/`bind_port` fields.
+    pub fn effective_listeners(&self) -> Vec<ListenerConfig> {
+        if !self.listeners.is_empty() {
+            return self.listeners.clone();
+        }
+
+        vec![ListenerConfig {
+            mode: self.listener_mode.clone(),
+            bind_host: self.bind_host.clone(),
+            bind_port: self.bind_port,
+            require_auth: None,
+        }]
+    }
+
+    /// Whether any listener requires token authentication
+    pub fn any_listener_requires_auth(&self) -> bool {
+        self.effective_listeners()
+            .iter()
+            .any(|l| l.requires_auth(self.require_auth))
+    }
+
+    /// Whether any listener uses TLS
+    pub fn has_tls_listener(&self) -> bool {
+        self.effective_listeners()
+            .iter()
+            .any(|l| l.mode == ListenerMode::TcpHttps)
+    }
+
+    /// Parsed `allowed_networks`
+    pub fn allowed_networks(&self) -> Result<Vec<IpNet>> {
+        self.allowed_networks
+            .iter()
+            .map(|network| {
+                // A bare address means just that host
+                network
+                    .parse::<IpNet>()
+                    .or_else(|_| network.parse::<IpAddr>().map(IpNet::from))
+                    .with_context(|| format!("Invalid network in allowed_networks: '{}'", network))
+            })
+            .collect()
+    }
+
     /// Validate the daemon configuration
     pub fn validate(&self) -> Result<()> {
-        // For TCP modes, check if bind host is non-loopback
-        if matches!(self.listener_mode, ListenerMode::TcpHttp | ListenerMode::TcpHttps) {
-            // Parse the bind host to check if it's loopback
-            let is_loopback = ssh_tunnel_common::is_loopback_address(&self.bind_host);
+        let listeners = self.effective_listeners();
+        let mut seen_addresses: Vec<(&str, u16)> = Vec::new();
+        let mut unix_sockets = 0;
+
+        for listener in &listeners {
+            if listener.mode == ListenerMode::UnixSocket {
+                unix_sockets += 1;
+                continue;
+            }
+
+            // For TCP modes, check if bind host is non-loopback
+            let is_loopback = ssh_tunnel_common::is_loopback_address(&listener.bind_host);
 
             // If non-loopback and not HTTPS, reject
-            if !is_loopback && self.listener_mode == ListenerMode::TcpHttp {
+            if !is_loopback && listener.mode == ListenerMode::TcpHttp {
                 anyhow::bail!(
                     "Security violation: Non-loopback TCP connections (bind_host: {}) require HTTPS mode.\n\
                      Current mode: TcpHttp\n\
@@ -171,9 +859,97 @@ impl DaemonConfig {
                      2. Use a loopback address (127.0.0.1 or localhost) for bind_host\n\
                      \n\
                      HTTP mode is only allowed for localhost connections due to lack of encryption.",
-                    self.bind_host
+                    listener.bind_host
                 );
             }
+
+            let address = (listener.bind_host.as_str(), listener.bind_port);
+            if seen_addresses.iter().any(|seen| binds_overlap(*seen, address)) {
+                anyhow::bail!(
+                    "Duplicate listener address: {}",
+                    listener.display_address()
+                );
+            }
+            seen_addresses.push(address);
+        }
+
+        if unix_sockets > 1 {
+            anyhow::bail!("Only one unix-socket listener can be configured");
+        }
+
+        if let Some(range) = self.ephemeral_port_range {
+            if range.start == 0 || range.start > range.end {
+                anyhow::bail!(
+                    "Invalid ephemeral_port_range {}-{}: start must be > 0 and <= end",
+                    range.start,
+                    range.end
+                );
+            }
+        }
+
+        if let Some(client_auth) = &self.client_auth {
+            if !self.has_tls_listener() {
+                anyhow::bail!("client_auth requires a tcp-https listener");
+            }
+            if client_auth.identities.is_empty() {
+                warn!(
+                    "client_auth has no [[client_auth.identity]] mappings: client certificates \
+                     only gate the TLS handshake and every request still needs a token"
+                );
+            }
+            for mapping in &client_auth.identities {
+                if mapping.common_name.is_none() && mapping.san.is_none() {
+                    anyhow::bail!(
+                        "client_auth identity '{}' needs common_name or san",
+                        mapping.name
+                    );
+                }
+                if mapping.scopes.is_empty() {
+                    anyhow::bail!("client_auth identity '{}' needs at least one scope", mapping.name);
+                }
+            }
+        }
+
+        if self.peer_access.is_some() && unix_sockets == 0 {
+            anyhow::bail!("peer_access requires a unix-socket listener");
+        }
+
+        self.allowed_networks()?;
+        let limits = &self.auth_limits;
+        if limits.max_failures == 0 || limits.lockout_secs == 0 {
+            anyhow::bail!("auth_limits: max_failures and lockout_secs must be > 0");
+        }
+        if limits.max_lockout_secs < limits.lockout_secs {
+            anyhow::bail!("auth_limits: max_lockout_secs must be >= lockout_secs");
+        }
+        if limits.unauthenticated_per_minute == 0 {
+            anyhow::bail!("auth_limits: unauthenticated_per_minute must be > 0");
+        }
+
+        if let Some(port) = self.metrics.bind_port.filter(|_| self.metrics.enabled) {
+            // No token on this listener, so it must not be reachable from the network
+            if !ssh_tunnel_common::is_loopback_address(&self.metrics.bind_host) {
+                anyhow::bail!(
+                    "metrics: bind_host must be a loopback address (got {})",
+                    self.metrics.bind_host
+                );
+            }
+            let address = (self.metrics.bind_host.as_str(), port);
+            if seen_addresses.iter().any(|seen| binds_overlap(*seen, address)) {
+                anyhow::bail!("metrics: bind_port {} is already used by an API listener", port);
+            }
+        }
+
+        if self.audit.enabled && self.audit.max_file_bytes == 0 {
+            anyhow::bail!("audit: max_file_bytes must be > 0");
+        }
+
+        if self.auth_prompts.timeout_secs == 0 {
+            anyhow::bail!("auth_prompts: timeout_secs must be > 0");
+        }
+
+        if self.secrets.command_timeout_secs == 0 {
+            anyhow::bail!("secrets: command_timeout_secs must be > 0");
         }
 
         Ok(())
@@ -241,6 +1017,22 @@ impl DaemonConfig {
 mod tests {
     use super::*;
 
+    fn listener(mode: ListenerMode, bind_host: &str, bind_port: u16) -> ListenerConfig {
+        ListenerConfig {
+            mode,
+            bind_host: bind_host.to_string(),
+            bind_port,
+            require_auth: None,
+        }
+    }
+
+    fn single_listener_config(mode: ListenerMode, bind_host: &str, bind_port: u16) -> DaemonConfig {
+        DaemonConfig {
+            listeners: vec![listener(mode, bind_host, bind_port)],
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_validate_unix_socket_mode() {
         let config = DaemonConfig {
@@ -370,6 +1162,317 @@ mod tests {
         assert_eq!(config.require_auth, true);
     }
 
+    #[test]
+    fn test_effective_listeners_legacy_fallback() {
+        let config = DaemonConfig {
+            listener_mode: ListenerMode::TcpHttp,
+            bind_host: "127.0.0.1".to_string(),
+            bind_port: 4000,
+            ..Default::default()
+        };
+        let listeners = config.effective_listeners();
+        assert_eq!(listeners, vec![listener(ListenerMode::TcpHttp, "127.0.0.1", 4000)]);
+    }
+
+    #[test]
+    fn test_validate_multiple_listeners() {
+        let config = DaemonConfig {
+            listeners: vec![
+                listener(ListenerMode::UnixSocket, "127.0.0.1", 3443),
+                listener(ListenerMode::TcpHttps, "0.0.0.0", 3443),
+            ],
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        // Any non-loopback tcp-http listener is rejected
+        let config = DaemonConfig {
+            listeners: vec![
+                listener(ListenerMode::TcpHttps, "0.0.0.0", 3443),
+                listener(ListenerMode::TcpHttp, "0.0.0.0", 3444),
+            ],
+            ..Default::default()
+        };
+        assert!(config.validate().unwrap_err().to_string().contains("require HTTPS"));
+
+        // Two listeners on the same address
+        let config = DaemonConfig {
+            listeners: vec![
+                listener(ListenerMode::TcpHttp, "127.0.0.1", 3443),
+                listener(ListenerMode::TcpHttps, "127.0.0.1", 3443),
+            ],
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        // A wildcard host takes the port on every address
+        let config = DaemonConfig {
+            listeners: vec![
+                listener(ListenerMode::TcpHttps, "0.0.0.0", 3443),
+                listener(ListenerMode::TcpHttp, "127.0.0.1", 3443),
+            ],
+            ..Default::default()
+        };
+        assert!(config.validate().unwrap_err().to_string().contains("Duplicate"));
+
+        // Same address, spelled differently
+        let config = DaemonConfig {
+            listeners: vec![
+                listener(ListenerMode::TcpHttp, "localhost", 3443),
+                listener(ListenerMode::TcpHttps, "[::1]", 3443),
+            ],
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        // Different addresses or ports don't clash
+        let config = DaemonConfig {
+            listeners: vec![
+                listener(ListenerMode::TcpHttp, "127.0.0.1", 3443),
+                listener(ListenerMode::TcpHttps, "192.168.1.10", 3443),
+                listener(ListenerMode::TcpHttps, "0.0.0.0", 3444),
+            ],
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        // Only one Unix socket
+        let config = DaemonConfig {
+            listeners: vec![
+                listener(ListenerMode::UnixSocket, "127.0.0.1", 3443),
+                listener(ListenerMode::UnixSocket, "127.0.0.1", 3444),
+            ],
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_ephemeral_port_range() {
+        let mut config = DaemonConfig {
+            ephemeral_port_range: Some(PortRange { start: 20000, end: 20010 }),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.ephemeral_port_range = Some(PortRange { start: 20010, end: 20000 });
+        assert!(config.validate().is_err());
+
+        config.ephemeral_port_range = Some(PortRange { start: 0, end: 100 });
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_metrics_listener() {
+        let mut config = single_listener_config(ListenerMode::TcpHttps, "0.0.0.0", 3443);
+        config.metrics.bind_port = Some(9187);
+        assert!(config.validate().is_ok());
+        assert_eq!(config.metrics.listen_address().as_deref(), Some("127.0.0.1:9187"));
+
+        // No token on the metrics listener, so only loopback
+        config.metrics.bind_host = "0.0.0.0".to_string();
+        assert!(config.validate().is_err());
+
+        // Disabled metrics don't open a listener at all
+        config.metrics.enabled = false;
+        assert!(config.validate().is_ok());
+        assert_eq!(config.metrics.listen_address(), None);
+
+        let mut config = single_listener_config(ListenerMode::TcpHttp, "127.0.0.1", 3443);
+        config.metrics.bind_port = Some(3443);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_listener_require_auth_override() {
+        let mut unix = listener(ListenerMode::UnixSocket, "127.0.0.1", 3443);
+        assert!(unix.requires_auth(true));
+        unix.require_auth = Some(false);
+        assert!(!unix.requires_auth(true));
+    }
+
+    #[test]
+    fn test_listeners_toml_roundtrip() {
+        let toml_str = r#"
+            require_auth = true
+            ephemeral_port_range = { start = 20000, end = 20010 }
+
+            [[listeners]]
+            mode = "unix-socket"
+            require_auth = false
+
+            [[listeners]]
+            mode = "tcp-https"
+            bind_host = "0.0.0.0"
+            bind_port = 3443
+        "#;
+        let config: DaemonConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.listeners.len(), 2);
+        assert_eq!(config.listeners[0].require_auth, Some(false));
+        assert_eq!(config.listeners[1].mode, ListenerMode::TcpHttps);
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        let reparsed: DaemonConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.listeners, config.listeners);
+        assert_eq!(reparsed.ephemeral_port_range, Some(PortRange { start: 20000, end: 20010 }));
+    }
+
+    #[test]
+    fn test_preferred_local_listener() {
+        let listeners = vec![
+            listener(ListenerMode::TcpHttps, "0.0.0.0", 3443),
+            listener(ListenerMode::TcpHttp, "127.0.0.1", 3444),
+            listener(ListenerMode::UnixSocket, "127.0.0.1", 3443),
+        ];
+        assert_eq!(
+            preferred_local_listener(&listeners).unwrap().mode,
+            ListenerMode::UnixSocket
+        );
+        assert_eq!(
+            preferred_local_listener(&listeners[..2]).unwrap().mode,
+            ListenerMode::TcpHttp
+        );
+
+        let https_only = vec![
+            listener(ListenerMode::TcpHttps, "0.0.0.0", 3443),
+            listener(ListenerMode::TcpHttps, "127.0.0.1", 3444),
+        ];
+        assert_eq!(preferred_local_listener(&https_only).unwrap().bind_port, 3444);
+        assert!(preferred_local_listener(&[]).is_none());
+    }
+
+    #[test]
+    fn test_client_auth_config() {
+        let toml_str = r#"
+            listener_mode = "tcp-https"
+            bind_host = "0.0.0.0"
+
+            [client_auth]
+            ca_path = "/etc/ssh-tunnel-manager/client-ca.pem"
+
+            [[client_auth.identity]]
+            name = "ops"
+            common_name = "ops.example.com"
+            scopes = ["read", "control"]
+        "#;
+        let mut config: DaemonConfig = toml::from_str(toml_str).unwrap();
+        let client_auth = config.client_auth.clone().unwrap();
+        assert!(client_auth.required);
+        assert_eq!(client_auth.identities[0].scopes, vec![TokenScope::Read, TokenScope::Control]);
+        assert!(config.validate().is_ok());
+
+        // A mapping must match on something
+        config.client_auth.as_mut().unwrap().identities[0].common_name = None;
+        assert!(config.validate().is_err());
+
+        // Client certificates need an HTTPS listener
+        let config = DaemonConfig {
+            client_auth: Some(client_auth),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_peer_access_config() {
+        let toml_str = r#"
+            group_access = true
+
+            [peer_access]
+            allowed_gids = [985]
+            token_exempt_uids = [1000]
+        "#;
+        let config: DaemonConfig = toml::from_str(toml_str).unwrap();
+        let peer_access = config.peer_access.clone().unwrap();
+        assert!(peer_access.allowed_uids.is_empty());
+        assert_eq!(peer_access.allowed_gids, vec![985]);
+        assert_eq!(peer_access.token_exempt_uids, vec![1000]);
+        assert!(config.validate().is_ok());
+
+        // Peer credentials only exist on the Unix socket
+        let config = DaemonConfig {
+            peer_access: Some(peer_access),
+            ..single_listener_config(ListenerMode::TcpHttp, "127.0.0.1", 3443)
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_auth_limits_and_allowed_networks() {
+        let toml_str = r#"
+            allowed_networks = ["192.168.1.0/24", "10.1.2.3"]
+
+            [auth_limits]
+            max_failures = 3
+        "#;
+        let mut config: DaemonConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.auth_limits.max_failures, 3);
+        assert_eq!(config.auth_limits.lockout_secs, 30);
+        let networks = config.allowed_networks().unwrap();
+        assert_eq!(networks[1].to_string(), "10.1.2.3/32");
+        assert!(config.validate().is_ok());
+
+        config.allowed_networks.push("not-a-network".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_auth_prompts() {
+        let config = DaemonConfig::default();
+        assert_eq!(config.auth_prompts.timeout_secs, 60);
+        assert_eq!(config.auth_prompts.no_client, NoClientPolicy::Wait);
+
+        let toml_str = r#"
+            [auth_prompts]
+            no_client = "defer"
+            askpass = "ssh-askpass"
+        "#;
+        let mut config: DaemonConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.auth_prompts.no_client, NoClientPolicy::Defer);
+        assert_eq!(config.auth_prompts.timeout_secs, 60);
+        assert_eq!(config.auth_prompts.askpass.as_deref(), Some("ssh-askpass"));
+        assert_eq!(config.auth_prompts.askpass_grace_secs, 5);
+        assert!(config.validate().is_ok());
+
+        config.auth_prompts.timeout_secs = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_secrets_config() {
+        let config = DaemonConfig::default();
+        assert_eq!(config.secrets, SecretsConfig::default());
+        assert_eq!(config.secrets.cache_ttl_secs, 300);
+
+        let toml_str = r#"
+            [secrets]
+            cache_ttl_secs = 0
+        "#;
+        let mut config: DaemonConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.secrets.cache_ttl_secs, 0);
+        assert_eq!(config.secrets.command_timeout_secs, 10);
+        assert!(config.validate().is_ok());
+
+        config.secrets.command_timeout_secs = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_key_cache_config() {
+        let config = DaemonConfig::default();
+        assert!(!config.key_cache.enabled);
+        assert_eq!(config.key_cache.ttl_secs, 3600);
+
+        let toml_str = r#"
+            [key_cache]
+            enabled = true
+            ttl_secs = 0
+        "#;
+        let config: DaemonConfig = toml::from_str(toml_str).unwrap();
+        assert!(config.key_cache.enabled);
+        assert_eq!(config.key_cache.ttl_secs, 0);
+    }
+
     #[test]
     fn test_cli_config_snippet_content() {
         use tempfile::TempDir;
@@ -382,9 +1485,7 @@ mod tests {
 
         // Test Unix Socket mode with auth
         let result = write_cli_config_snippet(
-            &ListenerMode::UnixSocket,
-            "127.0.0.1",
-            3443,
+            &single_listener_config(ListenerMode::UnixSocket, "127.0.0.1", 3443),
             Some(test_token),
             None,
         );
@@ -407,9 +1508,7 @@ mod tests {
 
         // Test HTTP mode with auth
         let result = write_cli_config_snippet(
-            &ListenerMode::TcpHttp,
-            "127.0.0.1",
-            3443,
+            &single_listener_config(ListenerMode::TcpHttp, "127.0.0.1", 3443),
             Some(test_token),
             None,
         );
@@ -422,9 +1521,7 @@ mod tests {
 
         // Test HTTPS mode with auth and fingerprint
         let result = write_cli_config_snippet(
-            &ListenerMode::TcpHttps,
-            "192.168.1.100",
-            3443,
+            &single_listener_config(ListenerMode::TcpHttps, "192.168.1.100", 3443),
             Some(test_token),
             Some(test_fingerprint),
         );
@@ -435,6 +1532,16 @@ mod tests {
         assert!(content.contains("daemon_port = 3443"));
         assert!(content.contains(&format!("auth_token = \"{}\"", test_token)));
         assert!(content.contains(&format!("tls_cert_fingerprint = \"{}\"", test_fingerprint)));
+
+        // Rewriting without the plaintext token (daemon restart) keeps the previous one
+        let result = write_cli_config_snippet(
+            &single_listener_config(ListenerMode::TcpHttps, "192.168.1.100", 3443),
+            None,
+            Some(test_fingerprint),
+        );
+        assert!(result.is_ok());
+        let content = fs::read_to_string(&snippet_path).unwrap();
+        assert!(content.contains(&format!("auth_token = \"{}\"", test_token)));
     }
 
     #[test]
@@ -482,19 +1589,59 @@ fn get_empty_host_comment(bind_host: &str) -> String {
     }
 }
 
+/// Pick the listener a client on this machine should use
+///
+/// Preference order: Unix socket, loopback HTTP, loopback HTTPS, then any HTTPS.
+pub fn preferred_local_listener(listeners: &[ListenerConfig]) -> Option<&ListenerConfig> {
+    let rank = |listener: &ListenerConfig| {
+        let loopback = ssh_tunnel_common::is_loopback_address(&listener.bind_host);
+        match listener.mode {
+            ListenerMode::UnixSocket => 0,
+            ListenerMode::TcpHttp => 1,
+            ListenerMode::TcpHttps if loopback => 2,
+            ListenerMode::TcpHttps => 3,
+        }
+    };
+
+    listeners.iter().min_by_key(|listener| rank(listener))
+}
+
+/// Token from an existing CLI config snippet, if any
+fn snippet_auth_token(snippet_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(snippet_path).ok()?;
+    let value: toml::Value = toml::from_str(&content).ok()?;
+    value
+        .get("auth_token")
+        .and_then(|token| token.as_str())
+        .map(str::to_string)
+}
+
 pub fn write_cli_config_snippet(
-    listener_mode: &ListenerMode,
-    bind_host: &str,
-    bind_port: u16,
+    daemon_config: &DaemonConfig,
     auth_token: Option<&str>,
     tls_fingerprint: Option<&str>,
 ) -> Result<()> {
+    let listeners = daemon_config.effective_listeners();
+    let listener = preferred_local_listener(&listeners)
+        .ok_or_else(|| anyhow::anyhow!("No listeners configured"))?;
+    let listener_mode = &listener.mode;
+    let bind_host = listener.bind_host.as_str();
+    let bind_port = listener.bind_port;
+
     let config_dir = dirs::config_dir()
         .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?;
     let snippet_path = config_dir
         .join("ssh-tunnel-manager")
         .join("cli-config.snippet");
 
+    // Only include the token if the chosen listener actually checks it.
+    // The daemon only keeps a hash, so when rewriting the snippet for another
+    // reason (e.g. a regenerated certificate) carry the previous token over.
+    let previous_token = auth_token.is_none().then(|| snippet_auth_token(&snippet_path)).flatten();
+    let auth_token = auth_token
+        .or(previous_token.as_deref())
+        .filter(|_| listener.requires_auth(daemon_config.require_auth));
+
     // Ensure parent directory exists
     if let Some(parent) = snippet_path.parent() {
         fs::create_dir_all(parent).context("Failed to create config directory")?;
@@ -0,0 +1,474 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Tunnel event bus
+//
+// Every tunnel event gets a sequence number, a timestamp, the profile name
+// and the status transition it caused, and is kept in a bounded replay ring,
+// so clients that reconnect (SSE `Last-Event-ID`) or fall behind the
+// broadcast channel can catch up on what they missed. When the ring no
+// longer holds the missing events the client gets a state snapshot instead.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::Utc;
+use tokio::sync::broadcast::{self, error::RecvError, error::SendError};
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use ssh_tunnel_common::{EventMeta, TunnelStatus};
+
+use crate::api::OutgoingEvent;
+use crate::tunnel::TunnelEvent;
+
+/// Events buffered per subscriber in the broadcast channel
+const CHANNEL_CAPACITY: usize = 100;
+
+/// Events kept for replay
+pub const REPLAY_CAPACITY: usize = 512;
+
+/// A tunnel event with its position in the daemon's event sequence
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub event: TunnelEvent,
+    pub meta: EventMeta,
+}
+
+impl From<SequencedEvent> for OutgoingEvent {
+    fn from(sequenced: SequencedEvent) -> Self {
+        let meta = sequenced.meta;
+        match sequenced.event {
+            TunnelEvent::Starting { id } => OutgoingEvent::Starting { id, meta },
+            TunnelEvent::Connected { id, bound_addr } => OutgoingEvent::Connected {
+                id,
+                bound_address: bound_addr,
+                meta,
+            },
+            TunnelEvent::Disconnected { id, reason } => {
+                OutgoingEvent::Disconnected { id, reason, meta }
+            }
+            TunnelEvent::Error { id, error } => OutgoingEvent::Error { id, error, meta },
+            TunnelEvent::AuthRequired { id, request } => {
+                OutgoingEvent::AuthRequired { id, request, meta }
+            }
+            TunnelEvent::AuthResolved { id, request_id } => {
+                OutgoingEvent::AuthResolved { id, request_id, meta }
+            }
+            TunnelEvent::AuthCancelled { id, request_id, reason } => {
+                OutgoingEvent::AuthCancelled { id, request_id, reason, meta }
+            }
+            TunnelEvent::Hook { id, stage, success, output } => OutgoingEvent::Hook {
+                id,
+                stage,
+                success,
+                output,
+                meta,
+            },
+            TunnelEvent::Stats { id, stats } => OutgoingEvent::Stats { id, stats, meta },
+        }
+    }
+}
+
+/// Broadcast channel plus replay ring; clones share both
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<SequencedEvent>,
+    ring: Arc<Mutex<Ring>>,
+    /// Open `EventFeed`s, i.e. connected SSE and WebSocket clients
+    feeds: Arc<AtomicUsize>,
+    /// Woken whenever a feed opens
+    feed_opened: Arc<Notify>,
+}
+
+struct Ring {
+    next_seq: u64,
+    capacity: usize,
+    events: VecDeque<SequencedEvent>,
+    /// Profile names of tunnels seen so far, for event metadata
+    names: HashMap<Uuid, String>,
+    /// Status of each tunnel as of its last event
+    statuses: HashMap<Uuid, TunnelStatus>,
+}
+
+/// Where a resuming subscriber picks up
+pub struct Resume {
+    /// Live events after `last_seq`
+    pub receiver: broadcast::Receiver<SequencedEvent>,
+    /// Missed events, oldest first; `None` when they are no longer buffered
+    pub replay: Option<Vec<SequencedEvent>>,
+    /// Sequence number of the last event sent before `receiver` was created
+    pub last_seq: u64,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        // Start from the wall clock (µs) so IDs keep increasing across daemon
+        // restarts and an ID from a previous run is never taken for one of ours
+        let first_seq = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(1)
+            .max(1);
+        Self {
+            tx,
+            ring: Arc::new(Mutex::new(Ring {
+                next_seq: first_seq,
+                capacity,
+                events: VecDeque::with_capacity(capacity),
+                names: HashMap::new(),
+                statuses: HashMap::new(),
+            })),
+            feeds: Arc::new(AtomicUsize::new(0)),
+            feed_opened: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Number of connected event stream clients
+    pub fn feed_count(&self) -> usize {
+        self.feeds.load(Ordering::Relaxed)
+    }
+
+    /// Wait until at least one event stream client is connected
+    pub async fn wait_for_feed(&self) {
+        loop {
+            // Registered before the check so an opening feed can't be missed
+            let opened = self.feed_opened.notified();
+            if self.feed_count() > 0 {
+                return;
+            }
+            opened.await;
+        }
+    }
+
+    /// Name events of this tunnel after its profile
+    pub fn track(&self, id: Uuid, profile_name: &str) {
+        let mut ring = self.ring.lock().unwrap_or_else(|e| e.into_inner());
+        ring.names.insert(id, profile_name.to_string());
+    }
+
+    /// Number the event, attach its metadata, keep it for replay and broadcast it
+    ///
+    /// Fails only when nobody is subscribed; the event is still kept for replay.
+    pub fn send(&self, event: TunnelEvent) -> Result<usize, SendError<()>> {
+        let mut ring = self.ring.lock().unwrap_or_else(|e| e.into_inner());
+
+        let id = event.id();
+        let previous = ring
+            .statuses
+            .get(&id)
+            .cloned()
+            .unwrap_or(TunnelStatus::NotConnected);
+        let status = status_after(&event, &previous);
+        ring.statuses.insert(id, status.clone());
+
+        let event = SequencedEvent {
+            seq: ring.next_seq,
+            event,
+            meta: EventMeta {
+                timestamp: Utc::now(),
+                profile_name: ring.names.get(&id).cloned(),
+                previous_status: Some(previous),
+                status: Some(status),
+            },
+        };
+        ring.next_seq += 1;
+        if ring.events.len() == ring.capacity {
+            ring.events.pop_front();
+        }
+        ring.events.push_back(event.clone());
+        // Sent under the lock so `resume` sees the ring and channel in step
+        self.tx.send(event).map_err(|_| SendError(()))
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SequencedEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Subscribe to events after `after`, with the buffered ones to replay first
+    pub fn resume(&self, after: u64) -> Resume {
+        let ring = self.ring.lock().unwrap_or_else(|e| e.into_inner());
+        let last_seq = ring.next_seq - 1;
+        let oldest = ring.events.front().map_or(ring.next_seq, |e| e.seq);
+
+        let replay = if after > last_seq || after + 1 < oldest {
+            None
+        } else {
+            Some(
+                ring.events
+                    .iter()
+                    .filter(|e| e.seq > after)
+                    .cloned()
+                    .collect(),
+            )
+        };
+
+        Resume {
+            receiver: self.tx.subscribe(),
+            replay,
+            last_seq,
+        }
+    }
+}
+
+/// Status a tunnel is in after `event`
+fn status_after(event: &TunnelEvent, previous: &TunnelStatus) -> TunnelStatus {
+    match event {
+        TunnelEvent::Starting { .. } | TunnelEvent::AuthResolved { .. } => TunnelStatus::Connecting,
+        TunnelEvent::Connected { .. } => TunnelStatus::Connected,
+        TunnelEvent::Disconnected { .. } => TunnelStatus::Disconnected,
+        TunnelEvent::Error { error, .. } => TunnelStatus::Failed(error.clone()),
+        TunnelEvent::AuthRequired { .. } => TunnelStatus::WaitingForAuth,
+        TunnelEvent::AuthCancelled { .. } | TunnelEvent::Hook { .. } | TunnelEvent::Stats { .. } => {
+            previous.clone()
+        }
+    }
+}
+
+/// Item produced by an `EventFeed`
+#[derive(Debug)]
+pub enum FeedItem {
+    Event(SequencedEvent),
+    /// Events were lost; send the current state, tagged with this sequence number
+    Snapshot(u64),
+}
+
+/// One client's view of the bus: replays missed events, recovers from lag,
+/// and asks for a snapshot when events can't be replayed
+pub struct EventFeed {
+    bus: EventBus,
+    receiver: broadcast::Receiver<SequencedEvent>,
+    backlog: VecDeque<SequencedEvent>,
+    last_seq: u64,
+    snapshot_due: bool,
+}
+
+impl EventFeed {
+    /// Feed of live events, starting after `last_event_id` if the client has one
+    pub fn new(bus: &EventBus, last_event_id: Option<u64>) -> Self {
+        bus.feeds.fetch_add(1, Ordering::Relaxed);
+        bus.feed_opened.notify_waiters();
+        match last_event_id {
+            Some(after) => {
+                let mut feed = Self {
+                    bus: bus.clone(),
+                    receiver: bus.subscribe(),
+                    backlog: VecDeque::new(),
+                    last_seq: after,
+                    snapshot_due: false,
+                };
+                feed.resume();
+                feed
+            }
+            None => {
+                let resume = bus.resume(0);
+                Self {
+                    bus: bus.clone(),
+                    receiver: resume.receiver,
+                    backlog: VecDeque::new(),
+                    last_seq: resume.last_seq,
+                    snapshot_due: false,
+                }
+            }
+        }
+    }
+
+    pub async fn next(&mut self) -> Option<FeedItem> {
+        loop {
+            if self.snapshot_due {
+                self.snapshot_due = false;
+                return Some(FeedItem::Snapshot(self.last_seq));
+            }
+            if let Some(event) = self.backlog.pop_front() {
+                self.last_seq = event.seq;
+                return Some(FeedItem::Event(event));
+            }
+
+            match self.receiver.recv().await {
+                // Already replayed
+                Ok(event) if event.seq <= self.last_seq => continue,
+                Ok(event) => {
+                    self.last_seq = event.seq;
+                    return Some(FeedItem::Event(event));
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::debug!("Event subscriber lagged by {} events, replaying", missed);
+                    self.resume();
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Re-subscribe after `last_seq`, queueing the missed events or a snapshot
+    fn resume(&mut self) {
+        let resume = self.bus.resume(self.last_seq);
+        self.receiver = resume.receiver;
+        match resume.replay {
+            Some(events) => self.backlog = events.into(),
+            None => {
+                self.backlog.clear();
+                self.last_seq = resume.last_seq;
+                self.snapshot_due = true;
+            }
+        }
+    }
+}
+
+impl Drop for EventFeed {
+    fn drop(&mut self) {
+        self.bus.feeds.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn starting() -> TunnelEvent {
+        TunnelEvent::Starting { id: Uuid::new_v4() }
+    }
+
+    async fn next_seq(feed: &mut EventFeed) -> u64 {
+        match feed.next().await.unwrap() {
+            FeedItem::Event(event) => event.seq,
+            FeedItem::Snapshot(seq) => panic!("unexpected snapshot at {}", seq),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_replays_missed_events() {
+        let bus = EventBus::new(4);
+        let mut live = bus.subscribe();
+        bus.send(starting()).unwrap();
+        let first = live.recv().await.unwrap().seq;
+        bus.send(starting()).unwrap();
+        bus.send(starting()).unwrap();
+
+        // Reconnect after the first event: the other two are replayed, then live ones follow
+        let mut feed = EventFeed::new(&bus, Some(first));
+        assert_eq!(next_seq(&mut feed).await, first + 1);
+        assert_eq!(next_seq(&mut feed).await, first + 2);
+        bus.send(starting()).unwrap();
+        assert_eq!(next_seq(&mut feed).await, first + 3);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_when_gap_cannot_be_filled() {
+        let bus = EventBus::new(2);
+        let mut live = bus.subscribe();
+        bus.send(starting()).unwrap();
+        let first = live.recv().await.unwrap().seq;
+        for _ in 0..3 {
+            bus.send(starting()).unwrap();
+        }
+
+        // Events after `first` have partly left the ring
+        let mut feed = EventFeed::new(&bus, Some(first));
+        match feed.next().await.unwrap() {
+            FeedItem::Snapshot(seq) => assert_eq!(seq, first + 3),
+            other => panic!("expected snapshot, got {:?}", other),
+        }
+        bus.send(starting()).unwrap();
+        assert_eq!(next_seq(&mut feed).await, first + 4);
+
+        // IDs from a previous daemon run (or the future) can't be replayed either
+        let mut feed = EventFeed::new(&bus, Some(u64::MAX - 1));
+        assert!(matches!(feed.next().await, Some(FeedItem::Snapshot(_))));
+    }
+
+    #[test]
+    fn test_feed_count() {
+        let bus = EventBus::new(2);
+        let _internal = bus.subscribe();
+        let first = EventFeed::new(&bus, None);
+        let second = EventFeed::new(&bus, Some(1));
+        assert_eq!(bus.feed_count(), 2);
+        drop(first);
+        drop(second);
+        assert_eq!(bus.feed_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_feed() {
+        let bus = EventBus::new(2);
+        let waiting = tokio::spawn({
+            let bus = bus.clone();
+            async move { bus.wait_for_feed().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        let _feed = EventFeed::new(&bus, None);
+        tokio::time::timeout(std::time::Duration::from_secs(1), waiting)
+            .await
+            .expect("a new feed wakes the waiter")
+            .unwrap();
+        // Returns at once while a feed is open
+        bus.wait_for_feed().await;
+    }
+
+    #[tokio::test]
+    async fn test_events_carry_name_and_transition() {
+        let bus = EventBus::new(8);
+        let mut rx = bus.subscribe();
+        let id = Uuid::new_v4();
+        bus.track(id, "prod-db");
+
+        bus.send(TunnelEvent::Starting { id }).unwrap();
+        bus.send(TunnelEvent::Connected { id, bound_addr: None }).unwrap();
+        bus.send(TunnelEvent::Hook {
+            id,
+            stage: ssh_tunnel_common::HookStage::PostUp,
+            success: true,
+            output: String::new(),
+        })
+        .unwrap();
+
+        let starting = rx.recv().await.unwrap().meta;
+        assert_eq!(starting.profile_name.as_deref(), Some("prod-db"));
+        assert_eq!(starting.previous_status, Some(TunnelStatus::NotConnected));
+        assert_eq!(starting.status, Some(TunnelStatus::Connecting));
+
+        let connected = rx.recv().await.unwrap();
+        assert_eq!(connected.meta.previous_status, Some(TunnelStatus::Connecting));
+        assert_eq!(connected.meta.status, Some(TunnelStatus::Connected));
+        assert!(connected.meta.is_transition());
+
+        // Hooks don't change the status
+        let hook = rx.recv().await.unwrap().meta;
+        assert_eq!(hook.status, Some(TunnelStatus::Connected));
+        assert!(!hook.is_transition());
+
+        // Wire format: metadata is flattened into the event
+        let json = serde_json::to_value(OutgoingEvent::from(connected)).unwrap();
+        assert_eq!(json["type"], "connected");
+        assert_eq!(json["profile_name"], "prod-db");
+        assert_eq!(json["previous_status"], "connecting");
+        assert_eq!(json["status"], "connected");
+        assert!(json["timestamp"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_lagged_subscriber_catches_up() {
+        let bus = EventBus::new(REPLAY_CAPACITY);
+        let mut feed = EventFeed::new(&bus, None);
+        let total = CHANNEL_CAPACITY + 10;
+        for _ in 0..total {
+            bus.send(starting()).unwrap();
+        }
+
+        // The broadcast channel overflowed, but every event comes out once and in order
+        let mut previous = next_seq(&mut feed).await;
+        for _ in 1..total {
+            let seq = next_seq(&mut feed).await;
+            assert_eq!(seq, previous + 1);
+            previous = seq;
+        }
+    }
+}
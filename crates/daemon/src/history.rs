@@ -0,0 +1,375 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Session history
+//
+// Every run of a tunnel, from `starting` until it is stopped or fails, is
+// recorded as a `TunnelSession` in `<path>/<profile-id>.jsonl`, keeping the
+// last `max_sessions` per profile. Sessions are assembled from the tunnel
+// manager's event stream; byte counts come from the profile's traffic
+// counters at the start and end of the session.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use ssh_tunnel_common::{EventMeta, TunnelSession};
+
+use crate::config::HistoryConfig;
+use crate::traffic::TrafficCounters;
+use crate::tunnel::{TunnelEvent, TunnelManager};
+
+/// End reason of a session whose tunnel was started again before it ended
+const RESTARTED: &str = "Restarted before previous session ended";
+
+/// A session that hasn't ended yet
+struct OpenSession {
+    profile_name: String,
+    started_at: DateTime<Utc>,
+    connected_at: Option<DateTime<Utc>>,
+    traffic: Option<Arc<TrafficCounters>>,
+    /// Counter values when the session started
+    bytes_sent: u64,
+    bytes_received: u64,
+}
+
+impl OpenSession {
+    fn finish(self, profile_id: Uuid, ended_at: DateTime<Utc>, reason: &str, failed: bool) -> TunnelSession {
+        let (bytes_sent, bytes_received) = match &self.traffic {
+            Some(traffic) => (
+                traffic.bytes_sent() - self.bytes_sent,
+                traffic.bytes_received() - self.bytes_received,
+            ),
+            None => (0, 0),
+        };
+        TunnelSession {
+            profile_id,
+            profile_name: self.profile_name,
+            started_at: self.started_at,
+            connected_at: self.connected_at,
+            ended_at,
+            end_reason: reason.to_string(),
+            failed,
+            bytes_sent,
+            bytes_received,
+        }
+    }
+}
+
+pub struct SessionHistory {
+    path: PathBuf,
+    max_sessions: usize,
+    open: Mutex<HashMap<Uuid, OpenSession>>,
+    /// Held while reading or rewriting files
+    files: Mutex<()>,
+}
+
+impl SessionHistory {
+    /// Open (or create) the history directory
+    pub fn open(config: &HistoryConfig) -> Result<Self> {
+        crate::permissions::ensure_directory_with_permissions(&config.path, false)
+            .with_context(|| format!("Failed to create history directory {}", config.path.display()))?;
+
+        info!("Session history: {}", config.path.display());
+        Ok(Self {
+            path: config.path.clone(),
+            max_sessions: config.max_sessions.max(1),
+            open: Mutex::new(HashMap::new()),
+            files: Mutex::new(()),
+        })
+    }
+
+    /// Update the open session of the event's tunnel, writing it out when it ends
+    ///
+    /// `traffic` is only used when the event starts a session.
+    fn apply(&self, event: &TunnelEvent, meta: &EventMeta, traffic: Option<Arc<TrafficCounters>>) {
+        let id = event.id();
+        let mut open = self.open.lock().unwrap();
+
+        let (reason, failed) = match event {
+            TunnelEvent::Starting { .. } => {
+                let (bytes_sent, bytes_received) = traffic
+                    .as_ref()
+                    .map(|t| (t.bytes_sent(), t.bytes_received()))
+                    .unwrap_or_default();
+                let previous = open.insert(
+                    id,
+                    OpenSession {
+                        profile_name: meta.profile_name.clone().unwrap_or_else(|| id.to_string()),
+                        started_at: meta.timestamp,
+                        connected_at: None,
+                        traffic,
+                        bytes_sent,
+                        bytes_received,
+                    },
+                );
+                let Some(previous) = previous else {
+                    return;
+                };
+                // Its end never came through; keep it rather than losing it
+                drop(open);
+                debug!("Tunnel {} restarted before its previous session ended", id);
+                self.record(previous.finish(id, meta.timestamp, RESTARTED, false));
+                return;
+            }
+            TunnelEvent::Connected { .. } => {
+                if let Some(session) = open.get_mut(&id) {
+                    session.connected_at.get_or_insert(meta.timestamp);
+                }
+                return;
+            }
+            TunnelEvent::Disconnected { reason, .. } => (reason.as_str(), false),
+            TunnelEvent::Error { error, .. } => (error.as_str(), true),
+            TunnelEvent::AuthRequired { .. }
+            | TunnelEvent::AuthResolved { .. }
+            | TunnelEvent::AuthCancelled { .. }
+            | TunnelEvent::Hook { .. }
+            | TunnelEvent::Stats { .. } => return,
+        };
+
+        // A tunnel reports its end only once, but be tolerant of repeats
+        let Some(session) = open.remove(&id) else {
+            return;
+        };
+        drop(open);
+        self.record(session.finish(id, meta.timestamp, reason, failed));
+    }
+
+    /// End all open sessions, e.g. when the daemon exits
+    pub fn end_all(&self, reason: &str) {
+        let sessions: Vec<(Uuid, OpenSession)> = self.open.lock().unwrap().drain().collect();
+        let now = Utc::now();
+        for (id, session) in sessions {
+            self.record(session.finish(id, now, reason, false));
+        }
+    }
+
+    /// Append a session
+    ///
+    /// Failures are logged; history is never worth failing a tunnel over.
+    fn record(&self, session: TunnelSession) {
+        if let Err(e) = self.append(&session) {
+            warn!(
+                "Failed to record session of '{}' in history: {:#}",
+                session.profile_name, e
+            );
+        }
+    }
+
+    fn append(&self, session: &TunnelSession) -> Result<()> {
+        let line = serde_json::to_string(session).context("Failed to serialize session")?;
+        let path = self.profile_path(&session.profile_id);
+        let _guard = self.files.lock().unwrap();
+
+        let mut lines = if path.exists() { read_lines(&path)? } else { Vec::new() };
+        if lines.len() < self.max_sessions {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            crate::permissions::set_file_permissions_private(&path)?;
+            writeln!(file, "{}", line).with_context(|| format!("Failed to append to {}", path.display()))?;
+            return Ok(());
+        }
+
+        // Full: rewrite without the oldest sessions
+        lines.drain(..=lines.len() - self.max_sessions);
+        lines.push(line);
+        let temp = path.with_extension("jsonl.tmp");
+        {
+            let mut file = File::create(&temp)
+                .with_context(|| format!("Failed to create {}", temp.display()))?;
+            crate::permissions::set_file_permissions_private(&temp)?;
+            for line in &lines {
+                writeln!(file, "{}", line)
+                    .with_context(|| format!("Failed to write {}", temp.display()))?;
+            }
+            file.sync_all()
+                .with_context(|| format!("Failed to write {}", temp.display()))?;
+        }
+        fs::rename(&temp, &path).with_context(|| format!("Failed to replace {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Recorded sessions of a profile, oldest first; `limit` keeps the most recent
+    pub fn query(&self, profile_id: &Uuid, limit: Option<usize>) -> Result<Vec<TunnelSession>> {
+        let path = self.profile_path(profile_id);
+        let _guard = self.files.lock().unwrap();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut sessions: Vec<TunnelSession> = read_lines(&path)?
+            .iter()
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(session) => Some(session),
+                Err(e) => {
+                    debug!("Skipping malformed history line in {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect();
+        if let Some(limit) = limit {
+            let skip = sessions.len().saturating_sub(limit);
+            sessions.drain(..skip);
+        }
+        Ok(sessions)
+    }
+
+    fn profile_path(&self, profile_id: &Uuid) -> PathBuf {
+        self.path.join(format!("{}.jsonl", profile_id))
+    }
+}
+
+fn read_lines(path: &Path) -> Result<Vec<String>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut lines = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+        if !line.trim().is_empty() {
+            lines.push(line);
+        }
+    }
+    Ok(lines)
+}
+
+/// Build sessions from the tunnel manager's event stream
+pub fn spawn_recorder(history: Arc<SessionHistory>, tunnel_manager: TunnelManager) {
+    let mut event_rx = tunnel_manager.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let sequenced = match event_rx.recv().await {
+                Ok(sequenced) => sequenced,
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("Session history missed {} tunnel events", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            let traffic = match &sequenced.event {
+                TunnelEvent::Starting { id } => tunnel_manager.traffic(id).await,
+                _ => None,
+            };
+            history.apply(&sequenced.event, &sequenced.meta, traffic);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn meta(name: &str) -> EventMeta {
+        EventMeta {
+            profile_name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn open_history(temp_dir: &TempDir, max_sessions: usize) -> SessionHistory {
+        SessionHistory::open(&HistoryConfig {
+            enabled: true,
+            path: temp_dir.path().join("history"),
+            max_sessions,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sessions_from_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = open_history(&temp_dir, 10);
+        let id = Uuid::new_v4();
+        let traffic = Arc::new(TrafficCounters::default());
+
+        history.apply(&TunnelEvent::Starting { id }, &meta("db"), Some(traffic.clone()));
+        history.apply(&TunnelEvent::Connected { id, bound_addr: None }, &meta("db"), None);
+        history.apply(
+            &TunnelEvent::Error { id, error: "Connection reset".to_string() },
+            &meta("db"),
+            None,
+        );
+        // A second end event for the same run is ignored
+        history.apply(
+            &TunnelEvent::Disconnected { id, reason: "Stopped by user".to_string() },
+            &meta("db"),
+            None,
+        );
+
+        history.apply(&TunnelEvent::Starting { id }, &meta("db"), Some(traffic));
+        history.end_all("Daemon stopped");
+
+        let sessions = history.query(&id, None).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].profile_name, "db");
+        assert!(sessions[0].failed);
+        assert_eq!(sessions[0].end_reason, "Connection reset");
+        assert!(sessions[0].connected_at.is_some());
+        assert!(!sessions[1].failed);
+        assert_eq!(sessions[1].end_reason, "Daemon stopped");
+        assert!(sessions[1].connected_at.is_none());
+
+        assert!(history.query(&Uuid::new_v4(), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restart_keeps_the_open_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = open_history(&temp_dir, 10);
+        let id = Uuid::new_v4();
+
+        history.apply(&TunnelEvent::Starting { id }, &meta("db"), None);
+        history.apply(&TunnelEvent::Connected { id, bound_addr: None }, &meta("db"), None);
+        history.apply(&TunnelEvent::Starting { id }, &meta("db"), None);
+        history.apply(
+            &TunnelEvent::Disconnected { id, reason: "Stopped by user".to_string() },
+            &meta("db"),
+            None,
+        );
+
+        let sessions = history.query(&id, None).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].end_reason, RESTARTED);
+        assert!(sessions[0].connected_at.is_some());
+        assert_eq!(sessions[1].end_reason, "Stopped by user");
+        assert!(sessions[1].connected_at.is_none());
+    }
+
+    #[test]
+    fn test_history_keeps_most_recent_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let history = open_history(&temp_dir, 3);
+        let id = Uuid::new_v4();
+
+        for n in 0..5 {
+            history.apply(&TunnelEvent::Starting { id }, &meta("db"), None);
+            history.apply(
+                &TunnelEvent::Disconnected { id, reason: format!("#{}", n) },
+                &meta("db"),
+                None,
+            );
+        }
+
+        let reasons: Vec<String> = history
+            .query(&id, None)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.end_reason)
+            .collect();
+        assert_eq!(reasons, ["#2", "#3", "#4"]);
+
+        let latest = history.query(&id, Some(1)).unwrap();
+        assert_eq!(latest[0].end_reason, "#4");
+    }
+}
@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Lifecycle hooks
+//
+// Runs a profile's pre-up/post-up/pre-down/post-down commands via `sh -c`.
+// Output is logged by the daemon and broadcast as a `Hook` event so clients
+// watching the tunnel see what happened.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::Result;
+use tracing::{debug, info, warn};
+
+use ssh_tunnel_common::{HookStage, Profile};
+
+use crate::events::EventBus;
+use crate::shell;
+use crate::tunnel::TunnelEvent;
+
+/// Maximum bytes of hook output forwarded in the event stream
+const MAX_EVENT_OUTPUT: usize = 4096;
+
+/// Run the hook configured for `stage`, if any.
+///
+/// Returns an error when the command exits non-zero, cannot be spawned or
+/// exceeds the profile's hook timeout (the child is killed in that case).
+/// Profiles without a hook for the stage return `Ok(())` immediately.
+pub async fn run_hook(
+    stage: HookStage,
+    profile: &Profile,
+    bound_addr: Option<SocketAddr>,
+    failure_reason: Option<&str>,
+    event_tx: &EventBus,
+) -> Result<()> {
+    let Some(command) = profile.hooks.command(stage) else {
+        return Ok(());
+    };
+
+    let id = profile.metadata.id;
+    let name = &profile.metadata.name;
+    info!("Running {} hook for '{}': {}", stage, name, command);
+
+    let result = execute(stage, command, profile, bound_addr, failure_reason).await;
+
+    let (success, output) = match &result {
+        Ok(output) => (true, output.clone()),
+        Err(e) => (false, format!("{:#}", e)),
+    };
+
+    if let Err(e) = event_tx.send(TunnelEvent::Hook {
+        id,
+        stage,
+        success,
+        output: truncate_output(&output),
+    }) {
+        debug!("Failed to broadcast Hook event for {}: {}", id, e);
+    }
+
+    match result {
+        Ok(_) => {
+            info!("{} hook for '{}' completed", stage, name);
+            Ok(())
+        }
+        Err(e) => {
+            warn!("{} hook for '{}' failed: {:#}", stage, name, e);
+            Err(e)
+        }
+    }
+}
+
+/// Spawn the command and collect its output, logging each line
+async fn execute(
+    stage: HookStage,
+    command: &str,
+    profile: &Profile,
+    bound_addr: Option<SocketAddr>,
+    failure_reason: Option<&str>,
+) -> Result<String> {
+    let mut cmd = shell::sh(command);
+    cmd.env("SSH_TUNNEL_HOOK", stage.as_str())
+        .env("SSH_TUNNEL_PROFILE_NAME", &profile.metadata.name)
+        .env("SSH_TUNNEL_PROFILE_ID", profile.metadata.id.to_string());
+
+    if let Some(addr) = bound_addr {
+        cmd.env("SSH_TUNNEL_BOUND_ADDRESS", addr.to_string())
+            .env("SSH_TUNNEL_BOUND_HOST", addr.ip().to_string())
+            .env("SSH_TUNNEL_BOUND_PORT", addr.port().to_string());
+    }
+    if let Some(reason) = failure_reason {
+        cmd.env("SSH_TUNNEL_FAILURE_REASON", reason);
+    }
+
+    let timeout = Duration::from_secs(profile.hooks.timeout_secs);
+    let output = shell::run(&mut cmd, timeout, "hook command").await?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for line in stdout.lines() {
+        info!("[{} hook '{}'] {}", stage, profile.metadata.name, line);
+    }
+    for line in stderr.lines() {
+        warn!("[{} hook '{}'] {}", stage, profile.metadata.name, line);
+    }
+
+    let combined = [stdout.trim_end(), stderr.trim_end()]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if !output.status.success() {
+        let status = shell::describe_failure(output.status);
+        if combined.is_empty() {
+            anyhow::bail!("{}", status);
+        }
+        anyhow::bail!("{}: {}", status, combined);
+    }
+
+    Ok(combined)
+}
+
+/// Keep event payloads small; the full output is in the daemon log
+fn truncate_output(output: &str) -> String {
+    if output.len() <= MAX_EVENT_OUTPUT {
+        return output.to_string();
+    }
+    let mut end = MAX_EVENT_OUTPUT;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}… (truncated)", &output[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssh_tunnel_common::{
+        AuthType, ConnectionConfig, ForwardingConfig, ForwardingType, PasswordStorage,
+    };
+
+    fn profile_with_hooks() -> Profile {
+        Profile::new(
+            "hooked".to_string(),
+            ConnectionConfig {
+                host: "example.com".to_string(),
+                port: 22,
+                user: "user".to_string(),
+                auth_type: AuthType::Password,
+                key_path: None,
+                password_storage: PasswordStorage::None,
+            },
+            ForwardingConfig {
+                forwarding_type: ForwardingType::Local,
+                bind_address: "127.0.0.1".to_string(),
+                local_port: Some(0),
+                remote_host: Some("localhost".to_string()),
+                remote_port: Some(80),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_hook_receives_environment() {
+        let mut profile = profile_with_hooks();
+        profile.hooks.post_up =
+            Some("echo \"$SSH_TUNNEL_HOOK $SSH_TUNNEL_PROFILE_NAME $SSH_TUNNEL_BOUND_PORT\"".to_string());
+        let tx = EventBus::new(4);
+        let mut rx = tx.subscribe();
+
+        let addr: SocketAddr = "127.0.0.1:40123".parse().unwrap();
+        run_hook(HookStage::PostUp, &profile, Some(addr), None, &tx)
+            .await
+            .unwrap();
+
+        match rx.recv().await.unwrap().event {
+            TunnelEvent::Hook { stage, success, output, .. } => {
+                assert_eq!(stage, HookStage::PostUp);
+                assert!(success);
+                assert_eq!(output, "post_up hooked 40123");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hook_failure_and_timeout() {
+        let mut profile = profile_with_hooks();
+        profile.hooks.pre_up = Some("echo boom >&2; exit 3".to_string());
+        let tx = EventBus::new(4);
+
+        let err = run_hook(HookStage::PreUp, &profile, None, None, &tx)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "exited with status 3: boom");
+
+        profile.hooks.pre_up = Some("sleep 5".to_string());
+        profile.hooks.timeout_secs = 1;
+        let err = run_hook(HookStage::PreUp, &profile, None, None, &tx)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+
+        // No hook configured for the stage is a no-op
+        assert!(run_hook(HookStage::PostDown, &profile, None, Some("x"), &tx).await.is_ok());
+    }
+}
@@ -0,0 +1,365 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Key cache
+//
+// Passphrase-protected SSH keys are decrypted once and shared by all tunnels
+// and reconnects, until `[key_cache] ttl_secs` runs out or they are locked
+// (`ssh-tunnel keys lock`). A decrypted key is kept in its unencrypted
+// OpenSSH encoding, in memory that is mlock()ed where the limits allow it
+// and wiped when the key is dropped.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::Utc;
+use tracing::{info, warn};
+use zeroize::{Zeroize, Zeroizing};
+
+use ssh_tunnel_common::UnlockedKey;
+
+use crate::config::KeyCacheConfig;
+
+/// Decrypted keys by key file
+pub struct KeyCache {
+    enabled: bool,
+    ttl: Option<Duration>,
+    keys: Mutex<HashMap<PathBuf, CachedKey>>,
+    /// Held while a tunnel unlocks a key, so tunnels starting together prompt once
+    unlocking: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+struct CachedKey {
+    encoded: LockedBytes,
+    info: UnlockedKey,
+    expires: Option<Instant>,
+}
+
+impl CachedKey {
+    fn is_expired(&self) -> bool {
+        self.expires.is_some_and(|expires| Instant::now() >= expires)
+    }
+}
+
+impl KeyCache {
+    pub fn new(config: &KeyCacheConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            ttl: (config.ttl_secs > 0).then(|| Duration::from_secs(config.ttl_secs)),
+            keys: Mutex::new(HashMap::new()),
+            unlocking: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// How long keys stay unlocked (`None`: until locked)
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Wait until no other tunnel is unlocking the key at `path`
+    ///
+    /// The waiting tunnels then find the key in the cache instead of
+    /// prompting for its passphrase again.
+    pub async fn exclusive(&self, path: &Path) -> tokio::sync::OwnedMutexGuard<()> {
+        let lock = self
+            .unlocking
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(path.to_path_buf())
+            .or_default()
+            .clone();
+        lock.lock_owned().await
+    }
+
+    /// Keep a decrypted key, given in its unencrypted OpenSSH encoding
+    ///
+    /// Does nothing when the cache is disabled.
+    pub fn insert(
+        &self,
+        path: &Path,
+        encoded: Zeroizing<Vec<u8>>,
+        algorithm: String,
+        fingerprint: String,
+    ) -> UnlockedKey {
+        let now = Utc::now();
+        let info = UnlockedKey {
+            path: path.display().to_string(),
+            algorithm,
+            fingerprint,
+            unlocked_at: now,
+            expires_at: self
+                .ttl
+                .and_then(|ttl| chrono::Duration::from_std(ttl).ok())
+                .map(|ttl| now + ttl),
+        };
+        if !self.enabled {
+            return info;
+        }
+
+        info!("Key {} ({}) unlocked", info.path, info.fingerprint);
+        self.lock_keys().insert(
+            path.to_path_buf(),
+            CachedKey {
+                encoded: LockedBytes::new(encoded),
+                info: info.clone(),
+                expires: self.ttl.map(|ttl| Instant::now() + ttl),
+            },
+        );
+        info
+    }
+
+    /// Run `f` on the encoding of the key at `path`, unless it isn't unlocked
+    pub fn with_key<T>(&self, path: &Path, f: impl FnOnce(&[u8]) -> T) -> Option<T> {
+        let mut keys = self.lock_keys();
+        match keys.get(path) {
+            Some(key) if key.is_expired() => {
+                keys.remove(path);
+                info!("Key {} locked (TTL expired)", path.display());
+                None
+            }
+            Some(key) => Some(f(&key.encoded.bytes)),
+            None => None,
+        }
+    }
+
+    /// The unlocked keys, by path
+    pub fn list(&self) -> Vec<UnlockedKey> {
+        self.purge_expired();
+        let mut keys: Vec<UnlockedKey> =
+            self.lock_keys().values().map(|key| key.info.clone()).collect();
+        keys.sort_by(|a, b| a.path.cmp(&b.path));
+        keys
+    }
+
+    /// Drop the key with the given path or fingerprint, or all keys
+    ///
+    /// Returns how many keys were dropped.
+    pub fn lock(&self, selector: Option<&str>) -> usize {
+        self.lock_within(selector, |_| true)
+    }
+
+    /// Like [`lock`](Self::lock), but only among the keys whose path is `allowed`
+    pub fn lock_within(&self, selector: Option<&str>, allowed: impl Fn(&Path) -> bool) -> usize {
+        let mut keys = self.lock_keys();
+        let before = keys.len();
+        let resolved = selector.and_then(|selector| resolve_key_path(Path::new(selector)).ok());
+        keys.retain(|path, key| {
+            let selected = selector.is_none_or(|selector| {
+                key.info.fingerprint == selector
+                    || path == Path::new(selector)
+                    || Some(path) == resolved.as_ref()
+            });
+            !(selected && allowed(path))
+        });
+        let locked = before - keys.len();
+        if locked > 0 {
+            info!("{} key(s) locked", locked);
+        }
+        locked
+    }
+
+    /// Drop keys whose TTL ran out
+    pub fn purge_expired(&self) {
+        self.lock_keys().retain(|path, key| {
+            let expired = key.is_expired();
+            if expired {
+                info!("Key {} locked (TTL expired)", path.display());
+            }
+            !expired
+        });
+    }
+
+    fn lock_keys(&self) -> MutexGuard<'_, HashMap<PathBuf, CachedKey>> {
+        self.keys.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for KeyCache {
+    fn default() -> Self {
+        Self::new(&KeyCacheConfig::default())
+    }
+}
+
+/// Full path of a profile's key file
+///
+/// For remote profiles (Hybrid mode) the key path is just a filename in the
+/// daemon user's `~/.ssh`.
+pub fn resolve_key_path(key_path: &Path) -> Result<PathBuf> {
+    if key_path.is_relative() {
+        let ssh_dir = dirs::home_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+            .join(".ssh");
+        Ok(ssh_dir.join(key_path))
+    } else {
+        Ok(key_path.to_path_buf())
+    }
+}
+
+/// Full path of a key to unlock on request (`ssh-tunnel keys unlock`)
+///
+/// Only plain paths relative to the daemon user's `~/.ssh` are accepted, so
+/// callers can't have the daemon read arbitrary files as keys.
+pub fn resolve_unlock_path(key_path: &Path) -> Result<PathBuf> {
+    let plain = key_path.components().next().is_some()
+        && key_path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !plain {
+        anyhow::bail!(
+            "Keys are unlocked by their path relative to ~/.ssh on the daemon host \
+             (e.g. id_ed25519); absolute paths and '..' are not allowed"
+        );
+    }
+    resolve_key_path(key_path)
+}
+
+/// Secret bytes kept out of swap (best effort) and wiped on drop
+struct LockedBytes {
+    bytes: Zeroizing<Vec<u8>>,
+    locked: bool,
+}
+
+impl LockedBytes {
+    fn new(bytes: Zeroizing<Vec<u8>>) -> Self {
+        // SAFETY: the range is the Vec's own allocation, which is never
+        // resized while it is locked
+        let locked = bytes.capacity() > 0
+            && unsafe { libc::mlock(bytes.as_ptr().cast(), bytes.capacity()) } == 0;
+        if !locked {
+            warn!(
+                "Could not lock key memory (it may be swapped out): {}",
+                std::io::Error::last_os_error()
+            );
+        }
+        Self { bytes, locked }
+    }
+}
+
+impl Drop for LockedBytes {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+        if self.locked {
+            // SAFETY: same range as locked in new()
+            unsafe {
+                libc::munlock(self.bytes.as_ptr().cast(), self.bytes.capacity());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> KeyCache {
+        KeyCache::new(&KeyCacheConfig {
+            enabled: true,
+            ..KeyCacheConfig::default()
+        })
+    }
+
+    fn insert(cache: &KeyCache, path: &str, fingerprint: &str) -> UnlockedKey {
+        cache.insert(
+            Path::new(path),
+            Zeroizing::new(format!("key at {}", path).into_bytes()),
+            "ssh-ed25519".to_string(),
+            fingerprint.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_unlock_and_lock() {
+        let cache = enabled();
+        let unlocked = insert(&cache, "/keys/a", "SHA256:aaa");
+        assert!(unlocked.expires_at.is_some());
+        insert(&cache, "/keys/b", "SHA256:bbb");
+        insert(&cache, "/keys/c", "SHA256:ccc");
+
+        let encoded = cache.with_key(Path::new("/keys/a"), |bytes| bytes.to_vec());
+        assert_eq!(encoded.as_deref(), Some(&b"key at /keys/a"[..]));
+        assert!(cache.with_key(Path::new("/keys/x"), |_| ()).is_none());
+
+        let paths: Vec<String> = cache.list().into_iter().map(|k| k.path).collect();
+        assert_eq!(paths, ["/keys/a", "/keys/b", "/keys/c"]);
+
+        assert_eq!(cache.lock(Some("SHA256:bbb")), 1);
+        assert_eq!(cache.lock(Some("/keys/a")), 1);
+        assert_eq!(cache.lock(Some("/keys/a")), 0);
+        assert_eq!(cache.list().len(), 1);
+        assert_eq!(cache.lock(None), 1);
+        assert!(cache.list().is_empty());
+    }
+
+    #[test]
+    fn test_lock_within() {
+        let cache = enabled();
+        insert(&cache, "/keys/a", "SHA256:aaa");
+        insert(&cache, "/keys/b", "SHA256:bbb");
+        let only_a = |path: &Path| path == Path::new("/keys/a");
+
+        assert_eq!(cache.lock_within(Some("SHA256:bbb"), only_a), 0);
+        assert_eq!(cache.lock_within(None, only_a), 1);
+        let paths: Vec<String> = cache.list().into_iter().map(|k| k.path).collect();
+        assert_eq!(paths, ["/keys/b"]);
+    }
+
+    #[test]
+    fn test_ttl_and_disabled() {
+        let mut cache = KeyCache::new(&KeyCacheConfig {
+            enabled: true,
+            ttl_secs: 0,
+        });
+        assert!(insert(&cache, "/keys/a", "SHA256:aaa").expires_at.is_none());
+        assert!(cache.with_key(Path::new("/keys/a"), |_| ()).is_some());
+
+        cache.ttl = Some(Duration::ZERO);
+        insert(&cache, "/keys/b", "SHA256:bbb");
+        assert!(cache.with_key(Path::new("/keys/b"), |_| ()).is_none());
+        insert(&cache, "/keys/b", "SHA256:bbb");
+        assert_eq!(cache.list().len(), 1);
+
+        let cache = KeyCache::new(&KeyCacheConfig {
+            enabled: false,
+            ttl_secs: 0,
+        });
+        insert(&cache, "/keys/a", "SHA256:aaa");
+        assert!(cache.list().is_empty());
+    }
+
+    #[test]
+    fn test_unlock_path_stays_in_ssh_dir() {
+        for path in ["id_ed25519", "work/id_rsa"] {
+            let resolved = resolve_unlock_path(Path::new(path)).unwrap();
+            assert!(resolved.ends_with(Path::new(".ssh").join(path)));
+        }
+        for path in ["/etc/shadow", "../.bash_history", "keys/../../x", "./id_rsa", ""] {
+            assert!(resolve_unlock_path(Path::new(path)).is_err(), "{}", path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exclusive_per_key() {
+        let cache = enabled();
+        let guard = cache.exclusive(Path::new("/keys/a")).await;
+
+        // Other keys aren't held up
+        drop(cache.exclusive(Path::new("/keys/b")).await);
+
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(50),
+            cache.exclusive(Path::new("/keys/a")),
+        )
+        .await;
+        assert!(waiting.is_err());
+
+        drop(guard);
+        drop(cache.exclusive(Path::new("/keys/a")).await);
+    }
+}
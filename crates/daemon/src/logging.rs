@@ -0,0 +1,700 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Daemon log output
+//
+// The daemon's log goes to stderr, a rotated file or the systemd journal
+// (`[logging]`), as text or JSON Lines. Events inside a tunnel's span carry
+// its ID and profile name: as `tunnel_id`/`profile` in JSON and as
+// `TUNNEL_ID`/`PROFILE` journal fields.
+//
+// Secrets never reach any output: fields named like secrets (`password`,
+// `token`, ...) are replaced by `[REDACTED]`, and so is any occurrence of a
+// registered secret, wherever it appears in a line. API tokens and stored
+// secrets stay registered for the daemon's lifetime; answers to auth prompts
+// only until the tunnel's authentication has finished, so that a short code
+// (e.g. a 6-digit OTP) doesn't hide matching numbers for long. While it is
+// registered it is redacted from every line, not only the tunnel's own.
+// Types holding secrets also redact them in `Debug`.
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::layer::{Context as LayerContext, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry};
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use crate::config::{LogFormat, LogOutput, LoggingConfig};
+use crate::tunnel_log::{self, TunnelLogs};
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Field names whose values are never logged; `*_password`, `*_token` etc. too
+const SENSITIVE_FIELDS: &[&str] = &["password", "passphrase", "secret", "token"];
+
+/// Other exact field names whose values are never logged
+const SENSITIVE_EXACT_FIELDS: &[&str] = &["response", "auth_response", "authorization"];
+
+/// Auth answers kept for redaction at once; only reached if tunnels pile up
+/// prompts without finishing authentication, and then the oldest are dropped
+const MAX_ANSWERS: usize = 64;
+
+/// Shorter values are not worth redacting and would match ordinary text
+const MIN_SECRET_LEN: usize = 4;
+
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// API tokens and stored secrets, never forgotten
+static SECRETS: Mutex<Vec<Zeroizing<String>>> = Mutex::new(Vec::new());
+
+/// Answers to auth prompts with the tunnel they were for
+static ANSWERS: Mutex<VecDeque<(Uuid, Zeroizing<String>)>> = Mutex::new(VecDeque::new());
+
+/// Whether values of a field with this name are redacted
+pub fn is_sensitive_field(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_EXACT_FIELDS.contains(&name.as_str())
+        || SENSITIVE_FIELDS.iter().any(|sensitive| {
+            name == *sensitive
+                || name
+                    .strip_suffix(sensitive)
+                    .is_some_and(|prefix| prefix.ends_with('_'))
+        })
+}
+
+/// Redact `secret` (an API token or stored secret) from all log output from now on
+pub fn register_secret(secret: &str) {
+    if secret.len() < MIN_SECRET_LEN {
+        return;
+    }
+    let mut secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    if secrets.iter().any(|known| known.as_str() == secret) {
+        return;
+    }
+    secrets.push(Zeroizing::new(secret.to_string()));
+}
+
+/// Redact an answer to one of `tunnel_id`'s auth prompts until [`forget_answers`]
+pub fn register_answer(tunnel_id: Uuid, answer: &str) {
+    if answer.len() < MIN_SECRET_LEN {
+        return;
+    }
+    let mut answers = ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+    if answers
+        .iter()
+        .any(|(id, known)| *id == tunnel_id && known.as_str() == answer)
+    {
+        return;
+    }
+    if answers.len() >= MAX_ANSWERS {
+        answers.pop_front();
+    }
+    answers.push_back((tunnel_id, Zeroizing::new(answer.to_string())));
+}
+
+/// The tunnel's authentication has finished; stop redacting its answers
+pub fn forget_answers(tunnel_id: &Uuid) {
+    let mut answers = ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+    answers.retain(|(id, _)| id != tunnel_id);
+}
+
+/// `text` with every registered secret replaced by `[REDACTED]`
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+    let answers = ANSWERS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut text = Cow::Borrowed(text);
+    let registered = secrets.iter().chain(answers.iter().map(|(_, answer)| answer));
+    for secret in registered {
+        if text.contains(secret.as_str()) {
+            text = Cow::Owned(text.replace(secret.as_str(), REDACTED));
+        }
+    }
+    text
+}
+
+/// Stderr output used until the configuration is loaded
+pub fn bootstrap() -> impl Subscriber + Send + Sync {
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .with_env_filter(
+            EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| EnvFilter::new(LoggingConfig::default().filter)),
+        )
+        .finish()
+}
+
+/// Set up the configured log output plus per-tunnel log capture
+pub fn init(config: &LoggingConfig, tunnel_logs: &TunnelLogs) -> Result<()> {
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::try_new(&config.filter)
+            .with_context(|| format!("Invalid [logging] filter '{}'", config.filter))?,
+    };
+
+    let output = match config.output {
+        LogOutput::Stderr => fmt_layer(
+            config.format,
+            Redacting(io::stderr),
+            io::stderr().is_terminal(),
+        ),
+        LogOutput::File => {
+            let file = RotatingFile::open(config)?;
+            fmt_layer(config.format, Redacting(file), false)
+        }
+        LogOutput::Journald => JournaldLayer::connect()
+            .context("Failed to connect to journald (is systemd-journald running?)")?
+            .boxed(),
+    };
+
+    tracing_subscriber::registry()
+        .with(output.with_filter(filter))
+        .with(tunnel_logs.layer())
+        .try_init()
+        .context("Failed to set up logging")
+}
+
+fn fmt_layer<W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(ansi)
+        .fmt_fields(RedactingFields);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.event_format(JsonFormat).boxed(),
+    }
+}
+
+/// Writer that redacts registered secrets from each formatted line
+struct Redacting<M>(M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for Redacting<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+}
+
+struct RedactingWriter<W>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Events are formatted into one buffer and written at once
+        match std::str::from_utf8(buf) {
+            Ok(text) => self.0.write_all(redact(text).as_bytes())?,
+            Err(_) => self.0.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Field formatter for text output: `message key=value ...` with sensitive
+/// fields redacted
+struct RedactingFields;
+
+impl<'writer> FormatFields<'writer> for RedactingFields {
+    fn format_fields<R: RecordFields>(&self, writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = TextVisitor {
+            writer,
+            separator: "",
+            result: Ok(()),
+        };
+        fields.record(&mut visitor);
+        visitor.result
+    }
+}
+
+struct TextVisitor<'writer> {
+    writer: Writer<'writer>,
+    separator: &'static str,
+    result: fmt::Result,
+}
+
+impl Visit for TextVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.result.is_err() || field.name().starts_with("log.") {
+            return;
+        }
+        self.result = match field.name() {
+            "message" => write!(self.writer, "{}{:?}", self.separator, value),
+            name if is_sensitive_field(name) => {
+                write!(self.writer, "{}{}={}", self.separator, name, REDACTED)
+            }
+            name => write!(self.writer, "{}{}={:?}", self.separator, name, value),
+        };
+        self.separator = " ";
+    }
+}
+
+/// One JSON object per event
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+        let meta = event.metadata();
+
+        let mut line = Map::new();
+        line.insert(
+            "timestamp".into(),
+            Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true).into(),
+        );
+        line.insert("level".into(), meta.level().as_str().into());
+        line.insert(
+            "target".into(),
+            fields.log_target.unwrap_or_else(|| meta.target().to_string()).into(),
+        );
+        line.insert("message".into(), fields.message.into());
+        if let Some(tunnel) = tunnel_log::scope_tunnel(ctx.event_scope()) {
+            line.insert("tunnel_id".into(), tunnel.id.to_string().into());
+            line.insert("profile".into(), tunnel.profile.into());
+        }
+        if !fields.values.is_empty() {
+            line.insert("fields".into(), Value::Object(fields.values));
+        }
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Message and fields of an event, with sensitive fields redacted
+#[derive(Default)]
+struct EventFields {
+    message: String,
+    values: Map<String, Value>,
+    /// Original target of events bridged from the `log` crate
+    log_target: Option<String>,
+}
+
+impl EventFields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        match field.name() {
+            name if name.starts_with("log.") => {}
+            name if is_sensitive_field(name) => {
+                self.values.insert(name.to_string(), REDACTED.into());
+            }
+            name => {
+                self.values.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl Visit for EventFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = redact(value).into_owned(),
+            "log.target" => self.log_target = Some(value.to_string()),
+            _ => self.insert(field, redact(value).into_owned().into()),
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let value = redact(&format!("{:?}", value)).into_owned();
+        match field.name() {
+            "message" => self.message = value,
+            _ => self.insert(field, value.into()),
+        }
+    }
+}
+
+/// Log file rotated by size and age like the audit log: `daemon.log` is
+/// current, `daemon.log.1` the newest rotated file
+struct RotatingFile {
+    path: PathBuf,
+    max_file_bytes: u64,
+    max_file_age: Option<Duration>,
+    max_files: usize,
+    state: Mutex<FileState>,
+}
+
+struct FileState {
+    file: Option<File>,
+    size: u64,
+    opened: SystemTime,
+}
+
+impl RotatingFile {
+    fn open(config: &LoggingConfig) -> Result<Self> {
+        if let Some(parent) = config.path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create log directory {}", parent.display())
+            })?;
+        }
+        let log = Self {
+            path: config.path.clone(),
+            max_file_bytes: config.max_file_bytes,
+            max_file_age: (config.max_file_age_hours > 0)
+                .then(|| Duration::from_secs(config.max_file_age_hours * 3600)),
+            max_files: config.max_files,
+            state: Mutex::new(FileState {
+                file: None,
+                size: 0,
+                opened: SystemTime::now(),
+            }),
+        };
+        let state = log.open_current()?;
+        *log.state.lock().unwrap() = state;
+        Ok(log)
+    }
+
+    fn open_current(&self) -> io::Result<FileState> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        crate::permissions::set_file_permissions_private(&self.path).map_err(io::Error::other)?;
+        let metadata = file.metadata()?;
+        Ok(FileState {
+            size: metadata.len(),
+            // A file left by an earlier run ages from when it was created
+            opened: metadata.created().unwrap_or_else(|_| SystemTime::now()),
+            file: Some(file),
+        })
+    }
+
+    fn write_line(&self, line: &[u8]) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let too_big = state.size + line.len() as u64 > self.max_file_bytes;
+        let too_old = self
+            .max_file_age
+            .is_some_and(|age| state.opened.elapsed().is_ok_and(|elapsed| elapsed >= age));
+        if state.size > 0 && (too_big || too_old) {
+            state.file = None;
+            crate::audit::rotate_files(&self.path, self.max_files).map_err(io::Error::other)?;
+        }
+        if state.file.is_none() {
+            *state = self.open_current()?;
+        }
+
+        state
+            .file
+            .as_mut()
+            .expect("log file is open")
+            .write_all(line)?;
+        state.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for RotatingFile {
+    type Writer = RotatingFileWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RotatingFileWriter(self)
+    }
+}
+
+struct RotatingFileWriter<'a>(&'a RotatingFile);
+
+impl Write for RotatingFileWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write_line(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sends events to journald over its native protocol
+struct JournaldLayer {
+    socket: UnixDatagram,
+}
+
+impl JournaldLayer {
+    fn connect() -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(JOURNALD_SOCKET)?;
+        Ok(Self { socket })
+    }
+}
+
+impl<S> Layer<S> for JournaldLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+        let meta = event.metadata();
+
+        let mut payload = Vec::new();
+        journald_field(&mut payload, "MESSAGE", &fields.message);
+        journald_field(&mut payload, "PRIORITY", priority(meta.level()));
+        journald_field(&mut payload, "SYSLOG_IDENTIFIER", "ssh-tunnel-daemon");
+        journald_field(
+            &mut payload,
+            "TARGET",
+            fields.log_target.as_deref().unwrap_or(meta.target()),
+        );
+        if let Some(file) = meta.file() {
+            journald_field(&mut payload, "CODE_FILE", file);
+        }
+        if let Some(line) = meta.line() {
+            journald_field(&mut payload, "CODE_LINE", &line.to_string());
+        }
+        if let Some(tunnel) = tunnel_log::scope_tunnel(ctx.event_scope(event)) {
+            journald_field(&mut payload, "TUNNEL_ID", &tunnel.id.to_string());
+            journald_field(&mut payload, "PROFILE", &tunnel.profile);
+        }
+        for (name, value) in &fields.values {
+            let Some(name) = journald_name(name) else {
+                continue;
+            };
+            match value {
+                Value::String(value) => journald_field(&mut payload, &name, value),
+                value => journald_field(&mut payload, &name, &value.to_string()),
+            }
+        }
+
+        // Nowhere to report a failure to log
+        let _ = self.socket.send(&payload);
+    }
+}
+
+/// syslog priority of a level
+fn priority(level: &Level) -> &'static str {
+    match *level {
+        Level::ERROR => "3",
+        Level::WARN => "4",
+        Level::INFO => "6",
+        Level::DEBUG | Level::TRACE => "7",
+    }
+}
+
+/// Journal field name for an event field: uppercase letters, digits and `_`,
+/// not starting with `_` (reserved for trusted fields) or a digit
+fn journald_name(name: &str) -> Option<String> {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .skip_while(|c| *c == '_' || c.is_ascii_digit())
+        .take(64)
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Append one field in journald's native format; values with newlines use
+/// the length-prefixed binary form
+fn journald_field(payload: &mut Vec<u8>, name: &str, value: &str) {
+    payload.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        payload.push(b'\n');
+        payload.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        payload.push(b'=');
+    }
+    payload.extend_from_slice(value.as_bytes());
+    payload.push(b'\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tracing::info;
+    use uuid::Uuid;
+
+    /// Captures formatted output
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Output {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Output {
+        type Writer = Output;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn log_secrets(format: LogFormat) -> String {
+        let output = Output::default();
+        let tunnel_logs = TunnelLogs::new(&Default::default());
+        let subscriber = tracing_subscriber::registry()
+            .with(fmt_layer(format, Redacting(output.clone()), false))
+            .with(tunnel_logs.layer());
+
+        register_secret("s3cr3t-token-value");
+        let id = Uuid::new_v4();
+        tracing::subscriber::with_default(subscriber, || {
+            tunnel_log::tunnel_span(id, "db").in_scope(|| {
+                info!(password = "hunter2", auth_token = %"abc123", user = "alice", "Logging in");
+                info!("Header was s3cr3t-token-value");
+            });
+        });
+
+        // The in-memory tunnel log is redacted as well
+        let captured: Vec<String> = tunnel_logs
+            .entries(&id, None, None)
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(
+            captured,
+            [
+                "Logging in password=[REDACTED] auth_token=[REDACTED] user=alice",
+                "Header was [REDACTED]",
+            ]
+        );
+
+        output.text()
+    }
+
+    #[test]
+    fn test_secrets_are_redacted() {
+        let text = log_secrets(LogFormat::Text);
+        assert!(text.contains("password=[REDACTED]"), "{}", text);
+        assert!(text.contains("user=\"alice\""), "{}", text);
+        assert!(text.contains("Header was [REDACTED]"), "{}", text);
+
+        let json = log_secrets(LogFormat::Json);
+        let lines: Vec<Value> = json.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["message"], "Logging in");
+        assert_eq!(lines[0]["profile"], "db");
+        assert_eq!(lines[0]["fields"]["password"], REDACTED);
+        assert_eq!(lines[0]["fields"]["auth_token"], REDACTED);
+        assert_eq!(lines[0]["fields"]["user"], "alice");
+        assert_eq!(lines[1]["message"], "Header was [REDACTED]");
+
+        for output in [text, json] {
+            assert!(!output.contains("hunter2"));
+            assert!(!output.contains("abc123"));
+            assert!(!output.contains("s3cr3t-token-value"));
+        }
+    }
+
+    #[test]
+    fn test_answers_are_forgotten_with_their_tunnel() {
+        let token = format!("token-{}", Uuid::new_v4());
+        register_secret(&token);
+        let (tunnel, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let otp = format!("otp-{}", Uuid::new_v4());
+        let password = format!("password-{}", Uuid::new_v4());
+        register_answer(tunnel, &otp);
+        register_answer(other, &password);
+        assert_eq!(redact(&format!("{} {}", otp, password)), "[REDACTED] [REDACTED]");
+
+        forget_answers(&tunnel);
+        assert_eq!(redact(&format!("{} {}", otp, password)), format!("{} [REDACTED]", otp));
+
+        // However many prompts are answered, tokens stay redacted
+        for _ in 0..=MAX_ANSWERS {
+            register_answer(other, &Uuid::new_v4().to_string());
+        }
+        assert_eq!(redact(&format!("Bearer {}", token)), "Bearer [REDACTED]");
+        forget_answers(&other);
+    }
+
+    #[test]
+    fn test_sensitive_field_names() {
+        assert!(is_sensitive_field("password"));
+        assert!(is_sensitive_field("new_token"));
+        assert!(is_sensitive_field("Response"));
+        assert!(!is_sensitive_field("token_name"));
+        assert!(!is_sensitive_field("user"));
+    }
+
+    #[test]
+    fn test_rotating_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = LoggingConfig {
+            path: temp_dir.path().join("logs").join("daemon.log"),
+            max_file_bytes: 100,
+            max_files: 2,
+            ..Default::default()
+        };
+        let file = RotatingFile::open(&config).unwrap();
+
+        let line = [b'x'; 39];
+        for _ in 0..10 {
+            file.make_writer().write_all(&line).unwrap();
+        }
+
+        // Two lines per file; the oldest rotated files are dropped
+        assert_eq!(fs::metadata(&config.path).unwrap().len(), 78);
+        assert!(crate::audit::rotated_path(&config.path, 2).exists());
+        assert!(!crate::audit::rotated_path(&config.path, 3).exists());
+    }
+
+    #[test]
+    fn test_journald_fields() {
+        let mut payload = Vec::new();
+        journald_field(&mut payload, "PROFILE", "db");
+        journald_field(&mut payload, "MESSAGE", "a\nb");
+        assert_eq!(payload, b"PROFILE=db\nMESSAGE\n\x03\0\0\0\0\0\0\0a\nb\n");
+
+        assert_eq!(journald_name("bound_addr").as_deref(), Some("BOUND_ADDR"));
+        assert_eq!(journald_name("_1x.y").as_deref(), Some("X_Y"));
+        assert_eq!(journald_name("__"), None);
+    }
+}
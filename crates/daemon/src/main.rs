@@ -5,16 +5,33 @@
 // Core service for managing SSH tunnels
 
 mod api;
+mod askpass;
+mod audit;
 mod auth;
 mod config;
+mod events;
+mod history;
+mod hooks;
+mod keycache;
 mod known_hosts;
+mod logging;
+mod metrics;
 mod monitor;
+mod peercred;
 mod permissions;
 mod pidfile;
+mod ratelimit;
 mod security;
+mod shell;
+mod systemd;
 mod tls;
+mod traffic;
 mod tunnel;
+mod tunnel_log;
+mod ws;
 
+use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::error::Error;
 
@@ -22,14 +39,14 @@ use anyhow::{Context, Result};
 use axum_server::tls_rustls::RustlsConfig;
 use axum_server::Handle;
 use hyper_util::rt::TokioIo;
+use ipnet::IpNet;
 use tokio::net::UnixListener;
 use tower::Service;
 use tracing::{debug, error, info};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use api::{create_router, AppState};
-use config::{DaemonConfig, ListenerMode};
-use tunnel::TunnelManager;
+use config::{ClientAuthConfig, DaemonConfig, ListenerMode};
+use tunnel::{TunnelEvent, TunnelManager};
 
 /// Connection error categories for better diagnostics
 ///
@@ -220,19 +237,28 @@ fn log_connection_error(err: &Box<dyn std::error::Error + Send + Sync>, listener
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
+    // Sockets handed over by systemd socket activation, if any; this clears
+    // their environment variables, which must happen before threads exist
+    let activated = systemd::ActivatedSockets::from_env();
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the async runtime")?
+        .block_on(run(activated))
+}
+
+async fn run(mut activated: systemd::ActivatedSockets) -> Result<()> {
     // Set restrictive umask before creating any files
     permissions::set_restrictive_umask();
 
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "ssh_tunnel_daemon=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Load daemon configuration; until then, log to stderr
+    let daemon_config = tracing::subscriber::with_default(logging::bootstrap(), DaemonConfig::load)?;
+
+    // Initialize logging; per-tunnel logs are captured regardless of the filter
+    let tunnel_logs = tunnel_log::TunnelLogs::new(&daemon_config.tunnel_logs);
+    logging::init(&daemon_config.logging, &tunnel_logs)?;
 
     info!("SSH Tunnel Manager Daemon starting...");
     info!("Version: {}", env!("CARGO_PKG_VERSION"));
@@ -246,156 +272,305 @@ async fn main() -> Result<()> {
     let _pid_guard = pidfile::PidFileGuard::create()
         .context("Failed to create PID file - another daemon may already be running")?;
 
-    // Load daemon configuration
-    let daemon_config = DaemonConfig::load()?;
-    info!("Listener mode: {:?}", daemon_config.listener_mode);
-    info!("Authentication required: {}", daemon_config.require_auth);
+    let listeners = daemon_config.effective_listeners();
+    for listener in &listeners {
+        info!(
+            "Listener: {:?} on {} (auth required: {})",
+            listener.mode,
+            listener.display_address(),
+            listener.requires_auth(daemon_config.require_auth)
+        );
+    }
 
-    // Load or generate authentication token if required
-    let (auth_token, token_was_generated) = if daemon_config.require_auth {
-        let (token, was_new) = auth::load_or_generate_token(&daemon_config.auth_token_path)?;
-        (Some(token), was_new)
+    // Load or generate authentication token if any listener requires it
+    // The plaintext is only known when the token was generated (or migrated) in this run
+    let (primary_token, new_token) = if daemon_config.any_listener_requires_auth() {
+        let (primary, token) = auth::PrimaryToken::load_or_generate(&daemon_config.auth_token_path)?;
+        (Some(Arc::new(tokio::sync::RwLock::new(primary))), token)
     } else {
         info!("Authentication disabled - API endpoints are publicly accessible");
-        (None, false)
+        (None, None)
     };
 
-    // For HTTPS mode, ensure certificate is valid and get fingerprint
+    // For HTTPS listeners, ensure certificate is valid and get fingerprint
     // This must happen BEFORE writing the CLI config snippet
     // We use create_tls_config() which handles both generation and expiry checking,
     // ensuring we get the fingerprint of the actual cert that will be used
-    let tls_fingerprint = if daemon_config.listener_mode == ListenerMode::TcpHttps {
+    let tls_fingerprint = if daemon_config.has_tls_listener() {
         // Create TLS config - this handles generation, expiry checking, and auto-regeneration
-        let _ = tls::create_tls_config(&daemon_config.tls_cert_path, &daemon_config.tls_key_path)?;
+        // The client CA bundle (mTLS) is validated here as well
+        let _ = tls::create_tls_config(
+            &daemon_config.tls_cert_path,
+            &daemon_config.tls_key_path,
+            daemon_config.client_auth.as_ref(),
+        )?;
         // Now we can safely get the fingerprint of the current, valid certificate
         Some(tls::get_cert_fingerprint(&daemon_config.tls_cert_path)?)
     } else {
         None
     };
 
-    // Write CLI config snippet if token was newly generated OR in HTTPS mode
+    // Write CLI config snippet if token was newly generated OR an HTTPS listener exists
     // (HTTPS always writes snippet because cert may have been regenerated)
-    if token_was_generated || daemon_config.listener_mode == ListenerMode::TcpHttps {
+    if new_token.is_some() || daemon_config.has_tls_listener() {
         config::write_cli_config_snippet(
-            &daemon_config.listener_mode,
-            &daemon_config.bind_host,
-            daemon_config.bind_port,
-            auth_token.as_deref(),
+            &daemon_config,
+            new_token.as_deref(),
             tls_fingerprint.as_deref(),
         )?;
     }
 
     // Create the tunnel manager
-    let tunnel_manager = TunnelManager::new(daemon_config.known_hosts_path.clone());
+    let tunnel_manager = TunnelManager::new(daemon_config.known_hosts_path.clone())
+        .with_ephemeral_port_range(daemon_config.ephemeral_port_range)
+        .with_stats_interval(daemon_config.stats_interval_secs)
+        .with_auth_prompts(daemon_config.auth_prompts.clone())
+        .with_secrets(&daemon_config.secrets)
+        .with_key_cache(&daemon_config.key_cache);
+
+    // Wipe decrypted keys once their TTL runs out
+    if let Some(ttl) = tunnel_manager.key_cache().ttl() {
+        let key_cache = tunnel_manager.key_cache().clone();
+        let period = ttl.min(std::time::Duration::from_secs(30));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                key_cache.purge_expired();
+            }
+        });
+    }
 
     // Subscribe to tunnel events for logging
     let mut event_rx = tunnel_manager.subscribe();
     tokio::spawn(async move {
         while let Ok(event) = event_rx.recv().await {
-            info!("Tunnel event: {:?}", event);
+            // Stats arrive every few seconds per tunnel; keep them out of the default log
+            if matches!(event.event, TunnelEvent::Stats { .. }) {
+                debug!("Tunnel event #{}: {:?}", event.seq, event.event);
+            } else {
+                info!("Tunnel event #{}: {:?}", event.seq, event.event);
+            }
         }
     });
 
+    // Additional scoped API tokens (see `ssh-tunnel token`)
+    let tokens = Arc::new(tokio::sync::RwLock::new(auth::TokenRegistry::load(
+        &daemon_config.tokens_path,
+    )?));
+
+    // Audit log of API actions and tunnel state changes
+    let audit_log = if daemon_config.audit.enabled {
+        let audit_log = Arc::new(audit::AuditLog::open(&daemon_config.audit)?);
+        audit::spawn_transition_recorder(audit_log.clone(), tunnel_manager.clone());
+        Some(audit_log)
+    } else {
+        info!("Audit log disabled");
+        None
+    };
+
+    // Per-profile session history
+    let history = if daemon_config.history.enabled {
+        let history = Arc::new(history::SessionHistory::open(&daemon_config.history)?);
+        history::spawn_recorder(history.clone(), tunnel_manager.clone());
+        Some(history)
+    } else {
+        info!("Session history disabled");
+        None
+    };
+
+    // Failed-authentication tracking shared by all listeners
+    let auth_limiter = Arc::new(ratelimit::AuthLimiter::new(daemon_config.auth_limits.clone()));
+
     // Create shutdown broadcast channel for graceful SSE stream termination
+    // and for stopping every listener at once
     let (shutdown_tx, _) = tokio::sync::broadcast::channel(1);
+    let shutdown_request = Arc::new(tokio::sync::Notify::new());
 
     // Create shared state
     let state = Arc::new(AppState {
         tunnel_manager,
         shutdown_tx: shutdown_tx.clone(),
+        shutdown_request: shutdown_request.clone(),
         started_at: Arc::new(tokio::sync::RwLock::new(std::time::SystemTime::now())),
         config: Arc::new(daemon_config.clone()),
+        tokens: tokens.clone(),
+        primary_token: primary_token.clone(),
+        auth_limiter: auth_limiter.clone(),
+        audit_log,
+        history: history.clone(),
+        tunnel_logs,
     });
     let shutdown_manager = state.tunnel_manager.clone();
+    let metrics_state = state.clone();
+
+    // One router shared by all listeners; authentication is layered per listener
+    let router = create_router(state);
+    let auth_state =
+        primary_token.map(|primary| auth::AuthState::new(primary, tokens, auth_limiter));
+
+    activated.log_received();
+
+    // Bind every configured listener before serving, so readiness means "accepting"
+    let mut servers = tokio::task::JoinSet::new();
+    for listener in listeners {
+        let app = match &auth_state {
+            Some(auth_state) if listener.requires_auth(daemon_config.require_auth) => {
+                router.clone().layer(axum::middleware::from_fn_with_state(
+                    auth_state.clone(),
+                    auth::auth_middleware,
+                ))
+            }
+            _ => router.clone(),
+        };
+        let shutdown_rx = shutdown_tx.subscribe();
+
+        match bind_listener(&listener, &daemon_config, &mut activated)? {
+            BoundListener::Unix(std_listener, socket_path) => {
+                let peer_policy = peercred::PeerPolicy::new(daemon_config.peer_access.clone());
+                servers.spawn(serve_unix_socket(
+                    app,
+                    std_listener,
+                    socket_path,
+                    peer_policy,
+                    shutdown_rx,
+                ));
+            }
+            BoundListener::Tcp(std_listener) if listener.mode == ListenerMode::TcpHttps => {
+                servers.spawn(serve_tcp_https(
+                    app,
+                    std_listener,
+                    daemon_config.tls_cert_path.clone(),
+                    daemon_config.tls_key_path.clone(),
+                    daemon_config.client_auth.clone(),
+                    daemon_config.allowed_networks()?,
+                    shutdown_rx,
+                ));
+            }
+            BoundListener::Tcp(std_listener) => {
+                servers.spawn(serve_tcp_http(app, std_listener, shutdown_rx));
+            }
+        }
+    }
+    // Optional scrape listener without token authentication (loopback only)
+    if let Some(address) = daemon_config.metrics.listen_address() {
+        let std_listener = std::net::TcpListener::bind(&address)
+            .with_context(|| format!("Failed to bind metrics listener on {}", address))?;
+        servers.spawn(metrics::serve(metrics_state, std_listener, shutdown_tx.subscribe()));
+    }
+    activated.close_unclaimed();
+    info!("Daemon started successfully");
+    systemd::spawn_notifier(shutdown_manager.clone());
 
-    // Create API router with optional authentication
-    let app = if let Some(token) = auth_token {
-        let auth_state = auth::AuthState::new(token);
-        create_router(state)
-            .layer(axum::middleware::from_fn_with_state(
-                auth_state,
-                auth::auth_middleware,
-            ))
-    } else {
-        create_router(state)
-    };
+    // Stop all tunnels on Ctrl+C/SIGTERM or an API shutdown, then tell SSE
+    // streams and listeners to finish
+    tokio::spawn(async move {
+        wait_for_shutdown(shutdown_manager, shutdown_request).await;
+        let _ = shutdown_tx.send(());
+    });
 
-    // Start listener based on configured mode
-    match daemon_config.listener_mode {
-        ListenerMode::UnixSocket => {
-            serve_unix_socket(app, &daemon_config, shutdown_manager.clone(), shutdown_tx).await?;
-        }
-        ListenerMode::TcpHttp => {
-            let bind_address = ssh_tunnel_common::format_host_port(&daemon_config.bind_host, daemon_config.bind_port);
-            serve_tcp_http(app, &bind_address, shutdown_manager.clone(), shutdown_tx)
-                .await?;
-        }
-        ListenerMode::TcpHttps => {
-            let bind_address = ssh_tunnel_common::format_host_port(&daemon_config.bind_host, daemon_config.bind_port);
-            serve_tcp_https(
-                app,
-                &bind_address,
-                &daemon_config.tls_cert_path,
-                &daemon_config.tls_key_path,
-                shutdown_manager.clone(),
-                shutdown_tx,
-            )
-            .await?;
-        }
+    // If any listener fails, the daemon exits with its error
+    while let Some(result) = servers.join_next().await {
+        result.context("Listener task panicked")??;
+    }
+
+    // Sessions whose end the recorder hasn't seen yet
+    if let Some(history) = history {
+        history.end_all("Daemon shut down");
     }
 
     info!("Daemon shut down");
     Ok(())
 }
 
-/// Serve on Unix domain socket (local-only, no TLS)
-async fn serve_unix_socket(
-    app: axum::Router,
-    daemon_config: &DaemonConfig,
-    tunnel_manager: TunnelManager,
-    shutdown_tx: tokio::sync::broadcast::Sender<()>,
-) -> Result<()> {
-    // Get socket path
-    let socket_path = config::socket_path()?;
+/// A bound API listener, ready to serve
+enum BoundListener {
+    /// Unix socket plus the path to remove on shutdown (None when systemd owns it)
+    Unix(std::os::unix::net::UnixListener, Option<PathBuf>),
+    Tcp(std::net::TcpListener),
+}
 
-    // Remove existing socket file if it exists
-    if socket_path.exists() {
-        std::fs::remove_file(&socket_path).context("Failed to remove existing socket file")?;
+/// Bind a listener, or adopt the matching socket passed by systemd
+fn bind_listener(
+    listener: &config::ListenerConfig,
+    daemon_config: &DaemonConfig,
+    activated: &mut systemd::ActivatedSockets,
+) -> Result<BoundListener> {
+    if let Some(socket) = activated.take_for(listener) {
+        info!(
+            "Using systemd socket '{}' for {} listener",
+            socket.name(),
+            listener.mode.as_str()
+        );
+        return Ok(match listener.mode {
+            ListenerMode::UnixSocket => BoundListener::Unix(socket.into_unix_listener(), None),
+            ListenerMode::TcpHttp => {
+                let std_listener = socket.into_tcp_listener();
+                systemd::check_http_socket(&std_listener)?;
+                BoundListener::Tcp(std_listener)
+            }
+            ListenerMode::TcpHttps => BoundListener::Tcp(socket.into_tcp_listener()),
+        });
     }
 
-    // Create parent directory with appropriate permissions
-    if let Some(parent) = socket_path.parent() {
-        permissions::ensure_directory_with_permissions(parent, daemon_config.group_access)?;
-    }
+    match listener.mode {
+        ListenerMode::UnixSocket => {
+            // Get socket path
+            let socket_path = config::socket_path()?;
 
-    // Bind to Unix socket
-    let listener = UnixListener::bind(&socket_path).context(format!(
-        "Failed to bind to socket: {}",
-        socket_path.display()
-    ))?;
+            // Remove existing socket file if it exists
+            if socket_path.exists() {
+                std::fs::remove_file(&socket_path).context("Failed to remove existing socket file")?;
+            }
 
-    // Set socket permissions immediately after binding
-    permissions::set_socket_permissions(&socket_path, daemon_config.group_access)?;
+            // Create parent directory with appropriate permissions
+            if let Some(parent) = socket_path.parent() {
+                permissions::ensure_directory_with_permissions(parent, daemon_config.group_access)?;
+            }
 
-    info!("Daemon listening on Unix socket: {}", socket_path.display());
-    info!("Daemon started successfully");
+            // Bind to Unix socket
+            let std_listener = std::os::unix::net::UnixListener::bind(&socket_path).context(format!(
+                "Failed to bind to socket: {}",
+                socket_path.display()
+            ))?;
 
-    // Set up shutdown signal
-    let (shutdown_signal_tx, mut shutdown_rx) = tokio::sync::mpsc::channel::<()>(1);
-    let shutdown_broadcast = shutdown_tx.clone();
-    tokio::spawn(async move {
-        wait_for_shutdown(tunnel_manager).await;
-        // Signal SSE streams to close
-        let _ = shutdown_broadcast.send(());
-        // Signal server to stop accepting connections
-        let _ = shutdown_signal_tx.send(()).await;
-    });
+            // Set socket permissions immediately after binding
+            permissions::set_socket_permissions(&socket_path, daemon_config.group_access)?;
+
+            Ok(BoundListener::Unix(std_listener, Some(socket_path)))
+        }
+        ListenerMode::TcpHttp | ListenerMode::TcpHttps => {
+            let bind_address = ssh_tunnel_common::format_host_port(&listener.bind_host, listener.bind_port);
+            let std_listener = std::net::TcpListener::bind(&bind_address)
+                .context(format!("Failed to bind to {}", bind_address))?;
+            Ok(BoundListener::Tcp(std_listener))
+        }
+    }
+}
+
+/// Serve on Unix domain socket (local-only, no TLS)
+async fn serve_unix_socket(
+    app: axum::Router,
+    std_listener: std::os::unix::net::UnixListener,
+    socket_path: Option<PathBuf>,
+    peer_policy: peercred::PeerPolicy,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+) -> Result<()> {
+    std_listener
+        .set_nonblocking(true)
+        .context("Failed to set listener to non-blocking")?;
+    let listener = UnixListener::from_std(std_listener).context("Failed to register Unix socket")?;
+
+    match listener.local_addr().ok().and_then(|addr| addr.as_pathname().map(|p| p.to_path_buf())) {
+        Some(path) => info!("Daemon listening on Unix socket: {}", path.display()),
+        None => info!("Daemon listening on Unix socket"),
+    }
 
     // Accept connections
     loop {
         tokio::select! {
             _ = shutdown_rx.recv() => {
-                info!("Shutting down server...");
+                info!("Shutting down Unix socket server...");
                 break;
             }
 
@@ -403,12 +578,17 @@ async fn serve_unix_socket(
                 match accept_result {
                     Ok((stream, _addr)) => {
                         let app = app.clone();
+                        let peer_policy = peer_policy.clone();
 
                         tokio::spawn(async move {
+                            let Some(peer) = peer_policy.authorize(&stream).await else {
+                                return;
+                            };
                             let stream = TokioIo::new(stream);
 
-                            let hyper_service = hyper::service::service_fn(move |request: hyper::Request<hyper::body::Incoming>| {
+                            let hyper_service = hyper::service::service_fn(move |mut request: hyper::Request<hyper::body::Incoming>| {
                                 let mut app = app.clone();
+                                request.extensions_mut().insert(peer.clone());
                                 async move {
                                     app.call(request).await
                                 }
@@ -430,9 +610,11 @@ async fn serve_unix_socket(
         }
     }
 
-    // Cleanup socket
-    if socket_path.exists() {
-        let _ = std::fs::remove_file(&socket_path);
+    // Cleanup socket (only if we created it; systemd owns activated sockets)
+    if let Some(socket_path) = socket_path {
+        if socket_path.exists() {
+            let _ = std::fs::remove_file(&socket_path);
+        }
     }
 
     Ok(())
@@ -441,20 +623,22 @@ async fn serve_unix_socket(
 /// Serve on TCP with HTTP (localhost-only, no TLS)
 async fn serve_tcp_http(
     app: axum::Router,
-    bind_address: &str,
-    tunnel_manager: TunnelManager,
-    shutdown_tx: tokio::sync::broadcast::Sender<()>,
+    std_listener: std::net::TcpListener,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
 ) -> Result<()> {
-    info!("Daemon listening on TCP (HTTP): {}", bind_address);
-    info!("⚠️  WARNING: HTTP mode has no encryption - use only on localhost!");
-    info!("Daemon started successfully");
+    std_listener
+        .set_nonblocking(true)
+        .context("Failed to set listener to non-blocking")?;
+    let listener = tokio::net::TcpListener::from_std(std_listener)
+        .context("Failed to register TCP listener")?;
 
-    let listener = tokio::net::TcpListener::bind(bind_address)
-        .await
-        .context(format!("Failed to bind to {}", bind_address))?;
+    info!("Daemon listening on TCP (HTTP): {}", listener.local_addr()?);
+    info!("⚠️  WARNING: HTTP mode has no encryption - use only on localhost!");
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal(tunnel_manager, shutdown_tx))
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            let _ = shutdown_rx.recv().await;
+        })
         .await
         .context("TCP HTTP server error")?;
 
@@ -464,23 +648,29 @@ async fn serve_tcp_http(
 /// Serve on TCP with HTTPS/TLS (network-ready, secure)
 async fn serve_tcp_https(
     app: axum::Router,
-    bind_address: &str,
-    cert_path: &std::path::Path,
-    key_path: &std::path::Path,
-    tunnel_manager: TunnelManager,
-    shutdown_tx: tokio::sync::broadcast::Sender<()>,
+    std_listener: std::net::TcpListener,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    client_auth: Option<ClientAuthConfig>,
+    allowed_networks: Vec<IpNet>,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
 ) -> Result<()> {
     // Create or load TLS configuration
-    let rustls_config = tls::create_tls_config(cert_path, key_path)?;
+    let rustls_config = tls::create_tls_config(&cert_path, &key_path, client_auth.as_ref())?;
     let tls_config = RustlsConfig::from_config(rustls_config);
 
-    info!("Daemon listening on TCP (HTTPS): {}", bind_address);
+    info!("Daemon listening on TCP (HTTPS): {}", std_listener.local_addr()?);
     info!("TLS enabled - secure for network access");
-    info!("Daemon started successfully");
-
-    // Bind to TCP address using std::net::TcpListener (required by axum_server)
-    let std_listener = std::net::TcpListener::bind(bind_address)
-        .context(format!("Failed to bind to {}", bind_address))?;
+    if let Some(client_auth) = &client_auth {
+        info!(
+            "Client certificate authentication enabled ({}), CA: {}",
+            if client_auth.required { "required" } else { "optional" },
+            client_auth.ca_path.display()
+        );
+    }
+    if !allowed_networks.is_empty() {
+        info!("Accepting connections only from: {}", format_networks(&allowed_networks));
+    }
 
     // Set to non-blocking mode for tokio
     std_listener
@@ -490,32 +680,34 @@ async fn serve_tcp_https(
     let handle = Handle::new();
     let shutdown_handle = handle.clone();
     tokio::spawn(async move {
-        shutdown_signal(tunnel_manager, shutdown_tx).await;
+        let _ = shutdown_rx.recv().await;
         // Connections should close immediately when SSE streams end
         shutdown_handle.graceful_shutdown(None);
     });
 
-    axum_server::from_tcp_rustls(std_listener, tls_config)
+    axum_server::from_tcp(std_listener)
+        .acceptor(tls::NetworkAllowlist::new(
+            allowed_networks,
+            tls::ClientCertAcceptor::new(tls_config, client_auth),
+        ))
         .handle(handle)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .context("TCP HTTPS server error")?;
 
     Ok(())
 }
 
-/// Graceful shutdown signal handler
-async fn shutdown_signal(
-    tunnel_manager: TunnelManager,
-    shutdown_tx: tokio::sync::broadcast::Sender<()>,
-) {
-    wait_for_shutdown(tunnel_manager).await;
-    // Signal all SSE streams to close
-    let _ = shutdown_tx.send(());
+fn format_networks(networks: &[IpNet]) -> String {
+    networks
+        .iter()
+        .map(|network| network.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
-/// Wait for Ctrl+C or SIGTERM, then stop all tunnels and notify receivers (used by Unix socket server)
-async fn wait_for_shutdown(tunnel_manager: TunnelManager) {
+/// Wait for Ctrl+C, SIGTERM or a shutdown request from the API, then stop all tunnels
+async fn wait_for_shutdown(tunnel_manager: TunnelManager, request: Arc<tokio::sync::Notify>) {
     #[cfg(unix)]
     let mut sigterm = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
         .expect("Failed to install SIGTERM handler");
@@ -528,6 +720,9 @@ async fn wait_for_shutdown(tunnel_manager: TunnelManager) {
         _ = sigterm.recv() => {
             info!("Received SIGTERM, shutting down");
         }
+        _ = request.notified() => {
+            info!("Shutdown requested via API, shutting down");
+        }
     };
 
     #[cfg(not(unix))]
@@ -535,8 +730,12 @@ async fn wait_for_shutdown(tunnel_manager: TunnelManager) {
         _ = tokio::signal::ctrl_c() => {
             info!("Received Ctrl+C, shutting down");
         }
+        _ = request.notified() => {
+            info!("Shutdown requested via API, shutting down");
+        }
     };
 
+    systemd::notify("STOPPING=1");
     tunnel_manager.stop_all().await;
     info!("All tunnels stopped");
 }
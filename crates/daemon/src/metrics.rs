@@ -0,0 +1,413 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Prometheus / OpenMetrics exposition
+//
+// Every profile the daemon has started has a `TunnelMetrics`, carried over
+// when the tunnel is restarted so counters only reset with the daemon.
+// `/api/v1/metrics` (read scope) renders them together with the current
+// tunnel state; `[metrics] bind_port` adds a loopback listener that serves
+// only `/metrics`, without token authentication, for scrapers.
+
+use std::fmt::{Display, Write as _};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use ssh_tunnel_common::TunnelStatus;
+
+use crate::api::AppState;
+use crate::traffic::TrafficCounters;
+
+/// Lifetime counters of one profile's tunnel
+#[derive(Debug, Default)]
+pub struct TunnelMetrics {
+    /// Forwarded traffic, shared with the forward task
+    pub traffic: Arc<TrafficCounters>,
+    starts: AtomicU64,
+    auth_failures: AtomicU64,
+}
+
+impl TunnelMetrics {
+    pub fn record_start(&self) {
+        self.starts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_auth_failure(&self) {
+        self.auth_failures.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// State of one tunnel at scrape time
+pub struct TunnelSample {
+    pub id: Uuid,
+    pub name: String,
+    pub status: TunnelStatus,
+    /// Time since the tunnel connected, while it is connected
+    pub session_age: Option<Duration>,
+    pub pending_auth: bool,
+    pub metrics: Arc<TunnelMetrics>,
+}
+
+/// Exposition format, negotiated from the `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Prometheus,
+    OpenMetrics,
+}
+
+impl Format {
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        match accept {
+            Some(accept) if accept.contains("application/openmetrics-text") => Format::OpenMetrics,
+            _ => Format::Prometheus,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Prometheus => "text/plain; version=0.0.4; charset=utf-8",
+            Format::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Counter,
+    Gauge,
+    Info,
+}
+
+/// Builds the text exposition; metric families must be written in one go
+struct Writer {
+    out: String,
+    format: Format,
+}
+
+impl Writer {
+    fn new(format: Format) -> Self {
+        Self { out: String::new(), format }
+    }
+
+    /// Start a metric family; `name` is the sample name (counters end in `_total`)
+    fn family(&mut self, name: &str, kind: Kind, help: &str) {
+        let (family, kind) = match (self.format, kind) {
+            (Format::OpenMetrics, Kind::Counter) => (name.trim_end_matches("_total"), "counter"),
+            (Format::OpenMetrics, Kind::Info) => (name.trim_end_matches("_info"), "info"),
+            (_, Kind::Counter) => (name, "counter"),
+            (_, Kind::Gauge | Kind::Info) => (name, "gauge"),
+        };
+        let _ = writeln!(self.out, "# HELP {} {}", family, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", family, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (i, (key, value)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{}=\"{}\"", key, escape_label(value));
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {}", value);
+    }
+
+    /// One sample per tunnel, labelled with its profile
+    fn per_tunnel<V: Display>(
+        &mut self,
+        name: &str,
+        kind: Kind,
+        help: &str,
+        tunnels: &[TunnelSample],
+        value: impl Fn(&TunnelSample) -> V,
+    ) {
+        self.family(name, kind, help);
+        for tunnel in tunnels {
+            let id = tunnel.id.to_string();
+            self.sample(name, &[("profile", &tunnel.name), ("id", &id)], value(tunnel));
+        }
+    }
+
+    fn finish(mut self) -> String {
+        if self.format == Format::OpenMetrics {
+            self.out.push_str("# EOF\n");
+        }
+        self.out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Daemon-wide values
+pub struct DaemonSample {
+    pub uptime: Duration,
+    pub event_subscribers: usize,
+}
+
+/// Render all metrics
+pub fn render(format: Format, daemon: &DaemonSample, tunnels: &[TunnelSample]) -> String {
+    let mut w = Writer::new(format);
+
+    w.family("ssh_tunnel_daemon_info", Kind::Info, "Daemon build information");
+    w.sample("ssh_tunnel_daemon_info", &[("version", env!("CARGO_PKG_VERSION"))], 1);
+
+    w.family("ssh_tunnel_daemon_uptime_seconds", Kind::Gauge, "Seconds since the daemon started");
+    w.sample("ssh_tunnel_daemon_uptime_seconds", &[], daemon.uptime.as_secs());
+
+    w.family(
+        "ssh_tunnel_daemon_event_subscribers",
+        Kind::Gauge,
+        "Connected SSE and WebSocket event clients",
+    );
+    w.sample("ssh_tunnel_daemon_event_subscribers", &[], daemon.event_subscribers);
+
+    w.family(
+        "ssh_tunnel_daemon_pending_auth_requests",
+        Kind::Gauge,
+        "Tunnels waiting for an authentication response",
+    );
+    w.sample(
+        "ssh_tunnel_daemon_pending_auth_requests",
+        &[],
+        tunnels.iter().filter(|t| t.pending_auth).count(),
+    );
+
+    w.family("ssh_tunnel_daemon_tunnels", Kind::Gauge, "Known tunnels by status");
+    for status in ["connecting", "waiting_for_auth", "connected", "disconnecting", "disconnected", "failed"] {
+        let count = tunnels.iter().filter(|t| status_label(&t.status) == status).count();
+        w.sample("ssh_tunnel_daemon_tunnels", &[("status", status)], count);
+    }
+
+    w.per_tunnel("ssh_tunnel_up", Kind::Gauge, "1 while the tunnel is connected", tunnels, |t| {
+        u8::from(t.status == TunnelStatus::Connected)
+    });
+    w.per_tunnel(
+        "ssh_tunnel_starts_total",
+        Kind::Counter,
+        "Times the tunnel was started",
+        tunnels,
+        |t| t.metrics.starts.load(Ordering::Relaxed),
+    );
+    w.per_tunnel(
+        "ssh_tunnel_auth_failures_total",
+        Kind::Counter,
+        "SSH authentication failures",
+        tunnels,
+        |t| t.metrics.auth_failures.load(Ordering::Relaxed),
+    );
+    w.per_tunnel(
+        "ssh_tunnel_session_age_seconds",
+        Kind::Gauge,
+        "Seconds since the tunnel connected (0 while not connected)",
+        tunnels,
+        |t| t.session_age.unwrap_or_default().as_secs(),
+    );
+    w.per_tunnel(
+        "ssh_tunnel_connections_active",
+        Kind::Gauge,
+        "Forwarded connections currently open",
+        tunnels,
+        |t| t.metrics.traffic.active_connections(),
+    );
+    w.per_tunnel(
+        "ssh_tunnel_connections_total",
+        Kind::Counter,
+        "Forwarded connections accepted",
+        tunnels,
+        |t| t.metrics.traffic.total_connections(),
+    );
+    w.per_tunnel(
+        "ssh_tunnel_channel_open_failures_total",
+        Kind::Counter,
+        "Accepted connections the SSH server refused a channel for",
+        tunnels,
+        |t| t.metrics.traffic.channel_failures(),
+    );
+
+    w.family(
+        "ssh_tunnel_bytes_total",
+        Kind::Counter,
+        "Bytes forwarded (sent: local to remote, received: remote to local)",
+    );
+    for tunnel in tunnels {
+        let id = tunnel.id.to_string();
+        let traffic = &tunnel.metrics.traffic;
+        for (direction, bytes) in [
+            ("sent", traffic.bytes_sent()),
+            ("received", traffic.bytes_received()),
+        ] {
+            w.sample(
+                "ssh_tunnel_bytes_total",
+                &[("profile", &tunnel.name), ("id", &id), ("direction", direction)],
+                bytes,
+            );
+        }
+    }
+
+    w.finish()
+}
+
+fn status_label(status: &TunnelStatus) -> &'static str {
+    match status {
+        TunnelStatus::NotConnected | TunnelStatus::Disconnected => "disconnected",
+        TunnelStatus::Connecting | TunnelStatus::Reconnecting => "connecting",
+        TunnelStatus::WaitingForAuth => "waiting_for_auth",
+        TunnelStatus::Connected => "connected",
+        TunnelStatus::Disconnecting => "disconnecting",
+        TunnelStatus::Failed(_) => "failed",
+    }
+}
+
+/// Metrics in the Prometheus text format (OpenMetrics when the scraper asks for it)
+#[utoipa::path(
+    get,
+    path = "/api/v1/metrics",
+    tag = "daemon",
+    responses(
+        (status = 200, description = "Prometheus text exposition", content_type = "text/plain", body = String),
+        (status = 404, description = "Metrics are disabled (`[metrics] enabled = false`)")
+    )
+)]
+pub async fn metrics_handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    if !state.config.metrics.enabled {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let format = Format::from_accept(
+        headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok()),
+    );
+    let started = *state.started_at.read().await;
+    let daemon = DaemonSample {
+        uptime: SystemTime::now().duration_since(started).unwrap_or_default(),
+        event_subscribers: state.tunnel_manager.events().feed_count(),
+    };
+    let tunnels = state.tunnel_manager.metrics_samples().await;
+
+    (
+        [(header::CONTENT_TYPE, format.content_type())],
+        render(format, &daemon, &tunnels),
+    )
+        .into_response()
+}
+
+/// Serve `/metrics` alone on a loopback listener without authentication
+pub async fn serve(
+    state: Arc<AppState>,
+    std_listener: std::net::TcpListener,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+) -> Result<()> {
+    std_listener
+        .set_nonblocking(true)
+        .context("Failed to set metrics listener to non-blocking")?;
+    let listener = tokio::net::TcpListener::from_std(std_listener)
+        .context("Failed to register metrics listener")?;
+    info!("Metrics listening on http://{}/metrics", listener.local_addr()?);
+
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .with_state(state);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            let _ = shutdown_rx.recv().await;
+        })
+        .await
+        .context("Metrics server error")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(name: &str, status: TunnelStatus) -> TunnelSample {
+        TunnelSample {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            status,
+            session_age: None,
+            pending_auth: false,
+            metrics: Arc::default(),
+        }
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let db = sample("prod \"db\"", TunnelStatus::Connected);
+        db.metrics.record_start();
+        db.metrics.record_auth_failure();
+        let mut waiting = sample("jump", TunnelStatus::WaitingForAuth);
+        waiting.pending_auth = true;
+
+        let daemon = DaemonSample {
+            uptime: Duration::from_secs(90),
+            event_subscribers: 2,
+        };
+        let text = render(Format::Prometheus, &daemon, &[db, waiting]);
+
+        assert!(text.contains("# TYPE ssh_tunnel_starts_total counter\n"));
+        assert!(text.contains("ssh_tunnel_daemon_uptime_seconds 90\n"));
+        assert!(text.contains("ssh_tunnel_daemon_event_subscribers 2\n"));
+        assert!(text.contains("ssh_tunnel_daemon_pending_auth_requests 1\n"));
+        assert!(text.contains("ssh_tunnel_daemon_tunnels{status=\"connected\"} 1\n"));
+        assert!(text.contains("ssh_tunnel_up{profile=\"prod \\\"db\\\"\","));
+        assert!(text.contains("ssh_tunnel_up{profile=\"jump\","));
+
+        let line = |name: &str, profile: &str| {
+            text.lines()
+                .find(|l| l.starts_with(&format!("{}{{profile=\"{}\"", name, profile)))
+                .unwrap()
+                .rsplit(' ')
+                .next()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(line("ssh_tunnel_up", "jump"), "0");
+        assert_eq!(line("ssh_tunnel_starts_total", "prod \\\"db\\\""), "1");
+        assert_eq!(line("ssh_tunnel_auth_failures_total", "prod \\\"db\\\""), "1");
+        assert!(!text.contains("# EOF"));
+    }
+
+    #[test]
+    fn test_render_openmetrics() {
+        assert_eq!(
+            Format::from_accept(Some("application/openmetrics-text;version=1.0.0,text/plain;q=0.5")),
+            Format::OpenMetrics
+        );
+        assert_eq!(Format::from_accept(Some("*/*")), Format::Prometheus);
+
+        let daemon = DaemonSample {
+            uptime: Duration::ZERO,
+            event_subscribers: 0,
+        };
+        let text = render(Format::OpenMetrics, &daemon, &[sample("db", TunnelStatus::Connected)]);
+        // Counter families drop the `_total` suffix; samples keep it
+        assert!(text.contains("# TYPE ssh_tunnel_starts counter\n"));
+        assert!(text.contains("ssh_tunnel_starts_total{profile=\"db\""));
+        assert!(text.contains("# TYPE ssh_tunnel_daemon info\n"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Unix socket peer credentials
+//
+// Reads SO_PEERCRED for every accepted Unix socket connection, applies the
+// `[peer_access]` rules from daemon.toml and tags each request with the
+// caller's UID/PID so handlers and logs know which local user issued it.
+
+use std::fmt;
+
+use tokio::net::UnixStream;
+use tracing::warn;
+
+use crate::config::PeerAccessConfig;
+
+/// Credentials of the process on the other end of a Unix socket connection,
+/// attached to every request on that connection
+#[derive(Debug, Clone)]
+pub struct PeerCredentials {
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: Option<i32>,
+    pub username: Option<String>,
+    /// Requests are accepted without a token (`token_exempt_uids`)
+    pub token_exempt: bool,
+}
+
+impl PeerCredentials {
+    /// Identity name used for token-exempt requests
+    pub fn identity_name(&self) -> String {
+        match &self.username {
+            Some(name) => format!("unix:{}", name),
+            None => format!("uid:{}", self.uid),
+        }
+    }
+}
+
+impl fmt::Display for PeerCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.username {
+            Some(name) => write!(f, "{} (uid {}, gid {}", name, self.uid, self.gid)?,
+            None => write!(f, "uid {}, gid {}", self.uid, self.gid)?,
+        }
+        if let Some(pid) = self.pid {
+            write!(f, ", pid {}", pid)?;
+        }
+        if self.username.is_some() {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Decides which peers may use the Unix socket
+#[derive(Debug, Clone)]
+pub struct PeerPolicy {
+    config: PeerAccessConfig,
+    daemon_uid: u32,
+}
+
+impl PeerPolicy {
+    pub fn new(config: Option<PeerAccessConfig>) -> Self {
+        Self {
+            config: config.unwrap_or_default(),
+            daemon_uid: users::get_current_uid(),
+        }
+    }
+
+    /// Read the peer's credentials and apply the policy
+    ///
+    /// Returns `None` when the peer is not allowed or its credentials can't be
+    /// read; the caller should drop the connection. Group lookups may hit NSS,
+    /// so this runs on the blocking pool.
+    pub async fn authorize(&self, stream: &UnixStream) -> Option<PeerCredentials> {
+        let cred = match stream.peer_cred() {
+            Ok(cred) => cred,
+            Err(e) => {
+                warn!("Rejecting Unix socket connection: failed to read peer credentials: {}", e);
+                return None;
+            }
+        };
+
+        let policy = self.clone();
+        let (uid, gid, pid) = (cred.uid(), cred.gid(), cred.pid());
+        let peer = tokio::task::spawn_blocking(move || policy.check(uid, gid, pid))
+            .await
+            .ok()
+            .flatten();
+
+        if peer.is_none() {
+            warn!(
+                "Rejecting Unix socket connection from uid {} (gid {}, pid {:?}): not in peer_access",
+                uid, gid, pid
+            );
+        }
+        peer
+    }
+
+    fn check(&self, uid: u32, gid: u32, pid: Option<i32>) -> Option<PeerCredentials> {
+        let user = users::get_user_by_uid(uid);
+
+        let mut groups = vec![gid];
+        if !self.config.allowed_gids.is_empty() {
+            if let Some(user) = &user {
+                groups.extend(
+                    users::get_user_groups(user.name(), user.primary_group_id())
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|g| g.gid()),
+                );
+            }
+        }
+
+        if !self.is_allowed(uid, &groups) {
+            return None;
+        }
+
+        Some(PeerCredentials {
+            uid,
+            gid,
+            pid,
+            username: user.map(|u| u.name().to_string_lossy().into_owned()),
+            token_exempt: self.config.token_exempt_uids.contains(&uid),
+        })
+    }
+
+    fn is_allowed(&self, uid: u32, groups: &[u32]) -> bool {
+        let config = &self.config;
+        if config.allowed_uids.is_empty() && config.allowed_gids.is_empty() {
+            return true;
+        }
+        uid == self.daemon_uid
+            || config.allowed_uids.contains(&uid)
+            || groups.iter().any(|gid| config.allowed_gids.contains(gid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_policy() {
+        let policy = |config: PeerAccessConfig| PeerPolicy {
+            config,
+            daemon_uid: 500,
+        };
+
+        // No allowlist: socket permissions decide
+        let open = policy(PeerAccessConfig::default());
+        assert!(open.is_allowed(1234, &[1234]));
+
+        let restricted = policy(PeerAccessConfig {
+            allowed_uids: vec![1000],
+            allowed_gids: vec![985],
+            token_exempt_uids: vec![1000],
+        });
+        assert!(restricted.is_allowed(500, &[500]));
+        assert!(restricted.is_allowed(1000, &[1000]));
+        assert!(restricted.is_allowed(1001, &[1001, 985]));
+        assert!(!restricted.is_allowed(1002, &[1002, 100]));
+
+        let peer = PeerCredentials {
+            uid: 1000,
+            gid: 1000,
+            pid: Some(4242),
+            username: Some("alice".to_string()),
+            token_exempt: true,
+        };
+        assert_eq!(peer.identity_name(), "unix:alice");
+        assert_eq!(peer.to_string(), "alice (uid 1000, gid 1000, pid 4242)");
+
+        let anonymous = PeerCredentials { username: None, pid: None, ..peer };
+        assert_eq!(anonymous.identity_name(), "uid:1000");
+        assert_eq!(anonymous.to_string(), "uid 1000, gid 1000");
+    }
+}
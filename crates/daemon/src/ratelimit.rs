@@ -0,0 +1,264 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Brute-force protection for API authentication
+//
+// Tracks failed token checks per client address with exponential lockout,
+// and rate limits requests without valid credentials across all clients so
+// token guessing can't be parallelised (or used to burn CPU on Argon2).
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use ssh_tunnel_common::LockoutInfo;
+
+use crate::config::AuthLimitsConfig;
+
+/// Upper bound on tracked addresses; beyond it, sources that aren't locked
+/// out are forgotten first
+const MAX_TRACKED_SOURCES: usize = 10_000;
+
+/// Failure history of one client address
+#[derive(Debug, Clone)]
+struct SourceRecord {
+    /// Consecutive failures since the last success or lockout
+    failures: u32,
+    /// Lockouts so far; each one doubles the next lockout
+    lockouts: u32,
+    locked_until: Option<Instant>,
+    last_failure: Instant,
+}
+
+/// Token bucket for requests without valid credentials
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    sources: HashMap<IpAddr, SourceRecord>,
+    bucket: Bucket,
+}
+
+/// Shared by all listeners; the auth middleware consults it for every request
+#[derive(Debug)]
+pub struct AuthLimiter {
+    config: AuthLimitsConfig,
+    state: Mutex<LimiterState>,
+}
+
+impl AuthLimiter {
+    pub fn new(config: AuthLimitsConfig) -> Self {
+        let state = LimiterState {
+            sources: HashMap::new(),
+            bucket: Bucket {
+                tokens: f64::from(config.unauthenticated_per_minute),
+                refilled_at: Instant::now(),
+            },
+        };
+        Self {
+            config,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Remaining lockout for `source`, if it is locked out
+    pub fn locked_out(&self, source: IpAddr) -> Option<Duration> {
+        self.locked_out_at(source, Instant::now())
+    }
+
+    /// Take one slot from the global budget for unauthenticated requests
+    ///
+    /// Returns false when the budget is exhausted; the request should be
+    /// rejected without checking its token.
+    pub fn allow_unauthenticated(&self) -> bool {
+        self.allow_unauthenticated_at(Instant::now())
+    }
+
+    /// Record a failed authentication; returns the lockout it triggered
+    pub fn record_failure(&self, source: IpAddr) -> Option<Duration> {
+        self.record_failure_at(source, Instant::now())
+    }
+
+    /// Forget the failure history of a source that authenticated
+    pub fn record_success(&self, source: IpAddr) {
+        let mut state = self.state.lock().unwrap();
+        state.sources.remove(&source);
+    }
+
+    /// Sources currently locked out, for `DaemonInfo`
+    pub fn lockouts(&self) -> Vec<LockoutInfo> {
+        let now = Instant::now();
+        let state = self.state.lock().unwrap();
+        let mut lockouts: Vec<LockoutInfo> = state
+            .sources
+            .iter()
+            .filter_map(|(source, record)| {
+                let until = record.locked_until.filter(|until| *until > now)?;
+                Some(LockoutInfo {
+                    source: *source,
+                    lockouts: record.lockouts,
+                    remaining_secs: (until - now).as_secs().max(1),
+                })
+            })
+            .collect();
+        lockouts.sort_by_key(|lockout| std::cmp::Reverse(lockout.remaining_secs));
+        lockouts
+    }
+
+    fn locked_out_at(&self, source: IpAddr, now: Instant) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        let until = state.sources.get(&source)?.locked_until?;
+        (until > now).then(|| until - now)
+    }
+
+    fn allow_unauthenticated_at(&self, now: Instant) -> bool {
+        let capacity = f64::from(self.config.unauthenticated_per_minute);
+        let mut state = self.state.lock().unwrap();
+        let bucket = &mut state.bucket;
+
+        let elapsed = now.saturating_duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * capacity / 60.0).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn record_failure_at(&self, source: IpAddr, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        self.prune(&mut state.sources, now);
+
+        let record = state.sources.entry(source).or_insert(SourceRecord {
+            failures: 0,
+            lockouts: 0,
+            locked_until: None,
+            last_failure: now,
+        });
+        record.failures += 1;
+        record.last_failure = now;
+
+        if record.failures < self.config.max_failures {
+            return None;
+        }
+
+        let lockout = self.lockout_duration(record.lockouts);
+        record.failures = 0;
+        record.lockouts += 1;
+        record.locked_until = Some(now + lockout);
+        Some(lockout)
+    }
+
+    /// `lockout_secs * 2^previous`, capped at `max_lockout_secs`
+    fn lockout_duration(&self, previous: u32) -> Duration {
+        let secs = self
+            .config
+            .lockout_secs
+            .saturating_mul(1u64.checked_shl(previous).unwrap_or(u64::MAX))
+            .min(self.config.max_lockout_secs);
+        Duration::from_secs(secs)
+    }
+
+    /// Drop records that have been quiet for `max_lockout_secs`, then the
+    /// oldest unlocked ones if there are still too many
+    fn prune(&self, sources: &mut HashMap<IpAddr, SourceRecord>, now: Instant) {
+        let expiry = Duration::from_secs(self.config.max_lockout_secs);
+        sources.retain(|_, record| {
+            record.locked_until.is_some_and(|until| until > now)
+                || now.saturating_duration_since(record.last_failure) < expiry
+        });
+
+        if sources.len() < MAX_TRACKED_SOURCES {
+            return;
+        }
+        let mut unlocked: Vec<(IpAddr, Instant)> = sources
+            .iter()
+            .filter(|(_, record)| record.locked_until.is_none_or(|until| until <= now))
+            .map(|(source, record)| (*source, record.last_failure))
+            .collect();
+        unlocked.sort_by_key(|(_, last_failure)| *last_failure);
+        let excess = sources.len() + 1 - MAX_TRACKED_SOURCES;
+        for (source, _) in unlocked.into_iter().take(excess) {
+            sources.remove(&source);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> AuthLimiter {
+        AuthLimiter::new(AuthLimitsConfig {
+            max_failures: 3,
+            lockout_secs: 10,
+            max_lockout_secs: 25,
+            unauthenticated_per_minute: 2,
+        })
+    }
+
+    #[test]
+    fn test_exponential_lockout() {
+        let limiter = limiter();
+        let source: IpAddr = "203.0.113.7".parse().unwrap();
+        let other: IpAddr = "203.0.113.8".parse().unwrap();
+        let start = Instant::now();
+
+        assert_eq!(limiter.record_failure_at(source, start), None);
+        assert_eq!(limiter.record_failure_at(source, start), None);
+        assert_eq!(
+            limiter.record_failure_at(source, start),
+            Some(Duration::from_secs(10))
+        );
+        assert!(limiter.locked_out_at(source, start).is_some());
+        assert!(limiter.locked_out_at(other, start).is_none());
+        assert_eq!(limiter.lockouts().len(), 1);
+
+        // Lockout expires, the next one doubles, then hits the cap
+        let later = start + Duration::from_secs(11);
+        assert!(limiter.locked_out_at(source, later).is_none());
+        for _ in 0..2 {
+            limiter.record_failure_at(source, later);
+        }
+        assert_eq!(
+            limiter.record_failure_at(source, later),
+            Some(Duration::from_secs(20))
+        );
+        let later = later + Duration::from_secs(21);
+        for _ in 0..2 {
+            limiter.record_failure_at(source, later);
+        }
+        assert_eq!(
+            limiter.record_failure_at(source, later),
+            Some(Duration::from_secs(25))
+        );
+
+        // Success clears the history
+        limiter.record_success(source);
+        assert!(limiter.locked_out_at(source, later).is_none());
+        assert!(limiter.lockouts().is_empty());
+    }
+
+    #[test]
+    fn test_unauthenticated_budget() {
+        let limiter = limiter();
+        let start = Instant::now();
+
+        assert!(limiter.allow_unauthenticated_at(start));
+        assert!(limiter.allow_unauthenticated_at(start));
+        assert!(!limiter.allow_unauthenticated_at(start));
+
+        // Two per minute: one slot back after 30s
+        let later = start + Duration::from_secs(30);
+        assert!(limiter.allow_unauthenticated_at(later));
+        assert!(!limiter.allow_unauthenticated_at(later));
+    }
+}
@@ -2,9 +2,181 @@
 // Copyright 2025 SSH Tunnel Manager Contributors
 
 // Security module
+//
+// Stored passwords and key passphrases, by the profile's `password_storage`:
+// the system keychain, the encrypted vault file, or a secret command
+// (`pass show ...`, `op read ...`, a Vault agent ...) whose stdout is the
+// secret. Command output is cached for `[secrets] cache_ttl_secs` so
+// reconnects don't run it every time. The vault is unlocked with a master
+// passphrase asked once per daemon run.
 
-use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
+use zeroize::Zeroizing;
+
+use ssh_tunnel_common::{PasswordStorage, Profile, Vault};
+
+use crate::config::SecretsConfig;
+use crate::shell;
+
+/// Resolves the stored secret of a profile
+pub struct SecretStore {
+    command_timeout: Duration,
+    cache_ttl: Duration,
+    cache: Mutex<HashMap<Uuid, CachedSecret>>,
+    vault_path: Option<PathBuf>,
+    /// The unlocked vault, kept for the daemon's lifetime
+    vault: tokio::sync::Mutex<Option<Vault>>,
+}
+
+struct CachedSecret {
+    /// The command that printed it; editing the profile invalidates the entry
+    command: String,
+    secret: Zeroizing<String>,
+    fetched_at: Instant,
+}
+
+impl SecretStore {
+    pub fn new(config: &SecretsConfig) -> Self {
+        Self {
+            command_timeout: Duration::from_secs(config.command_timeout_secs),
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+            cache: Mutex::new(HashMap::new()),
+            vault_path: config.vault_path.clone().or_else(|| Vault::default_path().ok()),
+            vault: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// The stored password or passphrase of `profile`
+    ///
+    /// Returns `Ok(None)` when the profile doesn't store one, so the caller
+    /// prompts for it. `ask_master` is called for the vault's master
+    /// passphrase while the vault is locked.
+    pub async fn get<F, Fut>(
+        &self,
+        profile: &Profile,
+        ask_master: F,
+    ) -> Result<Option<Zeroizing<String>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        let secret = match &profile.connection.password_storage {
+            PasswordStorage::None => return Ok(None),
+            PasswordStorage::Keychain => {
+                // The keychain lookup may block on D-Bus or an unlock dialog
+                let id = profile.metadata.id;
+                tokio::task::spawn_blocking(move || get_stored_password(&id))
+                    .await
+                    .context("Keychain lookup task failed")?
+                    .map(Zeroizing::new)?
+            }
+            PasswordStorage::File => self.vault_secret(&profile.metadata.id, ask_master).await?,
+            PasswordStorage::Command { command } => self.command_secret(command, profile).await?,
+        };
+        crate::logging::register_secret(&secret);
+        Ok(Some(secret))
+    }
+
+    /// Drop the cached secret of a profile, e.g. after the server rejected it
+    pub fn forget(&self, profile_id: &Uuid) {
+        self.lock_cache().remove(profile_id);
+    }
+
+    async fn command_secret(
+        &self,
+        command: &str,
+        profile: &Profile,
+    ) -> Result<Zeroizing<String>> {
+        let id = profile.metadata.id;
+        if let Some(cached) = self.lock_cache().get(&id) {
+            if cached.command == command && cached.fetched_at.elapsed() < self.cache_ttl {
+                debug!("Using cached secret for '{}'", profile.metadata.name);
+                return Ok(cached.secret.clone());
+            }
+        }
+
+        let secret = run_command(command, profile, self.command_timeout).await?;
+        if !self.cache_ttl.is_zero() {
+            self.lock_cache().insert(
+                id,
+                CachedSecret {
+                    command: command.to_string(),
+                    secret: secret.clone(),
+                    fetched_at: Instant::now(),
+                },
+            );
+        }
+        Ok(secret)
+    }
+
+    async fn vault_secret<F, Fut>(
+        &self,
+        profile_id: &Uuid,
+        ask_master: F,
+    ) -> Result<Zeroizing<String>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        // Held across the prompt, so tunnels starting together ask only once
+        let mut vault = self.vault.lock().await;
+
+        // Pick up secrets stored since it was unlocked
+        if let Some(unlocked) = vault.as_mut() {
+            if let Err(e) = unlocked.reload() {
+                info!("Secret vault needs to be unlocked again: {}", e);
+                *vault = None;
+            }
+        }
+
+        let unlocked = match vault.as_mut() {
+            Some(unlocked) => unlocked,
+            None => {
+                let path = self
+                    .vault_path
+                    .clone()
+                    .ok_or_else(|| anyhow::anyhow!("Could not determine the secret vault path"))?;
+                if !path.exists() {
+                    anyhow::bail!(
+                        "No secret vault at {} (store secrets with `ssh-tunnel vault set`)",
+                        path.display()
+                    );
+                }
+                let master = Zeroizing::new(ask_master().await?);
+                crate::logging::register_secret(&master);
+                // Argon2 takes a moment; keep it off the async workers
+                let opened = tokio::task::spawn_blocking(move || Vault::open(&path, &master))
+                    .await
+                    .context("Vault unlock task failed")??;
+                info!("Secret vault unlocked");
+                vault.insert(opened)
+            }
+        };
+
+        unlocked
+            .get(profile_id)
+            .map(|secret| Zeroizing::new(secret.to_string()))
+            .ok_or_else(|| anyhow::anyhow!("The secret vault holds no secret for this profile"))
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, CachedSecret>> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for SecretStore {
+    fn default() -> Self {
+        Self::new(&SecretsConfig::default())
+    }
+}
 
 /// Retrieve password or passphrase from system keychain
 ///
@@ -14,3 +186,157 @@ pub fn get_stored_password(profile_id: &Uuid) -> Result<String> {
     ssh_tunnel_common::get_password(profile_id)
         .map_err(|e| anyhow::anyhow!("Failed to retrieve password from keychain: {}", e))
 }
+
+/// Run a secret command via `sh -c` and return its stdout without the final newline
+async fn run_command(
+    command: &str,
+    profile: &Profile,
+    timeout: Duration,
+) -> Result<Zeroizing<String>> {
+    let mut cmd = shell::sh(command);
+    cmd.env("SSH_TUNNEL_PROFILE_NAME", &profile.metadata.name)
+        .env("SSH_TUNNEL_PROFILE_ID", profile.metadata.id.to_string());
+    let output = shell::run(&mut cmd, timeout, "Secret command").await?;
+
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        warn!("[secret command '{}'] {}", profile.metadata.name, line);
+    }
+
+    if !output.status.success() {
+        anyhow::bail!("Secret command {}", shell::describe_failure(output.status));
+    }
+
+    let mut secret = Zeroizing::new(String::from_utf8(output.stdout).map_err(|e| {
+        drop(Zeroizing::new(e.into_bytes()));
+        anyhow::anyhow!("Secret command printed invalid UTF-8")
+    })?);
+    if secret.ends_with('\n') {
+        secret.pop();
+        if secret.ends_with('\r') {
+            secret.pop();
+        }
+    }
+    if secret.is_empty() {
+        anyhow::bail!("Secret command printed nothing");
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssh_tunnel_common::{AuthType, ConnectionConfig, ForwardingConfig, ForwardingType};
+
+    async fn no_prompt() -> Result<String> {
+        anyhow::bail!("not expected to prompt")
+    }
+
+    fn profile(command: &str) -> Profile {
+        Profile::new(
+            "db".to_string(),
+            ConnectionConfig {
+                host: "example.com".to_string(),
+                port: 22,
+                user: "user".to_string(),
+                auth_type: AuthType::Password,
+                key_path: None,
+                password_storage: PasswordStorage::Command {
+                    command: command.to_string(),
+                },
+            },
+            ForwardingConfig {
+                forwarding_type: ForwardingType::Local,
+                local_port: Some(5432),
+                remote_host: Some("localhost".to_string()),
+                remote_port: Some(5432),
+                bind_address: "127.0.0.1".to_string(),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_command_secret_is_cached() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let counter = dir.path().join("runs");
+        // Stand-in for `pass show`: counts its runs
+        let command = format!(
+            "echo x >> '{}'; echo \"secret-for-$SSH_TUNNEL_PROFILE_NAME\"",
+            counter.display()
+        );
+        let profile = profile(&command);
+        let store = SecretStore::default();
+        let runs = || std::fs::read_to_string(&counter).unwrap().lines().count();
+
+        let secret = store.get(&profile, no_prompt).await.unwrap();
+        assert_eq!(secret.as_deref().map(String::as_str), Some("secret-for-db"));
+        assert_eq!(store.get(&profile, no_prompt).await.unwrap(), secret);
+        assert_eq!(runs(), 1);
+
+        store.forget(&profile.metadata.id);
+        assert_eq!(store.get(&profile, no_prompt).await.unwrap(), secret);
+        assert_eq!(runs(), 2);
+
+        // Without caching the command runs every time
+        let store = SecretStore::new(&SecretsConfig {
+            cache_ttl_secs: 0,
+            ..SecretsConfig::default()
+        });
+        store.get(&profile, no_prompt).await.unwrap();
+        store.get(&profile, no_prompt).await.unwrap();
+        assert_eq!(runs(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_command_failures() {
+        let store = SecretStore::new(&SecretsConfig {
+            command_timeout_secs: 1,
+            ..SecretsConfig::default()
+        });
+
+        let err = store.get(&profile("exit 3"), no_prompt).await.unwrap_err();
+        assert!(err.to_string().contains("status 3"));
+
+        let err = store.get(&profile("true"), no_prompt).await.unwrap_err();
+        assert!(err.to_string().contains("printed nothing"));
+
+        let err = store.get(&profile("sleep 5"), no_prompt).await.unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+
+        let mut none = profile("true");
+        none.connection.password_storage = PasswordStorage::None;
+        assert!(store.get(&none, no_prompt).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_vault_unlocked_once() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("vault.toml");
+        let mut profile = profile("true");
+        profile.connection.password_storage = PasswordStorage::File;
+
+        let store = SecretStore::new(&SecretsConfig {
+            vault_path: Some(path.clone()),
+            ..SecretsConfig::default()
+        });
+        let err = store.get(&profile, no_prompt).await.unwrap_err();
+        assert!(err.to_string().contains("No secret vault"));
+
+        let mut vault = Vault::create(&path, "master").unwrap();
+        vault.set(profile.metadata.id, "key-passphrase");
+        vault.save().unwrap();
+
+        // A wrong master passphrase leaves the vault locked
+        let wrong = || async { Ok("wrong".to_string()) };
+        assert!(store.get(&profile, wrong).await.is_err());
+
+        let prompts = std::sync::atomic::AtomicUsize::new(0);
+        let ask = || async {
+            prompts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok("master".to_string())
+        };
+        let secret = store.get(&profile, ask).await.unwrap();
+        assert_eq!(secret.as_deref().map(String::as_str), Some("key-passphrase"));
+        assert_eq!(store.get(&profile, ask).await.unwrap(), secret);
+        assert_eq!(prompts.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Shell commands
+//
+// Lifecycle hooks, the askpass command and secret commands all run via
+// `sh -c` with a timeout. The shell gets its own process group so that a
+// timeout kills everything it started (pipelines, `pass` running gpg, ...),
+// not just `sh` itself.
+
+use std::process::{ExitStatus, Output, Stdio};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::process::Command;
+
+/// `sh -c <script>` with stdin closed and stdout/stderr captured
+///
+/// Add arguments and environment variables, then pass it to [`run`].
+pub fn sh(script: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(script)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd
+}
+
+/// Run `cmd` and collect its output
+///
+/// When `timeout` runs out, or the returned future is dropped, the command's
+/// whole process group is killed. `what` names the command in errors.
+pub async fn run(cmd: &mut Command, timeout: Duration, what: &str) -> Result<Output> {
+    let child = cmd
+        .spawn()
+        .with_context(|| format!("Failed to spawn {}", what))?;
+    let group = ProcessGroup(child.id());
+
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => output.with_context(|| format!("Failed to wait for {}", what))?,
+        Err(_) => anyhow::bail!("{} timed out after {:?}", what, timeout),
+    };
+    group.release();
+    Ok(output)
+}
+
+/// Why a command failed, e.g. "exited with status 3"
+pub fn describe_failure(status: ExitStatus) -> String {
+    match status.code() {
+        Some(code) => format!("exited with status {}", code),
+        None => "was terminated by signal".to_string(),
+    }
+}
+
+/// Kills the process group led by the child on drop, unless released
+struct ProcessGroup(Option<u32>);
+
+impl ProcessGroup {
+    /// The command finished; leave anything it left running alone
+    fn release(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.0.and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+            // SAFETY: plain syscall; the group was created by sh() for this child
+            unsafe {
+                libc::killpg(pgid, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_collects_output() {
+        let output = run(
+            &mut sh("echo out; echo err >&2; exit 3"),
+            Duration::from_secs(5),
+            "test",
+        )
+        .await
+        .unwrap();
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+        assert_eq!(describe_failure(output.status), "exited with status 3");
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_process_group() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let marker = dir.path().join("survived");
+        // A background job of the shell, which killing `sh` alone would miss
+        let script = format!("(sleep 1; touch '{}') & wait", marker.display());
+
+        let err = run(&mut sh(&script), Duration::from_millis(200), "test command")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "test command timed out after 200ms");
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists());
+    }
+}
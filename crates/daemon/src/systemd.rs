@@ -0,0 +1,399 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// SSH Tunnel Manager - systemd Integration
+// Socket activation (LISTEN_FDS) and sd_notify readiness/status/watchdog messages.
+// Implemented directly on the documented environment protocol, no libsystemd needed.
+
+use std::os::fd::{FromRawFd, OwnedFd, RawFd};
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use crate::config::{ListenerConfig, ListenerMode};
+use crate::tunnel::TunnelManager;
+
+/// First file descriptor passed by systemd (SD_LISTEN_FDS_START)
+const LISTEN_FDS_START: RawFd = 3;
+
+/// A listening socket inherited from systemd
+#[derive(Debug)]
+pub struct ActivatedSocket {
+    fd: RawFd,
+    /// FileDescriptorName= from the .socket unit (defaults to the unit name)
+    name: String,
+    is_unix: bool,
+}
+
+impl ActivatedSocket {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Take ownership of the descriptor as a Unix listener
+    pub fn into_unix_listener(self) -> std::os::unix::net::UnixListener {
+        // SAFETY: systemd passed us this listening socket and we claim it exactly once
+        unsafe { std::os::unix::net::UnixListener::from_raw_fd(self.fd) }
+    }
+
+    /// Take ownership of the descriptor as a TCP listener
+    pub fn into_tcp_listener(self) -> std::net::TcpListener {
+        // SAFETY: systemd passed us this listening socket and we claim it exactly once
+        unsafe { std::net::TcpListener::from_raw_fd(self.fd) }
+    }
+}
+
+/// Sockets passed by systemd socket activation, claimed by listeners one at a time
+#[derive(Debug, Default)]
+pub struct ActivatedSockets {
+    sockets: Vec<ActivatedSocket>,
+}
+
+impl ActivatedSockets {
+    /// Collect the sockets described by LISTEN_PID/LISTEN_FDS/LISTEN_FDNAMES
+    ///
+    /// The variables are removed afterwards so child processes don't inherit them.
+    /// Changing the environment is only sound while the process is single-threaded,
+    /// so call this in `main` before the tokio runtime starts.
+    pub fn from_env() -> Self {
+        let fds = parse_listen_fds(
+            std::env::var("LISTEN_PID").ok().as_deref(),
+            std::env::var("LISTEN_FDS").ok().as_deref(),
+            std::env::var("LISTEN_FDNAMES").ok().as_deref(),
+            std::process::id(),
+        );
+
+        std::env::remove_var("LISTEN_PID");
+        std::env::remove_var("LISTEN_FDS");
+        std::env::remove_var("LISTEN_FDNAMES");
+
+        let sockets = fds
+            .into_iter()
+            .map(|(fd, name)| {
+                // SAFETY: fcntl on a descriptor handed to us by systemd
+                unsafe {
+                    libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                }
+                let is_unix = socket_is_unix(fd);
+                ActivatedSocket { fd, name, is_unix }
+            })
+            .collect();
+
+        Self { sockets }
+    }
+
+    /// Claim the socket for a configured listener
+    ///
+    /// A socket named after the listener mode (FileDescriptorName=unix-socket,
+    /// tcp-http or tcp-https) is preferred; otherwise the first unnamed socket of
+    /// the right family is used.
+    pub fn take_for(&mut self, listener: &ListenerConfig) -> Option<ActivatedSocket> {
+        let want_unix = listener.mode == ListenerMode::UnixSocket;
+        let position = self
+            .sockets
+            .iter()
+            .position(|s| s.is_unix == want_unix && s.name == listener.mode.as_str())
+            .or_else(|| {
+                self.sockets
+                    .iter()
+                    .position(|s| s.is_unix == want_unix && !is_mode_name(&s.name))
+            })?;
+        Some(self.sockets.remove(position))
+    }
+
+    /// Log the received sockets (collected before logging was set up)
+    pub fn log_received(&self) {
+        for socket in &self.sockets {
+            info!(
+                "Received socket from systemd: fd={} name={} ({})",
+                socket.fd,
+                socket.name,
+                if socket.is_unix { "unix" } else { "inet" }
+            );
+        }
+    }
+
+    /// Close the sockets no listener claimed
+    pub fn close_unclaimed(&mut self) {
+        for socket in self.sockets.drain(..) {
+            warn!(
+                "Closing socket '{}' from systemd (fd={}): no matching listener in daemon.toml",
+                socket.name, socket.fd
+            );
+            // SAFETY: the descriptor is ours and no listener took ownership of it
+            drop(unsafe { OwnedFd::from_raw_fd(socket.fd) });
+        }
+    }
+}
+
+/// Refuse a socket from systemd that a tcp-http listener would serve beyond loopback
+///
+/// Its address comes from ListenStream= in the .socket unit, which the
+/// `bind_host` check of daemon.toml doesn't see.
+pub fn check_http_socket(listener: &std::net::TcpListener) -> Result<()> {
+    let addr = listener
+        .local_addr()
+        .context("Failed to read the address of the socket from systemd")?;
+    if !ssh_tunnel_common::is_loopback_address(&addr.ip().to_canonical().to_string()) {
+        anyhow::bail!(
+            "Security violation: the systemd socket for the tcp-http listener is bound to {}.\n\
+             HTTP mode is only allowed for localhost connections due to lack of encryption; \
+             use ListenStream=127.0.0.1:{} or a tcp-https listener.",
+            addr,
+            addr.port()
+        );
+    }
+    Ok(())
+}
+
+fn is_mode_name(name: &str) -> bool {
+    [ListenerMode::UnixSocket, ListenerMode::TcpHttp, ListenerMode::TcpHttps]
+        .iter()
+        .any(|mode| mode.as_str() == name)
+}
+
+/// Parse the socket activation variables into (fd, name) pairs
+///
+/// Returns nothing unless LISTEN_PID names this process.
+fn parse_listen_fds(
+    listen_pid: Option<&str>,
+    listen_fds: Option<&str>,
+    listen_fdnames: Option<&str>,
+    our_pid: u32,
+) -> Vec<(RawFd, String)> {
+    let pid_matches = listen_pid
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .is_some_and(|pid| pid == our_pid);
+    if !pid_matches {
+        return Vec::new();
+    }
+
+    let count = listen_fds
+        .and_then(|n| n.trim().parse::<RawFd>().ok())
+        .unwrap_or(0);
+    let names: Vec<&str> = listen_fdnames.map(|n| n.split(':').collect()).unwrap_or_default();
+
+    (0..count.max(0))
+        .map(|i| {
+            let name = names
+                .get(i as usize)
+                .filter(|n| !n.is_empty())
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            (LISTEN_FDS_START + i, name)
+        })
+        .collect()
+}
+
+/// Check whether a descriptor is an AF_UNIX socket
+fn socket_is_unix(fd: RawFd) -> bool {
+    // SAFETY: getsockname writes at most `len` bytes into the zeroed storage
+    unsafe {
+        let mut addr: libc::sockaddr_storage = std::mem::zeroed();
+        let mut len = std::mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        let rc = libc::getsockname(fd, &mut addr as *mut _ as *mut libc::sockaddr, &mut len);
+        rc == 0 && addr.ss_family as libc::c_int == libc::AF_UNIX
+    }
+}
+
+/// Send a state string to the service manager (no-op when NOTIFY_SOCKET is unset)
+pub fn notify(state: &str) {
+    let Ok(socket_path) = std::env::var("NOTIFY_SOCKET") else {
+        return;
+    };
+
+    if let Err(e) = send_notify(&socket_path, state) {
+        warn!("Failed to notify systemd ({}): {}", state.replace('\n', " "), e);
+    }
+}
+
+fn send_notify(socket_path: &str, state: &str) -> std::io::Result<()> {
+    let socket = UnixDatagram::unbound()?;
+    match socket_path.strip_prefix('@') {
+        Some(abstract_name) => send_abstract(&socket, abstract_name, state),
+        None => socket.send_to(state.as_bytes(), socket_path).map(|_| ()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn send_abstract(socket: &UnixDatagram, name: &str, state: &str) -> std::io::Result<()> {
+    use std::os::linux::net::SocketAddrExt;
+
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+    socket.send_to_addr(state.as_bytes(), &addr).map(|_| ())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn send_abstract(_socket: &UnixDatagram, _name: &str, _state: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "abstract NOTIFY_SOCKET is only supported on Linux",
+    ))
+}
+
+/// Watchdog ping interval: half of WATCHDOG_USEC, if the watchdog is enabled for us
+fn watchdog_interval() -> Option<Duration> {
+    parse_watchdog(
+        std::env::var("WATCHDOG_USEC").ok().as_deref(),
+        std::env::var("WATCHDOG_PID").ok().as_deref(),
+        std::process::id(),
+    )
+}
+
+fn parse_watchdog(usec: Option<&str>, pid: Option<&str>, our_pid: u32) -> Option<Duration> {
+    // WATCHDOG_PID is optional; when present it must name this process
+    if let Some(pid) = pid {
+        if pid.trim().parse::<u32>().ok() != Some(our_pid) {
+            return None;
+        }
+    }
+
+    let usec = usec?.trim().parse::<u64>().ok().filter(|&u| u > 0)?;
+    Some(Duration::from_micros(usec / 2))
+}
+
+async fn status_line(tunnel_manager: &TunnelManager) -> String {
+    let connected = tunnel_manager
+        .list_active()
+        .await
+        .iter()
+        .filter(|(_, status)| status.is_connected())
+        .count();
+    match connected {
+        1 => "1 tunnel active".to_string(),
+        n => format!("{} tunnels active", n),
+    }
+}
+
+/// Report readiness, then keep systemd's STATUS= and watchdog up to date
+///
+/// Call once every listener is bound. Does nothing outside systemd.
+pub fn spawn_notifier(tunnel_manager: TunnelManager) {
+    if std::env::var_os("NOTIFY_SOCKET").is_none() {
+        return;
+    }
+
+    let watchdog = watchdog_interval();
+    if let Some(interval) = watchdog {
+        info!("systemd watchdog enabled, pinging every {:?}", interval);
+    }
+
+    tokio::spawn(async move {
+        let mut events = tunnel_manager.subscribe();
+        let mut status = status_line(&tunnel_manager).await;
+        notify(&format!("READY=1\nSTATUS={}", status));
+        debug!("Sent READY=1 to systemd");
+
+        let mut ticker = tokio::time::interval(watchdog.unwrap_or(Duration::from_secs(3600)));
+        loop {
+            tokio::select! {
+                _ = ticker.tick(), if watchdog.is_some() => {
+                    notify("WATCHDOG=1");
+                }
+                event = events.recv() => match event {
+                    // Most events (stats, auth prompts) don't change the count
+                    Ok(_) | Err(RecvError::Lagged(_)) => {
+                        let current = status_line(&tunnel_manager).await;
+                        if current != status {
+                            notify(&format!("STATUS={}", current));
+                            status = current;
+                        }
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listen_fds_requires_matching_pid() {
+        assert!(parse_listen_fds(Some("1"), Some("2"), None, 42).is_empty());
+        assert!(parse_listen_fds(None, Some("2"), None, 42).is_empty());
+    }
+
+    #[test]
+    fn test_parse_listen_fds_names() {
+        let fds = parse_listen_fds(Some("42"), Some("3"), Some("unix-socket::tcp-https"), 42);
+        assert_eq!(
+            fds,
+            vec![
+                (3, "unix-socket".to_string()),
+                (4, "unknown".to_string()),
+                (5, "tcp-https".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_http_socket_must_be_loopback() {
+        use std::os::fd::IntoRawFd;
+
+        let adopt = |addr: &str| {
+            let fd = std::net::TcpListener::bind(addr).unwrap().into_raw_fd();
+            let socket = ActivatedSocket {
+                fd,
+                name: "tcp-http".to_string(),
+                is_unix: false,
+            };
+            check_http_socket(&socket.into_tcp_listener())
+        };
+
+        assert!(adopt("127.0.0.1:0").is_ok());
+        let err = adopt("0.0.0.0:0").unwrap_err();
+        assert!(err.to_string().contains("Security violation"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_watchdog() {
+        assert_eq!(parse_watchdog(Some("10000000"), None, 42), Some(Duration::from_secs(5)));
+        assert_eq!(parse_watchdog(Some("10000000"), Some("42"), 42), Some(Duration::from_secs(5)));
+        assert_eq!(parse_watchdog(Some("10000000"), Some("7"), 42), None);
+        assert_eq!(parse_watchdog(Some("0"), None, 42), None);
+        assert_eq!(parse_watchdog(None, None, 42), None);
+    }
+
+    #[test]
+    fn test_take_for_prefers_named_socket() {
+        let mut sockets = ActivatedSockets {
+            sockets: vec![
+                ActivatedSocket { fd: 3, name: "tcp-https".to_string(), is_unix: false },
+                ActivatedSocket { fd: 4, name: "tcp-http".to_string(), is_unix: false },
+                ActivatedSocket { fd: 5, name: "daemon.socket".to_string(), is_unix: true },
+            ],
+        };
+        let http = ListenerConfig {
+            mode: ListenerMode::TcpHttp,
+            bind_host: "127.0.0.1".to_string(),
+            bind_port: 3443,
+            require_auth: None,
+        };
+        let unix = ListenerConfig { mode: ListenerMode::UnixSocket, ..http.clone() };
+
+        assert_eq!(sockets.take_for(&http).map(|s| s.fd), Some(4));
+        assert_eq!(sockets.take_for(&unix).map(|s| s.fd), Some(5));
+        // The remaining socket is named for another mode and is never used as a fallback
+        assert!(sockets.take_for(&http).is_none());
+        assert_eq!(sockets.sockets.len(), 1);
+    }
+
+    #[test]
+    fn test_send_notify_to_path_socket() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("notify.sock");
+        let receiver = UnixDatagram::bind(&path).unwrap();
+
+        send_notify(path.to_str().unwrap(), "READY=1").unwrap();
+
+        let mut buf = [0u8; 64];
+        let n = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+    }
+}
@@ -5,15 +5,32 @@
 // Handles TLS certificate generation and loading
 
 use std::fs;
+use std::future::{ready, Ready};
+use std::io;
+use std::net::IpAddr;
 use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use axum::middleware::AddExtension;
+use axum::Extension;
+use axum_server::accept::Accept;
+use axum_server::tls_rustls::{RustlsAcceptor, RustlsConfig};
+use futures::future::{BoxFuture, Either};
+use ipnet::IpNet;
 use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair};
 use rustls::pki_types::{CertificateDer, PrivateKeyDer};
-use rustls::ServerConfig;
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
 use rustls_pemfile::{certs, private_key};
-use tracing::{info, warn};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_rustls::server::TlsStream;
+use tower::Layer;
+use tracing::{debug, info, warn};
+
+use crate::auth::{ClientCertIdentity, Identity};
+use crate::config::ClientAuthConfig;
 
 /// Generate a self-signed certificate for the daemon
 /// Returns the certificate fingerprint
@@ -191,8 +208,36 @@ pub fn load_tls_cert_and_key(
     Ok((certs, key))
 }
 
+/// Load the CA bundle that client certificates must chain to
+fn load_client_ca(ca_path: &Path) -> Result<RootCertStore> {
+    let ca_file = fs::File::open(ca_path)
+        .with_context(|| format!("Failed to open client CA bundle {}", ca_path.display()))?;
+    let mut ca_reader = std::io::BufReader::new(ca_file);
+
+    let mut roots = RootCertStore::empty();
+    for cert in certs(&mut ca_reader) {
+        let cert = cert.context("Failed to parse client CA bundle")?;
+        roots
+            .add(cert)
+            .context("Invalid certificate in client CA bundle")?;
+    }
+
+    if roots.is_empty() {
+        anyhow::bail!("No certificates found in client CA bundle {}", ca_path.display());
+    }
+
+    Ok(roots)
+}
+
 /// Create a rustls ServerConfig from certificate and key files
-pub fn create_tls_config(cert_path: &Path, key_path: &Path) -> Result<Arc<ServerConfig>> {
+///
+/// With `client_auth`, client certificates issued by the configured CA are
+/// verified (and required unless `required = false`).
+pub fn create_tls_config(
+    cert_path: &Path,
+    key_path: &Path,
+    client_auth: Option<&ClientAuthConfig>,
+) -> Result<Arc<ServerConfig>> {
     let mut needs_regeneration = false;
 
     // Check if certificate/key exist
@@ -231,8 +276,24 @@ pub fn create_tls_config(cert_path: &Path, key_path: &Path) -> Result<Arc<Server
     let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
 
     // Create rustls server config
-    let mut config = ServerConfig::builder()
-        .with_no_client_auth()
+    let builder = match client_auth {
+        Some(client_auth) => {
+            let roots = Arc::new(load_client_ca(&client_auth.ca_path)?);
+            let verifier = WebPkiClientVerifier::builder(roots);
+            let verifier = if client_auth.required {
+                verifier
+            } else {
+                verifier.allow_unauthenticated()
+            };
+            ServerConfig::builder().with_client_cert_verifier(
+                verifier
+                    .build()
+                    .context("Failed to create client certificate verifier")?,
+            )
+        }
+        None => ServerConfig::builder().with_no_client_auth(),
+    };
+    let mut config = builder
         .with_single_cert(certs, key)
         .context("Failed to create TLS configuration")?;
 
@@ -242,6 +303,158 @@ pub fn create_tls_config(cert_path: &Path, key_path: &Path) -> Result<Arc<Server
     Ok(Arc::new(config))
 }
 
+/// Subject common name and DNS/email/URI SANs of a certificate
+fn certificate_names(der: &[u8]) -> Option<(Option<String>, Vec<String>)> {
+    use x509_parser::prelude::*;
+
+    let (_, cert) = X509Certificate::from_der(der).ok()?;
+
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(str::to_string);
+
+    let sans = match cert.subject_alternative_name() {
+        Ok(Some(ext)) => ext
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(v) | GeneralName::RFC822Name(v) | GeneralName::URI(v) => {
+                    Some(v.to_string())
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Some((common_name, sans))
+}
+
+/// Identity for a client certificate that rustls already verified
+///
+/// The first mapping matching the CN or a SAN wins. Certificates matching no
+/// mapping (all of them, when none are configured) get no identity, so their
+/// requests still need a token.
+pub fn client_cert_identity(der: &[u8], client_auth: &ClientAuthConfig) -> Option<Identity> {
+    let (common_name, sans) = certificate_names(der)?;
+
+    client_auth
+        .identities
+        .iter()
+        .find(|mapping| {
+            mapping
+                .common_name
+                .as_ref()
+                .is_some_and(|cn| common_name.as_ref() == Some(cn))
+                || mapping.san.as_ref().is_some_and(|san| sans.contains(san))
+        })
+        .map(|mapping| {
+            Identity::new(
+                &mapping.name,
+                mapping.scopes.clone(),
+                mapping.profile_ids.clone(),
+                mapping.profile_tags.clone(),
+            )
+        })
+}
+
+/// HTTPS acceptor that attaches the client certificate identity to every
+/// request on the connection
+#[derive(Clone)]
+pub struct ClientCertAcceptor {
+    inner: RustlsAcceptor,
+    client_auth: Option<Arc<ClientAuthConfig>>,
+}
+
+impl ClientCertAcceptor {
+    pub fn new(config: RustlsConfig, client_auth: Option<ClientAuthConfig>) -> Self {
+        Self {
+            inner: RustlsAcceptor::new(config),
+            client_auth: client_auth.map(Arc::new),
+        }
+    }
+}
+
+impl<I, S> Accept<I, S> for ClientCertAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = TlsStream<I>;
+    type Service = AddExtension<S, ClientCertIdentity>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let acceptor = self.inner.clone();
+        let client_auth = self.client_auth.clone();
+
+        Box::pin(async move {
+            let (stream, service) = acceptor.accept(stream, service).await?;
+
+            let identity = client_auth.as_deref().and_then(|client_auth| {
+                let peer_certs = stream.get_ref().1.peer_certificates()?;
+                client_cert_identity(peer_certs.first()?, client_auth)
+            });
+            if let Some(identity) = &identity {
+                debug!("TLS client authenticated as '{}'", identity.name);
+            }
+
+            Ok((stream, Extension(ClientCertIdentity(identity)).layer(service)))
+        })
+    }
+}
+
+/// Acceptor that drops connections from addresses outside `allowed_networks`
+/// before handing them to `inner` (so before the TLS handshake)
+#[derive(Clone)]
+pub struct NetworkAllowlist<A> {
+    networks: Arc<Vec<IpNet>>,
+    inner: A,
+}
+
+impl<A> NetworkAllowlist<A> {
+    /// An empty list allows every address
+    pub fn new(networks: Vec<IpNet>, inner: A) -> Self {
+        Self {
+            networks: Arc::new(networks),
+            inner,
+        }
+    }
+
+    fn permits(&self, ip: IpAddr) -> bool {
+        // IPv4 clients on a dual-stack listener show up as ::ffff:a.b.c.d
+        let ip = ip.to_canonical();
+        self.networks.is_empty() || self.networks.iter().any(|network| network.contains(&ip))
+    }
+}
+
+impl<A, S> Accept<TcpStream, S> for NetworkAllowlist<A>
+where
+    A: Accept<TcpStream, S>,
+{
+    type Stream = A::Stream;
+    type Service = A::Service;
+    type Future = Either<A::Future, Ready<io::Result<(Self::Stream, Self::Service)>>>;
+
+    fn accept(&self, stream: TcpStream, service: S) -> Self::Future {
+        match stream.peer_addr() {
+            Ok(addr) if self.permits(addr.ip()) => Either::Left(self.inner.accept(stream, service)),
+            Ok(addr) => {
+                warn!("Rejected connection from {}: not in allowed_networks", addr.ip());
+                Either::Right(ready(Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "client address not allowed",
+                ))))
+            }
+            Err(e) => Either::Right(ready(Err(e))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,7 +478,7 @@ mod tests {
         assert!(!certs.is_empty());
 
         // Verify we can create TLS config
-        let _config = create_tls_config(&cert_path, &key_path).unwrap();
+        let _config = create_tls_config(&cert_path, &key_path, None).unwrap();
     }
 
     #[test]
@@ -275,10 +488,81 @@ mod tests {
         let key_path = temp_dir.path().join("auto.key");
 
         // Should auto-generate if missing
-        let _config = create_tls_config(&cert_path, &key_path).unwrap();
+        let _config = create_tls_config(&cert_path, &key_path, None).unwrap();
 
         // Verify files were created
         assert!(cert_path.exists());
         assert!(key_path.exists());
     }
+
+    #[test]
+    fn test_network_allowlist() {
+        let allowlist = NetworkAllowlist::new(
+            vec!["192.168.1.0/24".parse().unwrap(), "2001:db8::/32".parse().unwrap()],
+            (),
+        );
+        assert!(allowlist.permits("192.168.1.20".parse().unwrap()));
+        assert!(allowlist.permits("::ffff:192.168.1.20".parse().unwrap()));
+        assert!(allowlist.permits("2001:db8::1".parse().unwrap()));
+        assert!(!allowlist.permits("10.0.0.1".parse().unwrap()));
+
+        assert!(NetworkAllowlist::new(Vec::new(), ()).permits("10.0.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_client_certificate_identity() {
+        use crate::config::ClientIdentityMapping;
+        use rcgen::{BasicConstraints, IsCa};
+        use ssh_tunnel_common::TokenScope;
+
+        let temp_dir = TempDir::new().unwrap();
+
+        // Internal CA and a client certificate it issued
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        ca_params.distinguished_name.push(DnType::CommonName, "Test CA");
+        let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+        let ca_path = temp_dir.path().join("ca.pem");
+        fs::write(&ca_path, ca_cert.pem()).unwrap();
+
+        let client_key = KeyPair::generate().unwrap();
+        let mut client_params = CertificateParams::new(vec!["ops.example.com".to_string()]).unwrap();
+        client_params.distinguished_name.push(DnType::CommonName, "ops-laptop");
+        let client_cert = client_params.signed_by(&client_key, &ca_cert, &ca_key).unwrap();
+
+        // Without mappings a certificate grants nothing by itself
+        let mut client_auth = ClientAuthConfig {
+            ca_path: ca_path.clone(),
+            required: true,
+            identities: vec![],
+        };
+        assert!(client_cert_identity(client_cert.der(), &client_auth).is_none());
+
+        // Mapped by SAN
+        client_auth.identities.push(ClientIdentityMapping {
+            name: "ops".to_string(),
+            common_name: None,
+            san: Some("ops.example.com".to_string()),
+            scopes: vec![TokenScope::Read],
+            profile_ids: vec![],
+            profile_tags: vec![],
+        });
+        let identity = client_cert_identity(client_cert.der(), &client_auth).unwrap();
+        assert_eq!(identity.name, "ops");
+        assert!(identity.has_scope(TokenScope::Read));
+        assert!(!identity.has_scope(TokenScope::Control));
+
+        // Certificates matching no mapping get no identity
+        client_auth.identities[0].san = Some("other.example.com".to_string());
+        assert!(client_cert_identity(client_cert.der(), &client_auth).is_none());
+
+        // Server config accepts the CA bundle
+        let cert_path = temp_dir.path().join("server.crt");
+        let key_path = temp_dir.path().join("server.key");
+        create_tls_config(&cert_path, &key_path, Some(&client_auth)).unwrap();
+
+        client_auth.ca_path = temp_dir.path().join("missing.pem");
+        assert!(create_tls_config(&cert_path, &key_path, Some(&client_auth)).is_err());
+    }
 }
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Traffic counters for forwarded connections
+//
+// Each profile has one `TrafficCounters`, kept across reconnects for the
+// metrics endpoint and shared with all of its forwarded connections. The
+// local side of every connection is wrapped in a `CountingStream`, so byte
+// counts are live while long connections are still open, not only once
+// they close. `stats` events report a `Session`: the counts since the
+// tunnel last connected.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::Interval;
+
+use ssh_tunnel_common::TunnelStats;
+
+/// Counters shared by all connections of one profile, since the daemon started
+#[derive(Debug, Default)]
+pub struct TrafficCounters {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    active_connections: AtomicU64,
+    total_connections: AtomicU64,
+    channel_failures: AtomicU64,
+}
+
+impl TrafficCounters {
+    /// Count a new connection; it stays active until the guard is dropped
+    pub fn open_connection(self: &Arc<Self>) -> ConnectionGuard {
+        self.active_connections.fetch_add(1, Ordering::Relaxed);
+        self.total_connections.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard(self.clone())
+    }
+
+    /// Count an accepted connection the SSH server refused a channel for
+    pub fn record_channel_failure(&self) {
+        self.channel_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received.load(Ordering::Relaxed)
+    }
+
+    pub fn active_connections(&self) -> u64 {
+        self.active_connections.load(Ordering::Relaxed)
+    }
+
+    pub fn total_connections(&self) -> u64 {
+        self.total_connections.load(Ordering::Relaxed)
+    }
+
+    pub fn channel_failures(&self) -> u64 {
+        self.channel_failures.load(Ordering::Relaxed)
+    }
+}
+
+/// One connected period of a tunnel
+pub struct Session {
+    counters: Arc<TrafficCounters>,
+    started: Instant,
+    bytes_sent: u64,
+    bytes_received: u64,
+    total_connections: u64,
+}
+
+impl Session {
+    pub fn start(counters: Arc<TrafficCounters>) -> Self {
+        Self {
+            started: Instant::now(),
+            bytes_sent: counters.bytes_sent(),
+            bytes_received: counters.bytes_received(),
+            total_connections: counters.total_connections(),
+            counters,
+        }
+    }
+
+    pub fn counters(&self) -> &Arc<TrafficCounters> {
+        &self.counters
+    }
+
+    /// Traffic since the session started
+    pub fn stats(&self) -> TunnelStats {
+        TunnelStats {
+            bytes_sent: self.counters.bytes_sent() - self.bytes_sent,
+            bytes_received: self.counters.bytes_received() - self.bytes_received,
+            active_connections: self.counters.active_connections(),
+            total_connections: self.counters.total_connections() - self.total_connections,
+            uptime_secs: self.started.elapsed().as_secs(),
+        }
+    }
+}
+
+/// Marks a connection as active while alive
+pub struct ConnectionGuard(Arc<TrafficCounters>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.active_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Local side of a forwarded connection: bytes read from it are sent through
+/// the tunnel, bytes written to it were received through the tunnel
+pub struct CountingStream<S> {
+    inner: S,
+    counters: Arc<TrafficCounters>,
+}
+
+impl<S> CountingStream<S> {
+    pub fn new(inner: S, counters: Arc<TrafficCounters>) -> Self {
+        Self { inner, counters }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CountingStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            let read = (buf.filled().len() - before) as u64;
+            self.counters.bytes_sent.fetch_add(read, Ordering::Relaxed);
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for CountingStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = poll {
+            self.counters
+                .bytes_received
+                .fetch_add(written as u64, Ordering::Relaxed);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Wait for the next stats tick; never completes when stats are disabled
+pub async fn next_tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn exchange(counters: &Arc<TrafficCounters>) {
+        let (local, mut peer) = tokio::io::duplex(64);
+        let _active = counters.open_connection();
+        let mut stream = CountingStream::new(local, counters.clone());
+
+        peer.write_all(b"request").await.unwrap();
+        let mut buf = [0u8; 7];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(b"response!").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_counting_stream() {
+        let counters = Arc::new(TrafficCounters::default());
+
+        let (local, mut peer) = tokio::io::duplex(64);
+        let guard = counters.open_connection();
+        let mut stream = CountingStream::new(local, counters.clone());
+
+        peer.write_all(b"request").await.unwrap();
+        let mut buf = [0u8; 7];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(b"response!").await.unwrap();
+
+        assert_eq!(counters.bytes_sent(), 7);
+        assert_eq!(counters.bytes_received(), 9);
+        assert_eq!(counters.active_connections(), 1);
+        assert_eq!(counters.total_connections(), 1);
+
+        drop(guard);
+        assert_eq!(counters.active_connections(), 0);
+        assert_eq!(counters.total_connections(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_session_counts_from_its_start() {
+        let counters = Arc::new(TrafficCounters::default());
+        exchange(&counters).await;
+
+        // A reconnect starts a new session; the profile totals keep growing
+        let session = Session::start(counters.clone());
+        exchange(&counters).await;
+        exchange(&counters).await;
+
+        let stats = session.stats();
+        assert_eq!(stats.bytes_sent, 14);
+        assert_eq!(stats.bytes_received, 18);
+        assert_eq!(stats.total_connections, 2);
+        assert_eq!(stats.active_connections, 0);
+        assert_eq!(counters.total_connections(), 3);
+    }
+}
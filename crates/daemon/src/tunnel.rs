@@ -8,38 +8,80 @@ use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use russh::client::{self, AuthResult, Config, Handle, KeyboardInteractiveAuthResponse};
+use russh::keys::ssh_key::{HashAlg, LineEnding};
 use russh::keys::{load_secret_key, PrivateKey, PrivateKeyWithHashAlg, Error as RusshKeyError};
 use tokio::io::copy_bidirectional;
 use tokio::net::TcpListener;
 use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
 //use tokio::task::JoinHandle;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Instrument};
 use uuid::Uuid;
+use zeroize::Zeroizing;
 
 use ssh_tunnel_common::{
-    AuthRequest, AuthRequestType, ForwardingType, PasswordStorage, Profile, TunnelStatus,
+    AuthPrompt, AuthRequest, AuthRequestType, AuthType, ForwardingType, HookStage,
+    Profile, RunningBind, TunnelStats, TunnelStatus, UnlockedKey,
 };
 
+use crate::askpass::Askpass;
+use crate::config::{AuthPromptsConfig, KeyCacheConfig, NoClientPolicy, PortRange, SecretsConfig};
+use crate::events::{EventBus, SequencedEvent, REPLAY_CAPACITY};
+use crate::hooks;
+use crate::keycache::{self, KeyCache};
+use crate::metrics::{TunnelMetrics, TunnelSample};
+use crate::security::SecretStore;
+use crate::traffic::{self, CountingStream, Session, TrafficCounters};
+
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
-const AUTH_RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Forwarding types this daemon can run (advertised in `/api/v1/capabilities`)
+pub const SUPPORTED_FORWARDING_TYPES: &[ForwardingType] = &[ForwardingType::Local];
+
+/// SSH authentication types this daemon can handle
+pub const SUPPORTED_AUTH_TYPES: &[AuthType] =
+    &[AuthType::Key, AuthType::Password, AuthType::PasswordWith2FA];
 
 /// Event sent when tunnel state changes (for future WebSocket notifications to GUI)
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
 pub enum TunnelEvent {
     Starting { id: Uuid },
-    Connected { id: Uuid },
+    Connected { id: Uuid, bound_addr: Option<SocketAddr> },
     Disconnected { id: Uuid, reason: String },
     Error { id: Uuid, error: String },
     AuthRequired { id: Uuid, request: AuthRequest },
+    /// The pending auth request was answered
+    AuthResolved { id: Uuid, request_id: Uuid },
+    /// The pending auth request was withdrawn without an answer
+    AuthCancelled { id: Uuid, request_id: Uuid, reason: String },
+    Hook { id: Uuid, stage: HookStage, success: bool, output: String },
+    /// Periodic traffic counters of a connected tunnel
+    Stats { id: Uuid, stats: TunnelStats },
 }
 
-/// Channel for sending auth responses to a waiting tunnel
-pub type AuthResponseSender = oneshot::Sender<String>;
+impl TunnelEvent {
+    /// Tunnel the event belongs to
+    pub fn id(&self) -> Uuid {
+        match self {
+            TunnelEvent::Starting { id }
+            | TunnelEvent::Connected { id, .. }
+            | TunnelEvent::Disconnected { id, .. }
+            | TunnelEvent::Error { id, .. }
+            | TunnelEvent::AuthRequired { id, .. }
+            | TunnelEvent::AuthResolved { id, .. }
+            | TunnelEvent::AuthCancelled { id, .. }
+            | TunnelEvent::Hook { id, .. }
+            | TunnelEvent::Stats { id, .. } => *id,
+        }
+    }
+}
+
+/// Channel for sending auth responses (one per prompt) to a waiting tunnel
+pub type AuthResponseSender = oneshot::Sender<Vec<String>>;
 
 /// Pending authentication request
 pub struct PendingAuth {
@@ -56,6 +98,74 @@ pub struct ActiveTunnel {
     /// Pending authentication request, if any
     pub pending_auth: Option<PendingAuth>,
     join_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Address the local forward is bound to (resolves port 0), while connected
+    pub bound_addr: Option<SocketAddr>,
+    /// When the tunnel last connected, while connected
+    connected_at: Option<Instant>,
+    /// Counters for `/metrics`, kept when the tunnel is restarted
+    metrics: Arc<TunnelMetrics>,
+}
+
+impl ActiveTunnel {
+    /// Drop the pending auth request, if any, telling clients it's gone
+    fn cancel_pending_auth(&mut self, id: Uuid, event_tx: &EventBus, reason: &str) {
+        if let Some(pending) = self.pending_auth.take() {
+            if let Err(e) = event_tx.send(TunnelEvent::AuthCancelled {
+                id,
+                request_id: pending.request.id,
+                reason: reason.to_string(),
+            }) {
+                debug!("Failed to broadcast AuthCancelled event for {}: {}", id, e);
+            }
+        }
+    }
+
+    /// Hand the answers to the pending auth request `request_id`
+    fn resolve_pending_auth(
+        &mut self,
+        id: Uuid,
+        event_tx: &EventBus,
+        request_id: Uuid,
+        responses: Vec<String>,
+    ) -> Result<()> {
+        let pending = self
+            .pending_auth
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No pending authentication request"))?;
+
+        // Verify request ID matches
+        if pending.request.id != request_id {
+            anyhow::bail!(
+                "Request ID mismatch (expected {}, got {})",
+                pending.request.id,
+                request_id
+            );
+        }
+
+        // One answer per prompt; fewer are fine, the rest are asked again
+        let expected = pending.request.prompts.len().max(1);
+        if responses.is_empty() || responses.len() > expected {
+            anyhow::bail!("Expected 1 to {} answers, got {}", expected, responses.len());
+        }
+
+        // Take the pending auth now that we've verified it
+        let pending = self.pending_auth.take().unwrap();
+
+        // Send the response to the waiting tunnel task
+        pending
+            .response_tx
+            .send(responses)
+            .map_err(|_| anyhow::anyhow!("Tunnel task is no longer waiting for auth"))?;
+
+        // Update status back to connecting
+        self.status = TunnelStatus::Connecting;
+
+        if let Err(e) = event_tx.send(TunnelEvent::AuthResolved { id, request_id }) {
+            debug!("Failed to broadcast AuthResolved event for {}: {}", id, e);
+        }
+
+        Ok(())
+    }
 }
 
 // Manual Debug impl since PendingAuth contains oneshot channels
@@ -65,6 +175,7 @@ impl std::fmt::Debug for ActiveTunnel {
             .field("profile", &self.profile.metadata.name)
             .field("status", &self.status)
             .field("has_pending_auth", &self.pending_auth.is_some())
+            .field("bound_addr", &self.bound_addr)
             .finish()
     }
 }
@@ -74,27 +185,92 @@ impl std::fmt::Debug for ActiveTunnel {
 pub struct TunnelManager {
     /// Active tunnels indexed by profile ID
     tunnels: Arc<RwLock<HashMap<Uuid, ActiveTunnel>>>,
-    /// Event broadcaster
-    event_tx: broadcast::Sender<TunnelEvent>,
+    /// Event broadcaster with replay buffer
+    event_tx: EventBus,
     /// Path to known_hosts file (from daemon config)
     known_hosts_path: Arc<PathBuf>,
+    /// Ports to pick from when a profile asks for any free local port
+    ephemeral_port_range: Option<PortRange>,
+    /// Period of `Stats` events for connected tunnels (`None`: disabled)
+    stats_interval: Option<Duration>,
+    /// Timeout of auth prompts and what they do without a connected client
+    auth_prompts: AuthPromptsConfig,
+    /// Stored passwords and passphrases (keychain, secret commands)
+    secrets: Arc<SecretStore>,
+    /// Decrypted keys shared by all tunnels
+    keys: Arc<KeyCache>,
 }
 
 impl TunnelManager {
     pub fn new(known_hosts_path: PathBuf) -> Self {
-        let (event_tx, _) = broadcast::channel(100);
+        let event_tx = EventBus::new(REPLAY_CAPACITY);
         Self {
             tunnels: Arc::new(RwLock::new(HashMap::new())),
             event_tx,
             known_hosts_path: Arc::new(known_hosts_path),
+            ephemeral_port_range: None,
+            stats_interval: Some(Duration::from_secs(30)),
+            auth_prompts: AuthPromptsConfig::default(),
+            secrets: Arc::new(SecretStore::default()),
+            keys: Arc::new(KeyCache::default()),
+        }
+    }
+
+    /// Restrict "any free port" local forwards to the given range
+    pub fn with_ephemeral_port_range(mut self, range: Option<PortRange>) -> Self {
+        self.ephemeral_port_range = range;
+        self
+    }
+
+    /// Emit traffic stats for connected tunnels every `secs` seconds (0: never)
+    pub fn with_stats_interval(mut self, secs: u64) -> Self {
+        self.stats_interval = (secs > 0).then(|| Duration::from_secs(secs));
+        self
+    }
+
+    /// Apply the daemon's auth prompt settings (profiles may override the timeout)
+    pub fn with_auth_prompts(mut self, config: AuthPromptsConfig) -> Self {
+        self.auth_prompts = config;
+        self
+    }
+
+    /// Apply the daemon's secret command timeout and cache lifetime
+    pub fn with_secrets(mut self, config: &SecretsConfig) -> Self {
+        self.secrets = Arc::new(SecretStore::new(config));
+        self
+    }
+
+    /// Apply the daemon's key cache settings
+    pub fn with_key_cache(mut self, config: &KeyCacheConfig) -> Self {
+        self.keys = Arc::new(KeyCache::new(config));
+        self
+    }
+
+    /// Decrypted keys shared by all tunnels
+    pub fn key_cache(&self) -> &Arc<KeyCache> {
+        &self.keys
+    }
+
+    /// Decrypt a key file into the key cache
+    pub async fn unlock_key(&self, key_path: &str, passphrase: Zeroizing<String>) -> Result<UnlockedKey> {
+        if !self.keys.is_enabled() {
+            anyhow::bail!("The key cache is disabled ([key_cache] enabled = false)");
         }
+        let path = keycache::resolve_unlock_path(Path::new(key_path))?;
+        let _unlocking = self.keys.exclusive(&path).await;
+        unlock_key_file(&self.keys, path, passphrase).await
     }
 
     /// Subscribe to tunnel events
-    pub fn subscribe(&self) -> broadcast::Receiver<TunnelEvent> {
+    pub fn subscribe(&self) -> broadcast::Receiver<SequencedEvent> {
         self.event_tx.subscribe()
     }
 
+    /// Event bus, for subscribers that resume from an event ID
+    pub fn events(&self) -> &EventBus {
+        &self.event_tx
+    }
+
     /// Stop all active tunnels (best-effort)
     pub async fn stop_all(&self) {
         let ids: Vec<Uuid> = {
@@ -124,6 +300,33 @@ impl TunnelManager {
             .collect()
     }
 
+    /// Profile of an active tunnel
+    pub async fn get_profile(&self, id: &Uuid) -> Option<Profile> {
+        let tunnels = self.tunnels.read().await;
+        tunnels.get(id).map(|t| t.profile.clone())
+    }
+
+    /// Get the address a tunnel's local forward is bound to
+    pub async fn get_bound_addr(&self, id: &Uuid) -> Option<SocketAddr> {
+        let tunnels = self.tunnels.read().await;
+        tunnels.get(id).and_then(|t| t.bound_addr)
+    }
+
+    /// Local addresses currently held by running tunnels
+    pub async fn running_binds(&self) -> Vec<RunningBind> {
+        let tunnels = self.tunnels.read().await;
+        tunnels
+            .iter()
+            .filter_map(|(id, t)| {
+                t.bound_addr.map(|addr| RunningBind {
+                    profile_id: *id,
+                    profile_name: t.profile.metadata.name.clone(),
+                    addr,
+                })
+            })
+            .collect()
+    }
+
     /// Get pending authentication request for a tunnel
     pub async fn get_pending_auth(&self, id: &Uuid) -> Option<AuthRequest> {
         let tunnels = self.tunnels.read().await;
@@ -133,50 +336,55 @@ impl TunnelManager {
             .map(|p| p.request.clone())
     }
 
+    /// Per-tunnel state and counters for `/metrics`
+    pub async fn metrics_samples(&self) -> Vec<TunnelSample> {
+        let tunnels = self.tunnels.read().await;
+        tunnels
+            .iter()
+            .map(|(id, t)| TunnelSample {
+                id: *id,
+                name: t.profile.metadata.name.clone(),
+                status: t.status.clone(),
+                session_age: t
+                    .connected_at
+                    .filter(|_| t.status == TunnelStatus::Connected)
+                    .map(|at| at.elapsed()),
+                pending_auth: t.pending_auth.is_some(),
+                metrics: t.metrics.clone(),
+            })
+            .collect()
+    }
+
+    /// Traffic counters of a tunnel's forwarded connections
+    pub async fn traffic(&self, id: &Uuid) -> Option<Arc<TrafficCounters>> {
+        let tunnels = self.tunnels.read().await;
+        tunnels.get(id).map(|t| t.metrics.traffic.clone())
+    }
+
     /// Submit authentication response for a tunnel with request ID verification
     pub async fn submit_auth_by_request_id(
         &self,
         tunnel_id: &Uuid,
         request_id: Uuid,
-        response: String,
+        responses: Vec<String>,
     ) -> Result<()> {
+        for response in &responses {
+            crate::logging::register_answer(*tunnel_id, response);
+        }
         let mut tunnels = self.tunnels.write().await;
 
         let tunnel = tunnels
             .get_mut(tunnel_id)
             .ok_or_else(|| anyhow::anyhow!("Tunnel not found"))?;
 
-        let pending = tunnel
-            .pending_auth
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("No pending authentication request"))?;
-
-        // Verify request ID matches
-        if pending.request.id != request_id {
-            anyhow::bail!(
-                "Request ID mismatch (expected {}, got {})",
-                pending.request.id,
-                request_id
-            );
-        }
-
-        // Take the pending auth now that we've verified it
-        let pending = tunnel.pending_auth.take().unwrap();
-
-        // Send the response to the waiting tunnel task
-        pending
-            .response_tx
-            .send(response)
-            .map_err(|_| anyhow::anyhow!("Tunnel task is no longer waiting for auth"))?;
-
-        // Update status back to connecting
-        tunnel.status = TunnelStatus::Connecting;
-
-        Ok(())
+        tunnel.resolve_pending_auth(*tunnel_id, &self.event_tx, request_id, responses)
     }
 
     /// Start a tunnel for the given profile
-    pub async fn start(&self, profile: Profile) -> Result<()> {
+    ///
+    /// Without `shared_keys` the tunnel neither uses nor fills the key cache,
+    /// so it can't borrow keys that were unlocked by someone else.
+    pub async fn start(&self, profile: Profile, shared_keys: bool) -> Result<()> {
         let id = profile.metadata.id;
 
         // Check if already running
@@ -203,11 +411,18 @@ impl TunnelManager {
         info!("Starting tunnel: {} ({})", profile.metadata.name, id);
 
         // Create shutdown channel
-        let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>(1);
+        let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
 
-        // Register tunnel as connecting
+        // Register tunnel as connecting, keeping the counters of earlier runs
+        let metrics_for_task;
         {
             let mut tunnels = self.tunnels.write().await;
+            let metrics = tunnels
+                .get(&id)
+                .map(|t| t.metrics.clone())
+                .unwrap_or_default();
+            metrics.record_start();
+            metrics_for_task = metrics.clone();
             tunnels.insert(
                 id,
                 ActiveTunnel {
@@ -216,10 +431,14 @@ impl TunnelManager {
                     shutdown_tx: Some(shutdown_tx),
                     pending_auth: None,
                     join_handle: None,
+                    bound_addr: None,
+                    connected_at: None,
+                    metrics,
                 },
             );
         }
 
+        self.event_tx.track(id, &profile.metadata.name);
         if let Err(e) = self.event_tx.send(TunnelEvent::Starting { id }) {
             debug!("Failed to broadcast Starting event for {}: {}", id, e);
         }
@@ -229,18 +448,61 @@ impl TunnelManager {
         let event_tx_for_task = self.event_tx.clone();
         let profile_for_task = profile.clone();
         let known_hosts_path_for_task = self.known_hosts_path.clone();
+        let port_range_for_task = self.ephemeral_port_range;
+        let stats_interval_for_task = self.stats_interval;
+        let auth_prompts_for_task = self.auth_prompts.clone();
+        let secrets_for_task = self.secrets.clone();
+        let keys_for_task = if shared_keys {
+            self.keys.clone()
+        } else {
+            // Disabled: every connect decrypts the key itself
+            Arc::new(KeyCache::default())
+        };
 
-        // Spawn the tunnel task
-        let handle = tokio::spawn(async move {
-            match run_tunnel(
-                profile_for_task.clone(),
-                shutdown_rx,
-                tunnels_for_task.clone(),
-                event_tx_for_task.clone(),
-                known_hosts_path_for_task,
+        // Spawn the tunnel task; its logs are captured for this tunnel
+        let span = crate::tunnel_log::tunnel_span(id, &profile.metadata.name);
+        let task = async move {
+            let pre_up = hooks::run_hook(
+                HookStage::PreUp,
+                &profile_for_task,
+                None,
+                None,
+                &event_tx_for_task,
             )
-            .await
-            {
+            .await;
+
+            let result = match pre_up {
+                Err(e) if profile_for_task.hooks.abort_on_pre_up_failure => {
+                    Err(anyhow::anyhow!("pre-up hook failed: {:#}", e))
+                }
+                // Stopped while the pre-up hook ran: don't connect at all
+                _ if shutdown_rx.try_recv().is_ok() => Ok(()),
+                _ => {
+                    run_tunnel(
+                        profile_for_task.clone(),
+                        shutdown_rx,
+                        tunnels_for_task.clone(),
+                        event_tx_for_task.clone(),
+                        known_hosts_path_for_task,
+                        port_range_for_task,
+                        stats_interval_for_task,
+                        auth_prompts_for_task,
+                        secrets_for_task,
+                        keys_for_task,
+                    )
+                    .await
+                }
+            };
+
+            // Captured before the state below clears them, for the post-down hook
+            let failure_reason = result.as_ref().err().map(|e| e.to_string());
+            let last_bound_addr = tunnels_for_task
+                .read()
+                .await
+                .get(&id)
+                .and_then(|t| t.bound_addr);
+
+            match result {
                 Ok(()) => {
                     info!("Tunnel {} stopped normally", profile_for_task.metadata.name);
                     if let Err(e) = event_tx_for_task.send(TunnelEvent::Disconnected {
@@ -257,6 +519,8 @@ impl TunnelManager {
                         tunnel.shutdown_tx = None;
                         tunnel.pending_auth = None;
                         tunnel.join_handle = None;
+                        tunnel.bound_addr = None;
+                        tunnel.connected_at = None;
                     }
                 }
                 Err(e) => {
@@ -287,9 +551,11 @@ impl TunnelManager {
                         if !matches!(tunnel.status, TunnelStatus::Failed(_)) {
                             tunnel.status = TunnelStatus::Failed(e.to_string());
                         }
-                        tunnel.pending_auth = None;
+                        tunnel.cancel_pending_auth(id, &event_tx_for_task, "Tunnel failed");
                         tunnel.shutdown_tx = None;
                         tunnel.join_handle = None;
+                        tunnel.bound_addr = None;
+                        tunnel.connected_at = None;
                     } else {
                         // If it was already removed, re-insert a minimal failed entry
                         tunnels.insert(
@@ -300,12 +566,25 @@ impl TunnelManager {
                                 shutdown_tx: None,
                                 pending_auth: None,
                                 join_handle: None,
+                                bound_addr: None,
+                                connected_at: None,
+                                metrics: metrics_for_task,
                             },
                         );
                     }
                 }
             }
-        });
+
+            let _ = hooks::run_hook(
+                HookStage::PostDown,
+                &profile_for_task,
+                last_bound_addr,
+                failure_reason.as_deref(),
+                &event_tx_for_task,
+            )
+            .await;
+        };
+        let handle = tokio::spawn(task.instrument(span));
 
         // Store the join handle back into the ActiveTunnel
         {
@@ -333,25 +612,31 @@ impl TunnelManager {
                     tunnel.profile.metadata.name
                 );
 
-                // Try graceful shutdown first
+                // Drop the pending auth sender first so the prompt fails at once
+                // ("Auth request was cancelled") and clients close their dialogs
+                tunnel.cancel_pending_auth(*id, &self.event_tx, "Tunnel stopped");
+
+                // Then shut down gracefully; with `shutdown_tx` gone the auth
+                // handler cancels prompts that were still on their way
                 if let Some(tx) = tunnel.shutdown_tx.take() {
-                    // Send shutdown signal - this should cause the tunnel task to exit gracefully
                     let _ = tx.send(()).await;
                 }
 
-                // Drop the pending auth sender - this will cause the oneshot receiver
-                // to return Err, which will be caught as "Auth request was cancelled"
-                tunnel.pending_auth = None;
+                tunnel.status = TunnelStatus::Disconnecting;
+                let shutdown_tx = tunnel.shutdown_tx.take();
+                let handle = tunnel.join_handle.take();
+                let grace = stop_grace(&tunnel.profile);
+
+                // The task records the stop in the tunnel map and then runs the
+                // post-down hook; don't hold the map while waiting for it
+                drop(tunnels);
+                if let Some(tx) = shutdown_tx {
+                    let _ = tx.send(()).await;
+                }
 
-                // Give the task a moment to respond to shutdown signal
-                // If it doesn't stop within 100ms, abort it forcefully
-                if let Some(mut handle) = tunnel.join_handle.take() {
-                    match tokio::time::timeout(
-                        tokio::time::Duration::from_millis(100),
-                        &mut handle
-                    ).await {
+                let finished = match handle {
+                    Some(mut handle) => match tokio::time::timeout(grace, &mut handle).await {
                         Ok(result) => {
-                            // Task finished gracefully within timeout
                             if let Err(e) = result {
                                 if e.is_cancelled() {
                                     debug!("Tunnel task was cancelled");
@@ -359,29 +644,48 @@ impl TunnelManager {
                                     debug!("Tunnel task panicked: {:?}", e);
                                 }
                             }
+                            true
                         }
                         Err(_) => {
-                            // Timeout elapsed, task didn't finish gracefully
+                            warn!("Tunnel task for {} did not stop within {:?}, aborting", id, grace);
                             handle.abort();
+                            false
                         }
-                    }
-                }
-
-                tunnel.status = TunnelStatus::Disconnected;
+                    },
+                    None => false,
+                };
 
-                // Emit disconnected event
-                if let Err(e) = self.event_tx.send(TunnelEvent::Disconnected {
-                    id: *id,
-                    reason: "Stopped during authentication".to_string(),
-                }) {
-                    debug!("Failed to broadcast Disconnected event for {}: {}", id, e);
+                // A task that finished has already recorded the stop
+                if !finished {
+                    let mut tunnels = self.tunnels.write().await;
+                    if let Some(tunnel) = tunnels.get_mut(id) {
+                        if tunnel.status == TunnelStatus::Disconnecting {
+                            tunnel.status = TunnelStatus::Disconnected;
+                        }
+                    }
+                    if let Err(e) = self.event_tx.send(TunnelEvent::Disconnected {
+                        id: *id,
+                        reason: "Stopped during authentication".to_string(),
+                    }) {
+                        debug!("Failed to broadcast Disconnected event for {}: {}", id, e);
+                    }
                 }
             }
             _ if tunnel.status.is_connected() => {
                 info!("Stopping tunnel: {}", tunnel.profile.metadata.name);
 
                 tunnel.status = TunnelStatus::Disconnecting;
-                if let Some(tx) = tunnel.shutdown_tx.take() {
+                let shutdown_tx = tunnel.shutdown_tx.take();
+                let profile = tunnel.profile.clone();
+                let bound_addr = tunnel.bound_addr;
+
+                // The pre-down hook runs while the forward is still up; don't hold
+                // the tunnel map meanwhile, and never let a failing hook block the stop
+                drop(tunnels);
+                let _ = hooks::run_hook(HookStage::PreDown, &profile, bound_addr, None, &self.event_tx)
+                    .await;
+
+                if let Some(tx) = shutdown_tx {
                     let _ = tx.send(()).await;
                 }
             }
@@ -394,6 +698,18 @@ impl TunnelManager {
     }
 }
 
+/// How long a tunnel stopped while connecting may take to wind down
+///
+/// Its task may still be in the pre-up hook, and runs post-down on the way
+/// out; it is only aborted once both could have timed out.
+fn stop_grace(profile: &Profile) -> Duration {
+    let hook = Duration::from_secs(profile.hooks.timeout_secs) + Duration::from_secs(1);
+    [HookStage::PreUp, HookStage::PostDown]
+        .into_iter()
+        .filter(|stage| profile.hooks.command(*stage).is_some())
+        .fold(Duration::from_millis(100), |grace, _| grace + hook)
+}
+
 /// SSH client handler for russh with keyboard-interactive support
 struct ClientHandler {
     /// Tunnel ID for this connection
@@ -522,7 +838,20 @@ impl client::Handler for ClientHandler {
 #[derive(Clone)]
 struct AuthContext {
     tunnel_id: Uuid,
-    auth_request_tx: mpsc::Sender<(AuthRequest, AuthResponseSender)>,
+    profile_name: String,
+    /// Where pending requests wait for the API to answer them
+    tunnels: Arc<RwLock<HashMap<Uuid, ActiveTunnel>>>,
+    /// Tells clients about prompts, and whether any is listening
+    events: EventBus,
+    /// How long to wait for an answer
+    timeout: Duration,
+    no_client: NoClientPolicy,
+    /// Answers prompts that no client answers
+    askpass: Option<Askpass>,
+    /// Where the profile's stored password or passphrase comes from
+    secrets: Arc<SecretStore>,
+    /// Keys unlocked earlier, by this or other tunnels
+    keys: Arc<KeyCache>,
 }
 
 impl AuthContext {
@@ -533,24 +862,122 @@ impl AuthContext {
         prompt: &str,
         hidden: bool,
     ) -> Result<String> {
-        let (response_tx, response_rx) = oneshot::channel();
-
         let request = AuthRequest {
             id: Uuid::new_v4(),
             tunnel_id: self.tunnel_id,
             auth_type,
             prompt: prompt.to_string(),
             hidden,
+            name: None,
+            instructions: None,
+            prompts: Vec::new(),
         };
+        let answers = self.send_request(request).await?;
+        answers
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Empty authentication response"))
+    }
 
-        // Send request to be picked up by API
-        self.auth_request_tx
-            .send((request, response_tx))
-            .await
-            .map_err(|_| anyhow::anyhow!("Failed to send auth request"))?;
+    /// Request answers to all prompts of a keyboard-interactive request
+    ///
+    /// Clients answer all prompts at once, or only the first one; the
+    /// remaining prompts are then asked again.
+    async fn request_prompts(
+        &self,
+        name: &str,
+        instructions: &str,
+        prompts: &[AuthPrompt],
+    ) -> Result<Vec<String>> {
+        let mut answers = Vec::with_capacity(prompts.len());
+        while let Some(first) = prompts.get(answers.len()) {
+            // Full text of the first prompt, for clients that show one at a time
+            let mut full_prompt = String::new();
+            for header in [name, instructions] {
+                if !header.trim().is_empty() {
+                    full_prompt.push_str(header);
+                    full_prompt.push('\n');
+                }
+            }
+            full_prompt.push_str(&first.prompt);
+
+            let request = AuthRequest {
+                id: Uuid::new_v4(),
+                tunnel_id: self.tunnel_id,
+                auth_type: AuthRequestType::KeyboardInteractive,
+                prompt: full_prompt,
+                hidden: !first.echo,
+                name: Some(name.to_string()).filter(|s| !s.trim().is_empty()),
+                instructions: Some(instructions.to_string()).filter(|s| !s.trim().is_empty()),
+                prompts: prompts[answers.len()..].to_vec(),
+            };
+            answers.extend(self.send_request(request).await?);
+        }
+        Ok(answers)
+    }
+
+    /// Send a request to clients and wait for its answers
+    async fn send_request(&self, request: AuthRequest) -> Result<Vec<String>> {
+        let no_client = self.events.feed_count() == 0;
+        let askpass = self
+            .askpass
+            .as_ref()
+            .filter(|_| Askpass::answers(&request.auth_type));
+        if no_client && self.no_client == NoClientPolicy::Fail && askpass.is_none() {
+            anyhow::bail!("No client is connected to answer the authentication prompt");
+        }
+
+        let (response_tx, mut response_rx) = oneshot::channel();
+        self.register(request.clone(), response_tx).await;
+
+        // Askpass fallback, once clients had their chance (none if none is connected)
+        if let Some(askpass) = askpass {
+            let grace = if no_client { Duration::ZERO } else { askpass.grace };
+            tokio::select! {
+                response = &mut response_rx => {
+                    return response.map_err(|_| anyhow::anyhow!("Auth request was cancelled"));
+                }
+                _ = tokio::time::sleep(grace) => {}
+            }
+
+            // Clients may still answer while the command runs
+            tokio::select! {
+                response = &mut response_rx => {
+                    return response.map_err(|_| anyhow::anyhow!("Auth request was cancelled"));
+                }
+                answer = askpass.ask(&request, &self.profile_name, self.timeout) => {
+                    match answer {
+                        Ok(answer) => {
+                            info!("Authentication prompt answered by askpass");
+                            self.resolve(request.id, answer).await;
+                        }
+                        Err(e) => {
+                            warn!("Askpass could not answer the authentication prompt: {:#}", e);
+                            if no_client && self.no_client == NoClientPolicy::Fail {
+                                anyhow::bail!(
+                                    "No client is connected to answer the authentication prompt"
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Deferred: the prompt stays pending, but the timeout only starts once
+        // a client is there to show it
+        if no_client && self.no_client == NoClientPolicy::Defer {
+            info!("No client connected; holding the authentication prompt until one connects");
+            tokio::select! {
+                response = &mut response_rx => {
+                    return response.map_err(|_| anyhow::anyhow!("Auth request was cancelled"));
+                }
+                _ = self.events.wait_for_feed() => {}
+            }
+        }
 
         // Wait for response from CLI (with a timeout)
-        match tokio::time::timeout(AUTH_RESPONSE_TIMEOUT, response_rx).await {
+        match tokio::time::timeout(self.timeout, response_rx).await {
             // Got a response before timeout
             Ok(Ok(response)) => Ok(response),
 
@@ -558,10 +985,66 @@ impl AuthContext {
             Ok(Err(_canceled)) => Err(anyhow::anyhow!("Auth request was cancelled")),
 
             // Timeout elapsed
-            Err(_elapsed) => Err(anyhow::anyhow!(format!(
+            Err(_elapsed) => Err(anyhow::anyhow!(
                 "Authentication prompt timed out after {:?}",
-                AUTH_RESPONSE_TIMEOUT
-            ))),
+                self.timeout
+            )),
+        }
+    }
+
+    /// The profile's stored password or passphrase, if it has one
+    ///
+    /// Asks for the vault's master passphrase when the vault is still locked.
+    async fn stored_secret(&self, profile: &Profile) -> Result<Option<Zeroizing<String>>> {
+        self.secrets
+            .get(profile, || {
+                self.request_input(
+                    AuthRequestType::VaultPassphrase,
+                    "Enter master passphrase of the secret vault: ",
+                    true,
+                )
+            })
+            .await
+    }
+
+    /// Make the request pending for the API and tell clients about it
+    ///
+    /// While the tunnel is being stopped `response_tx` is dropped instead,
+    /// which cancels the prompt right away.
+    async fn register(&self, request: AuthRequest, response_tx: AuthResponseSender) {
+        info!("Auth request received: {:?}", request.auth_type);
+        {
+            let mut tunnels = self.tunnels.write().await;
+            match tunnels.get_mut(&self.tunnel_id) {
+                Some(tunnel) if tunnel.shutdown_tx.is_some() => {
+                    tunnel.status = TunnelStatus::WaitingForAuth;
+                    tunnel.pending_auth = Some(PendingAuth {
+                        request: request.clone(),
+                        response_tx,
+                    });
+                }
+                _ => return,
+            }
+        }
+
+        if let Err(e) = self.events.send(TunnelEvent::AuthRequired {
+            id: self.tunnel_id,
+            request,
+        }) {
+            debug!("Failed to broadcast AuthRequired event for {}: {}", self.tunnel_id, e);
+        }
+    }
+
+    /// Answer the pending request as a client would
+    async fn resolve(&self, request_id: Uuid, answer: String) {
+        let mut tunnels = self.tunnels.write().await;
+        if let Some(tunnel) = tunnels.get_mut(&self.tunnel_id) {
+            // Fails when a client answered first or the tunnel is stopping
+            if let Err(e) =
+                tunnel.resolve_pending_auth(self.tunnel_id, &self.events, request_id, vec![answer])
+            {
+                debug!("Askpass answer not used: {}", e);
+            }
         }
     }
 }
@@ -571,8 +1054,11 @@ impl AuthContext {
 async fn establish_connection(
     profile: &Profile,
     tunnels: &Arc<RwLock<HashMap<Uuid, ActiveTunnel>>>,
-    event_tx: &broadcast::Sender<TunnelEvent>,
+    event_tx: &EventBus,
     known_hosts_path: Arc<PathBuf>,
+    auth_prompts: &AuthPromptsConfig,
+    secrets: Arc<SecretStore>,
+    keys: Arc<KeyCache>,
 ) -> Result<Handle<ClientHandler>> {
     let id = profile.metadata.id;
 
@@ -604,15 +1090,24 @@ async fn establish_connection(
         if let Some(t) = ts.get_mut(&id) {
             t.status = TunnelStatus::Connecting;
         }
-    } // Release lock immediately to avoid blocking auth_handler
-
-    // Channel for auth requests (daemon -> CLI)
-    let (auth_request_tx, mut auth_request_rx) =
-        mpsc::channel::<(AuthRequest, AuthResponseSender)>(1);
+    } // Release lock immediately; auth requests need it
 
+    // Auth requests can come as early as check_server_key() during connect
     let auth_ctx = AuthContext {
         tunnel_id: id,
-        auth_request_tx,
+        profile_name: profile.metadata.name.clone(),
+        tunnels: tunnels.clone(),
+        events: event_tx.clone(),
+        timeout: Duration::from_secs(
+            profile
+                .options
+                .auth_timeout_secs
+                .unwrap_or(auth_prompts.timeout_secs),
+        ),
+        no_client: auth_prompts.no_client,
+        askpass: Askpass::from_config(auth_prompts),
+        secrets,
+        keys,
     };
 
     let handler = ClientHandler {
@@ -622,39 +1117,6 @@ async fn establish_connection(
         known_hosts_path: (*known_hosts_path).clone(),
     };
 
-    // Spawn a task to handle auth requests and update tunnel state
-    // IMPORTANT: This must be spawned BEFORE connecting, because check_server_key()
-    // may be called during connection and needs someone listening on the channel
-    let tunnels_for_auth = tunnels.clone();
-    let event_tx_for_auth = event_tx.clone();
-
-    let auth_handler = tokio::spawn(async move {
-        while let Some((request, response_tx)) = auth_request_rx.recv().await {
-            let tunnel_id = request.tunnel_id;
-            info!("Auth request received: {:?}", request.auth_type);
-
-            // Update tunnel state with pending auth
-            {
-                let mut tunnels = tunnels_for_auth.write().await;
-                if let Some(tunnel) = tunnels.get_mut(&tunnel_id) {
-                    tunnel.status = TunnelStatus::WaitingForAuth;
-                    tunnel.pending_auth = Some(PendingAuth {
-                        request: request.clone(),
-                        response_tx,
-                    });
-                }
-            }
-
-            // Emit event to CLI/GUI
-            if let Err(e) = event_tx_for_auth.send(TunnelEvent::AuthRequired {
-                id: tunnel_id,
-                request,
-            }) {
-                debug!("Failed to broadcast AuthRequired event for {}: {}", tunnel_id, e);
-            }
-        }
-    });
-
     // Connect to SSH server
     let addr = ssh_tunnel_common::format_host_port(&profile.connection.host, profile.connection.port);
     info!("Connecting to SSH server: {}", addr);
@@ -696,15 +1158,12 @@ async fn establish_connection(
         let mut ts = tunnels.write().await;
         if let Some(t) = ts.get_mut(&id) {
             t.status = TunnelStatus::Disconnected;
-            t.pending_auth = None;
+            t.cancel_pending_auth(id, event_tx, &reason);
+            t.metrics.record_auth_failure();
         }
         anyhow::bail!(reason);
     }
 
-    // Stop the auth handler task
-    drop(auth_ctx);
-    auth_handler.abort();
-
     info!("SSH authentication successful");
 
     // Return the authenticated session for monitoring
@@ -718,7 +1177,9 @@ async fn monitor_tunnel(
     profile: Profile,
     mut shutdown_rx: mpsc::Receiver<()>,
     tunnels: Arc<RwLock<HashMap<Uuid, ActiveTunnel>>>,
-    event_tx: broadcast::Sender<TunnelEvent>,
+    event_tx: EventBus,
+    port_range: Option<PortRange>,
+    stats_interval: Option<Duration>,
 ) -> Result<()> {
     let id = profile.metadata.id;
 
@@ -735,7 +1196,15 @@ async fn monitor_tunnel(
         result = async {
             match profile.forwarding.forwarding_type {
                 ForwardingType::Local => {
-                    run_local_forward_task(&session, &profile, tunnels.clone(), event_tx.clone()).await
+                    run_local_forward_task(
+                        &session,
+                        &profile,
+                        tunnels.clone(),
+                        event_tx.clone(),
+                        port_range,
+                        stats_interval,
+                    )
+                    .await
                 }
                 ForwardingType::Remote => {
                     Err(anyhow::anyhow!("Remote forwarding not yet implemented"))
@@ -760,33 +1229,54 @@ async fn monitor_tunnel(
 }
 
 /// Run the actual SSH tunnel (connects, authenticates, then monitors)
+#[allow(clippy::too_many_arguments)]
 async fn run_tunnel(
     profile: Profile,
     mut shutdown_rx: mpsc::Receiver<()>,
     tunnels: Arc<RwLock<HashMap<Uuid, ActiveTunnel>>>,
-    event_tx: broadcast::Sender<TunnelEvent>,
+    event_tx: EventBus,
     known_hosts_path: Arc<PathBuf>,
+    port_range: Option<PortRange>,
+    stats_interval: Option<Duration>,
+    auth_prompts: AuthPromptsConfig,
+    secrets: Arc<SecretStore>,
+    keys: Arc<KeyCache>,
 ) -> Result<()> {
     // Phase 1: Establish connection and authenticate
     // Use tokio::select to allow cancellation during connection/auth
-    let session = tokio::select! {
-        result = establish_connection(&profile, &tunnels, &event_tx, known_hosts_path) => {
-            result?
-        }
-        _ = shutdown_rx.recv() => {
-            info!("Received shutdown signal during connection for tunnel {}", profile.metadata.id);
-            return Ok(()); // Exit gracefully
+    let connected = tokio::select! {
+        result = establish_connection(&profile, &tunnels, &event_tx, known_hosts_path, &auth_prompts, secrets, keys) => {
+            Some(result)
         }
+        _ = shutdown_rx.recv() => None,
     };
 
+    // Authentication is over, one way or another; its answers have been used
+    crate::logging::forget_answers(&profile.metadata.id);
+
+    let Some(session) = connected else {
+        info!("Received shutdown signal during connection for tunnel {}", profile.metadata.id);
+        return Ok(()); // Exit gracefully
+    };
+    let session = session?;
+
     // Phase 2: Monitor tunnel lifecycle
-    monitor_tunnel(session, profile, shutdown_rx, tunnels, event_tx).await
+    monitor_tunnel(
+        session,
+        profile,
+        shutdown_rx,
+        tunnels,
+        event_tx,
+        port_range,
+        stats_interval,
+    )
+    .await
 }
 
 // Failed tunnel text explanation
 async fn fail_tunnel(
     tunnels: &Arc<RwLock<HashMap<Uuid, ActiveTunnel>>>,
-    event_tx: &broadcast::Sender<TunnelEvent>,
+    event_tx: &EventBus,
     id: Uuid,
     reason: &str,
 ) -> Result<()> {
@@ -802,7 +1292,7 @@ async fn fail_tunnel(
     if let Some(t) = ts.get_mut(&id) {
         t.status = TunnelStatus::Failed(reason.to_string());
         t.shutdown_tx = None;
-        t.pending_auth = None;
+        t.cancel_pending_auth(id, event_tx, reason);
     }
     Ok(())
 }
@@ -814,7 +1304,7 @@ async fn authenticate(
     session: &mut Handle<ClientHandler>,
     profile: &Profile,
     auth_ctx: &AuthContext,
-    event_tx: &broadcast::Sender<TunnelEvent>,
+    event_tx: &EventBus,
 ) -> Result<bool> {
     let user = &profile.connection.user;
 
@@ -917,6 +1407,103 @@ async fn request_passphrase_and_load(
     ))
 }
 
+/// Load a key file, prompting for its passphrase if needed
+///
+/// Returns the key and whether it had to be decrypted.
+async fn load_key(
+    full_key_path: &Path,
+    key_path: &Path,
+    auth_ctx: &AuthContext,
+    profile: &Profile,
+) -> Result<(PrivateKey, bool)> {
+    info!("Loading SSH key from: {}", full_key_path.display());
+
+    // Try to load with stored passphrase first if available
+    let loaded = match auth_ctx.stored_secret(profile).await {
+        Ok(Some(passphrase)) => {
+            info!("Using stored passphrase");
+            match load_secret_key(full_key_path, Some(passphrase.as_str())) {
+                Ok(k) => (k, true),
+                Err(e) => {
+                    warn!("Stored passphrase failed, requesting new one: {}", e);
+                    auth_ctx.secrets.forget(&profile.metadata.id);
+                    // Fall through to interactive prompt
+                    (request_passphrase_and_load(full_key_path, auth_ctx).await?, true)
+                }
+            }
+        }
+        Err(e) => {
+            warn!("Failed to retrieve stored passphrase: {:#}", e);
+            (request_passphrase_and_load(full_key_path, auth_ctx).await?, true)
+        }
+        Ok(None) => {
+            // Try without passphrase first, then prompt if needed
+            match load_secret_key(full_key_path, None) {
+                Ok(key) => (key, false),
+                Err(e) => {
+                    // Check if the error is specifically about encryption using the russh-keys error type
+                    // This is language-independent and works regardless of system locale
+                    match e {
+                        RusshKeyError::KeyIsEncrypted => {
+                            info!("Key is encrypted, requesting passphrase");
+                            (request_passphrase_and_load(full_key_path, auth_ctx).await?, true)
+                        }
+                        _ => {
+                            // Other errors (corrupt key, file not found, unsupported type, permissions, etc.)
+                            let msg = build_key_load_error_message(full_key_path, key_path);
+                            return Err(anyhow::anyhow!("{}\n\nOriginal error: {}", msg, e));
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(loaded)
+}
+
+/// The key at `path` from the key cache, if it is unlocked there
+fn cached_key(keys: &KeyCache, path: &Path) -> Option<PrivateKey> {
+    match keys.with_key(path, |encoded| PrivateKey::from_openssh(encoded))? {
+        Ok(key) => Some(key),
+        Err(e) => {
+            warn!("Dropping unreadable key from the key cache: {}", e);
+            keys.lock(Some(&path.display().to_string()));
+            None
+        }
+    }
+}
+
+/// Keep a decrypted key for other tunnels and reconnects
+fn cache_key(keys: &KeyCache, path: &Path, key: &PrivateKey) -> Result<UnlockedKey> {
+    let encoded = key
+        .to_openssh(LineEnding::LF)
+        .context("Failed to encode the key")?;
+    Ok(keys.insert(
+        path,
+        Zeroizing::new(encoded.as_bytes().to_vec()),
+        key.algorithm().to_string(),
+        key.fingerprint(HashAlg::Sha256).to_string(),
+    ))
+}
+
+/// Decrypt a key file with `passphrase` into the key cache
+async fn unlock_key_file(
+    keys: &KeyCache,
+    path: PathBuf,
+    passphrase: Zeroizing<String>,
+) -> Result<UnlockedKey> {
+    // Key derivation takes a moment; keep it off the async workers
+    let key = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || load_secret_key(&path, Some(&passphrase))
+    })
+    .await
+    .context("Key unlock task failed")?
+    .with_context(|| format!("Failed to decrypt SSH key from {}", path.display()))?;
+    cache_key(keys, &path, &key)
+}
+
 /// Authenticate using an SSH key (with optional passphrase)
 /// Returns (success, remaining_methods)
 /// - If success=true, authentication is complete
@@ -928,56 +1515,25 @@ async fn authenticate_with_key(
     auth_ctx: &AuthContext,
     profile: &Profile,
 ) -> Result<(bool, Option<russh::MethodSet>)> {
-    // For remote profiles (Hybrid mode), key_path is just a filename
-    // Expand it to the full path in ~/.ssh
-    let full_key_path = if key_path.is_relative() {
-        let ssh_dir = dirs::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-            .join(".ssh");
-        ssh_dir.join(key_path)
-    } else {
-        key_path.to_path_buf()
-    };
-
-    info!("Loading SSH key from: {}", full_key_path.display());
-
-    // Try to load with stored passphrase first if available
-    let key = if profile.connection.password_storage == PasswordStorage::Keychain {
-        match crate::security::get_stored_password(&profile.metadata.id) {
-            Ok(passphrase) => {
-                info!("Using stored passphrase from keychain");
-                match load_secret_key(&full_key_path, Some(&passphrase)) {
-                    Ok(k) => k,
-                    Err(e) => {
-                        warn!("Stored passphrase failed, requesting new one: {}", e);
-                        // Fall through to interactive prompt
-                        request_passphrase_and_load(&full_key_path, auth_ctx).await?
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("Failed to retrieve stored passphrase: {}", e);
-                request_passphrase_and_load(&full_key_path, auth_ctx).await?
+    let full_key_path = keycache::resolve_key_path(key_path)?;
+
+    let key = {
+        // Tunnels using the same key wait here while one of them unlocks it
+        let _unlocking = auth_ctx.keys.exclusive(&full_key_path).await;
+        match cached_key(&auth_ctx.keys, &full_key_path) {
+            Some(key) => {
+                info!("Using key from the key cache: {}", full_key_path.display());
+                key
             }
-        }
-    } else {
-        // Try without passphrase first, then prompt if needed
-        match load_secret_key(&full_key_path, None) {
-            Ok(key) => key,
-            Err(e) => {
-                // Check if the error is specifically about encryption using the russh-keys error type
-                // This is language-independent and works regardless of system locale
-                match e {
-                    RusshKeyError::KeyIsEncrypted => {
-                        info!("Key is encrypted, requesting passphrase");
-                        request_passphrase_and_load(&full_key_path, auth_ctx).await?
-                    }
-                    _ => {
-                        // Other errors (corrupt key, file not found, unsupported type, permissions, etc.)
-                        let msg = build_key_load_error_message(&full_key_path, &key_path);
-                        return Err(anyhow::anyhow!("{}\n\nOriginal error: {}", msg, e));
+            None => {
+                let (key, decrypted) =
+                    load_key(&full_key_path, key_path, auth_ctx, profile).await?;
+                if decrypted && auth_ctx.keys.is_enabled() {
+                    if let Err(e) = cache_key(&auth_ctx.keys, &full_key_path, &key) {
+                        warn!("Key not kept in the key cache: {:#}", e);
                     }
                 }
+                key
             }
         }
     };
@@ -1037,32 +1593,33 @@ async fn authenticate_with_password(
     profile: &Profile,
 ) -> Result<bool> {
     // Try stored password first if available
-    let password = if profile.connection.password_storage == PasswordStorage::Keychain {
-        match crate::security::get_stored_password(&profile.metadata.id) {
-            Ok(pwd) => {
-                info!("Using stored password from keychain");
-                pwd
-            }
-            Err(e) => {
-                warn!(
-                    "Failed to retrieve stored password, requesting interactively: {}",
-                    e
-                );
-                auth_ctx
-                    .request_input(AuthRequestType::Password, "Enter SSH password: ", true)
-                    .await?
-            }
+    let stored = match auth_ctx.stored_secret(profile).await {
+        Ok(stored) => stored,
+        Err(e) => {
+            warn!(
+                "Failed to retrieve stored password, requesting interactively: {:#}",
+                e
+            );
+            None
+        }
+    };
+    let used_stored = stored.is_some();
+    let password = match stored {
+        Some(pwd) => {
+            info!("Using stored password");
+            pwd
         }
-    } else {
         // Request password interactively
-        auth_ctx
-            .request_input(AuthRequestType::Password, "Enter SSH password: ", true)
-            .await?
+        None => Zeroizing::new(
+            auth_ctx
+                .request_input(AuthRequestType::Password, "Enter SSH password: ", true)
+                .await?,
+        ),
     };
 
     // Try to authenticate
     let auth_result = session
-        .authenticate_password(user, &password)
+        .authenticate_password(user, password.as_str())
         .await
         .context("Password authentication failed")?;
 
@@ -1093,6 +1650,10 @@ async fn authenticate_with_password(
                     methods_str
                 )
             } else {
+                if used_stored {
+                    // Run the secret command again next time instead of reusing a stale answer
+                    auth_ctx.secrets.forget(&profile.metadata.id);
+                }
                 format!("Password authentication rejected. {}", methods_str)
             };
 
@@ -1107,7 +1668,7 @@ async fn authenticate_keyboard_interactive(
     session: &mut client::Handle<ClientHandler>,
     user: &str,
     auth_ctx: &AuthContext,
-    event_tx: &broadcast::Sender<TunnelEvent>,
+    event_tx: &EventBus,
 ) -> Result<bool> {
     info!(
         "Attempting keyboard-interactive authentication for user: {}",
@@ -1195,36 +1756,19 @@ async fn authenticate_keyboard_interactive(
                     continue;
                 }
 
-                let mut answers = Vec::with_capacity(prompts.len());
-
-                for prompt in &prompts {
-                    let mut full_prompt = String::new();
-
-                    if !name.trim().is_empty() {
-                        full_prompt.push_str(&name);
-                        full_prompt.push('\n');
-                    }
-
-                    if !instructions.trim().is_empty() {
-                        full_prompt.push_str(&instructions);
-                        full_prompt.push('\n');
-                    }
-
-                    full_prompt.push_str(&prompt.prompt);
-
-                    // Use KeyboardInteractive for all keyboard-interactive prompts
-                    // The server-provided prompt text will tell the user what's needed
-                    let answer = auth_ctx
-                        .request_input(
-                            AuthRequestType::KeyboardInteractive,
-                            &full_prompt,
-                            !prompt.echo,
-                        )
-                        .await
-                        .context("failed to get keyboard-interactive input from client")?;
-
-                    answers.push(answer);
-                }
+                // All prompts go out together so clients can show them as one form;
+                // the server-provided prompt text tells the user what's needed
+                let prompts: Vec<AuthPrompt> = prompts
+                    .iter()
+                    .map(|p| AuthPrompt {
+                        prompt: p.prompt.clone(),
+                        echo: p.echo,
+                    })
+                    .collect();
+                let answers = auth_ctx
+                    .request_prompts(&name, &instructions, &prompts)
+                    .await
+                    .context("failed to get keyboard-interactive input from client")?;
 
                 // Send answers and wait for the next step (another InfoRequest or final Success/Failure).
                 response = session
@@ -1243,19 +1787,51 @@ async fn authenticate_keyboard_interactive(
     }
 }
 
+/// Bind a local listener on any free port.
+///
+/// Without a configured range the OS picks the port; otherwise each port in
+/// the range is tried in order until one is free.
+async fn bind_ephemeral_port(bind_address: &str, port_range: Option<PortRange>) -> Result<TcpListener> {
+    let Some(range) = port_range else {
+        let addr: SocketAddr = ssh_tunnel_common::format_host_port(bind_address, 0)
+            .parse()
+            .context("Invalid bind address")?;
+        return TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind to {}", addr));
+    };
+
+    for port in range.start..=range.end {
+        let addr: SocketAddr = ssh_tunnel_common::format_host_port(bind_address, port)
+            .parse()
+            .context("Invalid bind address")?;
+        match TcpListener::bind(addr).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => continue,
+            Err(e) => return Err(anyhow::anyhow!("Failed to bind to {}: {}", addr, e)),
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "No free port in ephemeral range {}-{} on {}",
+        range.start,
+        range.end,
+        bind_address
+    ))
+}
+
 /// Run local port forwarding task (session health aware)
 /// Returns when the SSH session dies or encounters a fatal error
 async fn run_local_forward_task(
     session: &Handle<ClientHandler>,
     profile: &Profile,
     tunnels: Arc<RwLock<HashMap<Uuid, ActiveTunnel>>>,
-    event_tx: broadcast::Sender<TunnelEvent>,
+    event_tx: EventBus,
+    port_range: Option<PortRange>,
+    stats_interval: Option<Duration>,
 ) -> Result<()> {
     let id = profile.metadata.id;
-    let local_port = profile
-        .forwarding
-        .local_port
-        .ok_or_else(|| anyhow::anyhow!("Local port not specified"))?;
+    let local_port = profile.forwarding.local_port.unwrap_or(0);
     let remote_host = profile
         .forwarding
         .remote_host
@@ -1266,53 +1842,87 @@ async fn run_local_forward_task(
         .remote_port
         .ok_or_else(|| anyhow::anyhow!("Remote port not specified"))?;
 
-    let bind_addr: SocketAddr = ssh_tunnel_common::format_host_port(&profile.forwarding.bind_address, local_port)
-        .parse()
-        .context("Invalid bind address")?;
-
-    info!(
-        "Starting local forward: {} -> {}:{}",
-        bind_addr, remote_host, remote_port
-    );
+    let listener = if profile.forwarding.uses_ephemeral_port() {
+        bind_ephemeral_port(&profile.forwarding.bind_address, port_range).await?
+    } else {
+        let bind_addr: SocketAddr = ssh_tunnel_common::format_host_port(&profile.forwarding.bind_address, local_port)
+            .parse()
+            .context("Invalid bind address")?;
 
-    // Bind local port
-    let listener = match TcpListener::bind(bind_addr).await {
-        Ok(l) => l,
-        Err(e) => {
-            // Detect permission errors specifically for privileged ports
-            if e.kind() == std::io::ErrorKind::PermissionDenied {
-                return Err(anyhow::anyhow!(
-                    "Permission denied binding to {}. Port {} is privileged (<=1024) and requires elevated permissions.\n\
-                     Run the daemon with: sudo ssh-tunnel-daemon\n\
-                     Or grant CAP_NET_BIND_SERVICE capability: sudo setcap cap_net_bind_service=+ep /path/to/ssh-tunnel-daemon",
-                    bind_addr, local_port
-                ));
+        match TcpListener::bind(bind_addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                // Detect permission errors specifically for privileged ports
+                if e.kind() == std::io::ErrorKind::PermissionDenied {
+                    return Err(anyhow::anyhow!(
+                        "Permission denied binding to {}. Port {} is privileged (<=1024) and requires elevated permissions.\n\
+                         Run the daemon with: sudo ssh-tunnel-daemon\n\
+                         Or grant CAP_NET_BIND_SERVICE capability: sudo setcap cap_net_bind_service=+ep /path/to/ssh-tunnel-daemon",
+                        bind_addr, local_port
+                    ));
+                }
+                return Err(anyhow::anyhow!("Failed to bind to {}: {}", bind_addr, e));
             }
-            return Err(anyhow::anyhow!("Failed to bind to {}: {}", bind_addr, e));
         }
     };
 
-    info!("Listening on {}", bind_addr);
+    let bound_addr = listener
+        .local_addr()
+        .context("Failed to read bound address of local listener")?;
+
+    info!(
+        "Local forward listening on {} -> {}:{}",
+        bound_addr, remote_host, remote_port
+    );
 
     // Port binding successful! Update status and broadcast Connected event
-    {
+    let metrics = {
         let mut tunnels = tunnels.write().await;
-        if let Some(tunnel) = tunnels.get_mut(&id) {
-            tunnel.status = TunnelStatus::Connected;
-            tunnel.pending_auth = None;
+        match tunnels.get_mut(&id) {
+            Some(tunnel) => {
+                tunnel.status = TunnelStatus::Connected;
+                tunnel.pending_auth = None;
+                tunnel.bound_addr = Some(bound_addr);
+                tunnel.connected_at = Some(Instant::now());
+                tunnel.metrics.clone()
+            }
+            None => Arc::default(),
         }
-    }
-    if let Err(e) = event_tx.send(TunnelEvent::Connected { id }) {
+    };
+    if let Err(e) = event_tx.send(TunnelEvent::Connected { id, bound_addr: Some(bound_addr) }) {
         debug!("Failed to broadcast Connected event for {}: {}", id, e);
     }
 
+    // post-up runs alongside the forward; a failing hook doesn't take the tunnel down
+    {
+        let profile = profile.clone();
+        let event_tx = event_tx.clone();
+        tokio::spawn(async move {
+            let _ = hooks::run_hook(HookStage::PostUp, &profile, Some(bound_addr), None, &event_tx)
+                .await;
+        }.in_current_span());
+    }
+
     // Track consecutive channel failures to detect session death
     let mut consecutive_failures = 0;
     const MAX_CONSECUTIVE_FAILURES: u32 = 3;
 
+    let session = Session::start(metrics.traffic.clone());
+    let mut stats_tick = stats_interval.map(|period| {
+        tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+    });
+
     loop {
-        // Accept new connections
-        let accept_result = listener.accept().await;
+        // Accept new connections, reporting traffic in between
+        let accept_result = tokio::select! {
+            result = listener.accept() => result,
+            _ = traffic::next_tick(&mut stats_tick) => {
+                if let Err(e) = event_tx.send(TunnelEvent::Stats { id, stats: session.stats() }) {
+                    debug!("Failed to broadcast Stats event for {}: {}", id, e);
+                }
+                continue;
+            }
+        };
 
         match accept_result {
             Ok((stream, peer_addr)) => {
@@ -1331,6 +1941,7 @@ async fn run_local_forward_task(
                         ch
                     }
                     Err(e) => {
+                        session.counters().record_channel_failure();
                         consecutive_failures += 1;
                         error!(
                             "Failed to open channel ({}/{}): {}",
@@ -1349,11 +1960,12 @@ async fn run_local_forward_task(
                 };
 
                 // Spawn task to handle the connection
+                let counters = session.counters().clone();
                 tokio::spawn(async move {
-                    if let Err(e) = handle_forward_connection(stream, channel).await {
+                    if let Err(e) = handle_forward_connection(stream, channel, counters).await {
                         debug!("Forward connection ended: {}", e);
                     }
-                });
+                }.in_current_span());
             }
             Err(e) => {
                 error!("Failed to accept connection: {}", e);
@@ -1364,11 +1976,13 @@ async fn run_local_forward_task(
 
 /// Handle a single forwarded connection
 async fn handle_forward_connection(
-    mut tcp_stream: tokio::net::TcpStream,
+    tcp_stream: tokio::net::TcpStream,
     channel: russh::Channel<client::Msg>,
+    counters: Arc<TrafficCounters>,
 ) -> Result<()> {
-    /*     let (mut tcp_read, mut tcp_write) = tcp_stream.split();
-     */
+    let _active = counters.open_connection();
+    let mut tcp_stream = CountingStream::new(tcp_stream, counters);
+
     // Turn SSH channel into a bidirectional stream
     let mut channel_stream = channel.into_stream();
 
@@ -1392,3 +2006,49 @@ impl Default for TunnelManager {
         Self::new(known_hosts_path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ssh_tunnel_common::{AuthType, ConnectionConfig, ForwardingConfig, ForwardingType, PasswordStorage};
+
+    fn profile() -> Profile {
+        Profile::new(
+            "hooked".to_string(),
+            ConnectionConfig {
+                host: "example.com".to_string(),
+                port: 22,
+                user: "user".to_string(),
+                auth_type: AuthType::Password,
+                key_path: None,
+                password_storage: PasswordStorage::None,
+            },
+            ForwardingConfig {
+                forwarding_type: ForwardingType::Local,
+                bind_address: "127.0.0.1".to_string(),
+                local_port: Some(0),
+                remote_host: Some("localhost".to_string()),
+                remote_port: Some(80),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_stop_while_connecting_runs_post_down() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let marker = dir.path().join("post-down");
+        let mut profile = profile();
+        profile.hooks.pre_up = Some("sleep 1".to_string());
+        profile.hooks.post_down = Some(format!("touch '{}'", marker.display()));
+        let id = profile.metadata.id;
+
+        let manager = TunnelManager::new(dir.path().join("known_hosts"));
+        manager.start(profile, true).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(manager.get_status(&id).await, Some(TunnelStatus::Connecting));
+
+        manager.stop(&id).await.unwrap();
+        assert!(marker.exists());
+        assert_eq!(manager.get_status(&id).await, Some(TunnelStatus::Disconnected));
+    }
+}
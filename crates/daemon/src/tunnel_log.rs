@@ -0,0 +1,443 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// Per-tunnel diagnostic logs
+//
+// A tracing layer that keeps the log lines of each tunnel apart from the
+// daemon's combined output. Tunnel tasks run inside a `tunnel` span carrying
+// the tunnel ID; every event within it (from the daemon or from russh) is
+// kept in a bounded ring buffer for that tunnel and broadcast to followers.
+// Each tunnel is captured at the configured default level unless its level
+// was raised for a while with `set_level`.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::filter::Filtered;
+use tracing_subscriber::layer::{Context, Filter, Layer};
+use tracing_subscriber::registry::{LookupSpan, Scope};
+use uuid::Uuid;
+
+use ssh_tunnel_common::{LogLevel, TunnelLogEntry, TunnelLogLevel};
+
+use crate::config::TunnelLogsConfig;
+
+/// Targets whose events are captured: the daemon and the SSH library
+/// (`log` is where events bridged from the `log` crate come from)
+const CAPTURED_TARGETS: &[&str] = &["ssh_tunnel_daemon", "russh", "log"];
+
+/// Buffered lines sent to live followers
+const FOLLOW_CAPACITY: usize = 1024;
+
+/// Span that ties everything logged within it to a tunnel
+pub fn tunnel_span(id: Uuid, profile_name: &str) -> tracing::Span {
+    tracing::info_span!("tunnel", tunnel_id = %id, profile = %profile_name)
+}
+
+struct Ring {
+    entries: VecDeque<TunnelLogEntry>,
+    next_seq: u64,
+    /// Raised level and when it expires
+    raised: Option<(LogLevel, DateTime<Utc>)>,
+}
+
+impl Ring {
+    fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            next_seq: 1,
+            raised: None,
+        }
+    }
+}
+
+struct Inner {
+    capacity: usize,
+    default_level: LogLevel,
+    rings: HashMap<Uuid, Ring>,
+}
+
+/// Log rings of all tunnels; cheap to clone
+#[derive(Clone)]
+pub struct TunnelLogs {
+    inner: Arc<Mutex<Inner>>,
+    /// Most verbose level any tunnel is captured at, to skip other events cheaply
+    max_level: Arc<AtomicU8>,
+    tx: broadcast::Sender<(Uuid, TunnelLogEntry)>,
+}
+
+impl TunnelLogs {
+    pub fn new(config: &TunnelLogsConfig) -> Self {
+        let (tx, _) = broadcast::channel(FOLLOW_CAPACITY);
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                capacity: config.lines,
+                default_level: config.level,
+                rings: HashMap::new(),
+            })),
+            max_level: Arc::new(AtomicU8::new(config.level as u8)),
+            tx,
+        }
+    }
+
+    /// The tracing layer feeding these logs
+    pub fn layer<S>(&self) -> Filtered<TunnelLogLayer, CaptureFilter, S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        TunnelLogLayer { logs: self.clone() }.with_filter(CaptureFilter {
+            max_level: self.max_level.clone(),
+        })
+    }
+
+    /// Buffered lines after `after`, oldest first; `limit` keeps the most recent
+    pub fn entries(&self, id: &Uuid, after: Option<u64>, limit: Option<usize>) -> Vec<TunnelLogEntry> {
+        buffered(&self.lock(), id, after, limit)
+    }
+
+    /// Buffered lines plus a receiver for everything logged after them
+    pub fn follow(
+        &self,
+        id: &Uuid,
+        after: Option<u64>,
+        limit: Option<usize>,
+    ) -> (Vec<TunnelLogEntry>, broadcast::Receiver<(Uuid, TunnelLogEntry)>) {
+        // Lines are pushed and broadcast under the lock, so none fall in between
+        let inner = self.lock();
+        (buffered(&inner, id, after, limit), self.tx.subscribe())
+    }
+
+    /// Level the tunnel is currently captured at
+    pub fn level(&self, id: &Uuid) -> TunnelLogLevel {
+        let mut inner = self.lock();
+        let raised = self.current_raise(&mut inner, id);
+        match raised {
+            Some((level, until)) => TunnelLogLevel {
+                level,
+                until: Some(until),
+            },
+            None => TunnelLogLevel {
+                level: inner.default_level,
+                until: None,
+            },
+        }
+    }
+
+    /// Capture the tunnel at `level` for `duration`; the default level clears a raise
+    pub fn set_level(&self, id: Uuid, level: LogLevel, duration: Duration) -> TunnelLogLevel {
+        let mut inner = self.lock();
+        let until = (level != inner.default_level).then(|| {
+            Utc::now() + chrono::Duration::from_std(duration).unwrap_or_else(|_| chrono::Duration::days(1))
+        });
+        inner.rings.entry(id).or_insert_with(Ring::new).raised = until.map(|until| (level, until));
+        self.update_max_level(&inner);
+        TunnelLogLevel { level, until }
+    }
+
+    /// Raised level of a tunnel, dropping it once expired
+    fn current_raise(&self, inner: &mut Inner, id: &Uuid) -> Option<(LogLevel, DateTime<Utc>)> {
+        let ring = inner.rings.get_mut(id)?;
+        match ring.raised {
+            Some((_, until)) if until <= Utc::now() => {
+                ring.raised = None;
+                self.update_max_level(inner);
+                None
+            }
+            raised => raised,
+        }
+    }
+
+    fn update_max_level(&self, inner: &Inner) {
+        let max = inner
+            .rings
+            .values()
+            .filter_map(|ring| ring.raised.map(|(level, _)| level))
+            .fold(inner.default_level, LogLevel::max);
+        self.max_level.store(max as u8, Ordering::Relaxed);
+    }
+
+    fn push(&self, id: Uuid, level: LogLevel, target: String, message: String) {
+        let mut inner = self.lock();
+        let raised = self.current_raise(&mut inner, &id);
+        if level > raised.map_or(inner.default_level, |(level, _)| level) {
+            return;
+        }
+
+        let capacity = inner.capacity;
+        let ring = inner.rings.entry(id).or_insert_with(Ring::new);
+        let entry = TunnelLogEntry {
+            seq: ring.next_seq,
+            timestamp: Utc::now(),
+            level,
+            target,
+            message,
+        };
+        ring.next_seq += 1;
+        if ring.entries.len() >= capacity {
+            ring.entries.pop_front();
+        }
+        if capacity > 0 {
+            ring.entries.push_back(entry.clone());
+        }
+        // No followers is fine
+        let _ = self.tx.send((id, entry));
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn buffered(inner: &Inner, id: &Uuid, after: Option<u64>, limit: Option<usize>) -> Vec<TunnelLogEntry> {
+    let Some(ring) = inner.rings.get(id) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<TunnelLogEntry> = ring
+        .entries
+        .iter()
+        .filter(|e| after.is_none_or(|after| e.seq > after))
+        .cloned()
+        .collect();
+    if let Some(limit) = limit {
+        let skip = entries.len().saturating_sub(limit);
+        entries.drain(..skip);
+    }
+    entries
+}
+
+fn log_level(level: &Level) -> LogLevel {
+    match *level {
+        Level::ERROR => LogLevel::Error,
+        Level::WARN => LogLevel::Warn,
+        Level::INFO => LogLevel::Info,
+        Level::DEBUG => LogLevel::Debug,
+        Level::TRACE => LogLevel::Trace,
+    }
+}
+
+fn is_captured_target(target: &str) -> bool {
+    CAPTURED_TARGETS.iter().any(|prefix| {
+        target
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+    })
+}
+
+/// Lets through spans and events at or above the most verbose level in use
+pub struct CaptureFilter {
+    max_level: Arc<AtomicU8>,
+}
+
+impl<S> Filter<S> for CaptureFilter {
+    fn enabled(&self, meta: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        meta.is_span() || log_level(meta.level()) as u8 <= self.max_level.load(Ordering::Relaxed)
+    }
+
+    fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
+        // Levels change at runtime, so never let the answer be cached
+        if is_captured_target(meta.target()) {
+            Interest::sometimes()
+        } else {
+            Interest::never()
+        }
+    }
+}
+
+/// Span extension marking the tunnel a span belongs to
+#[derive(Debug, Clone)]
+pub struct TunnelSpan {
+    pub id: Uuid,
+    pub profile: String,
+}
+
+/// Tunnel of the innermost `tunnel` span in `scope`, if any
+///
+/// Spans are tagged by the tunnel log layer, which sees every span.
+pub fn scope_tunnel<'a, R: LookupSpan<'a>>(scope: Option<Scope<'a, R>>) -> Option<TunnelSpan> {
+    scope?
+        .into_iter()
+        .find_map(|span| span.extensions().get::<TunnelSpan>().cloned())
+}
+
+pub struct TunnelLogLayer {
+    logs: TunnelLogs,
+}
+
+impl<S> Layer<S> for TunnelLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = TunnelSpanVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(tunnel_id), Some(span)) = (visitor.id, ctx.span(id)) {
+            span.extensions_mut().insert(TunnelSpan {
+                id: tunnel_id,
+                profile: visitor.profile,
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(tunnel) = scope_tunnel(ctx.event_scope(event)) else {
+            return;
+        };
+
+        let mut line = LineVisitor::default();
+        event.record(&mut line);
+        let target = line
+            .log_target
+            .unwrap_or_else(|| event.metadata().target().to_string());
+        if !is_captured_target(&target) {
+            return;
+        }
+
+        line.message.push_str(&line.fields);
+        let message = crate::logging::redact(&line.message).into_owned();
+        self.logs
+            .push(tunnel.id, log_level(event.metadata().level()), target, message);
+    }
+}
+
+/// Reads the `tunnel_id` and `profile` fields of a span
+#[derive(Default)]
+struct TunnelSpanVisitor {
+    id: Option<Uuid>,
+    profile: String,
+}
+
+impl Visit for TunnelSpanVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "tunnel_id" => self.id = value.parse().ok(),
+            "profile" => self.profile = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "tunnel_id" => self.id = format!("{:?}", value).parse().ok(),
+            "profile" => self.profile = format!("{:?}", value),
+            _ => {}
+        }
+    }
+}
+
+/// Formats an event as its message followed by ` key=value` fields
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+    /// Original target of events bridged from the `log` crate
+    log_target: Option<String>,
+}
+
+impl Visit for LineVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message.push_str(value),
+            "log.target" => self.log_target = Some(value.to_string()),
+            name if name.starts_with("log.") => {}
+            name if crate::logging::is_sensitive_field(name) => {
+                let _ = write!(self.fields, " {}={}", name, crate::logging::REDACTED);
+            }
+            name => {
+                let _ = write!(self.fields, " {}={}", name, value);
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => {
+                let _ = write!(self.message, "{:?}", value);
+            }
+            name if name.starts_with("log.") => {}
+            name if crate::logging::is_sensitive_field(name) => {
+                let _ = write!(self.fields, " {}={}", name, crate::logging::REDACTED);
+            }
+            name => {
+                let _ = write!(self.fields, " {}={:?}", name, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::{debug, info};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn config(lines: usize) -> TunnelLogsConfig {
+        TunnelLogsConfig {
+            lines,
+            level: LogLevel::Info,
+            ..Default::default()
+        }
+    }
+
+    fn messages(logs: &TunnelLogs, id: &Uuid) -> Vec<String> {
+        logs.entries(id, None, None).into_iter().map(|e| e.message).collect()
+    }
+
+    #[test]
+    fn test_lines_are_kept_per_tunnel() {
+        let logs = TunnelLogs::new(&config(2));
+        let (db, web) = (Uuid::new_v4(), Uuid::new_v4());
+        let subscriber = tracing_subscriber::registry().with(logs.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            info!("not in a tunnel");
+            tunnel_span(db, "db").in_scope(|| {
+                info!("connecting");
+                info!(port = 5432, "bound");
+                debug!("too verbose");
+                info!("connected");
+            });
+            tunnel_span(web, "web").in_scope(|| info!("web line"));
+        });
+
+        // Oldest line dropped at capacity, debug not captured by default
+        assert_eq!(messages(&logs, &db), ["bound port=5432", "connected"]);
+        assert_eq!(messages(&logs, &web), ["web line"]);
+        let entries = logs.entries(&db, Some(2), None);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].seq, 3);
+        assert_eq!(entries[0].target, "ssh_tunnel_daemon::tunnel_log::tests");
+    }
+
+    #[test]
+    fn test_raised_level_applies_to_one_tunnel() {
+        let logs = TunnelLogs::new(&config(10));
+        let (db, web) = (Uuid::new_v4(), Uuid::new_v4());
+        let subscriber = tracing_subscriber::registry().with(logs.layer());
+
+        let raised = logs.set_level(db, LogLevel::Debug, Duration::from_secs(60));
+        assert!(raised.until.is_some());
+        assert_eq!(logs.level(&db).level, LogLevel::Debug);
+        assert_eq!(logs.level(&web), TunnelLogLevel { level: LogLevel::Info, until: None });
+
+        tracing::subscriber::with_default(subscriber, || {
+            tunnel_span(db, "db").in_scope(|| debug!("db detail"));
+            tunnel_span(web, "web").in_scope(|| debug!("web detail"));
+        });
+        assert_eq!(messages(&logs, &db), ["db detail"]);
+        assert!(messages(&logs, &web).is_empty());
+
+        // An expired raise falls back to the default
+        logs.set_level(db, LogLevel::Trace, Duration::ZERO);
+        assert_eq!(logs.level(&db).level, LogLevel::Info);
+        assert_eq!(logs.max_level.load(Ordering::Relaxed), LogLevel::Info as u8);
+    }
+}
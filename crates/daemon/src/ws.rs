@@ -0,0 +1,288 @@
+// SPDX-License-Identifier: Apache-2.0
+// Copyright 2025 SSH Tunnel Manager Contributors
+
+// WebSocket control channel
+//
+// `/api/v1/ws` streams the same events as `/api/events` and accepts
+// commands (start, stop, auth answers, subscriptions) on the same
+// connection. Commands run through the REST handlers, so scopes, profile
+// allowlists and the audit log behave exactly as for the REST routes.
+
+use std::sync::Arc;
+
+use axum::{
+    body::to_bytes,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::Utc;
+use futures::{SinkExt, StreamExt};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use ssh_tunnel_common::{StartTunnelRequest, TokenScope, WsCommand, WsMessage, WsRequest, WsResponse};
+
+use crate::api::{
+    heartbeat_interval, outgoing_event, start_tunnel, stop_tunnel, submit_auth, AppState, Caller,
+    CallerIdentity, OutgoingEvent, SubmitAuthPayload,
+};
+use crate::events::EventFeed;
+
+/// Largest REST response body turned into a command response
+const MAX_RESPONSE_BODY: usize = 64 * 1024;
+
+/// Open a WebSocket session (requires the `read` scope, like `/api/events`)
+#[utoipa::path(
+    get,
+    path = "/api/v1/ws",
+    tag = "tunnels",
+    responses((
+        status = 101,
+        description = "WebSocket upgrade. Client messages are `WsRequest` commands; the daemon \
+            sends `{\"type\":\"event\",\"event\":OutgoingEvent}` and \
+            `{\"type\":\"response\",\"id\":N,\"status\":...}` messages."
+    ))
+)]
+pub async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    identity: CallerIdentity,
+    caller: Caller,
+) -> Response {
+    ws.on_upgrade(move |socket| run_session(socket, state, identity, caller))
+}
+
+async fn run_session(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    identity: CallerIdentity,
+    caller: Caller,
+) {
+    info!("WebSocket session opened by {}", caller);
+
+    let (mut sink, mut stream) = socket.split();
+    let mut events = EventFeed::new(state.tunnel_manager.events(), None);
+    let mut shutdown_rx = state.shutdown_tx.subscribe();
+    let mut heartbeat = tokio::time::interval(heartbeat_interval());
+    // Empty: all tunnels the caller may see
+    let mut subscription: Vec<Uuid> = Vec::new();
+
+    loop {
+        let outgoing: WsMessage = tokio::select! {
+            _ = shutdown_rx.recv() => break,
+
+            _ = heartbeat.tick() => WsMessage::Event {
+                event: OutgoingEvent::Heartbeat { timestamp: Utc::now() },
+            },
+
+            item = events.next() => {
+                let Some(item) = item else { break };
+                let Some((_, event)) = outgoing_event(&state, &identity, item).await else {
+                    continue;
+                };
+                match event {
+                    OutgoingEvent::Snapshot { mut tunnels } if !subscription.is_empty() => {
+                        tunnels.retain(|tunnel| subscription.contains(&tunnel.id));
+                        WsMessage::Event { event: OutgoingEvent::Snapshot { tunnels } }
+                    }
+                    event => match event.tunnel_id() {
+                        Some(id) if !subscription.is_empty() && !subscription.contains(&id) => continue,
+                        _ => WsMessage::Event { event },
+                    },
+                }
+            }
+
+            message = stream.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let response = match serde_json::from_str::<WsRequest>(&text) {
+                        Ok(request) => {
+                            if let WsCommand::Subscribe { tunnel_ids } = &request.command {
+                                subscription = tunnel_ids.clone();
+                            }
+                            execute(&state, &identity, &caller, request).await
+                        }
+                        Err(e) => invalid_request(&text, e),
+                    };
+                    WsMessage::Response(response)
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                // Pings are answered by axum; binary frames aren't part of the protocol
+                Some(Ok(_)) => continue,
+                Some(Err(e)) => {
+                    debug!("WebSocket error from {}: {}", caller, e);
+                    break;
+                }
+            },
+        };
+
+        let json = match serde_json::to_string(&outgoing) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::error!("Failed to serialize WebSocket message: {e}");
+                continue;
+            }
+        };
+        if sink.send(Message::Text(json)).await.is_err() {
+            break;
+        }
+    }
+
+    let _ = sink.close().await;
+    info!("WebSocket session closed for {}", caller);
+}
+
+/// Run a command through the matching REST handler
+async fn execute(
+    state: &Arc<AppState>,
+    identity: &CallerIdentity,
+    caller: &Caller,
+    request: WsRequest,
+) -> WsResponse {
+    let WsRequest { id, command } = request;
+
+    // The route layer only checked `read`; commands need their REST scope
+    let scope = match command {
+        WsCommand::Start { .. } | WsCommand::Stop { .. } => TokenScope::Control,
+        WsCommand::SubmitAuth { .. } => TokenScope::Auth,
+        WsCommand::Subscribe { .. } => TokenScope::Read,
+    };
+    if let Some(Extension(identity)) = identity {
+        if !identity.has_scope(scope) {
+            warn!(
+                "Token '{}' denied WebSocket command '{}': missing '{}' scope",
+                identity.name,
+                command.name(),
+                scope
+            );
+            return WsResponse {
+                id,
+                status: 403,
+                message: None,
+                error: Some(format!("Token lacks the '{}' scope", scope)),
+            };
+        }
+    }
+
+    let response = match command {
+        WsCommand::Start { tunnel_id, mode, profile } => {
+            let request = StartTunnelRequest {
+                profile_id: tunnel_id.to_string(),
+                mode,
+                profile: profile.map(|profile| *profile),
+            };
+            start_tunnel(
+                State(state.clone()),
+                Path(tunnel_id),
+                identity.clone(),
+                caller.clone(),
+                Json(request),
+            )
+            .await
+            .into_response()
+        }
+        WsCommand::Stop { tunnel_id } => stop_tunnel(
+            State(state.clone()),
+            Path(tunnel_id),
+            identity.clone(),
+            caller.clone(),
+        )
+        .await
+        .into_response(),
+        WsCommand::SubmitAuth { tunnel_id, auth_request_id, response, responses } => submit_auth(
+            State(state.clone()),
+            Path(tunnel_id),
+            identity.clone(),
+            caller.clone(),
+            Json(SubmitAuthPayload {
+                request_id: auth_request_id,
+                response,
+                responses,
+            }),
+        )
+        .await
+        .into_response(),
+        WsCommand::Subscribe { tunnel_ids } => {
+            let message = if tunnel_ids.is_empty() {
+                "Subscribed to all tunnels".to_string()
+            } else {
+                format!("Subscribed to {} tunnel(s)", tunnel_ids.len())
+            };
+            return WsResponse {
+                id,
+                status: 200,
+                message: Some(message),
+                error: None,
+            };
+        }
+    };
+
+    into_ws_response(id, response).await
+}
+
+/// Turn a REST handler's `{message}` / `{error}` JSON response into a command response
+async fn into_ws_response(id: u64, response: Response) -> WsResponse {
+    let status = response.status().as_u16();
+    let body = to_bytes(response.into_body(), MAX_RESPONSE_BODY)
+        .await
+        .unwrap_or_default();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+    let field = |name: &str| body.get(name).and_then(|v| v.as_str()).map(str::to_string);
+
+    WsResponse {
+        id,
+        status,
+        message: field("message"),
+        error: field("error"),
+    }
+}
+
+/// 400 response for a message that isn't a valid command, echoing its ID if
+/// one can be found
+fn invalid_request(text: &str, error: serde_json::Error) -> WsResponse {
+    let id = serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|value| value.get("id").and_then(|id| id.as_u64()))
+        .unwrap_or(0);
+    WsResponse {
+        id,
+        status: 400,
+        message: None,
+        error: Some(format!("Invalid command: {}", error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn test_rest_response_conversion() {
+        let response = (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": "Tunnel is not active" })),
+        )
+            .into_response();
+        let response = into_ws_response(3, response).await;
+        assert_eq!(response.id, 3);
+        assert_eq!(response.status, 404);
+        assert_eq!(response.error.as_deref(), Some("Tunnel is not active"));
+        assert!(!response.is_success());
+
+        // Scope rejections from the route layer have no body
+        let response = into_ws_response(4, StatusCode::FORBIDDEN.into_response()).await;
+        assert_eq!(response.status, 403);
+        assert_eq!(response.error, None);
+
+        let invalid = invalid_request(
+            r#"{"id": 9, "command": "teleport"}"#,
+            serde_json::from_str::<WsRequest>(r#"{"id": 9, "command": "teleport"}"#).unwrap_err(),
+        );
+        assert_eq!(invalid.id, 9);
+        assert_eq!(invalid.status, 400);
+    }
+}
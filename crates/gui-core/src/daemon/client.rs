@@ -7,16 +7,23 @@
 //! - Unix socket (default, local-only)
 //! - HTTP (testing/localhost, no TLS)
 //! - HTTPS (network-ready with TLS and optional certificate pinning)
+//!
+//! Requests go to `/api/v1` when the daemon advertises it in its capabilities,
+//! and to the unversioned `/api` paths on older daemons.
+
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::Deserialize;
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use ssh_tunnel_common::{
     add_auth_header, create_daemon_client, prepare_profile_for_remote, AuthRequest, AuthResponse,
-    ConnectionMode, DaemonClientConfig, DaemonInfo, Profile, ProfileSourceMode,
-    StartTunnelRequest, TunnelStatus,
+    ConnectionMode, DaemonCapabilities, DaemonClientConfig, DaemonInfo, LogLevel, Profile,
+    ProfileSourceMode, SetLogLevelRequest, StartTunnelRequest, TunnelLogEntry, TunnelLogLevel,
+    TunnelSession, TunnelStatus, API_VERSION,
 };
 
 /// Daemon client for tunnel operations
@@ -24,6 +31,8 @@ use ssh_tunnel_common::{
 pub struct DaemonClient {
     client: Client,
     pub config: DaemonClientConfig,
+    /// Fetched on first use, shared between clones
+    capabilities: Arc<RwLock<Option<DaemonCapabilities>>>,
 }
 
 /// Response from start/stop operations
@@ -45,6 +54,8 @@ pub struct TunnelStatusResponse {
     pub id: Uuid,
     pub status: TunnelStatus,
     pub pending_auth: Option<AuthRequest>,
+    #[serde(default)]
+    pub bound_address: Option<std::net::SocketAddr>,
 }
 
 /// List of active tunnels
@@ -56,16 +67,17 @@ pub struct TunnelsListResponse {
 impl DaemonClient {
     /// Create a new daemon client with default configuration
     pub fn new() -> Result<Self> {
-        let config = DaemonClientConfig::default();
-        let client = create_daemon_client(&config)?;
-
-        Ok(Self { client, config })
+        Self::with_config(DaemonClientConfig::default())
     }
 
     /// Create a daemon client with custom configuration
     pub fn with_config(config: DaemonClientConfig) -> Result<Self> {
         let client = create_daemon_client(&config)?;
-        Ok(Self { client, config })
+        Ok(Self {
+            client,
+            config,
+            capabilities: Arc::new(RwLock::new(None)),
+        })
     }
 
     /// Update the skip SSH setup warning preference
@@ -78,6 +90,73 @@ impl DaemonClient {
         self.config.daemon_base_url()
     }
 
+    /// URL for an API path such as `/tunnels`, under `/api/v1` when the
+    /// daemon supports it
+    async fn api_url(&self, path: &str) -> Result<String> {
+        // If capabilities can't be fetched the request itself will most
+        // likely fail too; the unversioned paths work on every daemon
+        let prefix = match self.capabilities().await {
+            Ok(capabilities) => capabilities.api_prefix(),
+            Err(_) => "/api".to_string(),
+        };
+        Ok(format!("{}{}{}", self.base_url()?, prefix, path))
+    }
+
+    /// What the daemon supports
+    ///
+    /// Fetched once and cached. Daemons that predate the versioned API have
+    /// no capabilities endpoint and get `DaemonCapabilities::legacy()`.
+    pub async fn capabilities(&self) -> Result<DaemonCapabilities> {
+        if let Some(capabilities) = self.capabilities.read().await.as_ref() {
+            return Ok(capabilities.clone());
+        }
+
+        let url = format!("{}/api/{}/capabilities", self.base_url()?, API_VERSION);
+        let request = add_auth_header(self.client.get(&url), &self.config)?;
+        let response = request
+            .send()
+            .await
+            .context("Failed to send capabilities request")?;
+
+        let capabilities = if response.status().is_success() {
+            response
+                .json()
+                .await
+                .context("Failed to parse daemon capabilities")?
+        } else if response.status() == reqwest::StatusCode::NOT_FOUND {
+            tracing::info!("Daemon has no capabilities endpoint; assuming a pre-v1 daemon");
+            DaemonCapabilities::legacy()
+        } else {
+            anyhow::bail!("Failed to get daemon capabilities: HTTP {}", response.status())
+        };
+
+        *self.capabilities.write().await = Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    /// Reason the daemon can't run this profile, if it lacks a capability
+    ///
+    /// Returns `None` when the capabilities can't be fetched; the daemon then
+    /// reports the problem itself when the tunnel starts.
+    pub async fn unsupported_reason(&self, profile: &Profile) -> Option<String> {
+        let capabilities = self.capabilities().await.ok()?;
+        let forwarding_type = &profile.forwarding.forwarding_type;
+        if !capabilities.supports_forwarding(forwarding_type) {
+            return Some(format!(
+                "The daemon (version {}) does not support {:?} forwarding",
+                capabilities.daemon_version, forwarding_type
+            ));
+        }
+        let auth_type = &profile.connection.auth_type;
+        if !capabilities.supports_auth(auth_type) {
+            return Some(format!(
+                "The daemon (version {}) does not support {:?} authentication",
+                capabilities.daemon_version, auth_type
+            ));
+        }
+        None
+    }
+
     /// Check daemon health
     pub async fn health_check(&self) -> Result<bool> {
         let url = format!("{}/api/health", self.base_url()?);
@@ -132,6 +211,10 @@ impl DaemonClient {
     pub async fn start_tunnel(&self, profile: &Profile) -> Result<()> {
         let profile_id = profile.metadata.id;
 
+        if let Some(reason) = self.unsupported_reason(profile).await {
+            anyhow::bail!("Failed to start tunnel: {}", reason);
+        }
+
         // Determine if daemon is remote (HTTP/HTTPS) vs local (Unix socket)
         let is_remote_daemon = matches!(
             self.config.connection_mode,
@@ -156,7 +239,7 @@ impl DaemonClient {
             profile: profile_opt,
         };
 
-        let url = format!("{}/api/tunnels/{}/start", self.base_url()?, profile_id);
+        let url = self.api_url(&format!("/tunnels/{}/start", profile_id)).await?;
         let request = self.client.post(&url);
         let request = add_auth_header(request, &self.config)?;
 
@@ -181,7 +264,7 @@ impl DaemonClient {
 
     /// Stop a tunnel by profile ID
     pub async fn stop_tunnel(&self, profile_id: Uuid) -> Result<()> {
-        let url = format!("{}/api/tunnels/{}/stop", self.base_url()?, profile_id);
+        let url = self.api_url(&format!("/tunnels/{}/stop", profile_id)).await?;
         let request = self.client.post(&url);
         let request = add_auth_header(request, &self.config)?;
 
@@ -205,7 +288,7 @@ impl DaemonClient {
 
     /// Get tunnel status by profile ID
     pub async fn get_tunnel_status(&self, profile_id: Uuid) -> Result<Option<TunnelStatusResponse>> {
-        let url = format!("{}/api/tunnels/{}/status", self.base_url()?, profile_id);
+        let url = self.api_url(&format!("/tunnels/{}/status", profile_id)).await?;
         let request = self.client.get(&url);
         let request = add_auth_header(request, &self.config)?;
 
@@ -235,7 +318,7 @@ impl DaemonClient {
 
     /// List all active tunnels
     pub async fn list_tunnels(&self) -> Result<Vec<TunnelStatusResponse>> {
-        let url = format!("{}/api/tunnels", self.base_url()?);
+        let url = self.api_url("/tunnels").await?;
         let request = self.client.get(&url);
         let request = add_auth_header(request, &self.config)?;
 
@@ -261,9 +344,108 @@ impl DaemonClient {
         }
     }
 
+    /// Past sessions of a tunnel, oldest first (at most `limit` of the most recent)
+    pub async fn get_tunnel_history(
+        &self,
+        profile_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<TunnelSession>> {
+        let url = self.api_url(&format!("/tunnels/{}/history", profile_id)).await?;
+        let request = self.client.get(&url).query(&[("limit", limit)]);
+        let request = add_auth_header(request, &self.config)?;
+
+        let response = request
+            .send()
+            .await
+            .context("Failed to send history request")?;
+
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .context("Failed to parse session history")
+        } else {
+            let error: ErrorResponse = response
+                .json()
+                .await
+                .unwrap_or_else(|_| ErrorResponse {
+                    error: "Unknown error".to_string(),
+                });
+            anyhow::bail!("Failed to get session history: {}", error.error)
+        }
+    }
+
+    /// Log lines captured for a tunnel after `after`, oldest first (at most `limit`)
+    pub async fn get_tunnel_logs(
+        &self,
+        profile_id: Uuid,
+        after: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<TunnelLogEntry>> {
+        let url = self.api_url(&format!("/tunnels/{}/logs", profile_id)).await?;
+        let mut request = self.client.get(&url).query(&[("limit", limit)]);
+        if let Some(after) = after {
+            request = request.query(&[("after", after)]);
+        }
+        let request = add_auth_header(request, &self.config)?;
+
+        let response = request
+            .send()
+            .await
+            .context("Failed to send logs request")?;
+
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .context("Failed to parse tunnel logs")
+        } else {
+            let error: ErrorResponse = response
+                .json()
+                .await
+                .unwrap_or_else(|_| ErrorResponse {
+                    error: "Unknown error".to_string(),
+                });
+            anyhow::bail!("Failed to get tunnel logs: {}", error.error)
+        }
+    }
+
+    /// Capture a tunnel's log at `level` for `duration_secs` (daemon default when `None`)
+    pub async fn set_tunnel_log_level(
+        &self,
+        profile_id: Uuid,
+        level: LogLevel,
+        duration_secs: Option<u64>,
+    ) -> Result<TunnelLogLevel> {
+        let url = self.api_url(&format!("/tunnels/{}/logs/level", profile_id)).await?;
+        let request = self.client.put(&url);
+        let request = add_auth_header(request, &self.config)?;
+
+        let response = request
+            .json(&SetLogLevelRequest { level, duration_secs })
+            .send()
+            .await
+            .context("Failed to send log level request")?;
+
+        if response.status().is_success() {
+            response
+                .json()
+                .await
+                .context("Failed to parse log level")
+        } else {
+            let error: ErrorResponse = response
+                .json()
+                .await
+                .unwrap_or_else(|_| ErrorResponse {
+                    error: "Unknown error".to_string(),
+                });
+            anyhow::bail!("Failed to set log level: {}", error.error)
+        }
+    }
+
     /// Get pending authentication request for a tunnel
     pub async fn get_pending_auth(&self, profile_id: Uuid) -> Result<Option<AuthRequest>> {
-        let url = format!("{}/api/tunnels/{}/auth", self.base_url()?, profile_id);
+        let url = self.api_url(&format!("/tunnels/{}/auth", profile_id)).await?;
         let request = self.client.get(&url);
         let request = add_auth_header(request, &self.config)?;
 
@@ -293,13 +475,14 @@ impl DaemonClient {
 
     /// Submit authentication response for a tunnel
     pub async fn submit_auth(&self, profile_id: Uuid, auth_response: String) -> Result<()> {
-        let url = format!("{}/api/tunnels/{}/auth", self.base_url()?, profile_id);
+        let url = self.api_url(&format!("/tunnels/{}/auth", profile_id)).await?;
         let request = self.client.post(&url);
         let request = add_auth_header(request, &self.config)?;
 
         let auth = AuthResponse {
             tunnel_id: profile_id,
             response: auth_response,
+            responses: Vec::new(),
         };
 
         let response = request
@@ -328,17 +511,34 @@ impl DaemonClient {
         request_id: Uuid,
         auth_response: String,
     ) -> Result<()> {
-        let url = format!("{}/api/tunnels/{}/auth", self.base_url()?, profile_id);
-        let request = self.client.post(&url);
-        let request = add_auth_header(request, &self.config)?;
-
         let payload = serde_json::json!({
             "request_id": request_id,
             "response": auth_response,
         });
+        self.post_auth(profile_id, &payload).await
+    }
+
+    /// Submit the answers to all prompts of a multi-prompt request, in order
+    pub async fn submit_auth_answers(
+        &self,
+        profile_id: Uuid,
+        request_id: Uuid,
+        answers: Vec<String>,
+    ) -> Result<()> {
+        let payload = serde_json::json!({
+            "request_id": request_id,
+            "responses": answers,
+        });
+        self.post_auth(profile_id, &payload).await
+    }
+
+    async fn post_auth(&self, profile_id: Uuid, payload: &serde_json::Value) -> Result<()> {
+        let url = self.api_url(&format!("/tunnels/{}/auth", profile_id)).await?;
+        let request = self.client.post(&url);
+        let request = add_auth_header(request, &self.config)?;
 
         let response = request
-            .json(&payload)
+            .json(payload)
             .send()
             .await
             .context("Failed to send auth response")?;
@@ -358,7 +558,7 @@ impl DaemonClient {
 
     /// Get daemon information (version, config, uptime, etc.)
     pub async fn get_daemon_info(&self) -> Result<DaemonInfo> {
-        let url = format!("{}/api/daemon/info", self.base_url()?);
+        let url = self.api_url("/daemon/info").await?;
         let request = self.client.get(&url);
         let request = add_auth_header(request, &self.config)?;
 
@@ -386,7 +586,7 @@ impl DaemonClient {
 
     /// Shutdown the daemon
     pub async fn shutdown_daemon(&self) -> Result<()> {
-        let url = format!("{}/api/daemon/shutdown", self.base_url()?);
+        let url = self.api_url("/daemon/shutdown").await?;
         let request = self.client.post(&url);
         let request = add_auth_header(request, &self.config)?;
 
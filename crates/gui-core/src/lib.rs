@@ -15,7 +15,7 @@ pub mod daemon;
 // Re-export commonly used types
 pub use state::AppCore;
 pub use events::{TunnelEventHandler, GuiEvent};
-pub use profiles::{load_profiles, save_profile, delete_profile, validate_profile, profile_name_exists};
+pub use profiles::{load_profiles, save_profile, delete_profile, validate_profile, profile_name_exists, profile_conflicts};
 pub use view_models::{ProfileViewModel, StatusColor};
 pub use daemon::{
     DaemonClient, EventListener, TunnelEvent,
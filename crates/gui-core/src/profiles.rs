@@ -3,7 +3,7 @@
 
 //! Profile operations and validation
 
-use ssh_tunnel_common::Profile;
+use ssh_tunnel_common::{preflight, PreflightIssue, Profile};
 use anyhow::Result;
 use uuid::Uuid;
 
@@ -55,9 +55,7 @@ pub fn validate_profile(profile: &Profile) -> Result<()> {
     use ssh_tunnel_common::ForwardingType;
     match &profile.forwarding.forwarding_type {
         ForwardingType::Local => {
-            if profile.forwarding.local_port.is_none() || profile.forwarding.local_port == Some(0) {
-                anyhow::bail!("Local port must be greater than 0");
-            }
+            // Local port 0/unset is allowed: the daemon picks a free port
             if profile.forwarding.remote_port.is_none() || profile.forwarding.remote_port == Some(0) {
                 anyhow::bail!("Remote port must be greater than 0");
             }
@@ -80,6 +78,12 @@ pub fn validate_profile(profile: &Profile) -> Result<()> {
     Ok(())
 }
 
+/// Other saved profiles that bind the same local address as `profile`
+pub fn profile_conflicts(profile: &Profile) -> Vec<PreflightIssue> {
+    let others = load_profiles().unwrap_or_default();
+    preflight::check_profile_conflicts(profile, &others)
+}
+
 /// Check if profile name already exists (excluding given ID)
 pub fn profile_name_exists(name: &str, exclude_id: Option<Uuid>) -> bool {
     match load_profiles() {
@@ -10,7 +10,7 @@ use std::rc::Rc;
 use uuid::Uuid;
 
 use super::{details, profile_details, profiles_list, window::AppState};
-use ssh_tunnel_common::{AuthRequest, AuthRequestType, TunnelStatus};
+use ssh_tunnel_common::{AuthPrompt, AuthRequest, AuthRequestType, TunnelStatus};
 
 /// Handle an authentication request by queuing it for processing.
 /// Events are queued and processed sequentially to prevent GTK event loop overwhelm.
@@ -99,53 +99,53 @@ fn show_auth_dialog(
     state: Rc<AppState>,
 ) {
     let profile_id = request.tunnel_id;
-    let prompt = request.prompt.clone();
-    let hidden = request.hidden;
     let auth_type = request.auth_type;
+    // Multi-prompt (keyboard-interactive) requests become one form with a
+    // labelled field per prompt; other requests have a single field
+    let multi_prompt = !request.prompts.is_empty();
+    let (heading, body, prompts) = if multi_prompt {
+        (
+            request.name.unwrap_or_else(|| "Authentication Required".to_string()),
+            request.instructions.unwrap_or_default(),
+            request.prompts,
+        )
+    } else {
+        (
+            "Authentication Required".to_string(),
+            request.prompt,
+            vec![AuthPrompt { prompt: String::new(), echo: !request.hidden }],
+        )
+    };
     let parent = parent.clone();
     let dialog = adw::MessageDialog::builder()
         .transient_for(&parent)
         .modal(true)
-        .heading("Authentication Required")
-        .body(&prompt)
+        .heading(heading.as_str())
+        .body(body.as_str())
         .build();
 
-    // Determine appropriate placeholder text based on auth type
-    let placeholder = match auth_type {
-        AuthRequestType::KeyPassphrase => "Enter SSH key passphrase",
-        AuthRequestType::Password => "Enter remote user password",
-        AuthRequestType::TwoFactorCode => "Enter 2FA code",
-        AuthRequestType::KeyboardInteractive => {
-            if hidden {
-                "Enter password or code"
-            } else {
-                "Enter response"
-            }
-        }
-        AuthRequestType::HostKeyVerification => "Type 'yes' to accept or 'no' to reject",
-    };
-
-    // Create appropriate entry widget based on whether input should be hidden
-    let entry: gtk4::Widget = if hidden {
-        gtk4::PasswordEntry::builder()
-            .show_peek_icon(true)
-            .placeholder_text(placeholder)
-            .build()
-            .upcast()
-    } else {
-        gtk4::Entry::builder()
-            .placeholder_text(placeholder)
-            .build()
-            .upcast()
-    };
-
-    // Create a box to hold the entry
+    // Create a box to hold the entries
     let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
     content_box.set_margin_start(12);
     content_box.set_margin_end(12);
     content_box.set_margin_top(12);
     content_box.set_margin_bottom(12);
-    content_box.append(&entry);
+
+    let mut entries: Vec<gtk4::Widget> = Vec::with_capacity(prompts.len());
+    for prompt in &prompts {
+        if !prompt.prompt.trim().is_empty() {
+            let label = gtk4::Label::builder()
+                .label(prompt.prompt.trim())
+                .halign(gtk4::Align::Start)
+                .wrap(true)
+                .build();
+            content_box.append(&label);
+        }
+        let entry = create_entry(&auth_type, !prompt.echo);
+        content_box.append(&entry);
+        entries.push(entry);
+    }
+    let entries = Rc::new(entries);
 
     // Add content to dialog
     dialog.set_extra_child(Some(&content_box));
@@ -160,23 +160,30 @@ fn show_auth_dialog(
     // Store dialog reference in state so we can close it on retry
     state.active_auth_dialog.replace(Some(dialog.clone()));
 
-    // Focus the entry when dialog is shown
-    entry.grab_focus();
+    // Focus the first entry when dialog is shown
+    if let Some(first) = entries.first() {
+        first.grab_focus();
+    }
 
-    // Wire up Enter key to submit
-    let dialog_clone = dialog.clone();
-    if let Some(password_entry) = entry.downcast_ref::<gtk4::PasswordEntry>() {
-        password_entry.connect_activate(move |_| {
-            dialog_clone.response("submit");
-        });
-    } else if let Some(text_entry) = entry.downcast_ref::<gtk4::Entry>() {
-        text_entry.connect_activate(move |_| {
-            dialog_clone.response("submit");
-        });
+    // Wire up Enter key to move to the next field, and to submit from the last one
+    for (index, entry) in entries.iter().enumerate() {
+        let dialog_clone = dialog.clone();
+        let next = entries.get(index + 1).cloned();
+        let on_activate = move || match &next {
+            Some(next) => {
+                next.grab_focus();
+            }
+            None => dialog_clone.response("submit"),
+        };
+        if let Some(password_entry) = entry.downcast_ref::<gtk4::PasswordEntry>() {
+            password_entry.connect_activate(move |_| on_activate());
+        } else if let Some(text_entry) = entry.downcast_ref::<gtk4::Entry>() {
+            text_entry.connect_activate(move |_| on_activate());
+        }
     }
 
     // Handle response
-    let entry_clone = entry.clone();
+    let entries_clone = entries.clone();
     let state_clone = state.clone();
     let parent_clone = parent.clone();
     let response_handled = std::cell::RefCell::new(false);
@@ -189,16 +196,10 @@ fn show_auth_dialog(
         let cancelled = response != "submit";
 
         if !cancelled {
-            // Get the text from entry
-            let text = if let Some(password_entry) = entry_clone.downcast_ref::<gtk4::PasswordEntry>() {
-                password_entry.text().to_string()
-            } else if let Some(text_entry) = entry_clone.downcast_ref::<gtk4::Entry>() {
-                text_entry.text().to_string()
-            } else {
-                String::new()
-            };
+            // Get the text from the entries
+            let answers: Vec<String> = entries_clone.iter().map(entry_text).collect();
 
-            if text.is_empty() {
+            if answers.iter().all(String::is_empty) {
                 // Empty input treated as cancel
                 tracing::debug!("Empty authentication response - treating as cancel");
                 handle_cancel(dialog, &state_clone, &parent_clone, profile_id);
@@ -237,7 +238,9 @@ fn show_auth_dialog(
 
                 // Submit auth in background
                 glib::MainContext::default().spawn_local(async move {
-                    if let Err(e) = submit_auth_async(profile_id, request_id, text, &state).await {
+                    if let Err(e) =
+                        submit_auth_async(profile_id, request_id, answers, multi_prompt, &state).await
+                    {
                         tracing::error!("Failed to submit authentication for tunnel {}: {}", profile_id, e);
                         // Network error - daemon won't receive our response
                         // User will need to retry connection
@@ -294,11 +297,54 @@ fn handle_cancel(
     process_auth_queue(parent, state.clone());
 }
 
+/// Entry for one answer, hiding the input when `hidden`
+fn create_entry(auth_type: &AuthRequestType, hidden: bool) -> gtk4::Widget {
+    // Determine appropriate placeholder text based on auth type
+    let placeholder = match auth_type {
+        AuthRequestType::KeyPassphrase => "Enter SSH key passphrase",
+        AuthRequestType::Password => "Enter remote user password",
+        AuthRequestType::TwoFactorCode => "Enter 2FA code",
+        AuthRequestType::KeyboardInteractive => {
+            if hidden {
+                "Enter password or code"
+            } else {
+                "Enter response"
+            }
+        }
+        AuthRequestType::HostKeyVerification => "Type 'yes' to accept or 'no' to reject",
+        AuthRequestType::VaultPassphrase => "Enter master passphrase of the secret vault",
+    };
+
+    if hidden {
+        gtk4::PasswordEntry::builder()
+            .show_peek_icon(true)
+            .placeholder_text(placeholder)
+            .build()
+            .upcast()
+    } else {
+        gtk4::Entry::builder()
+            .placeholder_text(placeholder)
+            .build()
+            .upcast()
+    }
+}
+
+fn entry_text(entry: &gtk4::Widget) -> String {
+    if let Some(password_entry) = entry.downcast_ref::<gtk4::PasswordEntry>() {
+        password_entry.text().to_string()
+    } else if let Some(text_entry) = entry.downcast_ref::<gtk4::Entry>() {
+        text_entry.text().to_string()
+    } else {
+        String::new()
+    }
+}
+
 /// Submit authentication response to daemon
 async fn submit_auth_async(
     profile_id: Uuid,
     request_id: Uuid,
-    response: String,
+    mut answers: Vec<String>,
+    multi_prompt: bool,
     state: &Rc<AppState>,
 ) -> anyhow::Result<()> {
     // Get daemon client
@@ -310,7 +356,12 @@ async fn submit_auth_async(
         .clone();
 
     // Submit the auth response with request_id
-    daemon_client.submit_auth_with_id(profile_id, request_id, response).await?;
+    if multi_prompt {
+        daemon_client.submit_auth_answers(profile_id, request_id, answers).await?;
+    } else {
+        let response = answers.pop().unwrap_or_default();
+        daemon_client.submit_auth_with_id(profile_id, request_id, response).await?;
+    }
 
     tracing::debug!("Authentication submitted for tunnel {} (request {})", profile_id, request_id);
 
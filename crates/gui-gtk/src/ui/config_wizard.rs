@@ -333,6 +333,8 @@ fn show_manual_config_dialog(parent: Option<&impl IsA<gtk4::Window>>) -> Option<
                 daemon_url: String::new(),
                 auth_token,
                 tls_cert_fingerprint: String::new(),
+                client_cert_path: None,
+                client_key_path: None,
                 skip_ssh_setup_warning: false,
             }
         } else {
@@ -362,6 +364,8 @@ fn show_manual_config_dialog(parent: Option<&impl IsA<gtk4::Window>>) -> Option<
                 daemon_url: String::new(),
                 auth_token,
                 tls_cert_fingerprint: fingerprint,
+                client_cert_path: None,
+                client_key_path: None,
                 skip_ssh_setup_warning: false,
             }
         };
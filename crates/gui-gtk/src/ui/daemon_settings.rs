@@ -194,6 +194,27 @@ fn add_daemon_config_group(prefs_page: &adw::PreferencesPage, daemon_info: &Daem
         group.add(&socket_row);
     }
 
+    // Additional listeners (daemon serving several at once)
+    for listener in daemon_info.listeners.iter().skip(1) {
+        let mode_display = match listener.mode.as_str() {
+            "unix-socket" => "Unix Socket",
+            "tcp-http" => "TCP (HTTP)",
+            "tcp-https" => "TCP (HTTPS)",
+            _ => listener.mode.as_str(),
+        };
+        let address = match (&listener.bind_host, listener.bind_port, &listener.socket_path) {
+            (Some(host), Some(port), _) => format!("{}:{}", host, port),
+            (_, _, Some(path)) => path.clone(),
+            _ => "unknown".to_string(),
+        };
+        let auth_text = if listener.require_auth { "auth required" } else { "no auth" };
+        let listener_row = adw::ActionRow::builder()
+            .title("Additional Listener")
+            .subtitle(&format!("{} · {} · {}", mode_display, address, auth_text))
+            .build();
+        group.add(&listener_row);
+    }
+
     // Authentication
     let auth_text = if daemon_info.require_auth {
         "Required"
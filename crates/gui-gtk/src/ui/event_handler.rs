@@ -10,7 +10,7 @@
 use gtk4::prelude::*;
 use libadwaita as adw;
 use std::rc::Rc;
-use ssh_tunnel_common::{TunnelStatus, AuthRequest};
+use ssh_tunnel_common::{TunnelStatus, TunnelStatusResponse, AuthRequest};
 use uuid::Uuid;
 
 use super::window::AppState;
@@ -100,6 +100,34 @@ pub fn handle_auth_required(state: &Rc<AppState>, request: AuthRequest) {
     }
 }
 
+/// Handle an auth request that no longer needs an answer (answered by another
+/// client, or withdrawn because the tunnel stopped or the prompt timed out)
+pub fn handle_auth_withdrawn(state: &Rc<AppState>, profile_id: Uuid, request_id: Uuid) {
+    tracing::debug!("Event: Auth request {} for profile {} withdrawn", request_id, profile_id);
+
+    // A queued prompt for it must not be shown anymore
+    state.auth_request_queue.borrow_mut().retain(|req| req.id != request_id);
+    state.core.borrow_mut().active_auth_requests.remove(&request_id);
+
+    let is_active = state.active_auth_request_id.borrow()
+        .map(|id| id == request_id)
+        .unwrap_or(false);
+    if !is_active {
+        return;
+    }
+
+    // Close the dialog if it's still open (it isn't when we answered it ourselves)
+    if let Some(dialog) = state.active_auth_dialog.borrow_mut().take() {
+        dialog.close();
+    }
+    auth_dialog::clear_auth_state(state, profile_id);
+
+    *state.processing_auth_request.borrow_mut() = false;
+    if let Some(window) = state.window.borrow().as_ref() {
+        auth_dialog::process_auth_queue(window, state.clone());
+    }
+}
+
 /// Handle daemon connection state change
 pub fn handle_daemon_connected(state: &Rc<AppState>, connected: bool) {
     tracing::info!("Event: Daemon connection changed: {}", connected);
@@ -181,35 +209,83 @@ pub fn handle_error(state: &Rc<AppState>, profile_id: Option<Uuid>, error: Strin
     tracing::debug!("handle_error completed");
 }
 
+/// Replace known tunnel statuses with a daemon snapshot (sent after missed events)
+pub fn handle_snapshot(state: &Rc<AppState>, tunnels: Vec<TunnelStatusResponse>) {
+    let known: Vec<(Uuid, TunnelStatus)> = state
+        .core
+        .borrow()
+        .tunnel_statuses
+        .iter()
+        .map(|(id, status)| (*id, status.clone()))
+        .collect();
+
+    // Tunnels missing from the snapshot are no longer running
+    for (id, status) in known {
+        let active = !matches!(status, TunnelStatus::NotConnected | TunnelStatus::Disconnected);
+        if active && !tunnels.iter().any(|t| t.id == id) {
+            handle_status_changed(state, id, TunnelStatus::Disconnected);
+        }
+    }
+
+    for tunnel in tunnels {
+        let current = state.core.borrow().tunnel_statuses.get(&tunnel.id).cloned();
+        if current.as_ref() != Some(&tunnel.status) {
+            handle_status_changed(state, tunnel.id, tunnel.status);
+        }
+    }
+}
+
 /// Process a TunnelEvent from the SSE stream
 pub fn process_tunnel_event(state: &Rc<AppState>, event: TunnelEvent) {
     tracing::debug!("process_tunnel_event called with: {:?}", event);
 
     match event {
-        TunnelEvent::Connected { id } => {
+        TunnelEvent::Connected { id, .. } => {
             tracing::debug!("Processing Connected event for {}", id);
             handle_status_changed(state, id, TunnelStatus::Connected);
         }
-        TunnelEvent::Starting { id } => {
+        TunnelEvent::Starting { id, .. } => {
             tracing::debug!("Processing Starting event for {}", id);
             handle_status_changed(state, id, TunnelStatus::Connecting);
         }
-        TunnelEvent::Disconnected { id, reason } => {
+        TunnelEvent::Disconnected { id, reason, .. } => {
             tracing::debug!("Processing Disconnected event for {}: {}", id, reason);
             handle_status_changed(state, id, TunnelStatus::Disconnected);
         }
-        TunnelEvent::Error { id, error } => {
+        TunnelEvent::Error { id, error, .. } => {
             tracing::debug!("Processing Error event for {}: {}", id, error);
             handle_error(state, Some(id), error);
         }
-        TunnelEvent::AuthRequired { id: _, request } => {
+        TunnelEvent::AuthRequired { request, .. } => {
             tracing::debug!("Processing AuthRequired event for {}", request.tunnel_id);
             handle_auth_required(state, request);
         }
+        TunnelEvent::AuthResolved { id, request_id, .. } => {
+            handle_auth_withdrawn(state, id, request_id);
+        }
+        TunnelEvent::AuthCancelled { id, request_id, reason, .. } => {
+            tracing::debug!("Auth request {} for {} cancelled: {}", request_id, id, reason);
+            handle_auth_withdrawn(state, id, request_id);
+        }
+        TunnelEvent::Hook { id, stage, success, output, .. } => {
+            // Hook output is informational; a pre-up abort arrives as a separate Error event
+            if success {
+                tracing::info!("{} hook for {} completed: {}", stage, id, output);
+            } else {
+                tracing::warn!("{} hook for {} failed: {}", stage, id, output);
+            }
+        }
+        TunnelEvent::Stats { id, stats, .. } => {
+            tracing::trace!("Traffic stats for {}: {:?}", id, stats);
+        }
         TunnelEvent::Heartbeat { .. } => {
             // Heartbeat events are handled by the event listener for connection monitoring
             // Don't log these - too noisy
         }
+        TunnelEvent::Snapshot { tunnels } => {
+            tracing::info!("Resynchronizing {} tunnel(s) from daemon snapshot", tunnels.len());
+            handle_snapshot(state, tunnels);
+        }
     }
 
     tracing::debug!("process_tunnel_event completed");
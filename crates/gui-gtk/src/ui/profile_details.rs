@@ -12,7 +12,22 @@ use uuid::Uuid;
 
 use super::window::AppState;
 use crate::models::profile_model::ProfileModel;
-use ssh_tunnel_common::{ForwardingType, PasswordStorage, TunnelStatus};
+use ssh_tunnel_common::{
+    format_duration, ForwardingType, HistorySummary, LogLevel, PasswordStorage, TunnelLogEntry,
+    TunnelStatus,
+};
+
+/// Sessions shown in the history section
+const HISTORY_LIMIT: usize = 20;
+
+/// Log lines loaded when the page opens
+const LOG_LIMIT: usize = 200;
+
+/// How often the log panel asks the daemon for new lines
+const LOG_POLL_SECS: u32 = 2;
+
+/// How long the "Verbose" button raises the tunnel's log level
+const VERBOSE_LOG_SECS: u64 = 600;
 
 /// Create the profile details view
 pub fn create(state: Rc<AppState>, profile: &ProfileModel) -> adw::NavigationPage {
@@ -61,6 +76,28 @@ pub fn create(state: Rc<AppState>, profile: &ProfileModel) -> adw::NavigationPag
     let details_expander = create_details_expander(&profile);
     main_content.append(&details_expander);
 
+    // Recent sessions, filled in once the daemon answers
+    let history_group = create_history_group();
+    main_content.append(&history_group);
+    if let Some(prof) = profile.profile() {
+        glib::MainContext::default().spawn_local(load_history(
+            state.clone(),
+            prof.metadata.id,
+            history_group,
+        ));
+    }
+
+    // Tunnel log, followed while the page is shown
+    if let Some(prof) = profile.profile() {
+        let (log_group, log_view) = create_log_group(state.clone(), prof.metadata.id);
+        main_content.append(&log_group);
+        glib::MainContext::default().spawn_local(follow_logs(
+            state.clone(),
+            prof.metadata.id,
+            log_view.downgrade(),
+        ));
+    }
+
     scrolled.set_child(Some(&main_content));
 
     // Create clamp for centered content
@@ -202,7 +239,13 @@ fn create_details_expander(profile: &ProfileModel) -> adw::ExpanderRow {
         // Password stored
         let password_row = adw::ActionRow::new();
         password_row.set_title("Password Stored");
-        password_row.set_subtitle(if prof.connection.password_storage == PasswordStorage::Keychain { "Yes (in keyring)" } else { "No" });
+        let stored = match &prof.connection.password_storage {
+            PasswordStorage::Keychain => "Yes (in keyring)".to_string(),
+            PasswordStorage::File => "Yes (in encrypted vault)".to_string(),
+            PasswordStorage::Command { command } => format!("From command: {}", command),
+            PasswordStorage::None => "No".to_string(),
+        };
+        password_row.set_subtitle(&stored);
         expander.add_row(&password_row);
 
         // Keepalive interval
@@ -233,6 +276,188 @@ fn create_details_expander(profile: &ProfileModel) -> adw::ExpanderRow {
     expander
 }
 
+/// Create the session history section (empty until `load_history` fills it)
+fn create_history_group() -> adw::PreferencesGroup {
+    let group = adw::PreferencesGroup::new();
+    group.set_title("History");
+    group.set_description(Some("Loading…"));
+    group
+}
+
+/// Fill the history section with the profile's recent sessions, newest first
+async fn load_history(state: Rc<AppState>, profile_id: Uuid, group: adw::PreferencesGroup) {
+    let client = state.daemon_client.borrow().clone();
+    let Some(client) = client else {
+        group.set_description(Some("Daemon not available"));
+        return;
+    };
+
+    let sessions = match client.get_tunnel_history(profile_id, HISTORY_LIMIT).await {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            eprintln!("Failed to load session history: {}", e);
+            group.set_description(Some("History not available"));
+            return;
+        }
+    };
+    if sessions.is_empty() {
+        group.set_description(Some("No sessions recorded yet"));
+        return;
+    }
+
+    // Summary first, so recurring failures stand out
+    let summary = HistorySummary::from_sessions(&sessions);
+    let mut description = format!(
+        "Last {} sessions: {} failed, connected for {} in total",
+        summary.sessions,
+        summary.failed,
+        format_duration(summary.uptime)
+    );
+    if let Some((reason, count)) = &summary.top_failure {
+        description.push_str(&format!("\nMost frequent failure ({}×): {}", count, reason));
+    }
+    group.set_description(Some(glib::markup_escape_text(&description).as_str()));
+
+    for session in sessions.iter().rev() {
+        let row = adw::ActionRow::new();
+        row.set_use_markup(false);
+        row.set_title(
+            &session
+                .started_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+        );
+        let uptime = match session.uptime() {
+            Some(uptime) => format!("up {}", format_duration(uptime)),
+            None => "never connected".to_string(),
+        };
+        row.set_subtitle(&format!(
+            "{} · ↑ {} ↓ {} · {}",
+            uptime,
+            glib::format_size(session.bytes_sent),
+            glib::format_size(session.bytes_received),
+            session.end_reason
+        ));
+        let icon = gtk4::Image::from_icon_name(if session.failed {
+            "dialog-error-symbolic"
+        } else {
+            "emblem-ok-symbolic"
+        });
+        row.add_prefix(&icon);
+        group.add(&row);
+    }
+}
+
+/// Create the log section: a read-only view of the tunnel's recent log lines
+/// and a button that captures debug output for a while
+fn create_log_group(state: Rc<AppState>, profile_id: Uuid) -> (adw::PreferencesGroup, gtk4::TextView) {
+    let group = adw::PreferencesGroup::new();
+    group.set_title("Log");
+
+    let verbose_btn = gtk4::Button::with_label("Verbose");
+    verbose_btn.add_css_class("flat");
+    verbose_btn.set_tooltip_text(Some("Capture debug output for 10 minutes"));
+    verbose_btn.connect_clicked(move |btn| {
+        let state = state.clone();
+        let btn = btn.clone();
+        glib::MainContext::default().spawn_local(async move {
+            let client = state.daemon_client.borrow().clone();
+            let Some(client) = client else {
+                return;
+            };
+            match client
+                .set_tunnel_log_level(profile_id, LogLevel::Debug, Some(VERBOSE_LOG_SECS))
+                .await
+            {
+                Ok(level) => {
+                    if let Some(until) = level.until {
+                        let until = until.with_timezone(&chrono::Local).format("%H:%M");
+                        let tooltip = format!("Capturing debug output until {}", until);
+                        btn.set_tooltip_text(Some(tooltip.as_str()));
+                    }
+                }
+                Err(e) => eprintln!("Failed to raise log level: {}", e),
+            }
+        });
+    });
+    group.set_header_suffix(Some(&verbose_btn));
+
+    let text_view = gtk4::TextView::new();
+    text_view.set_editable(false);
+    text_view.set_cursor_visible(false);
+    text_view.set_monospace(true);
+    text_view.set_wrap_mode(gtk4::WrapMode::WordChar);
+    text_view.set_top_margin(6);
+    text_view.set_bottom_margin(6);
+    text_view.set_left_margin(6);
+    text_view.set_right_margin(6);
+
+    let scrolled = gtk4::ScrolledWindow::new();
+    scrolled.set_min_content_height(240);
+    scrolled.set_child(Some(&text_view));
+    scrolled.add_css_class("card");
+    group.add(&scrolled);
+
+    (group, text_view)
+}
+
+/// Append new log lines to the view until the page goes away
+async fn follow_logs(state: Rc<AppState>, profile_id: Uuid, view: glib::WeakRef<gtk4::TextView>) {
+    let mut last_seq = None;
+    loop {
+        let Some(text_view) = view.upgrade() else {
+            return;
+        };
+
+        // Only poll while the page is on screen
+        let client = state.daemon_client.borrow().clone();
+        if let Some(client) = client.filter(|_| text_view.is_mapped()) {
+            match client.get_tunnel_logs(profile_id, last_seq, LOG_LIMIT).await {
+                Ok(entries) => {
+                    if let Some(last) = entries.last() {
+                        last_seq = Some(last.seq);
+                    }
+                    append_log_lines(&text_view, &entries);
+                }
+                Err(e) => eprintln!("Failed to load tunnel log: {}", e),
+            }
+        }
+        drop(text_view);
+
+        glib::timeout_future_seconds(LOG_POLL_SECS).await;
+    }
+}
+
+fn append_log_lines(text_view: &gtk4::TextView, entries: &[TunnelLogEntry]) {
+    if entries.is_empty() {
+        return;
+    }
+    let buffer = text_view.buffer();
+    let mut end = buffer.end_iter();
+    for entry in entries {
+        let line = format!(
+            "{} {:>5} {}\n",
+            entry.timestamp.with_timezone(&chrono::Local).format("%H:%M:%S"),
+            entry.level.as_str().to_uppercase(),
+            entry.message
+        );
+        buffer.insert(&mut end, &line);
+    }
+
+    // Keep the view bounded like the daemon's ring
+    let excess = buffer.line_count() - 1 - LOG_LIMIT as i32;
+    if excess > 0 {
+        let mut start = buffer.start_iter();
+        if let Some(mut cut) = buffer.iter_at_line(excess) {
+            buffer.delete(&mut start, &mut cut);
+        }
+    }
+
+    let mut end = buffer.end_iter();
+    text_view.scroll_to_iter(&mut end, 0.0, false, 0.0, 1.0);
+}
+
 /// Create action buttons (Start/Stop/Edit/Delete)
 /// Returns (button_box, start_button, stop_button) for storing references in AppState
 fn create_action_buttons(state: Rc<AppState>, profile: &ProfileModel, window: &adw::ApplicationWindow) -> (gtk4::Box, gtk4::Button, gtk4::Button) {
@@ -16,7 +16,7 @@ use ssh_tunnel_common::config::{
     Profile, ProfileMetadata, ConnectionConfig, ForwardingConfig, PasswordStorage, TunnelOptions,
 };
 use ssh_tunnel_common::types::{AuthType, ForwardingType};
-use ssh_tunnel_gui_core::{validate_profile, profile_name_exists, save_profile};
+use ssh_tunnel_gui_core::{validate_profile, profile_name_exists, profile_conflicts, save_profile};
 
 /// Show profile editor dialog for creating a new profile
 pub fn show_new_profile_dialog(parent: &impl IsA<gtk4::Window>, state: Rc<AppState>) {
@@ -222,10 +222,23 @@ fn create_dialog(
         .build();
     key_password_row.add_suffix(&key_password_entry);
 
+    // Secret command (password managers, Vault agent); used instead of the keychain
+    let secret_command_entry = Entry::builder()
+        .placeholder_text("pass show ssh/server")
+        .hexpand(true)
+        .build();
+    let secret_command_row = adw::ActionRow::builder()
+        .title("Secret Command")
+        .subtitle("Command printing the password or passphrase, run by the daemon (optional)")
+        .focusable(false)
+        .build();
+    secret_command_row.add_suffix(&secret_command_entry);
+
     auth_group.add(&key_row);
     auth_group.add(&key_path_row);
     auth_group.add(&store_keychain_row);
     auth_group.add(&key_password_row);
+    auth_group.add(&secret_command_row);
     content_box.append(&auth_group);
 
     // Wire up key switch to enable/disable key fields
@@ -290,7 +303,7 @@ fn create_dialog(
     local_port_spin.set_value(8080.0);
     let local_port_row = adw::ActionRow::builder()
         .title("Local Port")
-        .subtitle("Port on your machine (0 = disabled)")
+        .subtitle("Port on your machine (0 = any free port)")
         .focusable(false)
         .build();
     local_port_row.add_suffix(&local_port_spin);
@@ -424,6 +437,16 @@ fn create_dialog(
         .build();
     window_size_row.add_suffix(&window_size_spin);
 
+    // Auth prompt timeout (0: the daemon's default)
+    let auth_timeout_spin = SpinButton::with_range(0.0, 3600.0, 10.0);
+    auth_timeout_spin.set_value(default_opts.auth_timeout_secs.unwrap_or(0) as f64);
+    let auth_timeout_row = adw::ActionRow::builder()
+        .title("Auth Prompt Timeout")
+        .focusable(false)
+        .subtitle("Seconds to wait for a password or code (0 = daemon default)")
+        .build();
+    auth_timeout_row.add_suffix(&auth_timeout_spin);
+
     // Add all advanced option rows to expander
     expander_row.add_row(&compression_row);
     expander_row.add_row(&keepalive_row);
@@ -433,6 +456,7 @@ fn create_dialog(
     expander_row.add_row(&tcp_keepalive_row);
     expander_row.add_row(&max_packet_row);
     expander_row.add_row(&window_size_row);
+    expander_row.add_row(&auth_timeout_row);
 
     // Add expander to advanced group and advanced group to content
     advanced_group.add(&expander_row);
@@ -463,6 +487,10 @@ fn create_dialog(
                 }
             }
 
+            if let PasswordStorage::Command { command } = &inner_profile.connection.password_storage {
+                secret_command_entry.set_text(command);
+            }
+
             // Populate forwarding fields
             local_host_entry.set_text(&inner_profile.forwarding.bind_address);
             local_port_spin.set_value(profile.local_port() as f64);
@@ -478,6 +506,8 @@ fn create_dialog(
             tcp_keepalive_switch.set_active(inner_profile.options.tcp_keepalive);
             max_packet_spin.set_value(inner_profile.options.max_packet_size as f64);
             window_size_spin.set_value(inner_profile.options.window_size as f64);
+            auth_timeout_spin
+                .set_value(inner_profile.options.auth_timeout_secs.unwrap_or(0) as f64);
         }
     }
 
@@ -495,6 +525,15 @@ fn create_dialog(
     {
         let dialog = dialog.clone();
         let profile_id = profile.and_then(|p| p.profile()).map(|p| p.metadata.id);
+        // Hooks are edited in the profile file; keep them when saving from the dialog
+        let hooks = profile
+            .and_then(|p| p.profile())
+            .map(|p| p.hooks)
+            .unwrap_or_default();
+        // Vault secrets are managed with `ssh-tunnel vault`; keep using them
+        let in_vault = profile
+            .and_then(|p| p.profile())
+            .is_some_and(|p| p.connection.password_storage == PasswordStorage::File);
         let state = state.clone();
 
         save_button.connect_clicked(move |_| {
@@ -509,6 +548,7 @@ fn create_dialog(
             let key_path_text = key_path_entry.text().to_string();
             let key_password = key_password_entry.text().to_string();
             let store_in_keychain = store_keychain_switch.is_active();
+            let secret_command = secret_command_entry.text().trim().to_string();
 
             // Forwarding fields
             let local_host = local_host_entry.text().to_string();
@@ -525,6 +565,7 @@ fn create_dialog(
             let tcp_keepalive = tcp_keepalive_switch.is_active();
             let max_packet_size = max_packet_spin.value() as u32;
             let window_size = window_size_spin.value() as u32;
+            let auth_timeout_secs = Some(auth_timeout_spin.value() as u64).filter(|&s| s > 0);
 
             // Validate required fields
             if name.trim().is_empty() {
@@ -540,6 +581,11 @@ fn create_dialog(
                 return;
             }
 
+            if store_in_keychain && !secret_command.is_empty() {
+                show_error_dialog(&dialog, "Use either the keychain or a secret command, not both");
+                return;
+            }
+
             // Validate SSH key if enabled
             if use_key {
                 if key_path_text.trim().is_empty() {
@@ -610,8 +656,12 @@ fn create_dialog(
                     } else {
                         None
                     },
-                    password_storage: if store_in_keychain && !key_password.is_empty() {
+                    password_storage: if !secret_command.is_empty() {
+                        PasswordStorage::Command { command: secret_command.clone() }
+                    } else if store_in_keychain && !key_password.is_empty() {
                         PasswordStorage::Keychain
+                    } else if in_vault && !store_in_keychain {
+                        PasswordStorage::File
                     } else {
                         PasswordStorage::None
                     },
@@ -636,7 +686,9 @@ fn create_dialog(
                     tcp_keepalive,
                     max_packet_size,
                     window_size,
+                    auth_timeout_secs,
                 },
+                hooks: hooks.clone(),
             };
 
             // Validate profile using gui-core
@@ -683,9 +735,23 @@ fn create_dialog(
                 }
             }
 
+            // Port clashes don't block saving: both profiles may exist, just not run together
+            let conflicts = profile_conflicts(&profile);
+
             // Close dialog
             dialog.close();
 
+            if !conflicts.is_empty() {
+                if let Some(parent) = dialog.transient_for() {
+                    let message = conflicts
+                        .iter()
+                        .map(|c| c.message.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    show_warning_dialog(&parent, "Local Port Conflict", &message);
+                }
+            }
+
             // Reload the main profiles list page
             if let Some(list_box) = state.profile_list.borrow().as_ref() {
                 super::profiles_list::populate_profiles(list_box, state.clone());
@@ -719,6 +785,21 @@ fn show_error_dialog(parent: &impl IsA<gtk4::Window>, message: &str) {
     dialog.present();
 }
 
+/// Show a non-blocking warning dialog
+fn show_warning_dialog(parent: &impl IsA<gtk4::Window>, heading: &str, message: &str) {
+    let dialog = adw::MessageDialog::builder()
+        .transient_for(parent)
+        .heading(heading)
+        .body(message)
+        .build();
+
+    dialog.add_response("ok", "OK");
+    dialog.set_default_response(Some("ok"));
+    dialog.set_close_response("ok");
+
+    dialog.present();
+}
+
 /// Show file chooser dialog for selecting SSH key
 fn show_file_chooser(parent: &adw::Window, entry: &Entry) {
     use gtk4::gio;
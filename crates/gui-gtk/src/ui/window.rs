@@ -444,8 +444,8 @@ fn start_event_listener(state: Rc<AppState>, status_icon: gtk4::Image) {
                             // Update profile details page if a profile is selected
                             // (This is GTK-specific UI that's not in the centralized handler)
                             match event {
-                                TunnelEvent::Connected { id } |
-                                TunnelEvent::Starting { id } |
+                                TunnelEvent::Connected { id, .. } |
+                                TunnelEvent::Starting { id, .. } |
                                 TunnelEvent::Disconnected { id, .. } |
                                 TunnelEvent::Error { id, .. } => {
                                     if let Some(selected) = state.selected_profile.borrow().as_ref() {
@@ -502,6 +502,14 @@ fn start_event_listener(state: Rc<AppState>, status_icon: gtk4::Image) {
                                         }
                                     }
                                 }
+                                TunnelEvent::AuthResolved { .. }
+                                | TunnelEvent::AuthCancelled { .. }
+                                | TunnelEvent::Hook { .. }
+                                | TunnelEvent::Stats { .. }
+                                | TunnelEvent::Snapshot { .. } => {
+                                    // These don't change tunnel status on their own; auth
+                                    // dialogs and snapshots go through the central handler
+                                }
                                 TunnelEvent::Heartbeat { .. } => {
                                     // Update heartbeat timestamp
                                     {
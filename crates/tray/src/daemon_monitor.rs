@@ -1,17 +1,17 @@
 // SPDX-License-Identifier: Apache-2.0
 // Copyright 2025 SSH Tunnel Manager Contributors
 
-// Daemon monitor - SSE event listener with heartbeat monitoring
+// Daemon monitor - WebSocket (or SSE) event listener with heartbeat monitoring
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use futures_util::StreamExt;
-use serde::Deserialize;
-use ssh_tunnel_common::{add_auth_header, create_daemon_client, AuthRequest, DaemonClientConfig};
+use ssh_tunnel_common::{
+    add_auth_header, create_daemon_client, DaemonClientConfig, TunnelEvent, TunnelStatus, WsClient,
+};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{sleep, Duration, Instant};
-use uuid::Uuid;
 
 use crate::notifications;
 use crate::state::{TrayState, TunnelState};
@@ -22,18 +22,6 @@ const HEARTBEAT_TIMEOUT_SECS: u64 = 60;
 /// Reconnect backoff in seconds
 const RECONNECT_BACKOFF_SECS: u64 = 5;
 
-/// Event from daemon SSE stream (matches daemon's OutgoingEvent)
-#[derive(Debug, Clone, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum TunnelEvent {
-    Starting { id: Uuid },
-    Connected { id: Uuid },
-    Disconnected { id: Uuid, reason: String },
-    Error { id: Uuid, error: String },
-    AuthRequired { id: Uuid, request: AuthRequest },
-    Heartbeat { timestamp: DateTime<Utc> },
-}
-
 /// Start monitoring the daemon
 pub async fn start_monitor(state: Arc<RwLock<TrayState>>) -> Result<()> {
     loop {
@@ -43,8 +31,17 @@ pub async fn start_monitor(state: Arc<RwLock<TrayState>>) -> Result<()> {
             state_lock.daemon_config.clone()
         };
 
-        // Try to connect and monitor
-        match monitor_events(&config, state.clone()).await {
+        // Prefer the WebSocket channel so tray commands share the
+        // connection; older daemons only offer the SSE stream
+        let result = match WsClient::connect(&config).await {
+            Ok((client, events)) => monitor_websocket(client, events, state.clone()).await,
+            Err(e) => {
+                tracing::debug!("WebSocket unavailable ({:#}), using SSE", e);
+                monitor_events(&config, state.clone()).await
+            }
+        };
+
+        match result {
             Ok(_) => {
                 tracing::info!("Event stream ended normally");
             }
@@ -56,6 +53,7 @@ pub async fn start_monitor(state: Arc<RwLock<TrayState>>) -> Result<()> {
         // Update status to disconnected
         {
             let mut state_lock = state.write().await;
+            state_lock.ws_client = None;
             state_lock.last_heartbeat = None;
             state_lock.update_status();
         }
@@ -65,7 +63,35 @@ pub async fn start_monitor(state: Arc<RwLock<TrayState>>) -> Result<()> {
     }
 }
 
-/// Monitor events from daemon
+/// Monitor events over the WebSocket channel
+async fn monitor_websocket(
+    client: WsClient,
+    mut events: tokio::sync::mpsc::Receiver<TunnelEvent>,
+    state: Arc<RwLock<TrayState>>,
+) -> Result<()> {
+    state.write().await.ws_client = Some(client);
+    tracing::info!("Connected to daemon WebSocket channel");
+
+    loop {
+        match tokio::time::timeout(Duration::from_secs(HEARTBEAT_TIMEOUT_SECS), events.recv())
+            .await
+        {
+            Ok(Some(event)) => handle_event(event, state.clone()).await,
+            Ok(None) => {
+                tracing::info!("WebSocket channel closed");
+                break;
+            }
+            Err(_) => {
+                tracing::warn!("Heartbeat timeout, reconnecting...");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Monitor events from daemon's SSE stream
 async fn monitor_events(
     config: &DaemonClientConfig,
     state: Arc<RwLock<TrayState>>,
@@ -147,26 +173,35 @@ async fn monitor_events(
 }
 
 /// Parse an SSE message into a TunnelEvent
+///
+/// Event types this tray doesn't know (from a newer daemon) are skipped.
 fn parse_sse_message(message: &str) -> Option<TunnelEvent> {
-    for line in message.lines() {
-        if let Some(data) = line.strip_prefix("data: ") {
-            if let Ok(event) = serde_json::from_str::<TunnelEvent>(data) {
-                return Some(event);
-            } else {
-                tracing::warn!("Failed to parse SSE event: {}", data);
-            }
+    let data: Vec<&str> = message
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+        .collect();
+    if data.is_empty() {
+        return None;
+    }
+
+    let data = data.join("\n");
+    match serde_json::from_str::<TunnelEvent>(&data) {
+        Ok(event) => Some(event),
+        Err(e) => {
+            tracing::debug!("Skipping unrecognized SSE event ({}): {}", e, data);
+            None
         }
     }
-    None
 }
 
 /// Handle a tunnel event
 async fn handle_event(event: TunnelEvent, state: Arc<RwLock<TrayState>>) {
     match event {
-        TunnelEvent::Starting { id } => {
+        TunnelEvent::Starting { id, .. } => {
             tracing::info!("Tunnel {} starting", id);
         }
-        TunnelEvent::Connected { id } => {
+        TunnelEvent::Connected { id, meta, .. } => {
             tracing::info!("Tunnel {} connected", id);
 
             // Add to active tunnels
@@ -177,14 +212,14 @@ async fn handle_event(event: TunnelEvent, state: Arc<RwLock<TrayState>>) {
                     TunnelState {
                         profile_id: id,
                         profile_name: profile.metadata.name.clone(),
-                        connected_at: Utc::now(),
+                        connected_at: meta.timestamp,
                     },
                 );
                 state_lock.add_recent_profile(&profile);
             }
             state_lock.update_status();
         }
-        TunnelEvent::Disconnected { id, reason } => {
+        TunnelEvent::Disconnected { id, reason, meta } => {
             tracing::info!("Tunnel {} disconnected: {}", id, reason);
 
             // Get profile name before removing
@@ -194,6 +229,7 @@ async fn handle_event(event: TunnelEvent, state: Arc<RwLock<TrayState>>) {
                     .active_tunnels
                     .get(&id)
                     .map(|t| t.profile_name.clone())
+                    .or(meta.profile_name)
             };
 
             // Remove from active tunnels
@@ -208,16 +244,17 @@ async fn handle_event(event: TunnelEvent, state: Arc<RwLock<TrayState>>) {
                 notifications::show_disconnect_notification(&name, &reason, id);
             }
         }
-        TunnelEvent::Error { id, error } => {
+        TunnelEvent::Error { id, error, meta } => {
             tracing::error!("Tunnel {} error: {}", id, error);
 
-            // Get profile name
+            // Get profile name (the daemon names it even if it never connected)
             let profile_name = {
                 let state_lock = state.read().await;
                 state_lock
                     .active_tunnels
                     .get(&id)
                     .map(|t| t.profile_name.clone())
+                    .or(meta.profile_name)
             };
 
             // Remove from active tunnels
@@ -232,10 +269,54 @@ async fn handle_event(event: TunnelEvent, state: Arc<RwLock<TrayState>>) {
                 notifications::show_error_notification(&name, &error);
             }
         }
-        TunnelEvent::AuthRequired { id, request } => {
+        TunnelEvent::AuthRequired { id, request, .. } => {
             tracing::info!("Auth required for tunnel {}: {}", id, request.prompt);
             // Auth requests are handled by the GUI when user clicks
         }
+        TunnelEvent::AuthResolved { id, .. } => {
+            tracing::debug!("Auth answered for tunnel {}", id);
+        }
+        TunnelEvent::AuthCancelled { id, reason, .. } => {
+            tracing::debug!("Auth request for tunnel {} cancelled: {}", id, reason);
+        }
+        TunnelEvent::Stats { id, stats, .. } => {
+            tracing::trace!("Traffic stats for tunnel {}: {:?}", id, stats);
+        }
+        TunnelEvent::Hook { id, stage, success, output, .. } => {
+            if success {
+                tracing::debug!("Tunnel {} {} hook completed", id, stage);
+            } else {
+                tracing::warn!("Tunnel {} {} hook failed: {}", id, stage, output);
+            }
+        }
+        TunnelEvent::Snapshot { tunnels } => {
+            tracing::info!("Resynchronizing {} tunnel(s) from daemon snapshot", tunnels.len());
+
+            let mut state_lock = state.write().await;
+            state_lock.active_tunnels.retain(|id, _| {
+                tunnels
+                    .iter()
+                    .any(|t| t.id == *id && t.status == TunnelStatus::Connected)
+            });
+            for tunnel in tunnels {
+                if tunnel.status != TunnelStatus::Connected
+                    || state_lock.active_tunnels.contains_key(&tunnel.id)
+                {
+                    continue;
+                }
+                if let Ok(profile) = ssh_tunnel_common::load_profile_by_id(&tunnel.id) {
+                    state_lock.active_tunnels.insert(
+                        tunnel.id,
+                        TunnelState {
+                            profile_id: tunnel.id,
+                            profile_name: profile.metadata.name.clone(),
+                            connected_at: Utc::now(),
+                        },
+                    );
+                }
+            }
+            state_lock.update_status();
+        }
         TunnelEvent::Heartbeat { timestamp } => {
             let mut state_lock = state.write().await;
             state_lock.last_heartbeat = Some(timestamp);
@@ -6,7 +6,7 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use ssh_tunnel_common::{DaemonClientConfig, Profile};
+use ssh_tunnel_common::{DaemonClientConfig, Profile, WsClient};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use uuid::Uuid;
@@ -58,6 +58,9 @@ pub struct TrayState {
     /// Daemon client configuration
     pub daemon_config: DaemonClientConfig,
 
+    /// WebSocket channel to the daemon, when connected over it
+    pub ws_client: Option<WsClient>,
+
     /// Persistent state (recent profiles, etc.)
     pub persistent: PersistentState,
 
@@ -77,6 +80,7 @@ impl TrayState {
             active_tunnels: HashMap::new(),
             last_heartbeat: None,
             daemon_config,
+            ws_client: None,
             persistent,
             state_file,
         })
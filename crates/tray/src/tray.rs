@@ -6,7 +6,10 @@
 use anyhow::Result;
 use ksni;
 use ksni::menu::StandardItem;
-use ssh_tunnel_common::{create_daemon_client, load_profile_by_id};
+use ssh_tunnel_common::{
+    create_daemon_client, load_profile_by_id, prepare_profile_for_remote, ConnectionMode,
+    ProfileSourceMode, StartTunnelRequest,
+};
 use std::process::Command;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -63,20 +66,36 @@ impl TrayIcon {
         state: Arc<RwLock<TrayState>>,
         profile_id: uuid::Uuid,
     ) -> Result<()> {
-        let config = {
+        let (config, ws_client) = {
             let state_lock = state.read().await;
-            state_lock.daemon_config.clone()
+            (state_lock.daemon_config.clone(), state_lock.ws_client.clone())
         };
 
+        let profile = load_profile_by_id(&profile_id)?;
+
         // Add profile to recent list
-        if let Ok(profile) = load_profile_by_id(&profile_id) {
-            let mut state_lock = state.write().await;
-            state_lock.add_recent_profile(&profile);
+        state.write().await.add_recent_profile(&profile);
+
+        // A local daemon reads the profile itself; remote daemons need it sent along
+        let (mode, profile) = match config.connection_mode {
+            ConnectionMode::UnixSocket => (ProfileSourceMode::Local, None),
+            ConnectionMode::Http | ConnectionMode::Https => (
+                ProfileSourceMode::Hybrid,
+                Some(prepare_profile_for_remote(&profile)?),
+            ),
+        };
+
+        if let Some(ws_client) = ws_client {
+            return ws_client.start_tunnel(profile_id, mode, profile).await;
         }
 
         let client = create_daemon_client(&config)?;
         let url = format!("{}/api/tunnels/{}/start", config.daemon_base_url()?, profile_id);
-        let request = client.post(&url);
+        let request = client.post(&url).json(&StartTunnelRequest {
+            profile_id: profile_id.to_string(),
+            mode,
+            profile,
+        });
         let request = ssh_tunnel_common::add_auth_header(request, &config)?;
 
         let response = request.send().await?;
@@ -93,11 +112,15 @@ impl TrayIcon {
         state: Arc<RwLock<TrayState>>,
         profile_id: uuid::Uuid,
     ) -> Result<()> {
-        let config = {
+        let (config, ws_client) = {
             let state_lock = state.read().await;
-            state_lock.daemon_config.clone()
+            (state_lock.daemon_config.clone(), state_lock.ws_client.clone())
         };
 
+        if let Some(ws_client) = ws_client {
+            return ws_client.stop_tunnel(profile_id).await;
+        }
+
         let client = create_daemon_client(&config)?;
         let url = format!("{}/api/tunnels/{}/stop", config.daemon_base_url()?, profile_id);
         let request = client.post(&url);
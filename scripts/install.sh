@@ -53,6 +53,8 @@ if [[ "${MODE}" == "user" ]]; then
   sed "s|/usr/local|${PREFIX}|g" docs/systemd/ssh-tunnel-daemon.user.service > /tmp/ssh-tunnel-daemon.user.service
   install -Dm644 /tmp/ssh-tunnel-daemon.user.service "${UNIT_DIR}/ssh-tunnel-daemon.service"
   rm /tmp/ssh-tunnel-daemon.user.service
+  # Optional socket unit for on-demand start (enable with: systemctl --user enable --now ssh-tunnel-daemon.socket)
+  install -Dm644 docs/systemd/ssh-tunnel-daemon.user.socket "${UNIT_DIR}/ssh-tunnel-daemon.socket"
   if [[ "${ENABLE}" -eq 1 ]]; then
     systemctl --user daemon-reload
     systemctl --user enable --now ssh-tunnel-daemon.service